@@ -0,0 +1,321 @@
+//! Incremental checking of a project. The [Driver] keeps the interface of every module after it's
+//! checked, so when a file changes only that file and the modules that use it are checked again,
+//! and the modules that use it only when its interface changed.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use petgraph::graph::{DiGraph, NodeIndex};
+use vulpi_intern::Symbol;
+use vulpi_location::{stable_hash, FileId};
+use vulpi_report::Report;
+use vulpi_resolver::{cycle::DepHolder, dependencies, Context, Module};
+use vulpi_syntax::concrete::tree::Program;
+use vulpi_typer::{
+    declare::{Declare, Programs},
+    EntryPoint,
+};
+use vulpi_vfs::{path::Path, Error, FileSystem};
+
+/// A file of the project and what is known about it since the last time it was checked.
+struct Unit {
+    id: FileId,
+
+    /// Hash of the content of the file.
+    hash: u64,
+
+    /// The modules that the file uses, taken from its `use` declarations.
+    imports: Vec<Path>,
+
+    /// The file parsed after it changed. The diagnostics of the parser were already reported, so it
+    /// must not be parsed again.
+    parsed: Option<Program>,
+
+    /// The interface written by the last check. Modules without declarations have none.
+    interface: Option<String>,
+
+    /// Hash of what other modules can see of the interface, so it does not change when only the
+    /// spans or the bodies of the declarations change. It's [None] if the module was never checked.
+    interface_hash: Option<u64>,
+}
+
+/// Checks the files of a project and checks them again when they change.
+pub struct Driver<FS: FileSystem> {
+    pub name: Symbol,
+    pub fs: FS,
+    pub reporter: Report,
+    units: HashMap<Path, Unit>,
+    files: HashMap<FileId, Path>,
+    available: Rc<RefCell<HashMap<Path, Module>>>,
+
+    /// The entry point that the root module must define. Libraries have none.
+    entry: Option<EntryPoint>,
+}
+
+impl<FS: FileSystem> Driver<FS> {
+    pub fn new(name: Symbol, fs: FS, reporter: Report) -> Self {
+        Self {
+            name,
+            fs,
+            reporter,
+            units: HashMap::new(),
+            files: HashMap::new(),
+            available: Default::default(),
+            entry: None,
+        }
+    }
+
+    /// Checks that the root module defines the given entry point whenever it's checked.
+    pub fn with_entry(mut self, entry: EntryPoint) -> Self {
+        self.entry = Some(entry);
+        self
+    }
+
+    /// Adds the file of a module to the project. It's checked in the next call to
+    /// [Driver::check].
+    pub fn add_file(&mut self, module: Path, path: FS::Path) -> Result<FileId, Error> {
+        let id = self.fs.load(path)?;
+        let source = self.fs.read(id)?;
+
+        let parsed = self.parse(id, &source);
+
+        let unit = Unit {
+            id,
+            hash: stable_hash(source.as_bytes()),
+            imports: self.imports(&parsed),
+            parsed: Some(parsed),
+            interface: None,
+            interface_hash: None,
+        };
+
+        self.files.insert(id, module.clone());
+        self.units.insert(module, unit);
+
+        Ok(id)
+    }
+
+    /// Checks the modules that were never checked, and returns them.
+    pub fn check(&mut self) -> HashSet<Path> {
+        let dirty = self
+            .units
+            .iter()
+            .filter(|(_, unit)| unit.interface_hash.is_none())
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        self.recheck(dirty)
+    }
+
+    /// Replaces the content of a file and checks it again together with the modules that depend
+    /// on its interface. The old diagnostics of the files that are checked again are dropped, and
+    /// the diagnostics of the other files are kept. Returns the modules that were checked.
+    pub fn update_file(
+        &mut self,
+        path: FS::Path,
+        new_text: String,
+    ) -> Result<HashSet<Path>, Error> {
+        let id = self.fs.load(path)?;
+
+        let Some(module) = self.files.get(&id).cloned() else {
+            self.fs.unload(id)?;
+            return Err(Error::NotFoundId);
+        };
+
+        if self.units[&module].hash == stable_hash(new_text.as_bytes()) {
+            return Ok(HashSet::new());
+        }
+
+        self.fs.store(id, new_text.clone())?;
+        self.reporter.clear(id);
+
+        let parsed = self.parse(id, &new_text);
+        let imports = self.imports(&parsed);
+
+        let unit = self.units.get_mut(&module).unwrap();
+        unit.hash = stable_hash(new_text.as_bytes());
+        unit.imports = imports;
+        unit.parsed = Some(parsed);
+
+        Ok(self.recheck(HashSet::from([module])))
+    }
+
+    fn parse(&self, id: FileId, source: &str) -> Program {
+        vulpi_parser::parse(self.reporter.clone(), id, source)
+    }
+
+    fn imports(&self, program: &Program) -> Vec<Path> {
+        dependencies::dependencies(self.name.clone(), program)
+            .imported
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// The module of the project that contains a path. Paths can point to inline modules, so the
+    /// longest module that is a prefix of the path is the one.
+    fn unit_of(&self, path: &Path) -> Option<Path> {
+        (1..=path.segments.len()).rev().find_map(|len| {
+            let prefix = Path {
+                segments: path.segments[..len].to_vec(),
+            };
+
+            self.units.contains_key(&prefix).then_some(prefix)
+        })
+    }
+
+    /// Splits the modules into groups of modules that use each other, ordered so every group comes
+    /// after the groups that it uses. Returns the groups and the modules used by each module.
+    fn groups(&self) -> (Vec<Vec<Path>>, HashMap<Path, Vec<Path>>) {
+        let mut modules = self.units.keys().cloned().collect::<Vec<_>>();
+        modules.sort_by_key(|path| path.symbol().get());
+
+        let mut graph = DiGraph::<Path, ()>::new();
+
+        let nodes = modules
+            .iter()
+            .map(|path| (path.clone(), graph.add_node(path.clone())))
+            .collect::<HashMap<Path, NodeIndex>>();
+
+        let mut uses = HashMap::new();
+
+        for path in &modules {
+            let used = self.units[path]
+                .imports
+                .iter()
+                .filter_map(|import| self.unit_of(import))
+                .filter(|used| used != path)
+                .collect::<Vec<_>>();
+
+            for used in &used {
+                graph.add_edge(nodes[path], nodes[used], ());
+            }
+
+            uses.insert(path.clone(), used);
+        }
+
+        let groups = petgraph::algo::tarjan_scc(&graph)
+            .into_iter()
+            .map(|group| {
+                let mut group = group
+                    .into_iter()
+                    .map(|node| graph[node].clone())
+                    .collect::<Vec<_>>();
+
+                group.sort_by_key(|path| path.symbol().get());
+                group
+            })
+            .collect();
+
+        (groups, uses)
+    }
+
+    /// Checks the dirty modules and the modules that use a module whose interface changed.
+    fn recheck(&mut self, dirty: HashSet<Path>) -> HashSet<Path> {
+        let (groups, uses) = self.groups();
+
+        let mut checked = HashSet::new();
+        let mut changed = HashSet::new();
+
+        for (i, group) in groups.iter().enumerate() {
+            let stale = group.iter().any(|path| {
+                dirty.contains(path) || uses[path].iter().any(|used| changed.contains(used))
+            });
+
+            if !stale {
+                continue;
+            }
+
+            for path in self.check_group(group, &groups[..i]) {
+                changed.insert(path);
+            }
+
+            checked.extend(group.iter().cloned());
+        }
+
+        checked
+    }
+
+    /// Resolves and type checks a group of modules against the interfaces of the groups before
+    /// it. Returns the modules of the group whose interfaces changed.
+    fn check_group(&mut self, group: &[Path], before: &[Vec<Path>]) -> Vec<Path> {
+        let mut solvers = Vec::new();
+
+        for path in group {
+            let id = self.units[path].id;
+
+            let program = match self.units.get_mut(path).unwrap().parsed.take() {
+                Some(program) => program,
+                None => {
+                    self.reporter.clear(id);
+                    let source = self.fs.read(id).unwrap();
+                    self.parse(id, &source)
+                }
+            };
+
+            let ctx = Context::new(self.available.clone(), path.clone(), self.reporter.clone());
+            let solver = vulpi_resolver::resolve(&ctx, program);
+            self.available
+                .borrow_mut()
+                .insert(path.clone(), ctx.module.clone());
+            solvers.push((ctx, solver));
+        }
+
+        let mut dep = DepHolder::default();
+        let mut programs = Vec::new();
+
+        for (ctx, solver) in solvers {
+            let program = solver.eval(ctx);
+            dep.register(&program);
+            programs.push(program);
+        }
+
+        dep.report_cycles(self.reporter.clone());
+
+        let mut ctx = vulpi_typer::Context::new(self.reporter.clone());
+        let env = vulpi_typer::Env::default();
+
+        for path in before.iter().flatten() {
+            let unit = &self.units[path];
+
+            if let Some(interface) = &unit.interface {
+                let source = self.fs.read(unit.id).unwrap();
+                vulpi_typer::read_interface(&mut ctx, interface, &source)
+                    .expect("the groups before were checked with their current source");
+            }
+        }
+
+        let programs = Programs(programs);
+        Declare::declare(&programs, (&mut ctx, env.clone()));
+        Declare::define(&programs, (&mut ctx, env));
+
+        if let Some(entry) = &self.entry {
+            for (path, program) in group.iter().zip(&programs.0) {
+                vulpi_typer::check_entry_point(&mut ctx, entry, self.units[path].id, program);
+            }
+        }
+
+        let mut changed = Vec::new();
+
+        for path in group {
+            let unit = &self.units[path];
+            let source = self.fs.read(unit.id).unwrap();
+
+            let interface = vulpi_typer::write_interface(&ctx, &path.symbol(), &source);
+            let interface_hash = Some(vulpi_typer::interface_hash(&ctx, &path.symbol()));
+
+            let unit = self.units.get_mut(path).unwrap();
+
+            if unit.interface_hash != interface_hash {
+                changed.push(path.clone());
+            }
+
+            unit.interface = interface;
+            unit.interface_hash = interface_hash;
+        }
+
+        changed
+    }
+}
@@ -1,11 +1,11 @@
 //! Facilities to build a entire crate of vulpi files. This module is responsible for building the
 //! crate from the source files and resolving the modules.
 
-use std::{collections::HashMap, path::PathBuf, fs::File, rc::Rc, cell::RefCell};
+use std::{cell::RefCell, collections::HashMap, fs::File, path::PathBuf, rc::Rc};
 
 use resw::Writer;
-use vulpi_intern::Symbol;
-use vulpi_ir::{transform, inline, dead_code, uncurry};
+use vulpi_intern::{well_known, Symbol};
+use vulpi_ir::{dead_code, inline, transform, uncurry};
 use vulpi_location::{FileId, Span};
 use vulpi_report::Report;
 
@@ -17,10 +17,15 @@ use vulpi_resolver::{
 
 use vulpi_show::Show;
 use vulpi_syntax::concrete::tree::Program;
-use vulpi_typer::declare::{Programs, Declare};
+use vulpi_typer::declare::{Declare, Programs};
 use vulpi_vfs::{path::Path, FileSystem};
 
+pub use driver::Driver;
+pub use source::{check_source, SourceCheckResult, SourceDiagnostic};
+
+pub mod driver;
 pub mod real;
+pub mod source;
 
 pub enum Interface {
     Compiled(Module, Dependencies),
@@ -31,6 +36,9 @@ pub struct ProjectCompiler<FS: FileSystem> {
     pub name: Symbol,
     pub fs: FS,
     pub reporter: Report,
+
+    /// Shows the whole types in the diagnostics.
+    pub verbose_types: bool,
 }
 
 impl<FS: FileSystem> ProjectCompiler<FS> {
@@ -72,7 +80,7 @@ impl<FS: FileSystem> ProjectCompiler<FS> {
         let parsed = self.parse(root);
 
         let path = Path {
-            segments: vec![module.clone(), Symbol::intern("Main")],
+            segments: vec![module.clone(), well_known::MAIN_MODULE],
         };
 
         let mut bag = HashMap::new();
@@ -91,7 +99,8 @@ impl<FS: FileSystem> ProjectCompiler<FS> {
                     modules.insert(path, (module, None, deps));
                 }
                 Interface::Uncompiled(parsed) => {
-                    let context = Context::new(available.clone(), path.clone(), self.reporter.clone());
+                    let context =
+                        Context::new(available.clone(), path.clone(), self.reporter.clone());
                     let solved = vulpi_resolver::resolve(&context, parsed);
                     modules.insert(
                         path,
@@ -124,26 +133,33 @@ impl<FS: FileSystem> ProjectCompiler<FS> {
         let mut ctx = vulpi_typer::Context::new(self.reporter.clone());
         let env = vulpi_typer::Env::default();
 
+        if self.verbose_types {
+            ctx = ctx.with_verbose_types();
+        }
+
         let programs = Programs(programs);
         println!("{}", programs.0[0].show());
 
         Declare::declare(&programs, (&mut ctx, env.clone()));
         let programs = Declare::define(&programs, (&mut ctx, env));
 
-        
-        if !self.reporter.has_errors() {
-            let mut res = transform::Transform::transform(&vulpi_ir::transform::Programs(programs), &mut Default::default());
-            
+        // Typed holes are only informational, but there is no code to generate for them.
+        if !self.reporter.has_errors() && ctx.typed_holes.is_empty() {
+            let mut res = transform::Transform::transform(
+                &vulpi_ir::transform::Programs(programs),
+                &mut Default::default(),
+            );
+
             uncurry::uncurry(&mut res);
             inline::inline(&mut res);
             dead_code::dead_code_remove(&mut res);
-            
-            let js = vulpi_js::Transform::transform(vulpi_js::Programs(res), &mut Default::default());
+
+            let js =
+                vulpi_js::Transform::transform(vulpi_js::Programs(res), &mut Default::default());
             let f = File::create(output).unwrap();
             let mut w = Writer::new(f);
 
             w.write_program(&js).unwrap();
         }
-        
     }
 }
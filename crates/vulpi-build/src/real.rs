@@ -1,9 +1,9 @@
-use std::{collections::HashMap, fs, path::PathBuf};
+use std::{cell::RefCell, collections::HashMap, fs, path::PathBuf, rc::Rc};
 
 use filetime::FileTime;
 use vulpi_intern::Symbol;
-use vulpi_location::FileId;
-use vulpi_vfs::{path::Path, Error};
+use vulpi_location::{FileId, LineIndex};
+use vulpi_vfs::{path::Path, Error, FileIds};
 
 use super::FileSystem;
 
@@ -12,8 +12,8 @@ pub struct RealFileSystem {
     build_root: PathBuf,
     root: Symbol,
     file_map: HashMap<FileId, (PathBuf, String)>,
-    path_map: HashMap<PathBuf, FileId>,
-    counter: usize,
+    ids: FileIds<PathBuf>,
+    line_indexes: RefCell<HashMap<FileId, Rc<LineIndex>>>,
 }
 
 impl RealFileSystem {
@@ -23,8 +23,8 @@ impl RealFileSystem {
             project_root,
             build_root: build,
             file_map: HashMap::new(),
-            path_map: HashMap::new(),
-            counter: 0,
+            ids: FileIds::default(),
+            line_indexes: Default::default(),
         }
     }
 
@@ -41,31 +41,35 @@ impl FileSystem for RealFileSystem {
     fn load(&mut self, path: PathBuf) -> Result<FileId, Error> {
         let path = self.get_path(path)?;
 
-        if let Some(id) = self.path_map.get(&path) {
-            return Ok(*id);
+        if let Some(id) = self.ids.id_of(&path) {
+            return Ok(id);
         }
 
         let content =
             fs::read_to_string(path.clone()).map_err(|_| Error::NotFound(path.clone()))?;
 
-        let id = FileId(self.counter);
-        self.counter += 1;
-
-        let content = (path.clone(), content);
-
-        self.file_map.insert(id, content);
-        self.path_map.insert(path, id);
+        let id = self.ids.insert(path.clone());
+        self.file_map.insert(id, (path, content));
 
         Ok(id)
     }
 
+    fn exists(&self, path: PathBuf) -> bool {
+        self.get_path(path).is_ok()
+    }
+
     fn unload(&mut self, id: FileId) -> Result<(), Error> {
         self.file_map.remove(&id).ok_or(Error::NotFoundId)?;
+        self.ids.remove(id);
+        self.line_indexes.borrow_mut().remove(&id);
         Ok(())
     }
 
-    fn store(&mut self, _id: FileId, _content: String) -> Result<(), Error> {
-        todo!()
+    fn store(&mut self, id: FileId, content: String) -> Result<(), Error> {
+        let file = self.file_map.get_mut(&id).ok_or(Error::NotFoundId)?;
+        file.1 = content;
+        self.line_indexes.borrow_mut().remove(&id);
+        Ok(())
     }
 
     fn read(&self, id: FileId) -> Result<String, Error> {
@@ -73,6 +77,19 @@ impl FileSystem for RealFileSystem {
         Ok(file.1.clone())
     }
 
+    fn line_index(&self, id: FileId) -> Result<Rc<LineIndex>, Error> {
+        if let Some(index) = self.line_indexes.borrow().get(&id) {
+            return Ok(index.clone());
+        }
+
+        let file = self.file_map.get(&id).ok_or(Error::NotFoundId)?;
+        let index = Rc::new(LineIndex::new(&file.1));
+
+        self.line_indexes.borrow_mut().insert(id, index.clone());
+
+        Ok(index)
+    }
+
     fn create(&mut self, path: PathBuf) -> Result<FileId, Error> {
         let path = self.get_path(path)?;
 
@@ -80,11 +97,8 @@ impl FileSystem for RealFileSystem {
             return Err(Error::AlreadyExists);
         }
 
-        let id = FileId(self.counter);
-        self.counter += 1;
-
-        self.file_map.insert(id, (path.clone(), String::new()));
-        self.path_map.insert(path, id);
+        let id = self.ids.insert(path.clone());
+        self.file_map.insert(id, (path, String::new()));
 
         Ok(id)
     }
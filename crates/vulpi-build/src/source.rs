@@ -0,0 +1,166 @@
+//! Checking of a single source text without a project around it. It runs the parser, the resolver
+//! and the type checker over a file that only exists in memory, so it's the easiest way to check a
+//! piece of code in tests and in a playground.
+
+use std::path::PathBuf;
+
+use vulpi_intern::Symbol;
+use vulpi_location::LineIndex;
+use vulpi_report::{hash_reporter, Severity};
+use vulpi_typer::{CheckOptions, Env};
+use vulpi_vfs::{path::Path, FileSystem, MemoryFileSystem};
+
+/// A diagnostic of [check_source] with its position in the source. Lines and columns start at 1,
+/// like editors show them, and columns are counted in characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub code: Option<usize>,
+    pub message: String,
+}
+
+/// The result of [check_source].
+pub struct SourceCheckResult {
+    signatures: Vec<(String, String)>,
+    diagnostics: Vec<SourceDiagnostic>,
+}
+
+impl SourceCheckResult {
+    /// The names and the types of the top level lets and externals of the source, in the order
+    /// that they were written. Definitions that failed to check have `<ERROR>` in their types.
+    pub fn signatures(&self) -> Vec<(String, String)> {
+        self.signatures.clone()
+    }
+
+    /// The diagnostics of every step, ordered by their positions.
+    pub fn diagnostics(&self) -> &[SourceDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Checks if any of the diagnostics is an error.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+/// Parses, resolves and type checks a source text as the module with the given name. The module is
+/// its own prelude, so it declares the builtin types that it uses, like `Int` and `String`.
+///
+/// ```
+/// let result = vulpi_build::check_source(
+///     "Main",
+///     "pub type Int\n\
+///      pub let twice (f: Int -> Int) (x: Int) : Int = f (f x)\n\
+///      pub let answer = 42\n",
+/// );
+///
+/// assert!(result.diagnostics().is_empty());
+///
+/// assert_eq!(
+///     result.signatures(),
+///     vec![
+///         ("twice".to_string(), "((Int -> Int) -> (Int -> Int))".to_string()),
+///         ("answer".to_string(), "Int".to_string()),
+///     ]
+/// );
+/// ```
+///
+/// The diagnostics say where they happened:
+///
+/// ```
+/// use vulpi_report::Severity;
+///
+/// let result = vulpi_build::check_source(
+///     "Main",
+///     "pub type Int\n\
+///      pub type String\n\
+///      pub let wrong : Int = \"text\"\n",
+/// );
+///
+/// let diagnostic = &result.diagnostics()[0];
+///
+/// assert!(result.has_errors());
+/// assert_eq!(diagnostic.severity, Severity::Error);
+/// assert_eq!((diagnostic.line, diagnostic.column), (3, 23));
+/// ```
+pub fn check_source(name: &str, src: &str) -> SourceCheckResult {
+    let report = hash_reporter();
+    let file = PathBuf::from(format!("{name}.vp"));
+
+    let mut fs = MemoryFileSystem::new();
+    fs.insert(file.clone(), src);
+
+    let id = fs.load(file).unwrap();
+    let source = fs.read(id).unwrap();
+
+    let path = Path {
+        segments: vec![Symbol::intern(name)],
+    };
+
+    let module = path.symbol();
+
+    let parsed = vulpi_parser::parse(report.clone(), id, &source);
+    let (programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    let mut names = programs
+        .iter()
+        .flat_map(|program| {
+            let lets = program
+                .lets
+                .iter()
+                .map(|decl| (&decl.signature.name, &decl.signature.span));
+
+            let externals = program
+                .externals
+                .iter()
+                .map(|decl| (&decl.name, &decl.span));
+
+            lets.chain(externals)
+        })
+        .filter(|(name, _)| !name.is_wildcard())
+        .map(|(name, span)| (span.start.0, name.clone()))
+        .collect::<Vec<_>>();
+
+    names.sort_by_key(|(start, _)| *start);
+
+    let options = CheckOptions {
+        prelude: module,
+        ..Default::default()
+    };
+
+    let checked = vulpi_typer::check(programs, report.clone(), options);
+
+    let signatures = names
+        .into_iter()
+        .filter_map(|(_, name)| {
+            let typ = checked.types.get(&name)?;
+            Some((name.name.get(), typ.show(&Env::default()).to_string()))
+        })
+        .collect();
+
+    let index = LineIndex::new(&source);
+
+    let diagnostics = report
+        .iter()
+        .map(|diagnostic| {
+            let (line, column) = index.position(diagnostic.location.start);
+
+            SourceDiagnostic {
+                line: line + 1,
+                column: column + 1,
+                severity: diagnostic.severity,
+                code: diagnostic.code,
+                message: diagnostic.message,
+            }
+        })
+        .collect();
+
+    SourceCheckResult {
+        signatures,
+        diagnostics,
+    }
+}
@@ -0,0 +1,118 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use vulpi_build::Driver;
+use vulpi_intern::Symbol;
+use vulpi_report::{hash_reporter, registry};
+use vulpi_typer::EntryPoint;
+use vulpi_vfs::{path::Path, MemoryFileSystem};
+
+const LIB: &str = "pub type Int\n\
+                   pub external zero : Int = \"0\"\n\
+                   pub let pick (x: Int) (y: Int) : Int = x\n";
+
+const MAIN: &str = "use Tests.Lib\n\
+                    let number : Int = pick zero zero\n";
+
+fn module(name: &str) -> Path {
+    Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern(name)],
+    }
+}
+
+fn names(modules: HashSet<Path>) -> Vec<String> {
+    let mut names = modules.iter().map(Path::to_string).collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn driver() -> Driver<MemoryFileSystem> {
+    let fs = MemoryFileSystem::from_iter([("Lib.vp", LIB), ("Main.vp", MAIN)]);
+    let mut driver = Driver::new(Symbol::intern("Tests"), fs, hash_reporter());
+
+    driver
+        .add_file(module("Lib"), PathBuf::from("Lib.vp"))
+        .unwrap();
+    driver
+        .add_file(module("Main"), PathBuf::from("Main.vp"))
+        .unwrap();
+
+    let checked = driver.check();
+    assert_eq!(names(checked), ["Tests.Lib", "Tests.Main"]);
+    assert!(!driver.reporter.has_errors());
+
+    driver
+}
+
+#[test]
+fn body_changes_only_check_the_changed_module() {
+    let mut driver = driver();
+
+    let lib = LIB.replace("= x", "= y");
+    let checked = driver.update_file(PathBuf::from("Lib.vp"), lib).unwrap();
+
+    assert_eq!(names(checked), ["Tests.Lib"]);
+    assert!(!driver.reporter.has_errors());
+}
+
+#[test]
+fn signature_changes_check_the_dependents() {
+    let mut driver = driver();
+
+    let lib = LIB.replace("(y: Int) : Int = x", ": Int = x");
+    let checked = driver.update_file(PathBuf::from("Lib.vp"), lib).unwrap();
+
+    assert_eq!(names(checked), ["Tests.Lib", "Tests.Main"]);
+
+    let codes = driver.reporter.iter().map(|d| d.code).collect::<Vec<_>>();
+    assert_eq!(codes, vec![Some(registry::TOO_MANY_ARGUMENTS)]);
+
+    // Fixing the signature drops the error of the dependent.
+    let checked = driver
+        .update_file(PathBuf::from("Lib.vp"), LIB.to_string())
+        .unwrap();
+
+    assert_eq!(names(checked), ["Tests.Lib", "Tests.Main"]);
+    assert!(!driver.reporter.has_errors());
+}
+
+#[test]
+fn unchanged_files_are_not_checked() {
+    let mut driver = driver();
+
+    let checked = driver
+        .update_file(PathBuf::from("Main.vp"), MAIN.to_string())
+        .unwrap();
+
+    assert!(checked.is_empty());
+}
+
+#[test]
+fn the_root_module_must_define_the_entry_point() {
+    let fs = MemoryFileSystem::from_iter([("Lib.vp", LIB), ("Main.vp", MAIN)]);
+    let entry = EntryPoint::new(Symbol::intern("Tests.Main"));
+    let mut driver = Driver::new(Symbol::intern("Tests"), fs, hash_reporter()).with_entry(entry);
+
+    driver
+        .add_file(module("Lib"), PathBuf::from("Lib.vp"))
+        .unwrap();
+    driver
+        .add_file(module("Main"), PathBuf::from("Main.vp"))
+        .unwrap();
+
+    driver.check();
+
+    let missing = |driver: &Driver<MemoryFileSystem>| {
+        driver
+            .reporter
+            .iter()
+            .any(|d| d.code == Some(registry::MISSING_ENTRY_POINT))
+    };
+
+    assert!(missing(&driver));
+
+    let main = format!("{MAIN}pub let main : () = ()\n");
+    driver.update_file(PathBuf::from("Main.vp"), main).unwrap();
+
+    assert!(!missing(&driver));
+    assert!(!driver.reporter.has_errors());
+}
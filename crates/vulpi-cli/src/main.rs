@@ -17,6 +17,17 @@ enum Cli {
 
         #[clap(short, long)]
         output: Option<String>,
+
+        /// Treats every warning as an error.
+        #[clap(long)]
+        deny_warnings: bool,
+
+        /// Shows the whole types in the diagnostics instead of eliding the parts that match.
+        #[clap(long)]
+        verbose_types: bool,
+    },
+    Explain {
+        code: String,
     },
 }
 
@@ -44,6 +55,8 @@ fn main() {
             file_name,
             package,
             output,
+            deny_warnings,
+            verbose_types,
         } => {
             let cwd = env::current_dir().unwrap();
 
@@ -57,8 +70,13 @@ fn main() {
                 fs: RealFileSystem::new(name.clone(), cwd.clone(), cwd.clone().join("build")),
                 reporter: vulpi_report::hash_reporter(),
                 name: name.clone(),
+                verbose_types,
             };
 
+            compiler
+                .reporter
+                .set_treat_warnings_as_errors(deny_warnings);
+
             compiler.compile(
                 name.clone(),
                 PathBuf::from(file_name),
@@ -68,5 +86,9 @@ fn main() {
             let ctx = Classic::new(&compiler.fs, cwd.clone());
             compiler.reporter.to_stderr(ctx)
         }
+        Cli::Explain { code } => match vulpi_report::explain(&code) {
+            Some(explanation) => println!("{}", explanation),
+            None => eprintln!("there's no error with the code '{}'", code),
+        },
     }
 }
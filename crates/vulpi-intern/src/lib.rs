@@ -9,6 +9,9 @@ pub mod no_rc;
 #[cfg(feature = "single-shot")]
 pub use no_rc::*;
 
+#[cfg(feature = "single-shot")]
+pub mod well_known;
+
 use std::marker::PhantomData;
 
 /// A interned symbol that contains a phantom data to make it unique.
@@ -1,13 +1,16 @@
 //! A simple string interner with no reference counting so it lives until the end of the program.
+//! It's shared by every thread, so symbols can be sent between threads and interning the same
+//! string in two threads gives the same symbol.
 
 use vulpi_show::Show;
 
-use std::cell::RefCell;
+use crate::well_known;
+
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
 
-thread_local! {
-    static INTERNER: Interner = Interner::default();
+lazy_static::lazy_static! {
+    static ref INTERNER: Interner = Interner::default();
 }
 
 /// A symbol is a reference to a string inside the interner. It is used to compare strings by
@@ -27,22 +30,24 @@ impl std::fmt::Debug for Symbol {
 
 impl Symbol {
     pub fn intern(string: &str) -> Self {
-        INTERNER.with(|i| i.intern(string))
+        INTERNER.intern(string)
     }
 
     pub fn get(&self) -> String {
-        INTERNER.with(|i| i.get(self).unwrap())
+        self.as_str().to_owned()
+    }
+
+    /// Gets the string of the symbol without copying it. The strings are never freed, so the
+    /// reference lives until the end of the program.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Symbol::Generated(n) => Symbol::intern(&format!("%{n}")).as_str(),
+            Symbol::Interned(id) => INTERNER.get(*id),
+        }
     }
 
     pub fn get_static(&self) -> &'static str {
-        INTERNER.with(|i| match self {
-            Symbol::Generated(_) => todo!(),
-            Symbol::Interned(id) => {
-                let id_to_string = i.id_to_string.borrow();
-                let string = id_to_string.get(*id).unwrap();
-                Box::leak(string.clone().into_boxed_str())
-            },
-        })
+        self.as_str()
     }
 }
 
@@ -51,33 +56,91 @@ impl Show for Symbol {
         vulpi_show::TreeDisplay::label(&format!("Symbol: {}", self.get()))
     }
 }
+
+/// Symbols are written as their strings, because the ids depend on the order that the strings were
+/// interned in.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Symbol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Symbol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Ok(Symbol::intern(&string))
+    }
+}
+
 #[derive(Default)]
-struct Interner {
-    id_to_string: RefCell<Vec<String>>,
-    string_to_id: RefCell<HashMap<String, Symbol>>,
-    counter: AtomicUsize,
+struct Table {
+    id_to_string: Vec<&'static str>,
+    string_to_id: HashMap<&'static str, Symbol>,
+}
+
+/// How much the interner is holding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InternerStats {
+    /// The number of strings that were interned.
+    pub count: usize,
+
+    /// The sum of the lengths of the strings, in bytes.
+    pub bytes: usize,
+}
+
+pub struct Interner {
+    table: RwLock<Table>,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        let interner = Self {
+            table: Default::default(),
+        };
+
+        for string in well_known::STRINGS {
+            interner.intern(string);
+        }
+
+        interner
+    }
 }
 
 impl Interner {
+    /// The statistics of the interner that is shared by the whole program.
+    pub fn stats() -> InternerStats {
+        let table = INTERNER.table.read().unwrap();
+
+        InternerStats {
+            count: table.id_to_string.len(),
+            bytes: table.id_to_string.iter().map(|string| string.len()).sum(),
+        }
+    }
+
     fn intern(&self, string: &str) -> Symbol {
-        if let Some(id) = self.string_to_id.borrow().get(string) {
+        if let Some(id) = self.table.read().unwrap().string_to_id.get(string) {
             return id.clone();
         }
 
-        let mut id_to_string = self.id_to_string.borrow_mut();
-        let mut string_to_id = self.string_to_id.borrow_mut();
+        let mut table = self.table.write().unwrap();
 
-        let id = Symbol::Interned(self.counter.fetch_add(1, Ordering::SeqCst));
-        id_to_string.push(string.to_owned());
-        string_to_id.insert(string.to_owned(), id.clone());
+        // Another thread could have interned the string while the lock was released.
+        if let Some(id) = table.string_to_id.get(string) {
+            return id.clone();
+        }
+
+        let string: &'static str = Box::leak(string.to_owned().into_boxed_str());
+
+        let id = Symbol::Interned(table.id_to_string.len());
+        table.id_to_string.push(string);
+        table.string_to_id.insert(string, id.clone());
 
         id
     }
 
-    fn get(&self, id: &Symbol) -> Option<String> {
-        match id {
-            Symbol::Generated(n) => Some(format!("%{n}")),
-            Symbol::Interned(id) => self.id_to_string.borrow().get(*id).cloned(),
-        }
+    fn get(&self, id: usize) -> &'static str {
+        self.table.read().unwrap().id_to_string[id]
     }
 }
@@ -0,0 +1,35 @@
+//! Names that the compiler itself needs to know about. They are interned when the interner is
+//! created, in the order that they are listed here, so their symbols are constants that can be
+//! compared without looking anything up.
+
+use crate::Symbol;
+
+macro_rules! well_known {
+    ($($name:ident = $id:literal => $string:literal,)*) => {
+        $(pub const $name: Symbol = Symbol::Interned($id);)*
+
+        /// The strings of the well known names, where the position of each one is its id.
+        pub(crate) const STRINGS: &[&str] = &[$($string),*];
+    };
+}
+
+well_known! {
+    EMPTY = 0 => "",
+    PRELUDE = 1 => "Prelude",
+    BOOL = 2 => "Bool",
+    INT = 3 => "Int",
+    FLOAT = 4 => "Float",
+    STRING = 5 => "String",
+    CHAR = 6 => "Char",
+    LIST = 7 => "List",
+    NIL = 8 => "Nil",
+    CONS = 9 => "Cons",
+    MAIN = 10 => "main",
+    MAIN_MODULE = 11 => "Main",
+    TRUE = 12 => "True",
+    FALSE = 13 => "False",
+    CONCAT = 14 => "concat",
+    DEPRECATED = 15 => "deprecated",
+    UNIT = 16 => "Unit",
+    KEEP = 17 => "keep",
+}
@@ -0,0 +1,42 @@
+use std::thread;
+
+use vulpi_intern::Symbol;
+
+#[test]
+fn interning_in_many_threads_gives_the_same_symbols() {
+    let words = (0..200).map(|i| format!("word{i}")).collect::<Vec<_>>();
+
+    let threads = (0..8)
+        .map(|n| {
+            let words = words.clone();
+            thread::spawn(move || {
+                // Each thread interns the words in a different order.
+                let mut symbols = words
+                    .iter()
+                    .cycle()
+                    .skip(n * 25)
+                    .take(words.len())
+                    .map(|word| (word.clone(), Symbol::intern(word)))
+                    .collect::<Vec<_>>();
+
+                symbols.sort();
+                symbols
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let results = threads
+        .into_iter()
+        .map(|thread| thread.join().unwrap())
+        .collect::<Vec<_>>();
+
+    for symbols in &results {
+        assert_eq!(symbols, &results[0]);
+
+        for (word, symbol) in symbols {
+            assert_eq!(symbol.as_str(), word);
+            assert_eq!(&symbol.get(), word);
+            assert_eq!(&Symbol::intern(word), symbol);
+        }
+    }
+}
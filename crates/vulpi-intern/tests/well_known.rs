@@ -0,0 +1,42 @@
+use vulpi_intern::{well_known, Interner, Symbol};
+
+#[test]
+fn well_known_symbols_are_the_interned_strings() {
+    let names = [
+        (well_known::EMPTY, ""),
+        (well_known::PRELUDE, "Prelude"),
+        (well_known::BOOL, "Bool"),
+        (well_known::INT, "Int"),
+        (well_known::FLOAT, "Float"),
+        (well_known::STRING, "String"),
+        (well_known::CHAR, "Char"),
+        (well_known::LIST, "List"),
+        (well_known::NIL, "Nil"),
+        (well_known::CONS, "Cons"),
+        (well_known::MAIN, "main"),
+        (well_known::MAIN_MODULE, "Main"),
+    ];
+
+    for (symbol, string) in names {
+        assert_eq!(symbol, Symbol::intern(string));
+        assert_eq!(symbol.as_str(), string);
+    }
+}
+
+#[test]
+fn stats_grow_with_new_strings() {
+    let before = Interner::stats();
+
+    Symbol::intern("a string that is only interned by this test");
+    let after = Interner::stats();
+
+    assert!(after.count > before.count);
+    assert!(after.bytes >= before.bytes + "a string that is only interned by this test".len());
+
+    // Interning it again does not store anything.
+    Symbol::intern("a string that is only interned by this test");
+    Symbol::intern("Bool");
+
+    let again = Interner::stats();
+    assert_eq!(again, after);
+}
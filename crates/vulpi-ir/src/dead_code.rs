@@ -1,7 +1,7 @@
 use std::{collections::HashMap, mem};
 
 use petgraph::{graph::DiGraph, stable_graph::NodeIndex};
-use vulpi_intern::Symbol;
+use vulpi_intern::well_known;
 use vulpi_syntax::{
     lambda::{self, LetDecl, Program},
     r#abstract::Qualified,
@@ -19,8 +19,8 @@ impl Default for Context {
             nodes: HashMap::new(),
             graph: DiGraph::new(),
             current: Qualified {
-                path: Symbol::intern(""),
-                name: Symbol::intern(""),
+                path: well_known::EMPTY,
+                name: well_known::EMPTY,
             },
         }
     }
@@ -106,9 +106,9 @@ impl Check for Program {
         for (name, decl) in &mut self.lets {
             ctx.current = name.clone();
             ctx.nodes
-            .entry(ctx.current.clone())
-            .or_insert_with(|| ctx.graph.add_node(()));
-        decl.check(ctx);
+                .entry(ctx.current.clone())
+                .or_insert_with(|| ctx.graph.add_node(()));
+            decl.check(ctx);
         }
     }
 }
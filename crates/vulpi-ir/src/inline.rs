@@ -1,9 +1,16 @@
-use std::{collections::{HashMap, HashSet}, rc::Rc, cell::RefCell};
-
-use petgraph::{stable_graph::NodeIndex, graph::DiGraph, visit::EdgeRef};
-use vulpi_intern::Symbol;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use petgraph::{graph::DiGraph, stable_graph::NodeIndex, visit::EdgeRef};
+use vulpi_intern::{well_known, Symbol};
 use vulpi_show::Show;
-use vulpi_syntax::{lambda::{self, LetDecl, Program}, r#abstract::Qualified};
+use vulpi_syntax::{
+    lambda::{self, LetDecl, Program},
+    r#abstract::Qualified,
+};
 
 pub struct Context<'a> {
     vars: HashMap<Qualified, Vec<&'a mut lambda::ExprKind>>,
@@ -11,7 +18,7 @@ pub struct Context<'a> {
     graph: DiGraph<(), ()>,
     should_inline: HashMap<Qualified, lambda::ExprKind>,
     current: Qualified,
-    changed: bool
+    changed: bool,
 }
 
 impl<'a> Default for Context<'a> {
@@ -21,11 +28,11 @@ impl<'a> Default for Context<'a> {
             nodes: HashMap::new(),
             graph: DiGraph::new(),
             current: Qualified {
-                path: Symbol::intern(""),
-                name: Symbol::intern(""),
+                path: well_known::EMPTY,
+                name: well_known::EMPTY,
             },
             should_inline: HashMap::new(),
-            changed: false
+            changed: false,
         }
     }
 }
@@ -49,10 +56,16 @@ impl Transform for lambda::ExprKind {
                 }
             }
             lambda::ExprKind::Variable(_) => {}
-            lambda::ExprKind::Constructor(c) |
-            lambda::ExprKind::Function(c) => {
-                let node = ctx.nodes.entry(c.clone()).or_insert_with(|| ctx.graph.add_node(())).clone();
-                let current = ctx.nodes.entry(ctx.current.clone()).or_insert_with(|| ctx.graph.add_node(()));
+            lambda::ExprKind::Constructor(c) | lambda::ExprKind::Function(c) => {
+                let node = ctx
+                    .nodes
+                    .entry(c.clone())
+                    .or_insert_with(|| ctx.graph.add_node(()))
+                    .clone();
+                let current = ctx
+                    .nodes
+                    .entry(ctx.current.clone())
+                    .or_insert_with(|| ctx.graph.add_node(()));
 
                 ctx.graph.add_edge(*current, node, ());
 
@@ -103,7 +116,7 @@ impl Transform for LetDecl {
     type Out = ();
 
     fn transform<'a>(&'a mut self, ctx: &mut Context<'a>) {
-        self.body.transform(ctx);        
+        self.body.transform(ctx);
     }
 }
 
@@ -112,10 +125,12 @@ impl Transform for Program {
 
     fn transform<'a>(&'a mut self, ctx: &mut Context<'a>) {
         for (name, decl) in &mut self.lets {
-            ctx.nodes.entry(ctx.current.clone()).or_insert_with(|| ctx.graph.add_node(()));
+            ctx.nodes
+                .entry(ctx.current.clone())
+                .or_insert_with(|| ctx.graph.add_node(()));
 
             ctx.current = name.clone();
-            
+
             if should_inline(&decl.body) {
                 ctx.should_inline.insert(name.clone(), *decl.body.clone());
             }
@@ -127,7 +142,7 @@ impl Transform for Program {
 
 impl Transform for Vec<Program> {
     type Out = ();
-    
+
     fn transform<'a>(&'a mut self, ctx: &mut Context<'a>) {
         for program in self {
             program.transform(ctx);
@@ -139,9 +154,7 @@ pub fn traverse<F: Fn(&mut lambda::ExprKind) -> ()>(expr: &mut lambda::ExprKind,
     f(expr);
 
     match expr {
-        lambda::ExprKind::Lambda(_, body) => {
-            traverse(body, f)
-        },
+        lambda::ExprKind::Lambda(_, body) => traverse(body, f),
         lambda::ExprKind::Application(func, args) => {
             traverse(func, f.clone());
             for arg in args {
@@ -149,8 +162,7 @@ pub fn traverse<F: Fn(&mut lambda::ExprKind) -> ()>(expr: &mut lambda::ExprKind,
             }
         }
         lambda::ExprKind::Variable(_) => {}
-        lambda::ExprKind::Constructor(_) |
-        lambda::ExprKind::Function(_) => {}
+        lambda::ExprKind::Constructor(_) | lambda::ExprKind::Function(_) => {}
         lambda::ExprKind::Object(_, args) => {
             for arg in args {
                 traverse(arg, f.clone());
@@ -188,7 +200,6 @@ pub fn traverse<F: Fn(&mut lambda::ExprKind) -> ()>(expr: &mut lambda::ExprKind,
                 traverse(action, f.clone());
             }
         }
-    
     }
 }
 
@@ -212,11 +223,11 @@ pub fn is_complex(expr: &lambda::ExprKind) -> bool {
         lambda::ExprKind::Projection(_, _) => true,
         lambda::ExprKind::Access(_, _) => true,
         lambda::ExprKind::Literal(_) => false,
-        
+
         lambda::ExprKind::RecordInstance(_, fields) => fields.iter().any(|(_, x)| is_complex(x)),
         lambda::ExprKind::RecordUpdate(_, _, fields) => fields.iter().any(|(_, x)| is_complex(x)),
         lambda::ExprKind::Tuple(fields) => fields.iter().any(|x| is_complex(x)),
-        
+
         lambda::ExprKind::Block(_) => true,
         lambda::ExprKind::Switch(_, _, _) => false,
     }
@@ -224,29 +235,30 @@ pub fn is_complex(expr: &lambda::ExprKind) -> bool {
 
 pub fn apply(expr: &mut lambda::ExprKind, changed: Rc<RefCell<bool>>) {
     match expr {
-        lambda::ExprKind::Application(func, args) => {
-            match &mut **func { 
-                lambda::ExprKind::Lambda(params,ref mut body) => {
-                    let mut subs = im_rc::HashMap::new();
+        lambda::ExprKind::Application(func, args) => match &mut **func {
+            lambda::ExprKind::Lambda(params, ref mut body) => {
+                let mut subs = im_rc::HashMap::new();
 
-                    for (param, arg) in params.into_iter().zip(args) {
-                        subs.insert(param.clone(), *arg.clone());
-                    }
-                    
-                    substitute( body, subs);
+                for (param, arg) in params.into_iter().zip(args) {
+                    subs.insert(param.clone(), *arg.clone());
+                }
+
+                substitute(body, subs);
 
-                    *expr = *body.clone();
+                *expr = *body.clone();
 
-                    *changed.borrow_mut() = true;
+                *changed.borrow_mut() = true;
 
-                    traverse(expr, Rc::new(|x: &mut _| {
+                traverse(
+                    expr,
+                    Rc::new(|x: &mut _| {
                         apply(x, changed.clone());
-                    }));
-                }
-                _ => ()
+                    }),
+                );
             }
-        }
-        _ => ()
+            _ => (),
+        },
+        _ => (),
     }
 }
 
@@ -321,22 +333,22 @@ pub fn are_complex(exprs: &[Box<lambda::ExprKind>]) -> bool {
 
 pub fn should_inline(expr: &lambda::ExprKind) -> bool {
     match expr {
-        lambda::ExprKind::Application(func, args) => {
-            !is_complex(func) && !are_complex(args)
-        }
+        lambda::ExprKind::Application(func, args) => !is_complex(func) && !are_complex(args),
         lambda::ExprKind::Constructor(_) => true,
         lambda::ExprKind::Variable(_) => true,
         lambda::ExprKind::Function(_) => true,
         lambda::ExprKind::Literal(_) => true,
-        lambda::ExprKind::Object(_, args) => !are_complex(args), 
+        lambda::ExprKind::Object(_, args) => !are_complex(args),
         lambda::ExprKind::Lambda(_, body) => should_inline(body),
         lambda::ExprKind::Projection(_, e) => !is_complex(e),
         lambda::ExprKind::Access(e, _) => !is_complex(e),
-        
+
         lambda::ExprKind::RecordInstance(_, fields) => !fields.iter().any(|(_, x)| is_complex(x)),
-        lambda::ExprKind::RecordUpdate(_, e, fields) => !is_complex(e) && !fields.iter().any(|(_, x)| is_complex(x)),
+        lambda::ExprKind::RecordUpdate(_, e, fields) => {
+            !is_complex(e) && !fields.iter().any(|(_, x)| is_complex(x))
+        }
         lambda::ExprKind::Tuple(fields) => !fields.iter().any(|x| is_complex(x)),
-        
+
         lambda::ExprKind::Block(_) => false,
         lambda::ExprKind::Switch(_, _, _) => false,
     }
@@ -345,30 +357,38 @@ pub fn should_inline(expr: &lambda::ExprKind) -> bool {
 pub fn remove_recursive_inline_marks(ctx: &mut Context) {
     'breaker: loop {
         let loops = petgraph::algo::tarjan_scc(&ctx.graph);
-        let inv_map = ctx.nodes.iter().map(|(k, v)| (*v, k.clone())).collect::<HashMap<_, _>>();
+        let inv_map = ctx
+            .nodes
+            .iter()
+            .map(|(k, v)| (*v, k.clone()))
+            .collect::<HashMap<_, _>>();
 
         for node in ctx.graph.node_indices() {
             if ctx.graph.contains_edge(node, node) {
                 ctx.should_inline.remove(&inv_map[&node]);
             }
         }
-        
+
         for loop_ in loops {
             if loop_.len() > 1 {
                 for node in loop_ {
                     let node_rem = ctx.should_inline.remove(&inv_map[&node]);
                     if node_rem.is_none() {
-                        let cloned = ctx.graph.edges_directed(node, petgraph::Direction::Incoming).map(|x| x.id()).collect::<Vec<_>>();
+                        let cloned = ctx
+                            .graph
+                            .edges_directed(node, petgraph::Direction::Incoming)
+                            .map(|x| x.id())
+                            .collect::<Vec<_>>();
                         for edge in cloned {
                             ctx.graph.remove_edge(edge);
                         }
-                        continue 'breaker
+                        continue 'breaker;
                     }
                 }
             }
         }
 
-        break
+        break;
     }
 }
 
@@ -377,9 +397,9 @@ pub fn inline(programs: &mut Vec<Program>) {
         let changed = {
             let mut ctx = Default::default();
             programs.transform(&mut ctx);
-    
+
             remove_recursive_inline_marks(&mut ctx);
-    
+
             for (name, value) in ctx.should_inline {
                 if let Some(exprs) = ctx.vars.get_mut(&name) {
                     for expr in exprs {
@@ -388,18 +408,16 @@ pub fn inline(programs: &mut Vec<Program>) {
                     }
                 }
             }
-    
+
             ctx.changed
         };
 
         let changed = Rc::new(RefCell::new(changed));
 
-        traverse_programs(programs, |x| {
-            apply(x, changed.clone())
-        });
+        traverse_programs(programs, |x| apply(x, changed.clone()));
 
         if !*changed.borrow() {
             break;
         }
     }
-}
\ No newline at end of file
+}
@@ -6,7 +6,7 @@ use vulpi_intern::Symbol;
 use vulpi_macros::Show;
 use vulpi_syntax::{
     elaborated::{PatApplication, Pattern, PatternKind},
-    lambda::{Expr, Case},
+    lambda::{Case, Expr},
 };
 
 #[derive(Default, Show)]
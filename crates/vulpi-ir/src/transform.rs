@@ -386,6 +386,28 @@ impl Transform for Expr<Type<Real>> {
                 field.field.clone(),
                 field.expr.transform(context),
             )),
+            ExprKind::TupleProjection(projection) => Box::new(lambda::ExprKind::Access(
+                projection.expr.transform(context),
+                projection.index,
+            )),
+            ExprKind::Let(let_expr) if let_expr.recursive => {
+                let group = std::iter::once((&let_expr.pattern, &let_expr.body))
+                    .chain(let_expr.group.iter().map(|(pattern, body)| (pattern, body)));
+
+                // The bindings of a group are variables, and they are named before any of the
+                // bodies so the bodies can refer to them.
+                let names = group
+                    .clone()
+                    .map(|(pattern, _)| generate_pattern_name(context, pattern).0)
+                    .collect::<Vec<_>>();
+
+                for (name, (_, body)) in names.into_iter().zip(group) {
+                    let body = body.transform(context);
+                    context.add_upwards(Stmt::Let(name, body));
+                }
+
+                let_expr.next.transform(context)
+            }
             ExprKind::Let(let_expr) => {
                 let arms = vec![let_expr.pattern.clone()];
                 let scrutinee = vec![let_expr.body.clone()];
@@ -445,7 +467,9 @@ impl Transform for (Qualified, LetDecl<Type<Real>>) {
     fn transform<'a>(&self, context: &mut Context) -> Self::Out {
         let mut context = context.clone();
 
-        let new_names = self.1.body[0].patterns.iter()
+        let new_names = self.1.body[0]
+            .patterns
+            .iter()
             .map(|x| {
                 if self.1.body.len() == 1 {
                     generate_pattern_name(&mut context, x).0
@@ -615,6 +639,17 @@ impl Transform for Program<Type<Real>> {
 
 pub struct Programs(pub Vec<Program<Type<Real>>>);
 
+/// A program together with the programs of its inline modules, that are compiled with it.
+fn with_modules(program: &Program<Type<Real>>) -> Vec<&Program<Type<Real>>> {
+    let mut all = vec![program];
+
+    for module in program.modules.values() {
+        all.extend(with_modules(module));
+    }
+
+    all
+}
+
 impl Transform for Programs {
     type Out = Vec<lambda::Program>;
 
@@ -625,18 +660,20 @@ impl Transform for Programs {
         let mut contexts = vec![Context::default(); self.0.len()];
 
         for (i, program) in self.0.iter().enumerate() {
-            for (name, external) in &program.externals {
-                programs[i]
-                    .externals
-                    .push((name.clone(), external.binding.clone()));
-            }
+            for program in with_modules(program) {
+                for (name, external) in &program.externals {
+                    programs[i]
+                        .externals
+                        .push((name.clone(), external.binding.clone()));
+                }
 
-            programs[i].commands.extend(program.commands.clone());
+                programs[i].commands.extend(program.commands.clone());
 
-            for (name, type_expr) in &program.types {
-                (name.clone(), type_expr.clone()).transform(&mut contexts[i]);
-                let definitions = contexts[i].constructors.borrow().clone();
-                programs[i].definitions = definitions;
+                for (name, type_expr) in &program.types {
+                    (name.clone(), type_expr.clone()).transform(&mut contexts[i]);
+                    let definitions = contexts[i].constructors.borrow().clone();
+                    programs[i].definitions = definitions;
+                }
             }
 
             for (name, (def, size)) in programs[i].definitions.clone() {
@@ -653,9 +690,11 @@ impl Transform for Programs {
         }
 
         for (i, program) in self.0.iter().enumerate() {
-            for (name, type_expr) in &program.lets {
-                let let_expr = (name.clone(), type_expr.clone()).transform(context);
-                programs[i].lets.push((name.clone(), let_expr));
+            for program in with_modules(program) {
+                for (name, type_expr) in &program.lets {
+                    let let_expr = (name.clone(), type_expr.clone()).transform(context);
+                    programs[i].lets.push((name.clone(), let_expr));
+                }
             }
         }
 
@@ -694,6 +733,6 @@ fn derive_let_from_constructor(
             Box::new(lambda::ExprKind::Lambda(vec![name], acc))
         }),
         constants: None,
-        is_in_source_code: false
+        is_in_source_code: false,
     }
 }
@@ -72,4 +72,4 @@ pub fn uncurry(programs: &mut Vec<Program>) {
     for program in programs {
         uncurry_program(program);
     }
-}
\ No newline at end of file
+}
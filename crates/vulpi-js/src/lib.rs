@@ -1,12 +1,13 @@
-use std::{borrow::Cow, vec, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, vec};
 
 use petgraph::graph::DiGraph;
 use resast::{
+    decl::Decl,
     decl::VarDecl,
     expr::*,
     pat::Pat,
     stmt::{Stmt, SwitchCase, SwitchStmt},
-    Func, FuncArg, FuncBody, Ident, ProgramPart, decl::Decl, VarKind, Program,
+    Func, FuncArg, FuncBody, Ident, Program, ProgramPart, VarKind,
 };
 use vulpi_intern::Symbol;
 use vulpi_location::Span;
@@ -100,13 +101,13 @@ impl Transform for (lambda::TagType, lambda::Case) {
 
     fn transform<'a>(self, _ctx: &mut Context<'a>) -> Self::Out<'a> {
         match self {
-            (lambda::TagType::Number(id), lambda::Case::Tuple(_)) => Expr::Lit(resast::expr::Lit::Number(
-                std::borrow::Cow::Owned(id.to_string()),
-            )),
-            (lambda::TagType::Field(id), lambda::Case::Constructor(_, _)) |
-            (lambda::TagType::Number(id), lambda::Case::Constructor(_, _)) => Expr::Lit(resast::expr::Lit::Number(
-                Cow::Owned(id.to_string()),
-            )),
+            (lambda::TagType::Number(id), lambda::Case::Tuple(_)) => Expr::Lit(
+                resast::expr::Lit::Number(std::borrow::Cow::Owned(id.to_string())),
+            ),
+            (lambda::TagType::Field(id), lambda::Case::Constructor(_, _))
+            | (lambda::TagType::Number(id), lambda::Case::Constructor(_, _)) => {
+                Expr::Lit(resast::expr::Lit::Number(Cow::Owned(id.to_string())))
+            }
             (lambda::TagType::Number(_), lambda::Case::Literal(l)) => match &*l {
                 LiteralKind::String(x) => Expr::Lit(resast::expr::Lit::String(
                     resast::expr::StringLit::Single(Cow::Owned(x.get())),
@@ -122,7 +123,7 @@ impl Transform for (lambda::TagType, lambda::Case) {
                 )),
                 LiteralKind::Unit => Expr::Lit(Lit::Number(Cow::Owned("0".to_string()))),
             },
-            _ => unreachable!()
+            _ => unreachable!(),
         }
     }
 }
@@ -181,7 +182,7 @@ impl Transform for lambda::ExprKind {
                 } else {
                     Expr::Ident(Ident::new(x.mangle()))
                 }
-            },
+            }
             lambda::ExprKind::Object(id, args) => Expr::Call(CallExpr {
                 callee: Box::new(Expr::Ident(Ident::new("obj".to_string()))),
                 arguments: vec![
@@ -200,7 +201,6 @@ impl Transform for lambda::ExprKind {
                 property: Box::new(Expr::Lit(Lit::Number(Cow::Owned(place.to_string())))),
             }),
             lambda::ExprKind::Block(statements) => {
-
                 let size = statements.len() - 1;
                 for (i, statement) in statements.into_iter().enumerate() {
                     let is_last = i == size;
@@ -280,24 +280,20 @@ impl Transform for lambda::ExprKind {
                     actions: &[lambda::Expr],
                 ) -> Stmt<'a> {
                     match switch {
-                        lambda::Tree::Leaf(x) => {
-                            context.scope(|context| {
-                                    
-                                let result = actions[x].clone().transform(context);
-                                let mut upwards = context.take_upwards();
-
-                                upwards.push(Stmt::Expr(Expr::Assign(AssignExpr {
-                                    operator: resast::AssignOp::Equal,
-                                    left: AssignLeft::Expr(Box::new(to_set)),
-                                    right: Box::new(*result),
-                                })));
-
-
-                                Stmt::Block(resast::stmt::BlockStmt(
-                                    upwards.into_iter().map(ProgramPart::Stmt).collect(),
-                                ))
-                            })
-                        }
+                        lambda::Tree::Leaf(x) => context.scope(|context| {
+                            let result = actions[x].clone().transform(context);
+                            let mut upwards = context.take_upwards();
+
+                            upwards.push(Stmt::Expr(Expr::Assign(AssignExpr {
+                                operator: resast::AssignOp::Equal,
+                                left: AssignLeft::Expr(Box::new(to_set)),
+                                right: Box::new(*result),
+                            })));
+
+                            Stmt::Block(resast::stmt::BlockStmt(
+                                upwards.into_iter().map(ProgramPart::Stmt).collect(),
+                            ))
+                        }),
                         lambda::Tree::Switch(scrutinee, branches) => {
                             let mut compiled_branches = vec![];
                             let mut tests = vec![];
@@ -324,7 +320,7 @@ impl Transform for lambda::ExprKind {
 
                             Stmt::Switch(SwitchStmt {
                                 discriminant: *tests[0].clone(),
-                                cases: compiled_branches
+                                cases: compiled_branches,
                             })
                         }
                     }
@@ -374,7 +370,10 @@ impl Transform for lambda::LetDecl {
 
                 Decl::Func(Func {
                     id: Some(Ident::new(self.name.clone().mangle())),
-                    params: param.iter().map(|x| FuncArg::Pat(pat_ident(x.clone()))).collect(),
+                    params: param
+                        .iter()
+                        .map(|x| FuncArg::Pat(pat_ident(x.clone())))
+                        .collect(),
                     body: FuncBody(upwards.into_iter().map(ProgramPart::Stmt).collect()),
                     generator: false,
                     is_async: false,
@@ -382,30 +381,41 @@ impl Transform for lambda::LetDecl {
             }
             body => {
                 let body = body.transform(ctx);
-                Decl::Var(VarKind::Let, vec![VarDecl {
-                    id: pat_ident(Symbol::intern(&self.name.clone().mangle())),
-                    init: Some(body),
-                }])
+                Decl::Var(
+                    VarKind::Let,
+                    vec![VarDecl {
+                        id: pat_ident(Symbol::intern(&self.name.clone().mangle())),
+                        init: Some(body),
+                    }],
+                )
             }
         }
     }
 }
 
 impl Transform for lambda::Program {
-    type Out<'a> = Vec<(Qualified, Vec<ProgramPart<'a>>, Option<HashMap<Qualified, Span>>)>;
+    type Out<'a> = Vec<(
+        Qualified,
+        Vec<ProgramPart<'a>>,
+        Option<HashMap<Qualified, Span>>,
+    )>;
 
     fn transform<'a>(self, ctx: &mut Context<'a>) -> Self::Out<'a> {
         let mut decls = vec![];
-        
+
         for (_, let_decl) in self.lets {
             let name = let_decl.name.clone();
             let hash_map = let_decl.constants.clone();
             let decl = let_decl.transform(ctx);
-            let mut new_decls = ctx.take_upwards().into_iter().map(ProgramPart::Stmt).collect::<Vec<_>>();
+            let mut new_decls = ctx
+                .take_upwards()
+                .into_iter()
+                .map(ProgramPart::Stmt)
+                .collect::<Vec<_>>();
             new_decls.push(ProgramPart::Decl(decl));
             decls.push((name, new_decls, hash_map));
         }
-        
+
         decls
     }
 }
@@ -436,33 +446,37 @@ impl Transform for Programs {
                 }
             }
         }
-        
+
         for program in self.0 {
             for (name, decl, dependencies) in program.transform(ctx) {
-                let from = nodes.entry(name.clone()).or_insert_with(|| {
-                    petgraph.add_node(())
-                }).clone();
+                let from = nodes
+                    .entry(name.clone())
+                    .or_insert_with(|| petgraph.add_node(()))
+                    .clone();
 
                 if let Some(dependencies) = dependencies {
                     for (to_, _) in dependencies {
-                        let to = nodes.entry(to_.clone()).or_insert_with(|| {
-                            petgraph.add_node(())
-                        });
+                        let to = nodes
+                            .entry(to_.clone())
+                            .or_insert_with(|| petgraph.add_node(()));
                         petgraph.add_edge(from, *to, ());
                     }
                 }
 
                 decls.insert(name, decl);
-            } 
+            }
         }
 
         let top_ = petgraph::algo::toposort(&petgraph, None).unwrap();
         let inv_map = nodes.iter().map(|(k, v)| (v, k)).collect::<HashMap<_, _>>();
 
-        let ordered_expr = top_.iter().rev().filter_map(|x| {
-            decls.get(&inv_map[x].clone()).cloned()
-        }).flatten().collect::<Vec<_>>();
+        let ordered_expr = top_
+            .iter()
+            .rev()
+            .filter_map(|x| decls.get(&inv_map[x].clone()).cloned())
+            .flatten()
+            .collect::<Vec<_>>();
 
         Program::Script(parts.into_iter().chain(ordered_expr.into_iter()).collect())
     }
-}
\ No newline at end of file
+}
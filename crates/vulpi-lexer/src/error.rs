@@ -6,6 +6,7 @@ use vulpi_report::IntoDiagnostic;
 /// The kind of lexing error.
 pub enum ErrorKind {
     UnfinishedString,
+    UnfinishedChar,
 }
 
 /// A lexing error.
@@ -14,10 +15,25 @@ pub struct Error {
     pub message: ErrorKind,
 }
 
+vulpi_report::codes! {
+    /// The code of each kind of lexing error.
+    LEXER_ERROR_CODES: ErrorKind {
+        UnfinishedString => UNFINISHED_STRING,
+        UnfinishedChar => UNFINISHED_CHAR,
+    }
+}
+
 impl IntoDiagnostic for Error {
+    fn code(&self) -> Option<usize> {
+        Some(self.message.code())
+    }
+
     fn message(&self) -> vulpi_report::Text {
         match self.message {
             ErrorKind::UnfinishedString => vulpi_report::Text::from("unfinished string literal"),
+            ErrorKind::UnfinishedChar => {
+                vulpi_report::Text::from("char literals must have exactly one character")
+            }
         }
     }
 
@@ -54,6 +54,11 @@ fn is_identifier_char(char: &char) -> bool {
     char.is_alphanumeric() || matches!(char, |'_'| '!' | '?' | '\'')
 }
 
+/// Checks if a char is a valid part of a number, including prefixes like `0x` and separators.
+fn is_number_char(char: &char) -> bool {
+    char.is_ascii_alphanumeric() || *char == '_'
+}
+
 /// Checks if a char is a whitespace, tab or something like that.
 fn is_whitespace(char: &char) -> bool {
     matches!(char, '\t' | '\x0C' | '\r' | ' ')
@@ -252,6 +257,9 @@ impl<'a> Lexer<'a> {
             "external" => TokenData::External,
             "trait" => TokenData::Trait,
             "impl" => TokenData::Impl,
+            "rec" => TokenData::Rec,
+            "derive" => TokenData::Derive,
+            "and" => TokenData::AndKeyword,
             _ => TokenData::LowerIdent,
         }
     }
@@ -355,6 +363,8 @@ impl<'a> Lexer<'a> {
                     }
                 }
                 '~' => TokenData::Tilde,
+                '?' => TokenData::Question,
+                '@' => TokenData::At,
                 '!' => {
                     if let Some('=') = self.peekable.peek() {
                         self.advance();
@@ -379,16 +389,33 @@ impl<'a> Lexer<'a> {
                 ',' => TokenData::Comma,
                 '.' => TokenData::Dot,
                 '0'..='9' => {
-                    self.accumulate(|char| char.is_ascii_digit());
-                    if let Some('.') = self.peekable.peek() {
+                    // Prefixes, separators and malformed digits are part of the token so the
+                    // resolver can check the whole literal.
+                    // A number right after a dot is the index of a tuple accessor, so `t.0.1` is
+                    // two accessors instead of a float.
+                    let index = self.input[..self.state.start].ends_with('.');
+
+                    self.accumulate(is_number_char);
+                    if let (false, Some('.')) = (index, self.peekable.peek()) {
                         self.advance();
-                        self.accumulate(|char| char.is_ascii_digit());
+                        self.accumulate(is_number_char);
+
+                        let text = &self.input[self.state.start..self.state.index];
+
+                        if text.ends_with(['e', 'E']) {
+                            if let Some('+' | '-') = self.peekable.peek() {
+                                self.advance();
+                                self.accumulate(is_number_char);
+                            }
+                        }
+
                         TokenData::Float
                     } else {
                         TokenData::Int
                     }
                 }
                 '"' => return self.string(),
+                '\'' => return self.char_literal(),
                 'A'..='Z' => {
                     self.accumulate(is_identifier_char);
                     TokenData::UpperIdent
@@ -1,6 +1,6 @@
 //! Lexing of literals like strings, integers, floats, etc.
 
-use vulpi_intern::Symbol;
+use vulpi_intern::{well_known, Symbol};
 use vulpi_syntax::tokens::TokenData;
 
 use crate::{error::ErrorKind, Lexer};
@@ -35,6 +35,31 @@ impl<'a> Lexer<'a> {
         Some(result)
     }
 
+    /// Lexes a char literal like `'a'` or `'\n'` after its opening quote.
+    pub(crate) fn char_literal(&mut self) -> (TokenData, Symbol) {
+        let char = match self.peekable.peek() {
+            Some('\'' | '\n') | None => None,
+            Some(_) => self.char(),
+        };
+
+        match (char, self.peekable.peek()) {
+            (Some(char), Some('\'')) => {
+                self.advance();
+                (TokenData::Char, Symbol::intern(&char.to_string()))
+            }
+            _ => {
+                self.accumulate(|x| *x != '\'' && *x != '\n');
+
+                if let Some('\'') = self.peekable.peek() {
+                    self.advance();
+                }
+
+                self.report(ErrorKind::UnfinishedChar);
+                (TokenData::Error, well_known::EMPTY)
+            }
+        }
+    }
+
     pub(crate) fn string(&mut self) -> (TokenData, Symbol) {
         let mut string = String::new();
 
@@ -0,0 +1,137 @@
+//! Conversion from bytes to lines and columns. It's built once per file so every consumer of
+//! [crate::Span] does not have to find the line breaks by itself.
+
+use std::ops::Range;
+
+use crate::Byte;
+
+/// A character that takes more than one byte in UTF-8. They are stored so columns can be counted
+/// in characters or in UTF-16 code units without the source code.
+#[derive(Clone, Debug)]
+struct WideChar {
+    /// Offset of the character from the start of the line.
+    offset: usize,
+    utf8: usize,
+    utf16: usize,
+}
+
+/// The start of each line of a file together with the wide characters that they contain.
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    starts: Vec<usize>,
+    wide: Vec<Vec<WideChar>>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(content: &str) -> Self {
+        let mut starts = vec![0];
+        let mut wide = vec![vec![]];
+
+        for (i, c) in content.char_indices() {
+            if c == '\n' {
+                starts.push(i + 1);
+                wide.push(vec![]);
+            } else if c.len_utf8() > 1 {
+                wide.last_mut().unwrap().push(WideChar {
+                    offset: i - starts.last().unwrap(),
+                    utf8: c.len_utf8(),
+                    utf16: c.len_utf16(),
+                });
+            }
+        }
+
+        Self {
+            starts,
+            wide,
+            len: content.len(),
+        }
+    }
+
+    /// The number of lines. A file that ends with a line break has an empty line at the end.
+    pub fn lines(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// The byte in which a line starts.
+    pub fn line_start(&self, line: usize) -> Option<Byte> {
+        self.starts.get(line).map(|start| Byte(*start))
+    }
+
+    fn line_and_offset(&self, byte: &Byte) -> (usize, usize) {
+        let byte = byte.0.min(self.len);
+        let line = self.starts.partition_point(|start| *start <= byte) - 1;
+        (line, byte - self.starts[line])
+    }
+
+    /// The zero based line and column of a byte, with the column counted in characters.
+    pub fn position(&self, byte: Byte) -> (usize, usize) {
+        let (line, offset) = self.line_and_offset(&byte);
+
+        let column = self.wide[line]
+            .iter()
+            .take_while(|wide| wide.offset < offset)
+            .fold(offset, |column, wide| column - (wide.utf8 - 1));
+
+        (line, column)
+    }
+
+    /// The zero based line and column of a byte, with the column counted in UTF-16 code units like
+    /// the language server protocol expects.
+    pub fn position_utf16(&self, byte: Byte) -> (usize, usize) {
+        let (line, offset) = self.line_and_offset(&byte);
+
+        let column = self.wide[line]
+            .iter()
+            .take_while(|wide| wide.offset < offset)
+            .fold(offset, |column, wide| column - (wide.utf8 - wide.utf16));
+
+        (line, column)
+    }
+
+    /// The positions of the start and of the end of a range.
+    pub fn range(&self, range: Range<Byte>) -> ((usize, usize), (usize, usize)) {
+        (self.position(range.start), self.position(range.end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_lines_and_columns() {
+        let index = LineIndex::new("let x = 1\nlet y = 2\n");
+
+        assert_eq!(index.position(Byte(0)), (0, 0));
+        assert_eq!(index.position(Byte(4)), (0, 4));
+        assert_eq!(index.position(Byte(10)), (1, 0));
+        assert_eq!(index.position(Byte(20)), (2, 0));
+        assert_eq!(index.lines(), 3);
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let index = LineIndex::new("let x = 1\r\nlet y = 2\r\n");
+
+        assert_eq!(index.position(Byte(9)), (0, 9));
+        assert_eq!(index.position(Byte(11)), (1, 0));
+        assert_eq!(index.range(Byte(15)..Byte(20)), ((1, 4), (1, 9)));
+    }
+
+    #[test]
+    fn multi_byte_characters() {
+        let content = "let café = \"𝕍\"\nlet é = 2";
+        let index = LineIndex::new(content);
+
+        let after_cafe = content.find(" =").unwrap();
+        assert_eq!(index.position(Byte(after_cafe)), (0, 8));
+
+        let after_v = content.find("\"\n").unwrap();
+        assert_eq!(index.position(Byte(after_v)), (0, 13));
+        assert_eq!(index.position_utf16(Byte(after_v)), (0, 14));
+
+        let after_e = content.rfind(" =").unwrap();
+        assert_eq!(index.position(Byte(after_e)), (1, 5));
+    }
+}
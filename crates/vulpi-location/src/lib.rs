@@ -5,12 +5,20 @@ use std::fmt::Debug;
 
 use vulpi_show::{Show, TreeDisplay};
 
+mod hash;
+mod index;
+
+pub use hash::stable_hash;
+pub use index::LineIndex;
+
 /// A new-type for a usize. It's used to locate a byte inside a source code.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Byte(pub usize);
 
 /// A span that locates a piece of data inside a source code.
-#[derive(Clone, Default)]
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     pub file: FileId,
     pub start: Byte,
@@ -24,7 +32,6 @@ impl Show for Span {
             self.start.0, self.end.0
         )))
     }
-    
 }
 
 impl Span {
@@ -63,10 +70,27 @@ impl Span {
             end: std::cmp::max(self.end, other.end),
         }
     }
+
+    /// The smallest span that covers both spans. It returns [None] if they are in different
+    /// files.
+    pub fn merge(&self, other: &Span) -> Option<Span> {
+        if self.file == other.file {
+            Some(self.clone().mix(other.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Checks if a byte is inside of the span. The end is included so a cursor right after a
+    /// token is still inside of it.
+    pub fn contains(&self, byte: Byte) -> bool {
+        self.start <= byte && byte <= self.end
+    }
 }
 
 /// A span that locates a piece of data inside a source code.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Spanned<T> {
     pub data: T,
     pub span: Span,
@@ -97,6 +121,13 @@ impl<T> Spanned<T> {
             span: self.span,
         }
     }
+
+    pub fn map_range(self, f: impl FnOnce(Span) -> Span) -> Spanned<T> {
+        Spanned {
+            data: self.data,
+            span: f(self.span),
+        }
+    }
 }
 
 impl<T: Debug> Debug for Spanned<T> {
@@ -113,4 +144,44 @@ impl<T> Spanned<T> {
 
 /// The identifier of a file.
 #[derive(Clone, Default, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FileId(pub usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_adjacent_spans() {
+        let left = Span::from_usize(FileId(0), 0, 4);
+        let right = Span::from_usize(FileId(0), 4, 9);
+
+        let merged = left.merge(&right).unwrap();
+
+        assert_eq!((merged.start, merged.end), (Byte(0), Byte(9)));
+    }
+
+    #[test]
+    fn merges_overlapping_spans() {
+        let outer = Span::from_usize(FileId(0), 2, 12);
+        let inner = Span::from_usize(FileId(0), 5, 14);
+
+        let merged = inner.merge(&outer).unwrap();
+
+        assert_eq!(
+            (merged.start.clone(), merged.end.clone()),
+            (Byte(2), Byte(14))
+        );
+        assert!(merged.contains(Byte(2)));
+        assert!(merged.contains(Byte(14)));
+        assert!(!merged.contains(Byte(15)));
+    }
+
+    #[test]
+    fn does_not_merge_spans_of_different_files() {
+        let left = Span::from_usize(FileId(0), 0, 4);
+        let right = Span::from_usize(FileId(1), 4, 9);
+
+        assert!(left.merge(&right).is_none());
+    }
+}
@@ -7,7 +7,18 @@ pub enum ParserError {
     UnexpectedToken(Box<Token>, Span),
 }
 
+vulpi_report::codes! {
+    /// The code of each kind of parser error.
+    PARSER_ERROR_CODES: ParserError {
+        UnexpectedToken => UNEXPECTED_TOKEN,
+    }
+}
+
 impl IntoDiagnostic for ParserError {
+    fn code(&self) -> Option<usize> {
+        Some(ParserError::code(self))
+    }
+
     fn message(&self) -> vulpi_report::Text {
         match self {
             ParserError::UnexpectedToken(token, _) => {
@@ -3,15 +3,21 @@ use crate::{Parser, Result};
 use vulpi_location::Spanned;
 use vulpi_syntax::{
     concrete::{tree::*, Either, Path, Upper},
-    tokens::TokenData,
+    tokens::{Token, TokenData},
 };
 
 impl<'a> Parser<'a> {
     pub fn record_field(&mut self) -> Result<RecordField> {
         let name = self.lower()?;
-        let eq = self.expect(TokenData::Equal)?;
-        let expr = self.expr()?;
-        Ok(RecordField { name, eq, expr })
+
+        let value = if self.at(TokenData::Equal) {
+            let eq = self.bump();
+            Some((eq, self.expr()?))
+        } else {
+            None
+        };
+
+        Ok(RecordField { name, value })
     }
 
     pub fn record_instance(&mut self, name: Path<Upper>) -> Result<RecordInstance> {
@@ -41,16 +47,28 @@ impl<'a> Parser<'a> {
     pub fn let_sttm(&mut self) -> Result<LetSttm> {
         let let_ = self.expect(TokenData::Let)?;
         let pattern = self.pattern()?;
+        let typ = self.let_annotation()?;
         let eq = self.expect(TokenData::Equal)?;
         let expr = self.expr()?;
         Ok(LetSttm {
             let_,
             pattern,
+            typ,
             eq,
             expr,
         })
     }
 
+    /// Parses the optional type of a local binding like the `: Int` of `let x : Int = 1`.
+    pub fn let_annotation(&mut self) -> Result<Option<(Token, Box<Type>)>> {
+        if self.at(TokenData::Colon) {
+            let colon = self.bump();
+            Ok(Some((colon, self.typ()?)))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn statement_kind(&mut self) -> Result<StatementKind> {
         match self.token() {
             TokenData::Let => self.let_sttm().map(StatementKind::Let),
@@ -84,12 +102,12 @@ impl<'a> Parser<'a> {
 
         Ok(statements)
     }
-    
+
     pub fn list_expr(&mut self) -> Result<ListExpr> {
         let left_bracket = self.expect(TokenData::LBracket)?;
         let values = self.sep_by(TokenData::Comma, Self::expr)?;
         let right_bracket = self.expect(TokenData::RBracket)?;
-        
+
         Ok(ListExpr {
             left_bracket,
             values,
@@ -97,9 +115,16 @@ impl<'a> Parser<'a> {
         })
     }
 
+    pub fn typed_hole(&mut self) -> Result<TypedHole> {
+        let question = self.expect(TokenData::Question)?;
+        let name = self.lower()?;
+        Ok(TypedHole { question, name })
+    }
+
     pub fn expr_atom_kind(&mut self) -> Result<ExprKind> {
         match self.token() {
             TokenData::LBracket => Ok(ExprKind::List(self.list_expr()?)),
+            TokenData::Question => Ok(ExprKind::Hole(self.typed_hole()?)),
             TokenData::Less => Ok(ExprKind::HtmlNode(self.html_node()?)),
             TokenData::UpperIdent | TokenData::LowerIdent => {
                 let path = self.path_ident()?;
@@ -253,30 +278,95 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    /// Parses a chain of accessors like `a.b.0`. Each accessor spans from the start of the chain
+    /// to the end of its own segment.
     pub fn acessor(&mut self) -> Result<Box<Expr>> {
-        let left = self.expr_atom()?;
-        if self.at(TokenData::Dot) {
+        let mut left = self.expr_atom()?;
+        let start = left.span.clone();
+
+        while self.at(TokenData::Dot) {
             let dot = self.bump();
-            let field = self.lower()?;
-            let range = self.with_span(left.span.clone());
-            Ok(Box::new(Spanned {
-                span: range,
-                data: ExprKind::Projection(ProjectionExpr {
+
+            let data = if self.at(TokenData::Int) {
+                ExprKind::TupleProjection(TupleProjectionExpr {
                     expr: left,
                     dot,
-                    field,
+                    index: self.bump(),
+                })
+            } else {
+                ExprKind::Projection(ProjectionExpr {
+                    expr: left,
+                    dot,
+                    field: self.lower()?,
+                })
+            };
+
+            left = Box::new(Spanned {
+                span: self.with_span(start.clone()),
+                data,
+            });
+        }
+
+        self.expr_type_application(left)
+    }
+
+    /// Parses the explicit type arguments like `@Int` that come after an expression. An `@` that
+    /// starts a line is the attribute of the next declaration instead.
+    pub fn expr_type_application(&mut self, mut left: Box<Expr>) -> Result<Box<Expr>> {
+        while self.at(TokenData::At) && !self.at_line_start() {
+            let at = self.bump();
+            let typ = self.type_atom()?;
+            left = Box::new(Spanned {
+                span: left.span.clone().mix(typ.span.clone()),
+                data: ExprKind::TypeApplication(TypeApplicationExpr {
+                    expr: left,
+                    at,
+                    typ,
                 }),
-            }))
-        } else {
-            Ok(left)
+            });
         }
+        Ok(left)
+    }
+
+    pub fn let_and(&mut self) -> Result<LetAnd> {
+        let and = self.expect(TokenData::AndKeyword)?;
+        let pattern = self.pattern()?;
+        let eq = self.expect(TokenData::Equal)?;
+        let body = self.expr()?;
+        Ok(LetAnd {
+            and,
+            pattern,
+            eq,
+            body,
+        })
     }
 
     pub fn let_expr(&mut self) -> Result<Box<Expr>> {
         let let_ = self.expect(TokenData::Let)?;
+        let rec = if self.at(TokenData::Rec) {
+            Some(self.bump())
+        } else {
+            None
+        };
         let pattern = self.pattern()?;
+
+        // The bindings of a `let rec` are not annotated, their types come from their bodies.
+        let typ = if rec.is_none() {
+            self.let_annotation()?
+        } else {
+            None
+        };
+
         let eq = self.expect(TokenData::Equal)?;
         let value = self.expr()?;
+
+        // Only the bindings of a `let rec` can be grouped.
+        let group = if rec.is_some() {
+            self.many(Self::let_and)?
+        } else {
+            Vec::new()
+        };
+
         let in_ = self.expect(TokenData::In)?;
         let body = self.expr()?;
 
@@ -286,9 +376,12 @@ impl<'a> Parser<'a> {
             span: range,
             data: ExprKind::Let(LetExpr {
                 let_,
+                rec,
                 pattern,
+                typ,
                 eq,
                 body: value,
+                group,
                 in_,
                 value: body,
             }),
@@ -301,7 +394,7 @@ impl<'a> Parser<'a> {
         let value = self.expr_atom()?;
         Ok(Attribute { name, eq, value })
     }
-    
+
     pub fn html_node(&mut self) -> Result<HtmlNode> {
         let left_angle = self.expect(TokenData::Less)?;
         let name = self.lower()?;
@@ -311,7 +404,7 @@ impl<'a> Parser<'a> {
         let left_angle_slash = self.expect(TokenData::LessSlash)?;
         let name_end = self.lower()?;
         let right_angle_end = self.expect(TokenData::Greater)?;
-        
+
         Ok(HtmlNode {
             left_angle,
             name,
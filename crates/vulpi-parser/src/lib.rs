@@ -65,6 +65,17 @@ impl<'a> Parser<'a> {
 
         self.last_pos = ret.value.span.clone();
 
+        // Layout tokens are not kept in the tree, so their comments go to the token after them.
+        if matches!(ret.kind, TokenData::Begin | TokenData::Sep | TokenData::End) {
+            let mut comments = std::mem::take(&mut ret.comments);
+            comments.append(&mut self.current.comments);
+            self.current.comments = comments;
+
+            if self.current.whitespace.data.get().is_empty() {
+                self.current.whitespace = ret.whitespace.clone();
+            }
+        }
+
         ret
     }
 
@@ -125,6 +136,12 @@ impl<'a> Parser<'a> {
         self.peek().kind == token
     }
 
+    /// Returns true if the current token starts a line that is not indented, like the top level
+    /// declarations do.
+    pub fn at_line_start(&self) -> bool {
+        self.peek().whitespace.data.get().ends_with('\n')
+    }
+
     /// Returns true if the next token matches the given one.
     pub fn then(&self, token: TokenData) -> bool {
         self.next.kind == token
@@ -1,5 +1,5 @@
 use vulpi_syntax::{
-    concrete::{pattern::*, Either},
+    concrete::{pattern::*, Either, Path, Upper},
     tokens::TokenData,
 };
 
@@ -34,10 +34,44 @@ impl<'a> Parser<'a> {
         Ok(PatApplication { func, args })
     }
 
+    pub fn pattern_field(&mut self) -> Result<PatField> {
+        let name = self.lower()?;
+
+        let pattern = if self.at(TokenData::Equal) {
+            let eq = self.bump();
+            Some((eq, self.pattern()?))
+        } else {
+            None
+        };
+
+        Ok(PatField { name, pattern })
+    }
+
+    pub fn pattern_record(&mut self, name: Path<Upper>) -> Result<PatRecord> {
+        let left_brace = self.expect(TokenData::LBrace)?;
+        let fields = self.sep_by(TokenData::Comma, Self::pattern_field)?;
+        let right_brace = self.expect(TokenData::RBrace)?;
+
+        Ok(PatRecord {
+            name,
+            left_brace,
+            fields,
+            right_brace,
+        })
+    }
+
     pub fn pattern_application(&mut self) -> Result<Box<Pattern>> {
         if self.at(TokenData::UpperIdent) {
             self.spanned(|this| {
-                let result = this.pattern_application_kind()?;
+                let func = this.path_upper()?;
+
+                if this.at(TokenData::LBrace) {
+                    return this.pattern_record(func).map(PatternKind::Record);
+                }
+
+                let args = this.many(Self::pattern_atom)?;
+                let result = PatApplication { func, args };
+
                 if result.args.is_empty() {
                     Ok(PatternKind::Constructor(result.func))
                 } else {
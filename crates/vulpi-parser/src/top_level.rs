@@ -1,7 +1,21 @@
-use vulpi_syntax::{concrete::top_level::*, tokens::TokenData};
+use vulpi_syntax::{
+    concrete::{top_level::*, Lower},
+    tokens::TokenData,
+};
 
 use crate::{Parser, Result};
 
+/// The tokens that start the declarations where the parser continues after an error.
+const TOP_LEVEL: &[TokenData] = &[TokenData::Let, TokenData::Type, TokenData::Use];
+
+/// The tokens where the parser continues after an error in a clause of a let declaration.
+const CLAUSE: &[TokenData] = &[
+    TokenData::Bar,
+    TokenData::Let,
+    TokenData::Type,
+    TokenData::Use,
+];
+
 impl<'a> Parser<'a> {
     pub fn binder(&mut self) -> Result<Binder> {
         let left_paren = self.expect(TokenData::LPar)?;
@@ -57,26 +71,60 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parses a clause of a let declaration. A clause that cannot be parsed is reported and kept
+    /// as an error, so the other clauses are still checked.
     pub fn let_case(&mut self) -> Result<LetCase> {
         let pipe = self.expect(TokenData::Bar)?;
-        let arm = self.pattern_arm()?;
+
+        let arm = match self.pattern_arm() {
+            Ok(arm) => LetCaseKind::Arm(Box::new(arm)),
+            Err(err) => {
+                self.report(err);
+                LetCaseKind::Error(self.recover(CLAUSE))
+            }
+        };
+
         Ok(LetCase { pipe, arm })
     }
 
-    pub fn let_decl(&mut self, visibility: Visibility) -> Result<LetDecl> {
+    pub fn let_decl(
+        &mut self,
+        attributes: Vec<DeclAttribute>,
+        visibility: Visibility,
+    ) -> Result<LetDecl> {
         let signature = self.let_signature(visibility)?;
 
+        // Once the signature is parsed, errors in the body keep the declaration, so the places
+        // that use it don't report that it cannot be found.
         let body = if self.at(TokenData::Equal) {
             let eq = self.expect(TokenData::Equal)?;
-            let expr = self.expr()?;
-            LetMode::Body(eq, expr)
+
+            match self.expr() {
+                Ok(expr) => LetMode::Body(eq, expr),
+                Err(err) => {
+                    self.report(err);
+                    let mut tokens = vec![eq];
+                    tokens.extend(self.recover(TOP_LEVEL));
+                    LetMode::Error(tokens)
+                }
+            }
         } else if self.at(TokenData::Bar) {
-            LetMode::Cases(self.many(Self::let_case)?)
+            let mut cases = Vec::new();
+
+            while self.at(TokenData::Bar) {
+                cases.push(self.let_case()?);
+            }
+
+            LetMode::Cases(cases)
         } else {
             self.unexpected()?
         };
 
-        Ok(LetDecl { signature, body })
+        Ok(LetDecl {
+            attributes,
+            signature,
+            body,
+        })
     }
 
     fn trait_decl(&mut self, visibility: Visibility) -> Result<TraitDecl> {
@@ -103,7 +151,7 @@ impl<'a> Parser<'a> {
         let name = self.path_upper()?;
         let types = self.many(Self::type_atom)?;
         let where_ = self.expect(TokenData::Where)?;
-        let body = self.block(|ctx| ctx.let_decl(Visibility::Private))?;
+        let body = self.block(|ctx| ctx.let_decl(Vec::new(), Visibility::Private))?;
         Ok(TraitImpl {
             impl_,
             supers,
@@ -116,7 +164,12 @@ impl<'a> Parser<'a> {
 
     fn let_signature(&mut self, visibility: Visibility) -> Result<LetSignature> {
         let let_ = self.expect(TokenData::Let)?;
-        let name = self.lower()?;
+
+        let name = if self.at(TokenData::Wildcard) {
+            Lower(self.bump())
+        } else {
+            self.lower()?
+        };
         let binders = self.many(Self::let_binder)?;
         let ret = if self.at(TokenData::Colon) {
             let colon = self.bump();
@@ -136,9 +189,16 @@ impl<'a> Parser<'a> {
     }
 
     pub fn constructor_decl(&mut self) -> Result<Constructor> {
+        let attributes = self.many(Self::decl_attribute)?;
         let pipe = self.expect(TokenData::Bar)?;
+        let visibility = self.visibility()?;
         let name = self.upper()?;
-        let args = self.many(Self::type_atom)?;
+
+        let (args, fields) = if self.at(TokenData::LBrace) {
+            (Vec::new(), Some(self.record_decl()?))
+        } else {
+            (self.many(Self::type_atom)?, None)
+        };
 
         let typ = if self.at(TokenData::Colon) {
             let colon = self.bump();
@@ -149,9 +209,12 @@ impl<'a> Parser<'a> {
         };
 
         Ok(Constructor {
+            attributes,
             pipe,
+            visibility,
             name,
             args,
+            fields,
             typ,
         })
     }
@@ -197,13 +260,17 @@ impl<'a> Parser<'a> {
 
     pub fn type_def(&mut self) -> Result<TypeDef> {
         match self.token() {
-            TokenData::Bar => self.sum_decl().map(TypeDef::Sum),
+            TokenData::Bar | TokenData::At => self.sum_decl().map(TypeDef::Sum),
             TokenData::LBrace => self.record_decl().map(TypeDef::Record),
             _ => self.type_atom().map(TypeDef::Synonym),
         }
     }
 
-    pub fn type_decl(&mut self, visibility: Visibility) -> Result<TypeDecl> {
+    pub fn type_decl(
+        &mut self,
+        attributes: Vec<DeclAttribute>,
+        visibility: Visibility,
+    ) -> Result<TypeDecl> {
         let type_ = self.expect(TokenData::Type)?;
         let name = self.upper()?;
         let binders = self.many(Self::type_binder)?;
@@ -216,15 +283,47 @@ impl<'a> Parser<'a> {
             None
         };
 
+        let deriving = if self.at(TokenData::Derive) {
+            Some(self.deriving()?)
+        } else {
+            None
+        };
+
         Ok(TypeDecl {
+            attributes,
             type_,
             name,
             binders,
             def,
+            deriving,
             visibility,
         })
     }
 
+    pub fn deriving(&mut self) -> Result<Deriving> {
+        let derive = self.expect(TokenData::Derive)?;
+        let names = self.parenthesis(|this| this.sep_by(TokenData::Comma, Self::upper))?;
+        Ok(Deriving { derive, names })
+    }
+
+    pub fn decl_attribute(&mut self) -> Result<DeclAttribute> {
+        let at = self.expect(TokenData::At)?;
+        let name = self.lower()?;
+
+        let args = if self.at(TokenData::LPar) {
+            Some(self.parenthesis(|this| this.sep_by(TokenData::Comma, Self::literal))?)
+        } else {
+            None
+        };
+
+        // Inside of a block, the declaration after the attribute is in the same column.
+        if self.at(TokenData::Sep) {
+            self.bump();
+        }
+
+        Ok(DeclAttribute { at, name, args })
+    }
+
     pub fn use_alias(&mut self) -> Result<UseAlias> {
         let as_ = self.expect(TokenData::As)?;
         let alias = self.upper()?;
@@ -302,10 +401,23 @@ impl<'a> Parser<'a> {
     }
 
     pub fn top_level(&mut self) -> Result<TopLevel> {
+        let attributes = self.many(Self::decl_attribute)?;
         let vis = self.visibility()?;
+
+        // Only lets and types take attributes.
+        if !attributes.is_empty() && !self.at_any(&[TokenData::Let, TokenData::Type]) {
+            return self.unexpected();
+        }
+
         match self.token() {
-            TokenData::Let => self.let_decl(vis).map(Box::new).map(TopLevel::Let),
-            TokenData::Type => self.type_decl(vis).map(Box::new).map(TopLevel::Type),
+            TokenData::Let => self
+                .let_decl(attributes, vis)
+                .map(Box::new)
+                .map(TopLevel::Let),
+            TokenData::Type => self
+                .type_decl(attributes, vis)
+                .map(Box::new)
+                .map(TopLevel::Type),
             TokenData::Use => self.use_decl(vis).map(Box::new).map(TopLevel::Use),
             TokenData::Impl => self.trait_impl().map(Box::new).map(TopLevel::Impl),
             TokenData::Trait => self.trait_decl(vis).map(Box::new).map(TopLevel::Trait),
@@ -327,7 +439,7 @@ impl<'a> Parser<'a> {
                 Ok(top_level) => top_levels.push(top_level),
                 Err(err) => {
                     self.report(err);
-                    let errs = self.recover(&[TokenData::Let, TokenData::Type, TokenData::Use]);
+                    let errs = self.recover(TOP_LEVEL);
                     top_levels.push(TopLevel::Error(errs))
                 }
             }
@@ -0,0 +1,207 @@
+use std::path::Path;
+
+use vulpi_location::FileId;
+use vulpi_report::hash_reporter;
+use vulpi_show::{Show, TreeDisplay};
+use vulpi_syntax::{concrete::tree::Program, format};
+
+const WIDTH: usize = 80;
+
+fn parse(source: &str) -> Option<Program> {
+    let report = hash_reporter();
+    let program = vulpi_parser::parse(report.clone(), FileId(0), source);
+    (!report.has_errors()).then_some(program)
+}
+
+/// The tree of a program without the locations, that change with the formatting.
+fn structure(tree: TreeDisplay) -> TreeDisplay {
+    let is_location = |label: &str| {
+        label == "Span"
+            || label.split_once('~').is_some_and(|(start, end)| {
+                start.parse::<usize>().is_ok() && end.parse::<usize>().is_ok()
+            })
+    };
+
+    TreeDisplay {
+        children: tree
+            .children
+            .into_iter()
+            .filter(|child| !is_location(&child.label))
+            .map(structure)
+            .collect(),
+        ..tree
+    }
+}
+
+fn corpus() -> Vec<(String, String)> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let mut files = Vec::new();
+
+    for dir in [root.join("vulpi-tests/suite"), root.join("../example")] {
+        for entry in std::fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+
+            if path.extension().is_some_and(|ext| ext == "vp") {
+                let source = std::fs::read_to_string(&path).unwrap();
+                files.push((path.display().to_string(), source));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+#[test]
+fn formatting_keeps_the_structure_of_the_corpus() {
+    let mut checked = 0;
+
+    for (name, source) in corpus() {
+        // Files with syntax errors are only in the corpus to test the parser.
+        let Some(program) = parse(&source) else {
+            continue;
+        };
+
+        let formatted = format(&program, WIDTH);
+
+        let reparsed = parse(&formatted)
+            .unwrap_or_else(|| panic!("the formatted {name} does not parse:\n{formatted}"));
+
+        assert_eq!(
+            structure(program.show()).to_string(),
+            structure(reparsed.show()).to_string(),
+            "the formatted {name} has another structure:\n{formatted}"
+        );
+
+        assert_eq!(
+            format(&reparsed, WIDTH),
+            formatted,
+            "formatting {name} twice changes it"
+        );
+
+        checked += 1;
+    }
+
+    assert!(checked > 10, "only {checked} files were checked");
+}
+
+#[test]
+fn comments_and_blocks_are_kept() {
+    let source = "-- The header\n\
+                  use Prelude -- trailing\n\
+                  \n\
+                  let choose (x: Bool) : Int =\n    \
+                      when x is\n        \
+                          -- first arm\n        \
+                          True   =>   1\n        \
+                          False => 2\n\
+                  let piped = (when x is\n    \
+                      A => 1\n  \
+                  ) |> show\n\
+                  -- the end\n";
+
+    let formatted = format(&parse(source).unwrap(), WIDTH);
+
+    assert_eq!(
+        formatted,
+        "-- The header\n\
+         use Prelude -- trailing\n\
+         \n\
+         let choose (x: Bool) : Int =\n  \
+           when x is\n    \
+             -- first arm\n    \
+             True => 1\n    \
+             False => 2\n\
+         let piped =\n  \
+           (when x is\n    \
+             A => 1\n  \
+           )\n    \
+             |> show\n\
+         -- the end\n"
+    );
+}
+
+#[test]
+fn long_applications_are_broken() {
+    let source = "let main = do\n  \
+                  call someLongArgument anotherLongArgument yetAnotherLongArgument\n";
+
+    let formatted = format(&parse(source).unwrap(), 40);
+
+    assert_eq!(
+        formatted,
+        "let main = do\n  \
+           call\n    \
+             someLongArgument\n    \
+             anotherLongArgument\n    \
+             yetAnotherLongArgument\n"
+    );
+}
+
+#[test]
+fn recursive_groups_put_each_binding_in_its_own_line() {
+    let source = "let main =\n  \
+                  let rec even = \\n => odd n and odd = \\n => even n in even 2\n";
+
+    let formatted = format(&parse(source).unwrap(), WIDTH);
+
+    assert_eq!(
+        formatted,
+        "let main =\n  \
+           let rec even = \\n => odd n\n  \
+           and odd = \\n => even n\n  \
+           in even 2\n"
+    );
+}
+
+#[test]
+fn labeled_constructors_keep_their_fields() {
+    let source = "type Shape =\n  \
+                  | Rect { width : Int, height : Int }\n  \
+                  | Square Int\n\
+                  let width (s: Shape) : Int =\n  \
+                  when s is\n    \
+                  Shape.Rect { width, height = _ } => width\n    \
+                  Shape.Square side => side\n";
+
+    let formatted = format(&parse(source).unwrap(), WIDTH);
+
+    assert_eq!(formatted, format(&parse(&formatted).unwrap(), WIDTH));
+    assert!(formatted.contains("| Rect { width : Int, height : Int }"));
+    assert!(formatted.contains("Shape.Rect { width, height = _ } => width"));
+}
+
+#[test]
+fn deriving_clauses_are_kept() {
+    let source = "type List a =\n  \
+                  | Nil\n  \
+                  | Cons a (List a)\n  \
+                  derive (Eq, Show)\n\
+                  type Point = { x : Int, y : Int } derive (Eq)\n";
+
+    let formatted = format(&parse(source).unwrap(), WIDTH);
+
+    assert_eq!(formatted, format(&parse(&formatted).unwrap(), WIDTH));
+    assert!(formatted.contains("derive (Eq, Show)"));
+    assert!(formatted.contains("} derive (Eq)"));
+}
+
+#[test]
+fn attributes_are_kept_before_their_declarations() {
+    let source = "--- Old.\n\
+                  @deprecated(\"use new\")   @inline\n\
+                  pub let old : Int = 1\n\
+                  @deprecated(\"gone\", 2)\n\
+                  type Gone\n\
+                  type Shape =\n  \
+                  | Square Int\n  \
+                  @deprecated(\"use Square\")\n  \
+                  | Rect Int Int\n";
+
+    let formatted = format(&parse(source).unwrap(), WIDTH);
+
+    assert_eq!(formatted, format(&parse(&formatted).unwrap(), WIDTH));
+    assert!(formatted.starts_with("--- Old.\n@deprecated(\"use new\")\n@inline\npub let old"));
+    assert!(formatted.contains("@deprecated(\"gone\", 2)\ntype Gone"));
+    assert!(formatted.contains("  @deprecated(\"use Square\")\n  | Rect Int Int"));
+}
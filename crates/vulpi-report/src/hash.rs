@@ -1,6 +1,6 @@
 //! Simple reporter for diagnostics using a hashmap to store things.
 
-use crate::{Diagnostic, Reporter};
+use crate::{Diagnostic, Reporter, Severity};
 use std::collections::HashMap;
 use vulpi_location::FileId;
 
@@ -18,7 +18,7 @@ impl HashReporter {
 
 impl Reporter for HashReporter {
     fn report(&mut self, diagnostic: Diagnostic) {
-        self.errored = true;
+        self.errored |= diagnostic.severity() == Severity::Error;
         self.map
             .entry(diagnostic.location().file)
             .or_default()
@@ -1,15 +1,26 @@
 //! Module for handling errors that can occur during the compilation process. It's used to report
 //! errors to the user.
 
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Display,
+    rc::Rc,
+    sync::Arc,
+};
 
 use renderer::{classic::Classic, Renderer};
+use sink::Sink;
 use vulpi_location::{FileId, Span};
 
 pub mod hash;
+pub mod registry;
 pub mod renderer;
+pub mod sink;
+
+pub use registry::explain;
 
 /// A type for representing the severity of a [Diagnostic].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum Severity {
     Error,
     Warning,
@@ -18,6 +29,7 @@ pub enum Severity {
 
 /// A type for representing the color of a [Word]. It's all numerated because it's easier to change
 /// the color of a word according to what the user wants.
+#[derive(Clone)]
 pub enum Color {
     Fst,
     Snd,
@@ -26,6 +38,7 @@ pub enum Color {
 }
 
 /// A type for representing the style of a [Word].
+#[derive(Clone)]
 pub enum Style {
     Bold,
     Dimmed,
@@ -33,9 +46,11 @@ pub enum Style {
 }
 
 /// A type for representing a word in a [Text].
+#[derive(Clone)]
 pub struct Word(Style, Color, String);
 
 /// A type for representing a text. It's used to generate error messages.
+#[derive(Clone)]
 pub enum Text {
     Phrase(Vec<Word>),
     Styled(Style, String),
@@ -56,15 +71,32 @@ impl From<String> for Text {
     }
 }
 
+impl Display for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Text::Phrase(words) => {
+                let words = words.iter().map(|Word(_, _, text)| text.as_str());
+                write!(f, "{}", words.collect::<Vec<_>>().join(" "))
+            }
+            Text::Styled(_, text) | Text::Colored(_, text) | Text::Text(text) => {
+                write!(f, "{text}")
+            }
+            Text::Break => writeln!(f),
+        }
+    }
+}
+
 /// A position in the source code that has or not a message. It's used to generate underlined parts
 /// with messages.
+#[derive(Clone)]
 pub struct Marker {
     pub position: Span,
     pub subtitle: Option<Text>,
 }
 
-/// Errors that can occur during the compilation process.
-pub trait IntoDiagnostic {
+/// Errors that can occur during the compilation process. They can be sent between threads, so the
+/// parts of a project that are checked in parallel can give their diagnostics back.
+pub trait IntoDiagnostic: Send + Sync {
     fn code(&self) -> Option<usize> {
         None
     }
@@ -75,39 +107,131 @@ pub trait IntoDiagnostic {
 
     fn message(&self) -> Text;
 
+    /// Secondary positions in the source code that help to understand the diagnostic.
+    fn markers(&self) -> Vec<Marker> {
+        Vec::new()
+    }
+
+    /// Free-form notes that are printed after the source code.
+    fn notes(&self) -> Vec<Text> {
+        Vec::new()
+    }
+
+    /// Data that was shortened or left out of the rendered diagnostic, like the whole types of a
+    /// mismatch. It's never printed, but tools that output the diagnostics as data can use it.
+    fn details(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
     fn severity(&self) -> Severity;
 
     fn location(&self) -> Span;
 }
 
-/// A diagnostic with reference counting. It is a wrapper around a [IntoDiagnostic] trait object.
+/// A diagnostic with atomic reference counting. It is a wrapper around a [IntoDiagnostic] trait
+/// object with some extra labels and notes that can be attached by the one that reports it.
 #[derive(Clone)]
-pub struct Diagnostic(Rc<dyn IntoDiagnostic>);
+pub struct Diagnostic {
+    inner: Arc<dyn IntoDiagnostic>,
+    labels: Vec<Marker>,
+    notes: Vec<Text>,
+    severity: Option<Severity>,
+}
 
 impl Diagnostic {
     pub fn new(diagnostic: impl IntoDiagnostic + 'static) -> Self {
-        Self(Rc::new(diagnostic))
+        Self {
+            inner: Arc::new(diagnostic),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            severity: None,
+        }
+    }
+
+    /// Overrides the severity that the diagnostic declares.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Attaches a secondary position with a short message to the diagnostic.
+    pub fn with_label(mut self, position: Span, message: impl Into<Text>) -> Self {
+        self.labels.push(Marker {
+            position,
+            subtitle: Some(message.into()),
+        });
+        self
+    }
+
+    /// Attaches a note that is printed after the source code.
+    pub fn with_note(mut self, note: impl Into<Text>) -> Self {
+        self.notes.push(note.into());
+        self
     }
 
     pub fn code(&self) -> Option<usize> {
-        self.0.code()
+        self.inner.code()
     }
 
     pub fn hint(&self) -> Option<Text> {
-        self.0.hint()
+        self.inner.hint()
     }
 
     pub fn message(&self) -> Text {
-        self.0.message()
+        self.inner.message()
+    }
+
+    /// The secondary positions of the diagnostic in the order that they should be shown.
+    pub fn markers(&self) -> Vec<Marker> {
+        let mut markers = self.inner.markers();
+        markers.extend(self.labels.iter().cloned());
+        markers
+    }
+
+    pub fn notes(&self) -> Vec<Text> {
+        let mut notes = self.inner.notes();
+        notes.extend(self.notes.iter().cloned());
+        notes
+    }
+
+    pub fn details(&self) -> Vec<(String, String)> {
+        self.inner.details()
     }
 
     pub fn severity(&self) -> Severity {
-        self.0.severity()
+        self.severity.unwrap_or_else(|| self.inner.severity())
     }
 
     pub fn location(&self) -> Span {
-        self.0.location()
+        self.inner.location()
     }
+
+    /// Checks if two diagnostics would show the same thing at the same place.
+    pub fn is_duplicate_of(&self, other: &Diagnostic) -> bool {
+        let (left, right) = (self.location(), other.location());
+
+        self.code() == other.code()
+            && self.severity() == other.severity()
+            && (left.file, left.start, left.end) == (right.file, right.start, right.end)
+            && self.message().to_string() == other.message().to_string()
+    }
+
+    /// The key used to show diagnostics in a deterministic order.
+    fn order(&self) -> (FileId, usize, Severity) {
+        let location = self.location();
+        (location.file, location.start.0, self.severity())
+    }
+}
+
+/// A diagnostic turned into plain data. It's useful for tools that embed the compiler and want to
+/// inspect the diagnostics without rendering them.
+#[derive(Clone, Debug)]
+pub struct RenderedDiagnostic {
+    pub file: FileId,
+    pub code: Option<usize>,
+    pub message: String,
+    pub location: Span,
+    pub severity: Severity,
 }
 
 /// A reporter is a structure that gets and record errors. It's used to store and report errors to
@@ -132,38 +256,145 @@ pub trait Reporter {
 /// A structure that stores and reports errors to the user. It's inside a Rc or Arc because it
 /// needs to be shared between all steps of the compiler
 #[derive(Clone)]
-pub struct Report(Rc<RefCell<dyn Reporter>>);
+pub struct Report {
+    reporter: Rc<RefCell<dyn Reporter>>,
+    sinks: Rc<RefCell<Vec<Box<dyn Sink>>>>,
+    treat_warnings_as_errors: Rc<Cell<bool>>,
+}
 
 impl Report {
     pub fn new(reporter: impl Reporter + 'static) -> Self {
-        Self(Rc::new(RefCell::new(reporter)))
+        Self {
+            reporter: Rc::new(RefCell::new(reporter)),
+            sinks: Default::default(),
+            treat_warnings_as_errors: Default::default(),
+        }
+    }
+
+    /// Adds a sink that receives every diagnostic reported from now on. The sink is shared by all
+    /// the clones of the report.
+    pub fn with_sink(self, sink: impl Sink + 'static) -> Self {
+        self.sinks.borrow_mut().push(Box::new(sink));
+        self
+    }
+
+    /// Makes a report that only gives the diagnostics to the sink and stores none of them, so the
+    /// sink takes the place of the storage. The queries of the report see no diagnostics, and the
+    /// duplicated ones are not filtered out.
+    pub fn from_sink(sink: impl Sink + 'static) -> Self {
+        Self::new(NoStorage).with_sink(sink)
     }
 
+    /// Reports a diagnostic unless the same one was already reported at the same place.
     pub fn report(&self, diagnostic: Diagnostic) {
-        self.0.borrow_mut().report(diagnostic);
+        let file = diagnostic.location().file;
+
+        let duplicated = self
+            .reporter
+            .borrow()
+            .diagnostics(file)
+            .iter()
+            .any(|reported| reported.is_duplicate_of(&diagnostic));
+
+        if duplicated {
+            return;
+        }
+
+        self.reporter.borrow_mut().report(diagnostic.clone());
+
+        // The reporter is not borrowed anymore, so the sinks can ask anything to the report.
+        for sink in self.sinks.borrow_mut().iter_mut() {
+            sink.emit(file, &diagnostic);
+        }
     }
 
     pub fn diagnostics(&self, file: FileId) -> Vec<Diagnostic> {
-        self.0.borrow().diagnostics(file).to_vec()
+        self.reporter.borrow().diagnostics(file).to_vec()
     }
 
+    /// Gets all the diagnostics ordered by file, position and severity.
     pub fn all_diagnostics(&self) -> Vec<Diagnostic> {
-        self.0.borrow().all_diagnostics()
+        let mut diagnostics = self.reporter.borrow().all_diagnostics();
+        diagnostics.sort_by_key(Diagnostic::order);
+        diagnostics
     }
 
     pub fn clear(&self, file: FileId) {
-        self.0.borrow_mut().clear(file);
+        self.reporter.borrow_mut().clear(file);
+    }
+
+    pub fn len(&self) -> usize {
+        self.reporter.borrow().all_diagnostics().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over all the diagnostics in the same order of [Report::all_diagnostics] as plain
+    /// data.
+    pub fn iter(&self) -> impl Iterator<Item = RenderedDiagnostic> + '_ {
+        self.all_diagnostics()
+            .into_iter()
+            .map(|diagnostic| RenderedDiagnostic {
+                file: diagnostic.location().file,
+                code: diagnostic.code(),
+                message: diagnostic.message().to_string(),
+                location: diagnostic.location(),
+                severity: self.severity(&diagnostic),
+            })
+    }
+
+    /// Makes every warning count as an error, both when rendering and when deciding if the
+    /// compilation failed.
+    pub fn set_treat_warnings_as_errors(&self, value: bool) {
+        self.treat_warnings_as_errors.set(value);
+    }
+
+    pub fn treat_warnings_as_errors(&self) -> bool {
+        self.treat_warnings_as_errors.get()
+    }
+
+    /// The severity of a diagnostic after applying the settings of the report.
+    pub fn severity(&self, diagnostic: &Diagnostic) -> Severity {
+        match diagnostic.severity() {
+            Severity::Warning if self.treat_warnings_as_errors() => Severity::Error,
+            severity => severity,
+        }
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.all_diagnostics()
+            .iter()
+            .filter(|diagnostic| self.severity(diagnostic) == severity)
+            .count()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
     }
 
+    /// Checks if the compilation failed. Only errors are taken into account.
     pub fn has_errors(&self) -> bool {
-        self.0.borrow().has_errors()
+        self.error_count() > 0
     }
 
     pub fn to_stderr(&self, ctx: Classic) {
-        if self.has_errors() {
+        let diagnostics = self.all_diagnostics();
+
+        if !diagnostics.is_empty() {
             eprintln!();
 
-            for diagnostic in self.all_diagnostics().iter().rev() {
+            for diagnostic in diagnostics {
+                let diagnostic = match self.severity(&diagnostic) {
+                    Severity::Error => diagnostic.with_severity(Severity::Error),
+                    _ => diagnostic,
+                };
+
                 diagnostic.render(&ctx, &mut std::io::stderr()).unwrap();
             }
         }
@@ -173,3 +404,160 @@ impl Report {
 pub fn hash_reporter() -> Report {
     Report::new(hash::HashReporter::new())
 }
+
+/// The reporter of [Report::from_sink], that forgets every diagnostic.
+struct NoStorage;
+
+impl Reporter for NoStorage {
+    fn report(&mut self, _: Diagnostic) {}
+
+    fn diagnostics(&self, _: FileId) -> &[Diagnostic] {
+        &[]
+    }
+
+    fn all_diagnostics(&self) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+
+    fn clear(&mut self, _: FileId) {}
+
+    fn has_errors(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Unused(Severity);
+
+    impl IntoDiagnostic for Unused {
+        fn message(&self) -> Text {
+            Text::from("unused variable")
+        }
+
+        fn severity(&self) -> Severity {
+            self.0
+        }
+
+        fn location(&self) -> Span {
+            Span::ghost()
+        }
+    }
+
+    struct Mismatch(Span);
+
+    impl IntoDiagnostic for Mismatch {
+        fn message(&self) -> Text {
+            Text::from(format!("type mismatch at {}", self.0.start.0))
+        }
+
+        fn severity(&self) -> Severity {
+            Severity::Error
+        }
+
+        fn location(&self) -> Span {
+            self.0.clone()
+        }
+    }
+
+    fn positions(report: &Report) -> Vec<(usize, usize)> {
+        report
+            .all_diagnostics()
+            .iter()
+            .map(|diagnostic| (diagnostic.location().file.0, diagnostic.location().start.0))
+            .collect()
+    }
+
+    #[test]
+    fn warnings_do_not_fail_the_compilation() {
+        let report = hash_reporter();
+        report.report(Diagnostic::new(Unused(Severity::Warning)));
+        report.report(Diagnostic::new(Unused(Severity::Info)));
+
+        assert_eq!(report.error_count(), 0);
+        assert_eq!(report.warning_count(), 1);
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn warnings_fail_the_compilation_when_treated_as_errors() {
+        let report = hash_reporter();
+        report.report(Diagnostic::new(Unused(Severity::Warning)));
+        report.set_treat_warnings_as_errors(true);
+
+        assert_eq!(report.error_count(), 1);
+        assert_eq!(report.warning_count(), 0);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn duplicated_diagnostics_are_reported_once() {
+        let report = hash_reporter();
+        let span = Span::from_usize(FileId(0), 4, 8);
+
+        report.report(Diagnostic::new(Mismatch(span.clone())));
+        report.report(Diagnostic::new(Mismatch(span)));
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(
+            FileId(0),
+            9,
+            12,
+        ))));
+
+        assert_eq!(positions(&report), vec![(0, 4), (0, 9)]);
+    }
+
+    #[test]
+    fn diagnostics_are_ordered_by_file_and_position() {
+        let report = hash_reporter();
+
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(
+            FileId(1),
+            20,
+            22,
+        ))));
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(
+            FileId(0),
+            30,
+            31,
+        ))));
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(FileId(1), 2, 5))));
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(FileId(0), 1, 3))));
+
+        assert_eq!(positions(&report), vec![(0, 1), (0, 30), (1, 2), (1, 20)]);
+    }
+
+    /// A sink that asks the report how many diagnostics it has every time that one arrives.
+    struct Counting(Report, Rc<RefCell<Vec<usize>>>);
+
+    impl Sink for Counting {
+        fn emit(&mut self, _: FileId, _: &Diagnostic) {
+            self.1.borrow_mut().push(self.0.len());
+        }
+    }
+
+    #[test]
+    fn sinks_can_query_the_report() {
+        let report = hash_reporter();
+        let counts = Rc::new(RefCell::new(Vec::new()));
+        let report = report.clone().with_sink(Counting(report, counts.clone()));
+
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(FileId(0), 1, 3))));
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(FileId(0), 5, 7))));
+
+        assert_eq!(*counts.borrow(), vec![1, 2]);
+    }
+
+    #[test]
+    fn sinks_can_replace_the_storage() {
+        let collector = sink::Collector::new();
+        let report = Report::from_sink(collector.clone());
+
+        report.report(Diagnostic::new(Mismatch(Span::from_usize(FileId(0), 1, 3))));
+        report.report(Diagnostic::new(Unused(Severity::Warning)));
+
+        assert!(report.is_empty());
+        assert_eq!(collector.diagnostics().len(), 2);
+    }
+}
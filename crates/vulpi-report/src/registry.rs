@@ -0,0 +1,263 @@
+//! Registry of the stable codes of every diagnostic of the compiler together with a longer
+//! explanation of what they mean. The codes are written like `E0012` for the user.
+
+macro_rules! registry {
+    ($($name:ident = $code:literal => $explanation:literal,)*) => {
+        $(pub const $name: usize = $code;)*
+
+        /// All the codes with their explanations.
+        pub const REGISTRY: &[(usize, &str)] = &[$(($code, $explanation)),*];
+    };
+}
+
+/// Gives a code to each variant of the kind of an error. It writes the `code` method of the kind,
+/// that is an exhaustive match so a new variant does not compile without a code, and a public
+/// table from the name of each variant to its code, so the tests can check that no two variants
+/// share one.
+#[macro_export]
+macro_rules! codes {
+    ($(#[$meta:meta])* $table:ident: $kind:ident { $($variant:ident => $code:ident,)* }) => {
+        $(#[$meta])*
+        pub const $table: &[(&str, usize)] =
+            &[$((stringify!($variant), $crate::registry::$code)),*];
+
+        impl $kind {
+            pub(crate) fn code(&self) -> usize {
+                match self {
+                    $($kind::$variant { .. } => $crate::registry::$code,)*
+                }
+            }
+        }
+    };
+}
+
+registry! {
+    UNFINISHED_STRING = 1 =>
+        "A string literal was opened with `\"` but the file ended before it was closed.",
+    UNEXPECTED_TOKEN = 2 =>
+        "The parser found a token that cannot appear at this place of the program. It usually \
+         means that something before it is missing or that the layout is wrong.",
+    NOT_FOUND = 3 =>
+        "The name is not declared in the current module and it was not imported with `use`.",
+    LIST_IS_NOT_AVAILABLE = 4 =>
+        "List literals need the `List` type of the prelude, but it cannot be found.",
+    INVALID_PATH = 5 =>
+        "The path does not lead to any module. Check the name of each one of the segments.",
+    DUPLICATE_PATTERN = 6 =>
+        "The same variable is bound twice in the same pattern. Each variable of a pattern must \
+         have a distinct name.",
+    PRIVATE_DEFINITION = 7 =>
+        "The definition exists, but it is private to its module. Mark it with `pub` to use it \
+         outside of the module.",
+    CYCLE_BETWEEN_CONSTANTS = 8 =>
+        "Constants are evaluated when the program starts, so a constant cannot depend on itself \
+         through other constants. Turn one of them into a function to break the cycle.",
+    METHOD_NOT_IMPLEMENTED = 9 =>
+        "The implementation defines a method that is not declared in the trait.",
+    EMPTY_CASE = 10 =>
+        "A pattern match needs at least one arm.",
+    UNBOUND_TYPE_VARIABLE = 11 =>
+        "The type variable is not bound by any `forall` or by the parameters of the type.",
+    TYPE_MISMATCH = 12 =>
+        "A value of one type was used where a value of another type was expected. The message \
+         points at the innermost types that differ.",
+    KIND_MISMATCH = 13 =>
+        "A type was applied to arguments that it does not accept, or a type constructor was used \
+         where a complete type was expected.",
+    INFINITE_TYPE = 14 =>
+        "A type would have to contain itself to make the program type check, like `a = List a`.",
+    CANNOT_FIND_TYPE = 15 =>
+        "A type that the compiler needs from the prelude cannot be found.",
+    AT_LEAST_ONE_ARGUMENT = 16 =>
+        "The construction requires at least one argument.",
+    ESCAPING_SCOPE = 17 =>
+        "A type variable was used outside of the scope where it was introduced, which usually \
+         means that a polymorphic value is being used as if it had a concrete type.",
+    NOT_A_FUNCTION_KIND = 18 =>
+        "A type was applied to an argument but it does not have a function kind.",
+    WRONG_ARITY = 19 =>
+        "The number of patterns or arguments is not the same everywhere. Every arm of a pattern \
+         match must have the same number of patterns.",
+    CONSTRUCTOR_ARITY_MISMATCH = 20 =>
+        "Constructors must receive all of their arguments inside of patterns. Partial application \
+         is only allowed in expressions.",
+    NOT_A_FUNCTION = 21 =>
+        "A value was applied to arguments but its type is not a function.",
+    NOT_IMPLEMENTED = 22 =>
+        "The feature is not implemented by the type checker yet.",
+    MISSING_LABEL = 23 =>
+        "A field of the record was not given a value.",
+    INVALID_LABELS = 24 =>
+        "The fields do not belong to the record that is being built.",
+    PATTERNS_NOT_ALLOWED_HERE = 25 =>
+        "This kind of declaration cannot match on patterns in its parameters.",
+    DUPLICATED_FIELD = 26 =>
+        "The same field was given a value twice.",
+    NOT_FOUND_FIELD = 27 =>
+        "The record or the labeled constructor does not have a field with this name.",
+    NOT_A_RECORD = 28 =>
+        "Fields can only be accessed or updated on values whose type is a record.",
+    MISSING_FIELD = 29 =>
+        "The record instance or the labeled constructor does not give a value to every field.",
+    NON_EXHAUSTIVE = 30 =>
+        "The pattern match does not cover every possible value. The message shows one of the \
+         values that is not covered; add an arm for it or a wildcard `_`.",
+    TYPED_HOLE = 31 =>
+        "A hole written as `?name` shows the type that is expected at its position and the \
+         variables in scope that fit in it. Programs with holes are not compiled.",
+    AMBIGUOUS_TYPE = 32 =>
+        "The type of a public definition still has parts that are not known after the module is \
+         checked. Add a type annotation to make them concrete.",
+    CYCLE_BETWEEN_MODULES = 33 =>
+        "Modules are checked after the modules that they use, so they cannot use each other. Move \
+         the definitions that both of them need to a third module.",
+    UNKNOWN_MODULE = 34 =>
+        "The name refers to a module that was not declared. Check the spelling of the module and \
+         that its file is part of the project.",
+    CONFLICTING_ALIAS = 35 =>
+        "Two `use` declarations of a module introduce the same alias. Rename one of them.",
+    UNUSED_ALIAS = 36 =>
+        "The alias of a `use` declaration is not used by any path of the module. Remove the alias \
+         or the whole declaration.",
+    MISSING_BUILTIN = 37 =>
+        "Literals and guards have types that are defined by the prelude, and the prelude of the \
+         project does not define one of them. Add the type to the prelude.",
+    AMBIGUOUS_NAME = 38 =>
+        "The name is defined by more than one of the modules opened by `use`. Qualify it with the \
+         module that it should come from, or define it in the module to shadow the others.",
+    UNUSED_VARIABLE = 39 =>
+        "The variable is bound but never used. Remove it, replace it by `_`, or start its name \
+         with `_` if it's unused on purpose.",
+    UNUSED_USE = 40 =>
+        "No name of the module opened by the `use` declaration is used by the module. Remove the \
+         declaration.",
+    SHADOWED_VARIABLE = 41 =>
+        "The variable has the same name as another variable that is in scope, so the other one \
+         cannot be used anymore. Rename one of them.",
+    DUPLICATE_DEFINITION = 42 =>
+        "Two types or traits of a module, or two constructors or fields of a type, have the same \
+         name. Only the last one is used, so rename or remove the other.",
+    AMBIGUOUS_FIELD = 43 =>
+        "The type of the record is not known yet, and more than one record has a field with this \
+         name. Annotate the type of the expression that is projected.",
+    INVALID_CONSTRUCTOR_RETURN_TYPE = 44 =>
+        "The return type written in the constructor is not the type that declares it. Constructors \
+         can refine the arguments of their type, but must return an application of it.",
+    INTEGER_OVERFLOW = 45 =>
+        "The integer literal is greater than the biggest signed 64 bit integer, \
+         9223372036854775807.",
+    INVALID_NUMBER = 46 =>
+        "The number literal has digits that are not valid for its base, or a malformed exponent. \
+         Integers can start with `0x`, `0o` or `0b`, and floats can end with an exponent like \
+         `e-3`.",
+    UNFINISHED_CHAR = 47 =>
+        "A char literal was opened with `'` but it does not have exactly one character before \
+         the closing `'`. Use a string for more than one character.",
+    FLOAT_PATTERN = 48 =>
+        "Float literals cannot be matched, because the result of comparing floats for equality \
+         depends on rounding. Bind the float to a variable and compare it in a guard.",
+    TOO_MANY_TYPE_ARGUMENTS = 49 =>
+        "An explicit type argument like `@Int` was given to an expression whose type does not \
+         start with a `forall`, or more type arguments were given than the type has variables.",
+    CHECKER_LIMIT_EXCEEDED = 50 =>
+        "The type checker recursed deeper than its limit while checking the definition, so the \
+         rest of it was not checked. Split the definition into smaller ones.",
+    MISSING_ENTRY_POINT = 51 =>
+        "The root module of the program does not define the function that is called when the \
+         program starts, usually `main`.",
+    PRIVATE_ENTRY_POINT = 52 =>
+        "The entry point of the program is called from outside of its module, so it must be \
+         marked with `pub`.",
+    INVALID_ENTRY_POINT = 53 =>
+        "The entry point of the program must be a constant or a function that takes a single \
+         `()`, and it must return the type that the compiler was configured with, usually `()`.",
+    TUPLE_ARITY_MISMATCH = 54 =>
+        "A tuple was used where a tuple with a different number of elements was expected. Tuples \
+         of different sizes are different types.",
+    RECURSIVE_PATTERN = 55 =>
+        "The bindings of a `let rec` can refer to each other before they are defined, so each one \
+         of them must bind a single variable instead of destructuring a value.",
+    CYCLIC_TYPE_SYNONYM = 56 =>
+        "A type synonym is defined in terms of itself, directly or through other synonyms, so it \
+         could never be expanded. Recursive types must be sums or records.",
+    TUPLE_INDEX_OUT_OF_BOUNDS = 57 =>
+        "A tuple was accessed at a position that it does not have. The positions start at `0`, \
+         so the last element of a tuple of `n` elements is at `n - 1`.",
+    NOT_A_TUPLE = 58 =>
+        "Elements can only be accessed by their position on values whose type is a tuple. The \
+         type must already be known where the element is accessed, so an annotation may help.",
+    UNUSED_DEFINITION = 59 =>
+        "The private let or type is not used by any public item of its module, by an external, \
+         by an implementation or by the entry point, so it can never run. Remove it, or start its \
+         name with `_` or mark it with `@keep` if it's unused on purpose.",
+    PRIVATE_TYPE_IN_PUBLIC_INTERFACE = 60 =>
+        "A public item takes or returns a value of a private type, so other modules can use the \
+         item but cannot write its type. Make the type public or the item private.",
+    UNLABELED_CONSTRUCTOR = 61 =>
+        "Only constructors that are declared with labeled fields, like `Circle { radius : Int }`, \
+         can be built or matched by the names of their fields. Give the arguments by position.",
+    UNKNOWN_DERIVE = 62 =>
+        "Only `Eq` and `Show` can be derived. Write the function for the type by hand instead.",
+    CANNOT_DERIVE = 63 =>
+        "A derived function needs the same function for the type of every field, like `eqInt` \
+         for a field of type `Int`, and the type must have constructors or fields. Define the \
+         function for the type of the field, or derive it for that type too.",
+    UNKNOWN_ATTRIBUTE = 64 =>
+        "The attribute is not one that the compiler knows, so it does nothing. The known \
+         attributes are `@deprecated(\"message\")` and `@keep`.",
+    ATTRIBUTE_ARITY = 65 =>
+        "The attribute was given a different number of arguments than it takes, like \
+         `@deprecated` without the message between parenthesis.",
+    DEPRECATED = 66 =>
+        "The item is marked with `@deprecated`, so it may be removed in a future version. The \
+         message of the attribute usually says what to use instead. Items that are deprecated \
+         themselves can use it without warnings.",
+    TOO_MANY_ARGUMENTS = 67 =>
+        "A function or a constructor was applied to more arguments than its type takes. The \
+         extra arguments are marked, and the note shows the whole type of the function.",
+    ARGUMENT_KIND_MISMATCH = 68 =>
+        "A type was given an argument whose kind is not the one of its parameter, like a type \
+         constructor where a complete type was expected. The note shows the kind of the type.",
+}
+
+/// Formats a code in the way that it's shown to the user, like `E0012`.
+pub fn format_code(code: usize) -> String {
+    format!("E{:04}", code)
+}
+
+/// Gets the explanation of a code written like `E0012`.
+pub fn explain(code: &str) -> Option<&'static str> {
+    let code = code
+        .strip_prefix('E')
+        .unwrap_or(code)
+        .parse::<usize>()
+        .ok()?;
+
+    REGISTRY
+        .iter()
+        .find(|(registered, _)| *registered == code)
+        .map(|(_, explanation)| *explanation)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn codes_are_unique() {
+        let mut seen = HashSet::new();
+
+        for (code, _) in REGISTRY {
+            assert!(seen.insert(code), "{} is used twice", format_code(*code));
+        }
+    }
+
+    #[test]
+    fn explains_formatted_codes() {
+        assert_eq!(explain(&format_code(TYPE_MISMATCH)), explain("12"));
+        assert!(explain("E0012").is_some());
+        assert!(explain("E9999").is_none());
+    }
+}
@@ -1,9 +1,10 @@
 use std::path::PathBuf;
 
+use vulpi_location::{Byte, LineIndex, Span};
 use vulpi_vfs::FileSystem;
 use yansi::Paint;
 
-use crate::{renderer::LineGuide, Color, Diagnostic, Style, Text, Word};
+use crate::{registry::format_code, Color, Diagnostic, Severity, Style, Text, Word};
 
 use super::Renderer;
 
@@ -82,84 +83,301 @@ impl<'a> Renderer<Classic<'a>> for Text {
     }
 }
 
-impl<'a> Renderer<Classic<'a>> for Diagnostic {
-    fn render(&self, ctx: &Classic<'a>, writer: &mut impl std::io::Write) -> std::io::Result<()> {
-        // At this point we are probably sure that the file exists, so we can unwrap.
-        let path = ctx.fs.path(self.location().file).unwrap();
-        let relative = path.strip_prefix(&ctx.cwd).unwrap();
+/// The number of columns that a tab takes when it's shown.
+const TAB_WIDTH: usize = 4;
+
+/// Replaces the tabs of a line so the carets below it stay aligned.
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
 
-        let content = ctx.fs.read(self.location().file).unwrap();
+/// The column in which the byte of a line is shown. It counts characters instead of bytes so
+/// multi-byte characters take a single column.
+fn display_column(line: &str, byte: usize) -> usize {
+    line.char_indices()
+        .take_while(|(i, _)| *i < byte)
+        .map(|(_, c)| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
 
-        let range = self.location();
+/// Renders the lines of source code around a span, underlining it with the given color.
+fn render_snippet(
+    ctx: &Classic,
+    writer: &mut impl std::io::Write,
+    range: &Span,
+    color: yansi::Color,
+    label: Option<&Text>,
+) -> std::io::Result<()> {
+    // At this point we are probably sure that the file exists, so we can unwrap.
+    let path = ctx.fs.path(range.file).unwrap();
+    let relative = path.strip_prefix(&ctx.cwd).unwrap();
+
+    let content = ctx.fs.read(range.file).unwrap();
+    let index = ctx.fs.line_index(range.file).unwrap();
+
+    write_snippet(
+        writer,
+        &relative.display().to_string(),
+        &content,
+        &index,
+        range,
+        color,
+        label.map(|label| label.to_string()),
+    )
+}
 
-        let line_guide = LineGuide::new(&content);
+/// Writes the snippet of a span inside of the content of a file. Spans that cross multiple lines
+/// underline the rest of the first line and the beginning of the last one, with `...` marking
+/// that the span continues.
+fn write_snippet(
+    writer: &mut impl std::io::Write,
+    path: &str,
+    content: &str,
+    index: &LineIndex,
+    range: &Span,
+    color: yansi::Color,
+    label: Option<String>,
+) -> std::io::Result<()> {
+    // The line of a byte together with its offset in bytes from the start of the line.
+    let locate = |byte: &Byte| {
+        let (line, _) = index.position(byte.clone());
+        (
+            line,
+            byte.0.min(content.len()) - index.line_start(line).unwrap().0,
+        )
+    };
+
+    let start = locate(&range.start);
+    let end = locate(&range.end);
+
+    // Unlike `str::lines`, it keeps the empty line at the end of the file so spans that point to
+    // the end of the file still have a line to be shown.
+    let lines = content
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+
+    let guide = Paint::new("┌─>").fg(yansi::Color::Cyan).dimmed();
+
+    writeln!(
+        writer,
+        "      {guide} {}:{}:{} ",
+        path,
+        start.0 + 1,
+        index.position(range.start.clone()).1 + 1
+    )?;
+
+    let vbar = Paint::new("│").fg(yansi::Color::Cyan).dimmed();
+
+    writeln!(writer, "      {vbar} ")?;
+
+    let minimum = start.0.saturating_sub(2);
+    let maximum = (end.0 + 2).min(lines.len());
+
+    let carets = |from: usize, to: usize| {
+        Paint::new("^".repeat(to.saturating_sub(from).max(1)))
+            .bold()
+            .fg(color)
+    };
+
+    for (line_index, line) in lines.iter().enumerate().take(maximum).skip(minimum) {
+        writeln!(
+            writer,
+            "  {:>3} {vbar} {}",
+            line_index + 1,
+            expand_tabs(line)
+        )?;
 
-        let start = line_guide.to_line_and_column(range.start).unwrap();
-        let end = line_guide.to_line_and_column(range.end).unwrap();
+        let (from, to) = if line_index == start.0 && line_index == end.0 {
+            (display_column(line, start.1), display_column(line, end.1))
+        } else if line_index == start.0 {
+            (
+                display_column(line, start.1),
+                display_column(line, line.len()),
+            )
+        } else if line_index == end.0 {
+            let indentation = line.len() - line.trim_start().len();
+            (
+                display_column(line, indentation),
+                display_column(line, end.1),
+            )
+        } else {
+            continue;
+        };
+
+        write!(
+            writer,
+            "      {vbar} {}{}",
+            " ".repeat(from),
+            carets(from, to)
+        )?;
+
+        if line_index != end.0 {
+            write!(writer, "{}", Paint::new("...").fg(color))?;
+        } else if let Some(label) = &label {
+            write!(writer, " {}", Paint::new(label).bold())?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+impl<'a> Renderer<Classic<'a>> for Diagnostic {
+    fn render(&self, ctx: &Classic<'a>, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        let (label, color) = match self.severity() {
+            Severity::Error => (" ERROR ", yansi::Color::Red),
+            Severity::Warning => (" WARNING ", yansi::Color::Yellow),
+            Severity::Info => (" INFO ", yansi::Color::Blue),
+        };
 
         write!(
             writer,
             "  {} ",
-            yansi::Color::White
-                .style()
-                .bg(yansi::Color::Red)
-                .paint(" ERROR ")
+            yansi::Color::White.style().bg(color).paint(label)
         )?;
 
-        self.message().render(ctx, writer)?;
+        if let Some(code) = self.code() {
+            write!(
+                writer,
+                "{} ",
+                Paint::new(format!("[{}]", format_code(code))).fg(color)
+            )?;
+        }
 
-        let guide = Paint::new("┌─>").fg(yansi::Color::Cyan).dimmed();
+        self.message().render(ctx, writer)?;
 
         writeln!(writer)?;
         writeln!(writer)?;
-        writeln!(
-            writer,
-            "      {guide} {}:{}:{} ",
-            relative.display(),
-            start.0 + 1,
-            start.1 + 1
-        )?;
+
+        render_snippet(ctx, writer, &self.location(), yansi::Color::Red, None)?;
+
+        for marker in self.markers() {
+            writeln!(writer)?;
+            render_snippet(
+                ctx,
+                writer,
+                &marker.position,
+                yansi::Color::Blue,
+                marker.subtitle.as_ref(),
+            )?;
+        }
 
         let vbar = Paint::new("│").fg(yansi::Color::Cyan).dimmed();
 
-        writeln!(writer, "      {vbar} ")?;
+        let hint = self.hint();
+        let notes = self.notes();
 
-        let is_inline = start.0 == end.0;
+        if hint.is_some() || !notes.is_empty() {
+            writeln!(writer, "      {vbar} ")?;
+        }
 
-        let lines = content.lines().collect::<Vec<_>>();
+        if let Some(hint) = hint {
+            write!(
+                writer,
+                "      {} ",
+                Paint::new("= hint:").fg(yansi::Color::Cyan)
+            )?;
+            hint.render(ctx, writer)?;
+            writeln!(writer)?;
+        }
 
-        let minimum = start.0.saturating_sub(2);
-        let maximum = (end.0 + 2).min(lines.len());
+        for note in notes {
+            write!(
+                writer,
+                "      {} ",
+                Paint::new("= note:").fg(yansi::Color::Cyan)
+            )?;
+            note.render(ctx, writer)?;
+            writeln!(writer)?;
+        }
 
-        for (i, line) in lines[minimum..maximum].iter().enumerate() {
-            let line_number = minimum + i + 1;
+        writeln!(writer)
+    }
+}
 
-            write!(writer, "  {:>3} {vbar} ", line_number)?;
+#[cfg(test)]
+mod tests {
+    use vulpi_location::FileId;
 
-            if is_inline && line_number == start.0 + 1 {
-                let line = line.to_string();
+    use super::*;
+    use crate::renderer::Reader;
 
-                writeln!(writer, "{}", line)?;
+    fn snippet(content: &str, start: usize, end: usize) -> String {
+        Paint::disable();
 
-                writeln!(
-                    writer,
-                    "      {vbar} {}{}",
-                    " ".repeat(start.1),
-                    Paint::new("^".repeat(end.1 - start.1))
-                        .bold()
-                        .fg(yansi::Color::Red)
-                )?;
-            } else if is_inline && line_number == end.0 + 1 {
-                let mut line = line.to_string();
+        let mut reader = Reader::default();
+        let span = Span::from_usize(FileId(0), start, end);
 
-                line.insert(end.1 + 1, '^');
+        write_snippet(
+            &mut reader,
+            "Main.vp",
+            content,
+            &LineIndex::new(content),
+            &span,
+            yansi::Color::Red,
+            Some("here".to_string()),
+        )
+        .unwrap();
 
-                writeln!(writer, "{}", line)?;
-            } else {
-                writeln!(writer, "{}", line)?;
-            }
-        }
+        reader.to_string()
+    }
 
-        writeln!(writer)
+    #[test]
+    fn single_line() {
+        let content = "let café = 1\nlet x =\tcafé\nlet y = 2\n";
+        let start = content.find("\tcafé").unwrap() + 1;
+
+        assert_eq!(
+            snippet(content, start, start + "café".len()),
+            concat!(
+                "      ┌─> Main.vp:2:9 \n",
+                "      │ \n",
+                "    1 │ let café = 1\n",
+                "    2 │ let x =    café\n",
+                "      │            ^^^^ here\n",
+                "    3 │ let y = 2\n",
+            )
+        );
+    }
+
+    #[test]
+    fn three_lines() {
+        let content = "let x =\n    add 1\n        2\n        3\nlet y = 2\n";
+        let start = content.find("add").unwrap();
+        let end = content.find("3\n").unwrap() + 1;
+
+        assert_eq!(
+            snippet(content, start, end),
+            concat!(
+                "      ┌─> Main.vp:2:5 \n",
+                "      │ \n",
+                "    1 │ let x =\n",
+                "    2 │     add 1\n",
+                "      │     ^^^^^...\n",
+                "    3 │         2\n",
+                "    4 │         3\n",
+                "      │         ^ here\n",
+                "    5 │ let y = 2\n",
+            )
+        );
+    }
+
+    #[test]
+    fn end_of_file() {
+        let content = "let x = 1\nlet y =\n";
+
+        assert_eq!(
+            snippet(content, content.len(), content.len()),
+            concat!(
+                "      ┌─> Main.vp:3:1 \n",
+                "      │ \n",
+                "    1 │ let x = 1\n",
+                "    2 │ let y =\n",
+                "    3 │ \n",
+                "      │ ^ here\n",
+            )
+        );
     }
 }
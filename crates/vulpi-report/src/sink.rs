@@ -0,0 +1,74 @@
+//! Sinks receive every diagnostic as soon as it's reported, so tools can stream them (e.g. an LSP
+//! that publishes them per file), count them or ignore them.
+
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use vulpi_location::FileId;
+
+use crate::{registry::format_code, Diagnostic, Severity};
+
+/// Something that receives the diagnostics in the order that they are reported.
+pub trait Sink {
+    fn emit(&mut self, file: FileId, diagnostic: &Diagnostic);
+}
+
+/// A sink that stores every diagnostic. It can be cloned before being given to the
+/// [crate::Report] so the diagnostics can be read afterwards.
+#[derive(Clone, Default)]
+pub struct Collector(Rc<RefCell<Vec<Diagnostic>>>);
+
+impl Collector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.0.borrow().clone()
+    }
+}
+
+impl Sink for Collector {
+    fn emit(&mut self, _: FileId, diagnostic: &Diagnostic) {
+        self.0.borrow_mut().push(diagnostic.clone());
+    }
+}
+
+/// A sink that writes a line of text for each diagnostic as soon as it's reported.
+pub struct Stream<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> Stream<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> Sink for Stream<W> {
+    fn emit(&mut self, file: FileId, diagnostic: &Diagnostic) {
+        let severity = match diagnostic.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        };
+
+        let code = diagnostic
+            .code()
+            .map(|code| format!("[{}]", format_code(code)))
+            .unwrap_or_default();
+
+        let location = diagnostic.location();
+
+        // A sink has nowhere to report a failure, so the diagnostic is just lost.
+        let _ = writeln!(
+            self.writer,
+            "{}{}: {} (file {} at {}..{})",
+            severity,
+            code,
+            diagnostic.message(),
+            file.0,
+            location.start.0,
+            location.end.0
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use vulpi_location::{Byte, Span};
+use vulpi_report::{
+    renderer::{classic::Classic, Reader, Renderer},
+    Diagnostic, IntoDiagnostic, Severity, Text,
+};
+use vulpi_vfs::{FileSystem, MemoryFileSystem};
+
+struct Unbound(Span);
+
+impl IntoDiagnostic for Unbound {
+    fn message(&self) -> Text {
+        Text::from("cannot find the variable 'y'")
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn location(&self) -> Span {
+        self.0.clone()
+    }
+}
+
+#[test]
+fn renders_the_source_of_files_in_memory() {
+    yansi::Paint::disable();
+
+    let mut fs = MemoryFileSystem::from_iter([("Main.vp", "let x = 1\nlet z = y\n")]);
+    let id = fs.load(PathBuf::from("Main.vp")).unwrap();
+
+    let span = Span {
+        file: id,
+        start: Byte(18),
+        end: Byte(19),
+    };
+
+    let classic = Classic::new(&fs, PathBuf::new());
+
+    let mut reader = Reader::default();
+    Diagnostic::new(Unbound(span))
+        .render(&classic, &mut reader)
+        .unwrap();
+
+    let rendered = reader.to_string();
+
+    assert!(rendered.contains("cannot find the variable 'y'"));
+    assert!(rendered.contains("Main.vp:2:9"), "{rendered}");
+    assert!(rendered.contains("let z = y"));
+}
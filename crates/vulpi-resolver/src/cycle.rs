@@ -58,10 +58,17 @@ impl DepHolder {
 
                 let span = self.spans[&first].clone();
 
-                report.report(Diagnostic::new(ResolverError {
+                let mut diagnostic = Diagnostic::new(ResolverError {
                     span,
-                    kind: crate::error::ResolverErrorKind::CycleBetweenConstants(cycle),
-                }))
+                    kind: crate::error::ResolverErrorKind::CycleBetweenConstants(cycle.clone()),
+                });
+
+                for name in cycle.iter().skip(1) {
+                    let message = format!("'{}' is used here", name.to_string());
+                    diagnostic = diagnostic.with_label(self.spans[name].clone(), message);
+                }
+
+                report.report(diagnostic)
             }
         }
     }
@@ -2,6 +2,7 @@ use vulpi_intern::Symbol;
 use vulpi_location::Span;
 use vulpi_report::IntoDiagnostic;
 use vulpi_syntax::r#abstract::Qualified;
+use vulpi_vfs::path::Path;
 
 pub enum ResolverErrorKind {
     NotFound(Symbol),
@@ -11,6 +12,14 @@ pub enum ResolverErrorKind {
     PrivateDefinition,
     CycleBetweenConstants(Vec<Qualified>),
     NotImplemented(Symbol, Symbol),
+    ConflictingAlias(Symbol),
+    UnusedAlias(Symbol),
+    AmbiguousName(Symbol, Vec<Path>),
+    UnusedUse(Symbol),
+    IntegerOverflow(Symbol),
+    InvalidNumber(Symbol),
+    RecursivePattern,
+    UnknownDerive(Symbol),
 }
 
 pub struct ResolverError {
@@ -18,7 +27,32 @@ pub struct ResolverError {
     pub kind: ResolverErrorKind,
 }
 
+vulpi_report::codes! {
+    /// The code of each kind of resolver error.
+    RESOLVER_ERROR_CODES: ResolverErrorKind {
+        NotFound => NOT_FOUND,
+        ListIsNotAvailable => LIST_IS_NOT_AVAILABLE,
+        InvalidPath => INVALID_PATH,
+        DuplicatePattern => DUPLICATE_PATTERN,
+        PrivateDefinition => PRIVATE_DEFINITION,
+        CycleBetweenConstants => CYCLE_BETWEEN_CONSTANTS,
+        NotImplemented => METHOD_NOT_IMPLEMENTED,
+        ConflictingAlias => CONFLICTING_ALIAS,
+        UnusedAlias => UNUSED_ALIAS,
+        AmbiguousName => AMBIGUOUS_NAME,
+        UnusedUse => UNUSED_USE,
+        IntegerOverflow => INTEGER_OVERFLOW,
+        InvalidNumber => INVALID_NUMBER,
+        RecursivePattern => RECURSIVE_PATTERN,
+        UnknownDerive => UNKNOWN_DERIVE,
+    }
+}
+
 impl IntoDiagnostic for ResolverError {
+    fn code(&self) -> Option<usize> {
+        Some(self.kind.code())
+    }
+
     fn message(&self) -> vulpi_report::Text {
         match &self.kind {
             ResolverErrorKind::NotImplemented(name, feature) => format!(
@@ -27,7 +61,7 @@ impl IntoDiagnostic for ResolverError {
                 name.get()
             )
             .into(),
-            ResolverErrorKind::ListIsNotAvailable => "List is not available".into(), 
+            ResolverErrorKind::ListIsNotAvailable => "List is not available".into(),
             ResolverErrorKind::NotFound(name) => format!("cannot find '{}'", name.get()).into(),
             ResolverErrorKind::InvalidPath(name) => format!(
                 "the path '{}' cannot be found",
@@ -44,11 +78,49 @@ impl IntoDiagnostic for ResolverError {
 
                 format!("cycle between '{}'", cycle.join(" -> ")).into()
             }
+            ResolverErrorKind::ConflictingAlias(name) => {
+                format!("the alias '{}' is already defined", name.get()).into()
+            }
+            ResolverErrorKind::UnusedAlias(name) => {
+                format!("the alias '{}' is never used", name.get()).into()
+            }
+            ResolverErrorKind::AmbiguousName(name, modules) => format!(
+                "the name '{}' is ambiguous, it's defined in {}",
+                name.get(),
+                modules
+                    .iter()
+                    .map(|module| format!("'{}'", module.symbol().get()))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            )
+            .into(),
+            ResolverErrorKind::UnusedUse(path) => {
+                format!("nothing from the module '{}' is used", path.get()).into()
+            }
+            ResolverErrorKind::IntegerOverflow(text) => {
+                format!("the integer '{}' does not fit in 64 bits", text.get()).into()
+            }
+            ResolverErrorKind::InvalidNumber(text) => {
+                format!("'{}' is not a valid number", text.get()).into()
+            }
+            ResolverErrorKind::RecursivePattern => {
+                "only variables can be bound by 'let rec'".into()
+            }
+            ResolverErrorKind::UnknownDerive(name) => format!(
+                "'{}' cannot be derived, only 'Eq' and 'Show' can",
+                name.get()
+            )
+            .into(),
         }
     }
 
     fn severity(&self) -> vulpi_report::Severity {
-        vulpi_report::Severity::Error
+        match &self.kind {
+            ResolverErrorKind::UnusedAlias(_) | ResolverErrorKind::UnusedUse(_) => {
+                vulpi_report::Severity::Warning
+            }
+            _ => vulpi_report::Severity::Error,
+        }
     }
 
     fn location(&self) -> Span {
@@ -1,25 +1,29 @@
 //! The resolver is responsible for taking a single concrete tree and turn it into an abstract
 //! syntax tree with all the names resolved.
 
-use std::cell::{Ref, RefMut};
-use std::collections::HashMap;
+use std::cell::{Cell, Ref, RefMut};
+use std::collections::{HashMap, HashSet};
 use std::{cell::RefCell, rc::Rc};
 
 use petgraph::prelude::DiGraph;
 use petgraph::stable_graph::NodeIndex;
 
-use vulpi_intern::Symbol;
+use vulpi_intern::{well_known, Symbol};
 use vulpi_location::{Span, Spanned};
 use vulpi_report::{Diagnostic, Report};
-use vulpi_syntax::concrete::tree::LetMode;
+use vulpi_syntax::concrete::tree::{LetCaseKind, LetMode};
 use vulpi_syntax::concrete::{self, tree};
 use vulpi_syntax::r#abstract as abs;
 use vulpi_syntax::r#abstract::Visibility;
+use vulpi_syntax::tokens::{Token, TokenData};
 use vulpi_vfs::path::{Path, Qualified};
 
 pub mod cycle;
 pub mod dependencies;
 mod error;
+mod literal;
+
+pub use error::RESOLVER_ERROR_CODES;
 
 pub enum Either<L, R> {
     Left(L),
@@ -62,14 +66,20 @@ pub type Alias = (Qualified, abs::Visibility);
 /// Namespace of a module.
 pub struct Namespace {
     name: Path,
-    declared: Bag<HashMap<Symbol, abs::Visibility>>,
+    declared: Bag<HashMap<Symbol, (abs::Visibility, Span)>>,
     constants: HashMap<abs::Qualified, HashMap<abs::Qualified, Span>>,
     traits: HashMap<Symbol, HashMap<Symbol, Span>>,
 
     aliases: Bag<HashMap<Symbol, Alias>>,
-    modules: HashMap<Symbol, (Path, abs::Visibility)>,
+    modules: HashMap<Symbol, (Path, abs::Visibility, Span)>,
+    used_modules: HashSet<Symbol>,
     submodules: HashMap<Symbol, Module>,
-    opened: HashMap<Path, Visibility>,
+    opened: HashMap<Path, (Visibility, Span)>,
+    used_opened: HashSet<Path>,
+
+    /// If it's the namespace of a type or of a trait. Their private definitions belong to the
+    /// module that declares them.
+    item: bool,
 }
 
 pub fn from_upper_path(path: &concrete::Path<concrete::Upper>) -> Path {
@@ -110,6 +120,64 @@ pub fn from_constructor_upper_path(path: &concrete::Path<concrete::Upper>) -> Qu
     }
 }
 
+/// Checks if the module `from` is the module `module` or one of its submodules. Private
+/// definitions of a module are only visible inside of it, so parents cannot see the private
+/// definitions of their submodules.
+fn inside(from: &Path, module: &Path) -> bool {
+    from.segments.starts_with(&module.segments)
+}
+
+/// The error for a private definition that is used outside of its module, pointing at the
+/// definition.
+fn private_definition(span: Span, definition: Span) -> Diagnostic {
+    Diagnostic::new(error::ResolverError {
+        span,
+        kind: error::ResolverErrorKind::PrivateDefinition,
+    })
+    .with_label(definition, "it's defined here without `pub`")
+}
+
+/// Chooses the definition of a name that was found through the opened modules. The same
+/// definition can be found through more than one of them, but if there are different definitions
+/// the name is ambiguous and all of them are shown.
+fn disambiguate(
+    span: Span,
+    availables: &Rc<RefCell<HashMap<Path, Module>>>,
+    kind: DefinitionKind,
+    name: Symbol,
+    mut found: Vec<Qualified>,
+) -> Result<Option<Qualified>, Diagnostic> {
+    found.sort_by_key(|qualified| qualified.path.symbol().get());
+    found.dedup();
+
+    if found.len() < 2 {
+        return Ok(found.pop());
+    }
+
+    let modules = found
+        .iter()
+        .map(|qualified| qualified.path.clone())
+        .collect();
+
+    let mut diagnostic = Diagnostic::new(error::ResolverError {
+        span,
+        kind: error::ResolverErrorKind::AmbiguousName(name, modules),
+    });
+
+    for qualified in &found {
+        let module = availables.borrow().get(&qualified.path).cloned();
+
+        if let Some((_, definition)) =
+            module.and_then(|module| module.search_declared(kind, qualified.name.clone()))
+        {
+            let message = format!("defined in '{}' here", qualified.path.symbol().get());
+            diagnostic = diagnostic.with_label(definition, message);
+        }
+    }
+
+    Err(diagnostic)
+}
+
 /// Module is a wrapper around the namespace. It is used to make the namespace mutable, and to
 /// be easy to clone.
 #[derive(Clone)]
@@ -117,11 +185,11 @@ pub struct Module(Rc<RefCell<Namespace>>);
 
 /// Getters for the namespace.
 impl Module {
-    pub fn modules_mut(&self) -> RefMut<'_, HashMap<Symbol, (Path, abs::Visibility)>> {
+    pub fn modules_mut(&self) -> RefMut<'_, HashMap<Symbol, (Path, abs::Visibility, Span)>> {
         std::cell::RefMut::map(self.borrow_mut(), |this| &mut this.modules)
     }
 
-    pub fn modules(&self) -> Ref<'_, HashMap<Symbol, (Path, abs::Visibility)>> {
+    pub fn modules(&self) -> Ref<'_, HashMap<Symbol, (Path, abs::Visibility, Span)>> {
         std::cell::Ref::map(self.borrow(), |this| &this.modules)
     }
 
@@ -129,7 +197,7 @@ impl Module {
         std::cell::Ref::map(self.borrow(), |this| &this.name)
     }
 
-    fn declared(&self) -> Ref<'_, Bag<HashMap<Symbol, abs::Visibility>>> {
+    fn declared(&self) -> Ref<'_, Bag<HashMap<Symbol, (abs::Visibility, Span)>>> {
         std::cell::Ref::map(self.borrow(), |this| &this.declared)
     }
 
@@ -137,7 +205,7 @@ impl Module {
         std::cell::Ref::map(self.borrow(), |this| &this.aliases)
     }
 
-    fn opened(&self) -> Ref<'_, HashMap<Path, abs::Visibility>> {
+    fn opened(&self) -> Ref<'_, HashMap<Path, (abs::Visibility, Span)>> {
         std::cell::Ref::map(self.borrow(), |this| &this.opened)
     }
 
@@ -145,7 +213,7 @@ impl Module {
         std::cell::RefMut::map(self.borrow_mut(), |this| &mut this.traits)
     }
 
-    fn opened_mut(&self) -> RefMut<'_, HashMap<Path, abs::Visibility>> {
+    fn opened_mut(&self) -> RefMut<'_, HashMap<Path, (abs::Visibility, Span)>> {
         std::cell::RefMut::map(self.borrow_mut(), |this| &mut this.opened)
     }
 }
@@ -161,10 +229,25 @@ impl Module {
             constants: Default::default(),
             submodules: Default::default(),
             opened: Default::default(),
+            used_opened: Default::default(),
             modules: Default::default(),
+            used_modules: Default::default(),
+            item: false,
         })))
     }
 
+    /// The module that can see the private definitions of this one from inside of it.
+    fn owner(&self) -> Path {
+        let this = self.borrow();
+        let mut owner = this.name.clone();
+
+        if this.item {
+            owner.segments.pop();
+        }
+
+        owner
+    }
+
     pub fn borrow(&self) -> Ref<Namespace> {
         self.0.borrow()
     }
@@ -174,15 +257,84 @@ impl Module {
     }
 
     /// Defines a name in the current namespace. It takes the visibility of the definition, the
-    /// kind of the definition, and the name of the definition.
-    pub fn define<Vis: Into<abs::Visibility>>(&self, kind: DefinitionKind, vis: Vis, name: Symbol) {
+    /// kind of the definition, the name of the definition and the location of the name.
+    pub fn define<Vis: Into<abs::Visibility>>(
+        &self,
+        kind: DefinitionKind,
+        vis: Vis,
+        name: Symbol,
+        span: Span,
+    ) {
         let bag = &mut self.borrow_mut().declared;
+        let entry = (vis.into(), span);
 
         match kind {
-            DefinitionKind::Type => bag.types.insert(name, vis.into()),
-            DefinitionKind::Value => bag.values.insert(name, vis.into()),
-            DefinitionKind::Trait => bag.traits.insert(name, vis.into()),
+            DefinitionKind::Type => bag.types.insert(name, entry),
+            DefinitionKind::Value => bag.values.insert(name, entry),
+            DefinitionKind::Trait => bag.traits.insert(name, entry),
+        };
+    }
+
+    /// Replaces the first segment of a path by the module that it's an alias of.
+    fn expand_alias(&self, path: &mut Path) {
+        let Some(first) = path.segments.first().cloned() else {
+            return;
+        };
+
+        let Some((alias, ..)) = self.modules().get(&first).cloned() else {
+            return;
         };
+
+        self.borrow_mut().used_modules.insert(first);
+
+        let mut segments = alias.segments;
+        segments.extend(path.segments.drain(1..));
+        path.segments = segments;
+    }
+
+    /// Reports the aliases of the module that were never used by a path.
+    fn report_unused_aliases(&self, reporter: &Report) {
+        let namespace = self.borrow();
+
+        let mut unused = namespace
+            .modules
+            .iter()
+            .filter(|(name, _)| !namespace.used_modules.contains(*name))
+            .collect::<Vec<_>>();
+
+        unused.sort_by_key(|(_, (_, _, span))| span.start.clone());
+
+        for (name, (_, _, span)) in unused {
+            reporter.report(Diagnostic::new(error::ResolverError {
+                span: span.clone(),
+                kind: error::ResolverErrorKind::UnusedAlias(name.clone()),
+            }));
+        }
+    }
+
+    /// Reports the private uses of the module that were never needed to find a name. Public uses
+    /// re-export the module, so they are always needed.
+    fn report_unused_uses(&self, reporter: &Report, available: &HashMap<Path, Module>) {
+        let namespace = self.borrow();
+
+        let mut unused = namespace
+            .opened
+            .iter()
+            .filter(|(path, (visibility, _))| {
+                *visibility == Visibility::Private
+                    && !namespace.used_opened.contains(*path)
+                    && available.contains_key(*path)
+            })
+            .collect::<Vec<_>>();
+
+        unused.sort_by_key(|(_, (_, span))| span.start.clone());
+
+        for (path, (_, span)) in unused {
+            reporter.report(Diagnostic::new(error::ResolverError {
+                span: span.clone(),
+                kind: error::ResolverErrorKind::UnusedUse(path.symbol()),
+            }));
+        }
     }
 
     pub fn fork(&self, name: Symbol) -> Module {
@@ -197,13 +349,22 @@ impl Module {
 }
 
 impl Module {
-    fn search_declared(&self, kind: DefinitionKind, name: Symbol) -> Option<abs::Visibility> {
+    fn search_declared(
+        &self,
+        kind: DefinitionKind,
+        name: Symbol,
+    ) -> Option<(abs::Visibility, Span)> {
         self.declared()
             .apply(kind, |declared| declared.get(&name).cloned())
     }
 
-    fn search_submodules(&self, name: Symbol) -> Option<Module> {
-        self.borrow().submodules.get(&name).cloned()
+    /// Searches a module nested inside of this one, going through one submodule for each segment
+    /// of the path.
+    fn search_submodules(&self, path: &Path) -> Option<Module> {
+        path.segments.iter().try_fold(self.clone(), |module, name| {
+            let submodule = module.borrow().submodules.get(name).cloned();
+            submodule
+        })
     }
 
     fn search_aliases(&self, kind: DefinitionKind, name: Symbol) -> Option<Alias> {
@@ -240,12 +401,9 @@ impl Module {
             return Ok(None);
         }
 
-        if let Some(visibility) = self.search_declared(kind, name.clone()) {
+        if let Some((visibility, definition)) = self.search_declared(kind, name.clone()) {
             if let abs::Visibility::Private = visibility {
-                return Err(Diagnostic::new(error::ResolverError {
-                    span,
-                    kind: error::ResolverErrorKind::PrivateDefinition,
-                }));
+                return Err(private_definition(span, definition));
             }
 
             return Ok(Some(qualified.clone()));
@@ -278,7 +436,9 @@ impl Module {
             );
         }
 
-        for (path, visibility) in self.opened().iter() {
+        let mut found = Vec::new();
+
+        for (path, (visibility, _)) in self.opened().iter() {
             let module = availables.borrow().get(path).cloned();
 
             if module.is_none() || visibility == &abs::Visibility::Private {
@@ -294,11 +454,11 @@ impl Module {
                 &mut nodes.clone(),
                 &mut graph.clone(),
             )? {
-                return Ok(Some(path));
+                found.push(path);
             }
         }
 
-        Ok(None)
+        disambiguate(span, &availables, kind, name, found)
     }
 
     pub fn search(
@@ -343,8 +503,11 @@ impl Module {
             );
         }
 
-        for (path, _) in self.opened().iter() {
-            let module = availables.borrow().get(path).cloned();
+        let mut found = Vec::new();
+        let mut through = Vec::new();
+
+        for (opened, _) in self.opened().iter() {
+            let module = availables.borrow().get(opened).cloned();
 
             if module.is_none() {
                 continue;
@@ -359,11 +522,14 @@ impl Module {
                 &mut map.clone(),
                 &mut graph.clone(),
             )? {
-                return Ok(Some(path));
+                found.push(path);
+                through.push(opened.clone());
             }
         }
 
-        Ok(None)
+        self.borrow_mut().used_opened.extend(through);
+
+        disambiguate(span, &availables, kind, name, found)
     }
 }
 
@@ -372,15 +538,39 @@ impl Module {
 #[derive(Clone)]
 pub struct Context {
     pub module: Module,
+
+    /// The modules that contain the current one, from the outermost to the innermost. Names that
+    /// are not found in the current module are searched in them.
+    enclosing: Vec<Module>,
     scope: RefCell<Bag<im_rc::HashSet<Symbol>>>,
     reporter: Report,
     available: Rc<RefCell<HashMap<Path, Module>>>,
 
     in_head: bool,
     constant: Option<abs::Qualified>,
+
+    /// The module whose public names are in scope without a `use`. Names declared or opened by
+    /// the module come first. It's [None] in the prelude itself and in the modules that opt out.
+    prelude: Option<Path>,
+
+    /// The counter of the ids of the nodes. It's shared by the contexts of the inline modules, so
+    /// the ids are unique in the whole program.
+    ids: Rc<Cell<u32>>,
 }
 
 impl Context {
+    /// Gives the id of a new node of the abstract tree.
+    pub fn next_id(&self) -> abs::NodeId {
+        let id = self.ids.get();
+        self.ids.set(id + 1);
+        abs::NodeId(id)
+    }
+
+    /// Creates a node of the abstract tree with a new id.
+    pub fn node<T>(&self, data: T, span: Span) -> abs::Node<T> {
+        abs::Node::new(self.next_id(), data, span)
+    }
+
     pub fn insert_constant(&mut self, path: abs::Qualified, span: Span) {
         if let Some(constant) = &self.constant {
             self.module
@@ -417,19 +607,69 @@ impl Context {
     ) -> Context {
         Context {
             module: Module::new(name),
+            enclosing: Vec::new(),
             scope: Default::default(),
             available,
             reporter: report,
 
             in_head: false,
             constant: None,
+            prelude: None,
+            ids: Default::default(),
         }
+        .with_prelude(Some(Path {
+            segments: vec![well_known::PRELUDE],
+        }))
+    }
+
+    /// Changes the module whose public names are in scope without a `use`. [None] opts out of the
+    /// prelude.
+    pub fn with_prelude(mut self, prelude: Option<Path>) -> Context {
+        self.prelude = prelude.filter(|prelude| *prelude != *self.module.name());
+        self
+    }
+
+    /// Searches a name that was not found anywhere else in the public names of the prelude.
+    fn search_prelude(
+        &self,
+        kind: DefinitionKind,
+        span: &Span,
+        name: &Symbol,
+    ) -> Option<Qualified> {
+        let prelude = self.available().get(self.prelude.as_ref()?).cloned()?;
+
+        let mut graph = DiGraph::new();
+        let mut nodes = HashMap::default();
+
+        let from = graph.add_node(Qualified {
+            path: self.module.name().clone(),
+            name: name.clone(),
+        });
+
+        prelude
+            .search_recursively(
+                span.clone(),
+                self.available.clone(),
+                kind,
+                name.clone(),
+                from,
+                &mut nodes,
+                &mut graph,
+            )
+            .ok()
+            .flatten()
     }
 
     pub fn search(&self, kind: DefinitionKind, span: Span, name: Symbol) -> Option<abs::Qualified> {
+        let path = Qualified {
+            path: Path { segments: vec![] },
+            name: name.clone(),
+        };
+
         let searched = self
-            .module
-            .search(span.clone(), self.available.clone(), kind, name.clone());
+            .module_of(kind, &span, &path)
+            .search(span.clone(), self.available.clone(), kind, name.clone())
+            .map(|found| found.or_else(|| self.search_prelude(kind, &span, &name)));
 
         match searched {
             Ok(Some(res)) => Some(abs::Qualified {
@@ -450,64 +690,154 @@ impl Context {
         }
     }
 
+    /// The innermost module, starting from the current one, where a path can be found. Paths that
+    /// are not found in any of them are searched in the current module, so the errors are reported
+    /// there.
+    fn module_of(&self, kind: DefinitionKind, span: &Span, path: &Qualified) -> Module {
+        if self.enclosing.is_empty() {
+            return self.module.clone();
+        }
+
+        let modules = std::iter::once(&self.module).chain(self.enclosing.iter().rev());
+
+        for module in modules {
+            let found = match path.path.segments.first() {
+                None => !matches!(
+                    module.search(
+                        span.clone(),
+                        self.available.clone(),
+                        kind,
+                        path.name.clone()
+                    ),
+                    Ok(None)
+                ),
+                Some(first) => {
+                    module.modules().contains_key(first)
+                        || module.search_submodules(&path.path).is_some()
+                }
+            };
+
+            if found {
+                return module.clone();
+            }
+        }
+
+        self.module.clone()
+    }
+
     pub fn get_path(
         &self,
         kind: DefinitionKind,
         span: Span,
-        mut path: Qualified,
+        path: Qualified,
         first: bool,
     ) -> Option<Qualified> {
-        if let Some((alias, _)) = self.module.modules().get(&path.path.symbol()) {
-            path.path = alias.clone();
+        let from = self.module.name().clone();
+
+        let mut forked = self.clone();
+        forked.module = self.module_of(kind, &span, &path);
+        let found = forked.lookup_path(kind, span.clone(), path.clone(), &from);
+
+        if found.is_none() && first {
+            self.reporter.report(Diagnostic::new(error::ResolverError {
+                span,
+                kind: error::ResolverErrorKind::InvalidPath(path.path.segments),
+            }));
         }
 
+        found.flatten()
+    }
+
+    /// Finds a qualified path. The `from` path is the module that contains the reference, and it's
+    /// kept while searching through opened modules so private definitions are only visible
+    /// inside of the module that declares them. It returns [None] if no module matches the path,
+    /// otherwise the errors about the name are already reported.
+    fn lookup_path(
+        &self,
+        kind: DefinitionKind,
+        span: Span,
+        mut path: Qualified,
+        from: &Path,
+    ) -> Option<Option<Qualified>> {
+        self.module.expand_alias(&mut path.path);
+
         let module = if path.path.is_empty() {
             self.module.clone()
         } else if let Some(module) = self.available().get(&path.path).cloned() {
             module
-        } else if let Some(module) = self.module.search_submodules(path.path.symbol()) {
+        } else if let Some(module) = self.module.search_submodules(&path.path) {
             module
         } else {
-            for (module_path, _) in self.module.opened().iter() {
-                let available = self.available().get(module_path).cloned();
+            let inside = inside(from, &self.module.owner());
+            let opened = self.module.opened().clone();
+
+            for (module_path, (visibility, _)) in opened {
+                // Modules opened by a private use are not re-exported.
+                if !inside && visibility == Visibility::Private {
+                    continue;
+                }
+
+                let available = self.available().get(&module_path).cloned();
                 if let Some(module) = available {
                     let mut forked = self.clone();
                     forked.module = module;
-                    if let Some(result) = forked.get_path(kind, span.clone(), path.clone(), false) {
-                        return Some(result);
+                    forked.prelude = None;
+                    let result = forked.lookup_path(kind, span.clone(), path.clone(), from);
+
+                    if result.is_some() {
+                        self.module.borrow_mut().used_opened.insert(module_path);
+                        return result;
                     }
                 }
             }
 
-            if first {
-                self.reporter.report(Diagnostic::new(error::ResolverError {
-                    span: span.clone(),
-                    kind: error::ResolverErrorKind::InvalidPath(path.path.segments.clone()),
-                }));
-            }
+            let prelude = self.prelude.as_ref();
+            let module = prelude.and_then(|prelude| self.available().get(prelude).cloned())?;
+
+            let mut forked = self.clone();
+            forked.module = module;
+            forked.prelude = None;
 
-            return None;
+            return forked.lookup_path(kind, span, path, from);
         };
 
-        let searched = module.search(
-            span.clone(),
-            self.available.clone(),
-            kind,
-            path.name.clone(),
-        );
+        if !inside(from, &module.owner()) {
+            if let Some((Visibility::Private, definition)) =
+                module.search_declared(kind, path.name.clone())
+            {
+                self.reporter.report(private_definition(span, definition));
+                return Some(None);
+            }
+        }
+
+        let searched = module
+            .search(
+                span.clone(),
+                self.available.clone(),
+                kind,
+                path.name.clone(),
+            )
+            .map(|found| {
+                found.or_else(|| {
+                    path.path
+                        .is_empty()
+                        .then(|| self.search_prelude(kind, &span, &path.name))
+                        .flatten()
+                })
+            });
 
         match searched {
-            Ok(Some(res)) => Some(res),
+            Ok(Some(res)) => Some(Some(res)),
             Ok(None) => {
                 self.reporter.report(Diagnostic::new(error::ResolverError {
                     span: span.clone(),
                     kind: error::ResolverErrorKind::NotFound(path.name),
                 }));
-                None
+                Some(None)
             }
             Err(err) => {
                 self.reporter.report(err);
-                None
+                Some(None)
             }
         }
     }
@@ -525,6 +855,18 @@ impl Context {
         })
     }
 
+    /// Resolves a path like [Context::resolve], but without reporting anything if it's not found.
+    pub fn try_resolve(
+        &self,
+        kind: DefinitionKind,
+        span: Span,
+        path: Qualified,
+    ) -> Option<abs::Qualified> {
+        let mut probe = self.clone();
+        probe.reporter = vulpi_report::hash_reporter();
+        probe.resolve(kind, span, path)
+    }
+
     /// Creates a nested context.
     pub fn fork(&self, name: Symbol) -> Context {
         let path = { self.module.borrow().name.clone() };
@@ -537,13 +879,19 @@ impl Context {
             .entry(path.with(name))
             .or_insert_with(|| module.clone());
 
+        let mut enclosing = self.enclosing.clone();
+        enclosing.push(self.module.clone());
+
         Context {
             module,
+            enclosing,
             scope,
             reporter: self.reporter.clone(),
             available: self.available.clone(),
             in_head: self.in_head,
             constant: self.constant.clone(),
+            prelude: self.prelude.clone(),
+            ids: self.ids.clone(),
         }
     }
 
@@ -574,9 +922,9 @@ impl Context {
         let bag = &self.module.borrow().declared;
 
         match kind {
-            DefinitionKind::Type => bag.types.get(&name).cloned(),
-            DefinitionKind::Value => bag.values.get(&name).cloned(),
-            DefinitionKind::Trait => bag.traits.get(&name).cloned(),
+            DefinitionKind::Type => bag.types.get(&name).map(|(vis, _)| vis.clone()),
+            DefinitionKind::Value => bag.values.get(&name).map(|(vis, _)| vis.clone()),
+            DefinitionKind::Trait => bag.traits.get(&name).map(|(vis, _)| vis.clone()),
         }
     }
 }
@@ -614,7 +962,7 @@ pub mod top_level {
             Type(type_decl) => Some(resolve_type_decl(ctx, *type_decl).map(abs::TopLevel::Type)),
             Module(mod_decl) => Some(resolve_module(ctx, *mod_decl).map(abs::TopLevel::Module)),
             External(ext) => Some(resolve_external(ctx, *ext).map(abs::TopLevel::External)),
-            Use(use_decl) => Some(resolve_use(ctx, *use_decl).map(|_| abs::TopLevel::Use)),
+            Use(use_decl) => Some(resolve_use(ctx, *use_decl).map(abs::TopLevel::Use)),
             Trait(trait_) => Some(resolve_trait(ctx, *trait_).map(abs::TopLevel::Trait)),
             Impl(impl_) => Some(resolve_impl(ctx, *impl_).map(abs::TopLevel::Impl)),
             Command(cmd) => Some(Solver::new(move |_| {
@@ -627,9 +975,14 @@ pub mod top_level {
     pub fn resolve_trait(ctx: Context, decl: tree::TraitDecl) -> Solver<abs::TraitDecl> {
         let name = decl.name.symbol();
         let submodule = ctx.fork(decl.name.symbol());
+        submodule.module.borrow_mut().item = true;
 
-        ctx.module
-            .define(DefinitionKind::Type, decl.visibility.clone(), name.clone());
+        ctx.module.define(
+            DefinitionKind::Type,
+            decl.visibility.clone(),
+            name.clone(),
+            decl.name.0.value.span.clone(),
+        );
 
         ctx.module.traits().insert(
             name.clone(),
@@ -667,6 +1020,7 @@ pub mod top_level {
                 let body = body.into_iter().map(|x| x.eval(ctx.clone())).collect();
 
                 abs::TraitDecl {
+                    id: ctx.next_id(),
                     name,
                     supers,
                     namespace: submodule.module.name().symbol(),
@@ -742,6 +1096,7 @@ pub mod top_level {
                     }
 
                     Some(abs::TraitImpl {
+                        id: ctx.next_id(),
                         name: abs::Qualified {
                             path: searched.path.symbol(),
                             name: searched.name,
@@ -766,8 +1121,12 @@ pub mod top_level {
         // in the IDE.
         let span = sig.name.0.value.span.clone();
 
-        ctx.module
-            .define(DefinitionKind::Value, sig.visibility.clone(), name.clone());
+        ctx.module.define(
+            DefinitionKind::Value,
+            sig.visibility.clone(),
+            name.clone(),
+            span.clone(),
+        );
 
         Solver::new(move |ctx| {
             ctx.scoped(|ctx| {
@@ -803,14 +1162,21 @@ pub mod top_level {
         // in the IDE.
         let span = decl.signature.name.0.value.span.clone();
 
-        if declare {
+        // A top level `let _` is only checked, so it's not added to the module and it gets a name
+        // of its own.
+        let wildcard = declare && decl.signature.is_wildcard();
+
+        if declare && !wildcard {
             ctx.module.define(
                 DefinitionKind::Value,
                 decl.signature.visibility.clone(),
                 name.clone(),
+                span.clone(),
             );
         }
 
+        let docs = decl.docs();
+
         Solver::new(move |ctx| {
             ctx.scoped(|ctx| {
                 let binders = decl
@@ -820,9 +1186,13 @@ pub mod top_level {
                     .map(|x| transform_let_binder(ctx, x))
                     .collect();
 
-                let name = abs::Qualified {
-                    path: ctx.module.name().symbol(),
-                    name,
+                let name = if wildcard {
+                    abs::Qualified::wildcard(ctx.module.name().symbol(), ctx.next_id())
+                } else {
+                    abs::Qualified {
+                        path: ctx.module.name().symbol(),
+                        name,
+                    }
                 };
 
                 ctx.set_constant(name.clone());
@@ -856,7 +1226,10 @@ pub mod top_level {
                 };
 
                 abs::LetDecl {
+                    id: ctx.next_id(),
                     signature,
+                    docs,
+                    attributes: transform_attributes(ctx, decl.attributes),
                     body,
                     constant,
                 }
@@ -868,9 +1241,14 @@ pub mod top_level {
     pub fn resolve_type_decl(ctx: Context, decl: tree::TypeDecl) -> Solver<abs::TypeDecl> {
         let name = decl.name.symbol();
         let submodule = ctx.fork(decl.name.symbol());
+        submodule.module.borrow_mut().item = true;
 
-        ctx.module
-            .define(DefinitionKind::Type, decl.visibility.clone(), name.clone());
+        ctx.module.define(
+            DefinitionKind::Type,
+            decl.visibility.clone(),
+            name.clone(),
+            decl.name.0.value.span.clone(),
+        );
 
         match &decl.def {
             None => {}
@@ -878,21 +1256,55 @@ pub mod top_level {
                 for (field, _) in &record.fields {
                     let name = field.name.symbol();
                     let vis = into_field_visiblity(field.visibility.clone().into());
-                    submodule.module.define(DefinitionKind::Value, vis, name);
+                    let span = field.name.0.value.span.clone();
+                    submodule
+                        .module
+                        .define(DefinitionKind::Value, vis, name, span);
                 }
             }
             Some((_, tree::TypeDef::Sum(sum))) => {
                 for cons in &sum.constructors {
                     let name = cons.name.symbol();
-                    submodule
-                        .module
-                        .define(DefinitionKind::Value, Visibility::Public, name);
+                    let span = cons.name.0.value.span.clone();
+                    submodule.module.define(
+                        DefinitionKind::Value,
+                        cons.visibility.clone(),
+                        name,
+                        span,
+                    );
                 }
             }
-            Some((_, tree::TypeDef::Synonym(_synonym))) => todo!(),
+            Some((_, tree::TypeDef::Synonym(_))) => (),
+        }
+
+        // The derived functions are written by the type checker, but they can be used like the
+        // others, so they are defined with the type.
+        let mut derives = Vec::new();
+
+        for (derive, _) in decl.deriving.iter().flat_map(|x| &x.names.data) {
+            let span = derive.0.value.span.clone();
+
+            let Some(kind) = abs::Derive::from_name(&derive.symbol().get()) else {
+                ctx.reporter.report(Diagnostic::new(error::ResolverError {
+                    span,
+                    kind: error::ResolverErrorKind::UnknownDerive(derive.symbol()),
+                }));
+                continue;
+            };
+
+            ctx.module.define(
+                DefinitionKind::Value,
+                decl.visibility.clone(),
+                kind.function(&name),
+                span.clone(),
+            );
+
+            derives.push((kind, span));
         }
 
         let namespace = submodule.module.name().clone();
+        let span = decl.name.0.value.span.clone();
+        let docs = decl.docs();
 
         Solver::new(move |ctx| {
             ctx.scoped(|ctx| {
@@ -919,6 +1331,8 @@ pub mod top_level {
                             .into_iter()
                             .map(|(field, _)| {
                                 let symbol = field.name.symbol();
+                                let span = field.name.0.value.span.clone();
+                                let docs = field.docs();
                                 let transform_type = transform_type(ctx, *field.typ);
                                 let into = field.visibility.into();
                                 (
@@ -928,6 +1342,8 @@ pub mod top_level {
                                     },
                                     transform_type,
                                     into,
+                                    span,
+                                    docs,
                                 )
                             })
                             .collect();
@@ -939,38 +1355,64 @@ pub mod top_level {
                             .constructors
                             .into_iter()
                             .map(|cons| {
+                                let docs = cons.docs();
+                                let visibility = cons.visibility.into();
                                 let name = cons.name.symbol();
+                                let span = cons.name.0.value.span.clone();
+
+                                // The labeled fields are the arguments of the constructor.
+                                let fields = cons.fields.into_iter().flat_map(|x| x.fields);
+                                let (labels, args): (Vec<_>, Vec<_>) = fields
+                                    .map(|(field, _)| {
+                                        let label_span = field.name.0.value.span.clone();
+                                        ((field.name.symbol(), label_span), field.typ)
+                                    })
+                                    .unzip();
+
                                 let args = cons
                                     .args
                                     .into_iter()
+                                    .chain(args)
                                     .map(|x| transform_type(ctx, *x))
                                     .collect();
                                 let typ = cons.typ.map(|x| transform_type(ctx, *x.1));
                                 abs::Constructor {
+                                    visibility,
                                     name: abs::Qualified {
                                         path: namespace.clone().symbol(),
                                         name,
                                     },
+                                    docs,
+                                    attributes: transform_attributes(ctx, cons.attributes),
                                     args,
+                                    labels,
                                     typ,
+                                    span,
                                 }
                             })
                             .collect();
 
                         abs::TypeDef::Sum(abs::SumDecl { constructors })
                     }
-                    Some((_, tree::TypeDef::Synonym(_synonym))) => todo!(),
+                    Some((_, tree::TypeDef::Synonym(synonym))) => {
+                        abs::TypeDef::Synonym(transform_type(ctx, *synonym))
+                    }
                 };
 
                 abs::TypeDecl {
+                    id: ctx.next_id(),
                     name: abs::Qualified {
                         path: ctx.module.name().symbol(),
                         name,
                     },
                     namespace: namespace.symbol(),
                     visibility: decl.visibility.into(),
+                    docs,
+                    attributes: transform_attributes(ctx, decl.attributes),
                     binders,
                     def,
+                    derives,
+                    span,
                 }
             })
         })
@@ -980,12 +1422,18 @@ pub mod top_level {
     pub fn resolve_external(ctx: Context, decl: tree::ExtDecl) -> Solver<abs::ExtDecl> {
         let name = decl.name.symbol();
 
-        ctx.module
-            .define(DefinitionKind::Value, decl.visibility.clone(), name.clone());
+        ctx.module.define(
+            DefinitionKind::Value,
+            decl.visibility.clone(),
+            name.clone(),
+            decl.name.0.value.span.clone(),
+        );
 
         let namespace = ctx.module.name().clone();
+        let span = decl.name.0.value.span.clone();
 
         Solver::new(move |module| abs::ExtDecl {
+            id: module.next_id(),
             name: abs::Qualified {
                 path: namespace.clone().symbol(),
                 name,
@@ -994,6 +1442,7 @@ pub mod top_level {
             visibility: decl.visibility.into(),
             typ: transform_type(&module, *decl.typ),
             ret: decl.str.symbol(),
+            span,
         })
     }
 
@@ -1011,8 +1460,16 @@ pub mod top_level {
                 }
             }
 
-            Solver::new(move |ctx| {
-                let mut program = abs::Program::default();
+            let module = ctx.module.clone();
+            let name = module.name().symbol();
+
+            Solver::new(move |mut ctx| {
+                let mut program = abs::Program::new(name);
+
+                // The declarations are solved inside of the module, so their names are qualified
+                // by its path and they see the names that it defines.
+                let parent = std::mem::replace(&mut ctx.module, module.clone());
+                ctx.enclosing.push(parent);
 
                 for solver in solvers {
                     match solver.eval(ctx.clone()) {
@@ -1026,10 +1483,13 @@ pub mod top_level {
                         abs::TopLevel::Command(name, symbol) => {
                             program.commands.push((name, symbol))
                         }
-                        abs::TopLevel::Use => (),
+                        abs::TopLevel::Use(x) => program.uses.push(x),
                     }
                 }
 
+                module.report_unused_aliases(&ctx.reporter);
+                module.report_unused_uses(&ctx.reporter, &ctx.available());
+
                 program
             })
         }
@@ -1040,22 +1500,43 @@ pub mod top_level {
             .map(|x| resolve_module_inline(new_context.clone(), x));
 
         Solver::new(move |ctx| abs::ModuleDecl {
+            id: ctx.next_id(),
             visibility: decl.visibility.into(),
             name: decl.name.symbol(),
             decls: solver.map(|x| x.eval(ctx)),
+            span: decl.name.0.value.span.clone(),
         })
     }
 
-    pub fn resolve_use(ctx: Context, decl: tree::UseDecl) -> Solver<()> {
+    pub fn resolve_use(ctx: Context, decl: tree::UseDecl) -> Solver<abs::UseDecl> {
+        let alias_name = decl.alias.as_ref().map(|alias| alias.alias.symbol());
+
         if let Some(alias) = decl.alias {
-            ctx.module.modules_mut().insert(
+            let span = alias.alias.0.value.span.clone();
+
+            let previous = ctx.module.modules_mut().insert(
                 alias.alias.symbol(),
-                (from_upper_path(&decl.path), decl.visibility.clone().into()),
+                (
+                    from_upper_path(&decl.path),
+                    decl.visibility.clone().into(),
+                    span.clone(),
+                ),
             );
+
+            if let Some((_, _, first)) = previous {
+                let diagnostic = Diagnostic::new(ResolverError {
+                    span,
+                    kind: error::ResolverErrorKind::ConflictingAlias(alias.alias.symbol()),
+                });
+
+                ctx.reporter
+                    .report(diagnostic.with_label(first, "first used as an alias here"));
+            }
         } else {
-            ctx.module
-                .opened_mut()
-                .insert(from_upper_path(&decl.path), decl.visibility.clone().into());
+            ctx.module.opened_mut().insert(
+                from_upper_path(&decl.path),
+                (decl.visibility.clone().into(), decl.path.span.clone()),
+            );
         }
 
         Solver::new(move |ctx| {
@@ -1064,19 +1545,71 @@ pub mod top_level {
             if !ctx.available().contains_key(&path) {
                 ctx.reporter.report(Diagnostic::new(ResolverError {
                     span: decl.path.span.clone(),
-                    kind: error::ResolverErrorKind::InvalidPath(path.segments),
+                    kind: error::ResolverErrorKind::InvalidPath(path.segments.clone()),
                 }));
             }
+
+            abs::UseDecl {
+                visibility: decl.visibility.clone().into(),
+                path: path.symbol(),
+                alias: alias_name,
+                span: decl.path.span.clone(),
+            }
         })
     }
 }
 
-pub fn transform_literal(literal: tree::Literal) -> abs::Literal {
-    let data = match literal.data {
+/// Transforms the attributes of a declaration. Their names and arguments are checked by the type
+/// checker.
+pub fn transform_attributes(
+    ctx: &Context,
+    attributes: Vec<tree::DeclAttribute>,
+) -> Vec<abs::Attribute> {
+    attributes
+        .into_iter()
+        .map(|attribute| {
+            let end = match &attribute.args {
+                Some(args) => &args.right,
+                None => &attribute.name.0,
+            };
+
+            let span = attribute.at.value.span.clone().mix(end.value.span.clone());
+
+            abs::Attribute {
+                name: attribute.name.symbol(),
+                args: attribute
+                    .args
+                    .into_iter()
+                    .flat_map(|args| args.data)
+                    .map(|(literal, _)| transform_literal(ctx, literal))
+                    .collect(),
+                span,
+            }
+        })
+        .collect()
+}
+
+pub fn transform_literal(ctx: &Context, literal: tree::Literal) -> abs::Literal {
+    // Invalid numbers are reported and keep their text, the program is not compiled anyway.
+    let number = |result: Result<Symbol, error::ResolverErrorKind>, text: Symbol| {
+        result.unwrap_or_else(|kind| {
+            ctx.reporter.report(Diagnostic::new(error::ResolverError {
+                span: literal.span.clone(),
+                kind,
+            }));
+            text
+        })
+    };
+
+    let data = match &literal.data {
         tree::LiteralKind::String(x) => abs::LiteralKind::String(x.symbol()),
         tree::LiteralKind::Char(x) => abs::LiteralKind::Char(x.symbol()),
-        tree::LiteralKind::Integer(x) => abs::LiteralKind::Integer(x.symbol()),
-        tree::LiteralKind::Float(x) => abs::LiteralKind::Float(x.symbol()),
+        tree::LiteralKind::Integer(x) => {
+            abs::LiteralKind::Integer(number(literal::integer(&x.symbol()), x.symbol()))
+        }
+        tree::LiteralKind::Float(x) => {
+            abs::LiteralKind::Float(number(literal::float(&x.symbol()), x.symbol()))
+        }
         tree::LiteralKind::Unit(_) => abs::LiteralKind::Unit,
     };
 
@@ -1088,7 +1621,7 @@ pub fn transform_literal(literal: tree::Literal) -> abs::Literal {
 
 /// Patterns are the ones that can be used in a match expression.
 pub mod pattern {
-    use im_rc::HashSet;
+    use std::collections::HashMap;
 
     use vulpi_report::Diagnostic;
 
@@ -1097,7 +1630,7 @@ pub mod pattern {
     fn transform_pat(
         ctx: &Context,
         pattern: tree::Pattern,
-        vars: &mut HashSet<Symbol>,
+        vars: &mut HashMap<Symbol, Span>,
     ) -> abs::Pattern {
         let data = match pattern.data {
             tree::PatternKind::Wildcard(_) => abs::PatternKind::Wildcard,
@@ -1116,19 +1649,22 @@ pub mod pattern {
                 }
             }
             tree::PatternKind::Variable(x) => {
-                if vars.contains(&x.symbol()) {
-                    ctx.reporter.report(Diagnostic::new(error::ResolverError {
-                        span: pattern.span.clone(),
-                        kind: error::ResolverErrorKind::DuplicatePattern(x.symbol()),
-                    }));
+                if let Some(first) = vars.get(&x.symbol()) {
+                    ctx.reporter.report(
+                        Diagnostic::new(error::ResolverError {
+                            span: pattern.span.clone(),
+                            kind: error::ResolverErrorKind::DuplicatePattern(x.symbol()),
+                        })
+                        .with_label(first.clone(), "first bound here"),
+                    );
                     abs::PatternKind::Error
                 } else {
-                    vars.insert(x.symbol());
+                    vars.insert(x.symbol(), pattern.span.clone());
                     abs::PatternKind::Variable(x.symbol())
                 }
             }
             tree::PatternKind::Literal(x) => {
-                let lit = transform_literal(x);
+                let lit = transform_literal(ctx, x);
                 abs::PatternKind::Literal(lit)
             }
             tree::PatternKind::Annotation(app) => {
@@ -1152,16 +1688,46 @@ pub mod pattern {
                     from_constructor_upper_path(&app.func),
                 );
 
+                // The sub patterns still bind variables that the body of the arm refers to.
+                let args = app
+                    .args
+                    .into_iter()
+                    .map(|x| transform_pat(ctx, *x, vars))
+                    .collect();
+
                 match func {
-                    Some(func) => {
-                        let args = app
-                            .args
-                            .into_iter()
-                            .map(|x| transform_pat(ctx, *x, vars))
-                            .collect();
+                    Some(func) => abs::PatternKind::Application(abs::PatApplication { func, args }),
+                    None => abs::PatternKind::Error,
+                }
+            }
+            tree::PatternKind::Record(record) => {
+                let func = ctx.resolve(
+                    DefinitionKind::Value,
+                    pattern.span.clone(),
+                    from_constructor_upper_path(&record.name),
+                );
 
-                        abs::PatternKind::Application(abs::PatApplication { func, args })
-                    }
+                let fields = record
+                    .fields
+                    .into_iter()
+                    .map(|(field, _)| {
+                        let span = field.name.0.value.span.clone();
+                        let name = field.name.symbol();
+
+                        let pattern = match field.pattern {
+                            Some((_, pattern)) => *pattern,
+                            None => Spanned {
+                                span: span.clone(),
+                                data: tree::PatternKind::Variable(field.name),
+                            },
+                        };
+
+                        (span, name, transform_pat(ctx, pattern, vars))
+                    })
+                    .collect();
+
+                match func {
+                    Some(func) => abs::PatternKind::Labeled(abs::PatLabeled { func, fields }),
                     None => abs::PatternKind::Error,
                 }
             }
@@ -1170,10 +1736,7 @@ pub mod pattern {
             }
         };
 
-        Box::new(Spanned {
-            data,
-            span: pattern.span.clone(),
-        })
+        Box::new(ctx.node(data, pattern.span.clone()))
     }
 
     /// Transform a pattern into an abstract pattern.
@@ -1182,7 +1745,7 @@ pub mod pattern {
 
         let pattern = transform_pat(ctx, pattern, &mut vars);
 
-        for var in vars {
+        for var in vars.into_keys() {
             ctx.with(DefinitionKind::Value, var);
         }
 
@@ -1197,7 +1760,7 @@ pub mod pattern {
             .map(|x| transform_pat(ctx, *x, &mut vars))
             .collect::<Vec<_>>();
 
-        for var in vars {
+        for var in vars.into_keys() {
             ctx.with(DefinitionKind::Value, var);
         }
 
@@ -1205,30 +1768,32 @@ pub mod pattern {
     }
 
     pub fn transform_attribute(ctx: &mut Context, attribute: tree::Attribute) -> abs::Expr {
+        let name_span = attribute.name.0.value.span.clone();
+
         let func = ctx.resolve(
             DefinitionKind::Value,
-            attribute.name.0.value.span.clone(),
+            name_span.clone(),
             Qualified {
                 path: Path { segments: vec![] },
                 name: attribute.name.symbol(),
             },
         );
 
-        let res = if let Some(func) = func {
+        // The application covers the attribute from its name to the end of its value.
+        let (res, span) = if let Some(func) = func {
             let expr = expr::transform(ctx, *attribute.value);
-            abs::ExprKind::Application(abs::ApplicationExpr {
+            let span = name_span.merge(&expr.span).unwrap_or(name_span.clone());
+            let res = abs::ExprKind::Application(abs::ApplicationExpr {
                 app: abs::AppKind::Normal,
-                func: Box::new(Spanned::new(
-                    abs::ExprKind::Constructor(func),
-                    Default::default(),
-                )),
+                func: Box::new(ctx.node(abs::ExprKind::Constructor(func), name_span)),
                 args: vec![expr],
-            })
+            });
+            (res, span)
         } else {
-            abs::ExprKind::Error
+            (abs::ExprKind::Error, name_span)
         };
 
-        Box::new(Spanned::new(res, Default::default()))
+        Box::new(ctx.node(res, span))
     }
 
     /// Transform a pattern into an abstract pattern.
@@ -1248,6 +1813,34 @@ pub mod pattern {
         })
     }
 
+    /// The span from a token to the last one of the tokens that were skipped after it, leaving out
+    /// the layout tokens.
+    fn skipped_span(start: &Token, tokens: &[Token]) -> Span {
+        let last = tokens.iter().rev().find(|token| {
+            !matches!(
+                token.kind,
+                TokenData::Begin | TokenData::Sep | TokenData::End
+            )
+        });
+
+        match last {
+            Some(last) => start.value.span.clone().mix(last.value.span.clone()),
+            None => start.value.span.clone(),
+        }
+    }
+
+    /// An arm for a body or clause that could not be parsed. It has error patterns in the place of
+    /// the ones of the other clauses, so it matches everything and no other error is reported.
+    fn error_arm(ctx: &mut Context, arity: usize, span: Span) -> abs::PatternArm {
+        abs::PatternArm {
+            patterns: (0..arity)
+                .map(|_| Box::new(ctx.node(abs::PatternKind::Error, span.clone())))
+                .collect(),
+            expr: Box::new(ctx.node(abs::ExprKind::Error, span)),
+            guard: None,
+        }
+    }
+
     /// Transform a let mode into a list of pattern arms.
     pub fn transform_let_mode(ctx: &mut Context, mode: LetMode) -> Vec<abs::PatternArm> {
         match mode {
@@ -1258,10 +1851,30 @@ pub mod pattern {
                     guard: None,
                 }]
             }
-            LetMode::Cases(cases) => cases
-                .into_iter()
-                .map(|x| transform_pattern_arm(ctx, x.arm))
-                .collect(),
+            LetMode::Cases(cases) => {
+                let arity = cases
+                    .iter()
+                    .find_map(|case| match &case.arm {
+                        LetCaseKind::Arm(arm) => Some(arm.patterns.len()),
+                        LetCaseKind::Error(_) => None,
+                    })
+                    .unwrap_or_default();
+
+                cases
+                    .into_iter()
+                    .map(|case| match case.arm {
+                        LetCaseKind::Arm(arm) => transform_pattern_arm(ctx, *arm),
+                        LetCaseKind::Error(tokens) => {
+                            let span = skipped_span(&case.pipe, &tokens);
+                            error_arm(ctx, arity, span)
+                        }
+                    })
+                    .collect()
+            }
+            LetMode::Error(tokens) => {
+                let span = skipped_span(&tokens[0], &tokens);
+                vec![error_arm(ctx, 0, span)]
+            }
         }
     }
 }
@@ -1272,6 +1885,26 @@ pub mod expr {
     use crate::error::ResolverError;
     use vulpi_syntax::r#abstract::SttmKind::Expr;
 
+    /// Transforms a field of a record instance or update. A pun like `{ name }` is expanded to
+    /// `{ name = name }`, and both the field and the variable get the span of the pun.
+    pub fn transform_record_field(
+        ctx: &mut Context,
+        field: tree::RecordField,
+    ) -> (Span, Symbol, abs::Expr) {
+        let span = field.name.0.value.span.clone();
+        let name = field.name.symbol();
+
+        let expr = match field.value {
+            Some((_, expr)) => *expr,
+            None => Spanned {
+                span: span.clone(),
+                data: tree::ExprKind::Variable(field.name),
+            },
+        };
+
+        (span, name, transform(ctx, expr))
+    }
+
     /// Transforms an expression into an abstract expression.
     pub fn transform(ctx: &mut Context, expr: concrete::tree::Expr) -> abs::Expr {
         use tree::ExprKind::*;
@@ -1287,12 +1920,18 @@ pub mod expr {
 
                     let body = transform(ctx, *lam.expr);
 
-                    pats.into_iter().rev().fold(body, |body, param| {
-                        Box::new(Spanned {
-                            data: abs::ExprKind::Lambda(abs::LambdaExpr { param, body }),
-                            span: expr.span.clone(),
-                        })
-                    })
+                    // The inner lambdas only cover their own parameter and body, while the outer
+                    // one covers the whole expression.
+                    let lambda = pats.into_iter().rev().fold(body, |body, param| {
+                        let span = param.span.merge(&body.span).unwrap_or(expr.span.clone());
+                        Box::new(
+                            ctx.node(abs::ExprKind::Lambda(abs::LambdaExpr { param, body }), span),
+                        )
+                    });
+
+                    let mut lambda = lambda;
+                    lambda.span = expr.span.clone();
+                    lambda
                 });
             }
 
@@ -1365,6 +2004,31 @@ pub mod expr {
                 expr: transform(ctx, *projection.expr),
                 field: projection.field.symbol(),
             }),
+            TupleProjection(projection) => {
+                let text = projection.index.symbol();
+
+                // Indices are written like integers, so they are validated in the same way.
+                let index = literal::integer(&text).and_then(|index| {
+                    index
+                        .get()
+                        .parse()
+                        .map_err(|_| error::ResolverErrorKind::IntegerOverflow(text.clone()))
+                });
+
+                match index {
+                    Ok(index) => abs::ExprKind::TupleProjection(abs::TupleProjectionExpr {
+                        expr: transform(ctx, *projection.expr),
+                        index,
+                    }),
+                    Err(kind) => {
+                        ctx.reporter.report(Diagnostic::new(error::ResolverError {
+                            span: projection.index.value.span.clone(),
+                            kind,
+                        }));
+                        abs::ExprKind::Error
+                    }
+                }
+            }
             Binary(bin) => {
                 ctx.in_head = false;
 
@@ -1398,7 +2062,7 @@ pub mod expr {
                     expr.span.clone(),
                     Qualified {
                         path: Path {
-                            segments: vec![Symbol::intern("Prelude")],
+                            segments: vec![well_known::PRELUDE],
                         },
                         name: Symbol::intern(name),
                     },
@@ -1407,23 +2071,62 @@ pub mod expr {
                 if let Some(path) = path {
                     abs::ExprKind::Application(abs::ApplicationExpr {
                         app: abs::AppKind::Infix,
-                        func: Box::new(Spanned::new(
-                            abs::ExprKind::Function(path),
-                            bin.op.get_span(),
-                        )),
+                        func: Box::new(ctx.node(abs::ExprKind::Function(path), bin.op.get_span())),
                         args: vec![left, right],
                     })
                 } else {
                     abs::ExprKind::Error
                 }
             }
+            Let(let_expr) if let_expr.rec.is_some() => ctx.scoped(|ctx| {
+                let (patterns, bodies): (Vec<_>, Vec<_>) = let_expr
+                    .group
+                    .into_iter()
+                    .map(|binding| (binding.pattern, binding.body))
+                    .unzip();
+
+                // The names of the whole group are bound before any of the bodies.
+                let patterns = [let_expr.pattern].into_iter().chain(patterns).collect();
+                let patterns = pattern::transform_row(ctx, patterns);
+
+                for pattern in &patterns {
+                    if !matches!(pattern.data, abs::PatternKind::Variable(_)) {
+                        ctx.reporter.report(Diagnostic::new(ResolverError {
+                            span: pattern.span.clone(),
+                            kind: error::ResolverErrorKind::RecursivePattern,
+                        }));
+                    }
+                }
+
+                let mut patterns = patterns.into_iter();
+                let pattern = patterns.next().unwrap();
+                let body = expr::transform(ctx, *let_expr.body);
+
+                let group = patterns
+                    .zip(bodies)
+                    .map(|(pattern, body)| (pattern, expr::transform(ctx, *body)))
+                    .collect();
+
+                abs::ExprKind::Let(abs::LetExpr {
+                    pattern,
+                    typ: None,
+                    body,
+                    value: expr::transform(ctx, *let_expr.value),
+                    recursive: true,
+                    group,
+                })
+            }),
             Let(let_expr) => {
+                let typ = let_expr.typ.map(|(_, typ)| transform_type(ctx, *typ));
                 let body = expr::transform(ctx, *let_expr.body);
                 ctx.scoped(|ctx| {
                     abs::ExprKind::Let(abs::LetExpr {
                         pattern: pattern::transform(ctx, *let_expr.pattern),
+                        typ,
                         body,
                         value: expr::transform(ctx, *let_expr.value),
+                        recursive: false,
+                        group: Vec::new(),
                     })
                 })
             }
@@ -1452,35 +2155,59 @@ pub mod expr {
                         .collect(),
                 })
             }),
-            Literal(x) => abs::ExprKind::Literal(transform_literal(x)),
+            Literal(x) => abs::ExprKind::Literal(transform_literal(ctx, x)),
+            Hole(hole) => abs::ExprKind::Hole(hole.name.symbol()),
             Annotation(x) => {
                 let expr = transform(ctx, *x.expr);
                 let ty = transform_type(ctx, *x.typ);
 
                 abs::ExprKind::Annotation(abs::AnnotationExpr { expr, typ: ty })
             }
+            TypeApplication(x) => {
+                let expr = transform(ctx, *x.expr);
+                let ty = transform_type(ctx, *x.typ);
+
+                abs::ExprKind::TypeApplication(abs::TypeApplicationExpr { expr, typ: ty })
+            }
             RecordInstance(record_instance) => {
                 ctx.in_head = false;
-                let path = ctx.resolve(
-                    DefinitionKind::Type,
+
+                // Constructors with labeled fields are built like records.
+                let constructor = ctx.try_resolve(
+                    DefinitionKind::Value,
                     expr.span.clone(),
                     from_constructor_upper_path(&record_instance.name),
                 );
 
-                match path {
-                    Some(name) => abs::ExprKind::RecordInstance(abs::RecordInstance {
+                if let Some(name) = constructor {
+                    ctx.insert_constant(name.clone(), expr.span.clone());
+
+                    abs::ExprKind::Labeled(abs::RecordInstance {
                         name,
                         fields: record_instance
                             .fields
                             .into_iter()
-                            .map(|(field, _)| {
-                                let name = field.name.symbol();
-                                let expr = transform(ctx, *field.expr);
-                                (field.name.0.value.span, name, expr)
-                            })
+                            .map(|(field, _)| transform_record_field(ctx, field))
                             .collect(),
-                    }),
-                    None => abs::ExprKind::Error,
+                    })
+                } else {
+                    let path = ctx.resolve(
+                        DefinitionKind::Type,
+                        expr.span.clone(),
+                        from_constructor_upper_path(&record_instance.name),
+                    );
+
+                    match path {
+                        Some(name) => abs::ExprKind::RecordInstance(abs::RecordInstance {
+                            name,
+                            fields: record_instance
+                                .fields
+                                .into_iter()
+                                .map(|(field, _)| transform_record_field(ctx, field))
+                                .collect(),
+                        }),
+                        None => abs::ExprKind::Error,
+                    }
                 }
             }
             RecordUpdate(record_update) => {
@@ -1490,11 +2217,7 @@ pub mod expr {
                     fields: record_update
                         .fields
                         .into_iter()
-                        .map(|(field, _)| {
-                            let name = field.name.symbol();
-                            let expr = transform(ctx, *field.expr);
-                            (field.name.0.value.span, name, expr)
-                        })
+                        .map(|(field, _)| transform_record_field(ctx, field))
                         .collect(),
                 })
             }
@@ -1509,15 +2232,10 @@ pub mod expr {
                 })
             }
             Parenthesis(parenthesis) => return transform(ctx, *parenthesis.data.0),
-            HtmlNode(node) => {
-                transform_html(ctx, expr.span.clone(), node).data
-            }
+            HtmlNode(node) => transform_html(ctx, expr.span.clone(), node).data,
         };
 
-        Box::new(Spanned {
-            data,
-            span: expr.span.clone(),
-        })
+        Box::new(ctx.node(data, expr.span.clone()))
     }
 
     fn transform_html(ctx: &mut Context, span: Span, node: tree::HtmlNode) -> abs::Expr {
@@ -1531,39 +2249,41 @@ pub mod expr {
         );
 
         let kind = if let Some(name) = name {
-            let attributes = node.attributes.into_iter().map(|attr| {
-                pattern::transform_attribute(ctx, attr)
-            }).collect();
+            let attributes = node
+                .attributes
+                .into_iter()
+                .map(|attr| pattern::transform_attribute(ctx, attr))
+                .collect();
 
-            let children = node.children.into_iter().map(|child| {
-                transform_html(ctx, span.clone(), child)
-            }).collect();
+            let children = node
+                .children
+                .into_iter()
+                .map(|child| transform_html(ctx, span.clone(), child))
+                .collect();
+
+            let attributes = fold_list(ctx, span.clone(), attributes);
+            let children = fold_list(ctx, span.clone(), children);
 
             abs::ExprKind::Application(abs::ApplicationExpr {
                 app: abs::AppKind::Normal,
-                func: Box::new(Spanned::new(
-                    abs::ExprKind::Function(name),
-                    span.clone(),
-                )),
-                args: vec![Box::new(Spanned::new(
-                    abs::ExprKind::Literal(Box::new(Spanned {
-                        data: abs::LiteralKind::String(node.name.symbol()),
-                        span: node.name.0.value.span.clone(),
-                    })),
-                    node.name.0.value.span.clone(),
-                )),
-                Box::new(Spanned::new(fold_list(ctx, span.clone(), attributes), span.clone())),
-                Box::new(Spanned::new(fold_list(ctx, span.clone(), children), span.clone())),
-                ]
+                func: Box::new(ctx.node(abs::ExprKind::Function(name), span.clone())),
+                args: vec![
+                    Box::new(ctx.node(
+                        abs::ExprKind::Literal(Box::new(Spanned {
+                            data: abs::LiteralKind::String(node.name.symbol()),
+                            span: node.name.0.value.span.clone(),
+                        })),
+                        node.name.0.value.span.clone(),
+                    )),
+                    Box::new(ctx.node(attributes, span.clone())),
+                    Box::new(ctx.node(children, span.clone())),
+                ],
             })
         } else {
             abs::ExprKind::Error
         };
 
-        Box::new(Spanned {
-            data: kind,
-            span: span.clone(),
-        })
+        Box::new(ctx.node(kind, span.clone()))
     }
 
     fn fold_list(ctx: &mut Context, span: Span, values: Vec<abs::Expr>) -> abs::ExprKind {
@@ -1572,9 +2292,9 @@ pub mod expr {
             span.clone(),
             Qualified {
                 path: Path {
-                    segments: vec![Symbol::intern("List")],
+                    segments: vec![well_known::LIST],
                 },
-                name: Symbol::intern("Nil"),
+                name: well_known::NIL,
             },
         );
 
@@ -1583,9 +2303,9 @@ pub mod expr {
             span.clone(),
             Qualified {
                 path: Path {
-                    segments: vec![Symbol::intern("List")],
+                    segments: vec![well_known::LIST],
                 },
-                name: Symbol::intern("Cons"),
+                name: well_known::CONS,
             },
         );
 
@@ -1593,18 +2313,27 @@ pub mod expr {
             ctx.insert_constant(nil.clone(), span.clone());
             ctx.insert_constant(cons.clone(), span.clone());
 
+            // Each tail of the list covers from its first element to the end of the list, and the
+            // empty one is at the end of the list.
+            let end = Span::new(span.file, span.end.clone(), span.end.clone());
+            let nil = ctx.node(abs::ExprKind::Constructor(nil.clone()), end);
+
             values
                 .into_iter()
-                .rfold(abs::ExprKind::Constructor(nil.clone()), |acc, value| {
-                    abs::ExprKind::Application(abs::ApplicationExpr {
+                .rfold(nil, |acc, value| {
+                    let tail = value.span.merge(&acc.span).unwrap_or(span.clone());
+
+                    let app = abs::ExprKind::Application(abs::ApplicationExpr {
                         app: abs::AppKind::Normal,
-                        func: Box::new(Spanned::new(
-                            abs::ExprKind::Constructor(cons.clone()),
-                            Default::default(),
-                        )),
-                        args: vec![value, Box::new(Spanned::new(acc, Default::default()))],
-                    })
+                        func: Box::new(
+                            ctx.node(abs::ExprKind::Constructor(cons.clone()), value.span.clone()),
+                        ),
+                        args: vec![value, Box::new(acc)],
+                    });
+
+                    ctx.node(app, tail)
                 })
+                .data
         } else {
             ctx.reporter.report(Diagnostic::new(ResolverError {
                 span: span.clone(),
@@ -1711,10 +2440,7 @@ pub fn transform_type(ctx: &Context, concrete_type: tree::Type) -> abs::Type {
         tree::TypeKind::Unit(_) => abs::TypeKind::Unit,
     };
 
-    Box::new(Spanned {
-        data,
-        span: concrete_type.span.clone(),
-    })
+    Box::new(ctx.node(data, concrete_type.span.clone()))
 }
 
 pub fn transform_binder(ctx: &Context, binder: tree::Binder) -> abs::Binder {
@@ -1739,9 +2465,10 @@ pub fn transform_sttm(ctx: &mut Context, sttm: concrete::tree::Sttm) -> abs::Stt
     let data = match sttm.data {
         tree::StatementKind::Let(let_sttm) => {
             let pat = pattern::transform(ctx, *let_sttm.pattern);
+            let typ = let_sttm.typ.map(|(_, typ)| transform_type(ctx, *typ));
             let expr = expr::transform(ctx, *let_sttm.expr);
 
-            abs::SttmKind::Let(abs::LetSttm { pat, expr })
+            abs::SttmKind::Let(abs::LetSttm { pat, typ, expr })
         }
         tree::StatementKind::Expr(expr) => {
             let expr = expr::transform(ctx, *expr);
@@ -1750,10 +2477,7 @@ pub fn transform_sttm(ctx: &mut Context, sttm: concrete::tree::Sttm) -> abs::Stt
         tree::StatementKind::Error(_) => abs::SttmKind::Error,
     };
 
-    abs::Sttm {
-        data,
-        span: sttm.span.clone(),
-    }
+    ctx.node(data, sttm.span.clone())
 }
 
 /// Resolve all the top level declarations of a program.
@@ -1767,7 +2491,7 @@ pub fn resolve(ctx: &Context, program: tree::Program) -> Solver<abs::Program> {
     }
 
     Solver::new(|ctx| {
-        let mut program = abs::Program::default();
+        let mut program = abs::Program::new(ctx.module.name().symbol());
 
         for solver in solvers {
             match solver.eval(ctx.clone()) {
@@ -1779,10 +2503,97 @@ pub fn resolve(ctx: &Context, program: tree::Program) -> Solver<abs::Program> {
                 abs::TopLevel::Impl(Some(t)) => program.impls.push(t),
                 abs::TopLevel::Command(name, symbol) => program.commands.push((name, symbol)),
                 abs::TopLevel::Impl(None) => (),
-                abs::TopLevel::Use => (),
+                abs::TopLevel::Use(x) => program.uses.push(x),
             }
         }
 
+        // The prelude is used without a `use`, so the file depends on it like on the others.
+        if let Some(prelude) = ctx
+            .prelude
+            .as_ref()
+            .filter(|p| ctx.available().contains_key(p))
+        {
+            program.uses.push(abs::UseDecl {
+                visibility: abs::Visibility::Private,
+                path: prelude.symbol(),
+                alias: None,
+                span: Span::default(),
+            });
+        }
+
+        ctx.module.report_unused_aliases(&ctx.reporter);
+        ctx.module
+            .report_unused_uses(&ctx.reporter, &ctx.available());
+
+        program.next = abs::NodeId(ctx.ids.get());
+
         program
     })
 }
+
+/// The options of [resolve_modules_with_options].
+pub struct ResolveOptions {
+    /// The module whose public names are in scope in every module without a `use`.
+    pub prelude: Option<Path>,
+
+    /// The modules that opt out of the prelude, so they only see the names that they use.
+    pub without_prelude: Vec<Path>,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            prelude: Some(Path {
+                segments: vec![well_known::PRELUDE],
+            }),
+            without_prelude: Vec::new(),
+        }
+    }
+}
+
+/// Resolves a set of modules that can refer to each other. The programs are returned in the same
+/// order together with the report of everything that went wrong, including cycles between
+/// constants.
+pub fn resolve_modules(
+    modules: Vec<(Path, tree::Program)>,
+    report: Report,
+) -> (Vec<abs::Program>, Report) {
+    resolve_modules_with_options(modules, report, ResolveOptions::default())
+}
+
+/// Same as [resolve_modules], but with the given options.
+pub fn resolve_modules_with_options(
+    modules: Vec<(Path, tree::Program)>,
+    report: Report,
+    options: ResolveOptions,
+) -> (Vec<abs::Program>, Report) {
+    let available: Rc<RefCell<HashMap<Path, Module>>> = Default::default();
+
+    let mut solvers = vec![];
+
+    for (path, program) in modules {
+        let prelude = options
+            .prelude
+            .clone()
+            .filter(|_| !options.without_prelude.contains(&path));
+
+        let ctx =
+            Context::new(available.clone(), path.clone(), report.clone()).with_prelude(prelude);
+        let solver = resolve(&ctx, program);
+        available.borrow_mut().insert(path, ctx.module.clone());
+        solvers.push((ctx, solver));
+    }
+
+    let mut dep = cycle::DepHolder::default();
+    let mut programs = vec![];
+
+    for (ctx, solver) in solvers {
+        let program = solver.eval(ctx);
+        dep.register(&program);
+        programs.push(program);
+    }
+
+    dep.report_cycles(report.clone());
+
+    (programs, report)
+}
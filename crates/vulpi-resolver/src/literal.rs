@@ -0,0 +1,53 @@
+//! Validation of number literals. Integers can be written in binary, octal, decimal or
+//! hexadecimal with `_` as separators, and they are normalized to their decimal form so the later
+//! stages do not need to parse them again.
+
+use crate::error::ResolverErrorKind;
+use vulpi_intern::Symbol;
+
+/// Parses an integer literal like `1_000`, `0xFF`, `0o17` or `0b1010` into its decimal text.
+pub fn integer(text: &Symbol) -> Result<Symbol, ResolverErrorKind> {
+    let digits = text.get().replace('_', "");
+
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, &digits[..]),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|char| char.is_digit(radix)) {
+        return Err(ResolverErrorKind::InvalidNumber(text.clone()));
+    }
+
+    // The digits are valid, so the only way to fail is to not fit in 64 bits.
+    let value = i64::from_str_radix(digits, radix)
+        .map_err(|_| ResolverErrorKind::IntegerOverflow(text.clone()))?;
+
+    Ok(Symbol::intern(&value.to_string()))
+}
+
+/// Checks a float literal like `1.5`, `1_000.25` or `2.5e-3` and removes its separators.
+pub fn float(text: &Symbol) -> Result<Symbol, ResolverErrorKind> {
+    let digits = text.get().replace('_', "");
+
+    let (mantissa, exponent) = match digits.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (&digits[..], None),
+    };
+
+    let valid_mantissa = mantissa
+        .chars()
+        .all(|char| char.is_ascii_digit() || char == '.');
+
+    let valid_exponent = exponent.is_none_or(|exponent| {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        !exponent.is_empty() && exponent.chars().all(|char| char.is_ascii_digit())
+    });
+
+    if !valid_mantissa || !valid_exponent {
+        return Err(ResolverErrorKind::InvalidNumber(text.clone()));
+    }
+
+    Ok(Symbol::intern(&digits))
+}
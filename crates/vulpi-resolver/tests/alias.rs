@@ -0,0 +1,57 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, Report, Severity};
+use vulpi_vfs::path::Path;
+
+fn resolve(source: &str) -> Report {
+    let report = hash_reporter();
+
+    let lib = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Lib")],
+    };
+
+    let main = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let modules = vec![
+        (
+            lib,
+            vulpi_parser::parse(report.clone(), FileId(0), "pub type Int\n"),
+        ),
+        (main, vulpi_parser::parse(report.clone(), FileId(1), source)),
+    ];
+
+    vulpi_resolver::resolve_modules(modules, report.clone());
+    report
+}
+
+#[test]
+fn conflicting_aliases() {
+    let source = "use Tests.Lib as L\nuse Tests.Lib as L\nlet x (y: L.Int) : L.Int = y\n";
+    let report = resolve(source);
+
+    let diagnostics = report.all_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message().to_string(),
+        "the alias 'L' is already defined"
+    );
+
+    let spans = std::iter::once(diagnostics[0].location())
+        .chain(diagnostics[0].markers().iter().map(|m| m.position.clone()))
+        .map(|span| span.start.0)
+        .collect::<Vec<_>>();
+
+    assert_eq!(spans, vec![36, 17]);
+}
+
+#[test]
+fn unused_aliases_are_warnings() {
+    let report = resolve("use Tests.Lib as L\nuse Tests.Lib as K\nlet x (y: L.Int) : L.Int = y\n");
+
+    let diagnostics = report.iter().collect::<Vec<_>>();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "the alias 'K' is never used");
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+}
@@ -0,0 +1,91 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, registry, Report};
+use vulpi_vfs::path::Path;
+
+const CORE: &str = "pub type Int\n\
+                    pub external zero : Int = \"0\"\n";
+
+const A: &str = "use Tests.Core\n\
+                 pub let map (x: Int) : Int = x\n";
+
+const B: &str = "use Tests.Core\n\
+                 pub let map (x: Int) : Int = zero\n";
+
+fn resolve(source: &str) -> Report {
+    let report = hash_reporter();
+
+    let path = |name| Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern(name)],
+    };
+
+    let modules = [("Core", CORE), ("A", A), ("B", B), ("Main", source)]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, source))| {
+            (
+                path(name),
+                vulpi_parser::parse(report.clone(), FileId(i), source),
+            )
+        })
+        .collect();
+
+    vulpi_resolver::resolve_modules(modules, report.clone());
+    report
+}
+
+fn messages(report: &Report) -> Vec<String> {
+    report.iter().map(|diagnostic| diagnostic.message).collect()
+}
+
+#[test]
+fn names_from_two_opened_modules_are_ambiguous() {
+    let report = resolve(
+        "use Tests.Core\n\
+         use Tests.A\n\
+         use Tests.B\n\
+         let x : Int = map zero\n",
+    );
+
+    assert_eq!(
+        messages(&report),
+        vec!["the name 'map' is ambiguous, it's defined in 'Tests.A' and 'Tests.B'"]
+    );
+
+    let diagnostic = &report.diagnostics(FileId(3))[0];
+    assert_eq!(diagnostic.code(), Some(registry::AMBIGUOUS_NAME));
+
+    let definitions = diagnostic
+        .markers()
+        .iter()
+        .map(|marker| marker.position.file)
+        .collect::<Vec<_>>();
+
+    assert_eq!(definitions, vec![FileId(1), FileId(2)]);
+}
+
+#[test]
+fn qualified_names_are_not_ambiguous() {
+    let report = resolve(
+        "use Tests.Core\n\
+         use Tests.A\n\
+         use Tests.B\n\
+         let x : Int = Tests.A.map zero\n\
+         let y : Int = Tests.B.map zero\n",
+    );
+
+    assert!(!report.has_errors(), "{:?}", messages(&report));
+}
+
+#[test]
+fn local_definitions_shadow_opened_modules() {
+    let report = resolve(
+        "use Tests.Core\n\
+         use Tests.A\n\
+         use Tests.B\n\
+         let map (x: Int) : Int = x\n\
+         let x : Int = map zero\n",
+    );
+
+    assert!(!report.has_errors(), "{:?}", messages(&report));
+}
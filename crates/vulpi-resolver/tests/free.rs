@@ -0,0 +1,111 @@
+use vulpi_intern::Symbol;
+use vulpi_location::{FileId, Span};
+use vulpi_report::hash_reporter;
+use vulpi_syntax::r#abstract::{Expr, LetBinder, Node, NodeId, PatOr, PatternKind, Type};
+use vulpi_vfs::path::Path;
+
+/// Resolves a file that defines `f` and gives the first let declaration.
+fn resolve(source: &str) -> vulpi_syntax::r#abstract::LetDecl {
+    let report = hash_reporter();
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), source);
+    let (mut programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    assert!(!report.has_errors(), "{source}");
+    programs.pop().unwrap().lets.remove(0)
+}
+
+/// The body of `f`, where `y` is bound by the parameter of `f` and so is free inside of the body.
+fn body(expr: &str) -> Expr {
+    let source = format!("type Int\nlet f (y: Int) : Int =\n    {expr}\n");
+    resolve(&source).body.remove(0).expr
+}
+
+fn signature(typ: &str) -> Type {
+    let source = format!("type Int\nlet f (x: {typ}) : Int = x\n");
+    match resolve(&source).signature.binders.remove(0) {
+        LetBinder::Param(binder) => binder.typ,
+        LetBinder::Trait(typ) => typ,
+    }
+}
+
+fn names(symbols: impl IntoIterator<Item = Symbol>) -> Vec<String> {
+    symbols.into_iter().map(|symbol| symbol.get()).collect()
+}
+
+#[test]
+fn bound_variables_are_never_free() {
+    let bindings = [
+        "\\x => x y",
+        "let x = y in x",
+        "let rec x = \\z => x z y in x",
+        "do\n        let x = y\n        x",
+        "when y is\n        x => x",
+        "when y is\n        x if x => x",
+    ];
+
+    for expr in bindings {
+        assert_eq!(names(body(expr).free_term_variables()), vec!["y"], "{expr}");
+    }
+}
+
+#[test]
+fn shadowed_variables_are_free_before_the_shadowing() {
+    let uses = [
+        "(y, \\y => y)",
+        "let y = y in y",
+        "do\n        let y = y\n        y",
+    ];
+
+    for expr in uses {
+        assert_eq!(names(body(expr).free_term_variables()), vec!["y"], "{expr}");
+    }
+
+    // Only the inner `y` is used, so the outer one is not free.
+    assert!(body("\\y => y").free_term_variables().is_empty());
+}
+
+#[test]
+fn or_patterns_bind_the_variables_of_both_sides() {
+    let variable = |name: &str| {
+        Box::new(Node::new(
+            NodeId(0),
+            PatternKind::Variable(Symbol::intern(name)),
+            Span::default(),
+        ))
+    };
+
+    let or = Node::new(
+        NodeId(0),
+        PatternKind::Or(PatOr {
+            left: variable("a"),
+            right: variable("b"),
+        }),
+        Span::default(),
+    );
+
+    assert_eq!(names(or.bound_variables()), vec!["a", "b"]);
+}
+
+#[test]
+fn free_type_variables_respect_foralls_and_keep_their_order() {
+    let cases = [
+        ("b -> a -> b", vec!["b", "a"]),
+        ("forall a. a -> b", vec!["b"]),
+        ("(forall a. a) -> a", vec!["a"]),
+        ("(a, forall b. b -> c) -> (forall a. a)", vec!["a", "c"]),
+        ("forall a. forall b. a -> b", vec![]),
+    ];
+
+    for (typ, expected) in cases {
+        assert_eq!(
+            names(signature(typ).free_type_variables()),
+            expected,
+            "{typ}"
+        );
+    }
+}
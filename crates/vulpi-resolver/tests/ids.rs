@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::hash_reporter;
+use vulpi_syntax::{
+    r#abstract::{Expr, LetDecl, NodeId, Pattern, Program, Sttm, Type, TypeDecl},
+    visit::{
+        walk_expr, walk_let_decl, walk_pattern, walk_sttm, walk_type, walk_type_decl, Visitor,
+    },
+};
+use vulpi_vfs::path::Path;
+
+const SOURCE: &str = "pub type Int\n\
+                      pub type Pair a = { first : a, second : a }\n\
+                      pub external zero : Int = \"0\"\n\
+                      let swap (p: Pair Int) : Pair Int = Pair { first = p.second, second = p.first }\n\
+                      let twice (f: Int -> Int) (x: Int) : Int = f (f x)\n\
+                      let main : Int = do\n    let x = twice (\\y => y) zero\n    x\n\
+                      mod Inner where\n    let same (x: Int) : Int = x\n";
+
+fn resolve() -> Program {
+    let report = hash_reporter();
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), SOURCE);
+    let (mut programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    assert!(!report.has_errors());
+    programs.pop().unwrap()
+}
+
+/// Collects the ids of the nodes in the order that they are visited.
+#[derive(Default)]
+struct Ids(Vec<NodeId>);
+
+impl Visitor for Ids {
+    fn visit_let_decl(&mut self, decl: &LetDecl) {
+        self.0.push(decl.id);
+        walk_let_decl(self, decl)
+    }
+
+    fn visit_type_decl(&mut self, decl: &TypeDecl) {
+        self.0.push(decl.id);
+        walk_type_decl(self, decl)
+    }
+
+    fn visit_type(&mut self, typ: &Type) {
+        self.0.push(typ.id);
+        walk_type(self, typ)
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        self.0.push(pattern.id);
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.0.push(expr.id);
+        walk_expr(self, expr)
+    }
+
+    fn visit_sttm(&mut self, sttm: &Sttm) {
+        self.0.push(sttm.id);
+        walk_sttm(self, sttm)
+    }
+}
+
+fn ids(program: &Program) -> Vec<NodeId> {
+    let mut ids = Ids::default();
+    ids.visit_program(program);
+    ids.0
+}
+
+#[test]
+fn ids_are_unique_in_a_program() {
+    let program = resolve();
+    let ids = ids(&program);
+
+    assert!(ids.len() > 20);
+    assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+    assert!(ids.iter().all(|id| *id < program.next));
+}
+
+#[test]
+fn ids_do_not_change_when_the_program_is_desugared_again() {
+    assert_eq!(ids(&resolve()), ids(&resolve()));
+}
+
+#[test]
+fn fresh_ids_are_not_used_by_the_program() {
+    let mut program = resolve();
+    let ids = ids(&program);
+
+    let fresh = program.next_id();
+    assert!(!ids.contains(&fresh));
+    assert_ne!(program.next_id(), fresh);
+}
@@ -0,0 +1,86 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, registry};
+use vulpi_syntax::r#abstract::{ExprKind, LiteralKind};
+use vulpi_vfs::path::Path;
+
+/// Resolves a declaration with the literal as its body, returning the text that the resolver
+/// stored or the code of the error that it reported.
+fn literal(text: &str) -> Result<String, usize> {
+    let report = hash_reporter();
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let source = format!("let value = {text}\n");
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), &source);
+    let (programs, report) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report);
+
+    if let Some(diagnostic) = report.iter().next() {
+        return Err(diagnostic.code.unwrap());
+    }
+
+    let ExprKind::Literal(literal) = &programs[0].lets[0].body[0].expr.data else {
+        panic!("the body of the declaration is not a literal");
+    };
+
+    match &literal.data {
+        LiteralKind::Integer(n) | LiteralKind::Float(n) => Ok(n.get()),
+        _ => panic!("the literal is not a number"),
+    }
+}
+
+#[test]
+fn integers_are_normalized_to_decimal() {
+    assert_eq!(literal("42"), Ok("42".to_string()));
+    assert_eq!(literal("0xFF"), Ok("255".to_string()));
+    assert_eq!(literal("0o17"), Ok("15".to_string()));
+    assert_eq!(literal("0b1010"), Ok("10".to_string()));
+    assert_eq!(literal("1_000_000"), Ok("1000000".to_string()));
+    assert_eq!(literal("0xFF_FF"), Ok("65535".to_string()));
+}
+
+#[test]
+fn integers_must_fit_in_64_bits() {
+    assert_eq!(
+        literal("9223372036854775807"),
+        Ok("9223372036854775807".to_string())
+    );
+    assert_eq!(
+        literal("0x7FFF_FFFF_FFFF_FFFF"),
+        Ok("9223372036854775807".to_string())
+    );
+    assert_eq!(
+        literal("9223372036854775808"),
+        Err(registry::INTEGER_OVERFLOW)
+    );
+    assert_eq!(
+        literal("999999999999999999999999"),
+        Err(registry::INTEGER_OVERFLOW)
+    );
+}
+
+#[test]
+fn rejects_digits_outside_of_the_base() {
+    assert_eq!(literal("0b102"), Err(registry::INVALID_NUMBER));
+    assert_eq!(literal("0xG"), Err(registry::INVALID_NUMBER));
+    assert_eq!(literal("0x"), Err(registry::INVALID_NUMBER));
+    assert_eq!(literal("12ab"), Err(registry::INVALID_NUMBER));
+}
+
+#[test]
+fn floats_can_have_separators_and_exponents() {
+    assert_eq!(literal("1.5"), Ok("1.5".to_string()));
+    assert_eq!(literal("1_000.25"), Ok("1000.25".to_string()));
+    assert_eq!(literal("2.5e-3"), Ok("2.5e-3".to_string()));
+    assert_eq!(literal("2.5E+10"), Ok("2.5E+10".to_string()));
+    assert_eq!(literal("2.5e10"), Ok("2.5e10".to_string()));
+}
+
+#[test]
+fn rejects_malformed_exponents() {
+    assert_eq!(literal("2.5e"), Err(registry::INVALID_NUMBER));
+    assert_eq!(literal("2.5e-"), Err(registry::INVALID_NUMBER));
+    assert_eq!(literal("2.5e1x"), Err(registry::INVALID_NUMBER));
+}
@@ -0,0 +1,112 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::hash_reporter;
+use vulpi_syntax::outline::{outline, SymbolInfo, SymbolKind};
+use vulpi_vfs::path::Path;
+
+const SOURCE: &str = "pub type Int\n\
+                      pub type Option a =\n    \
+                          | pub Some a\n    \
+                          | pub None\n\
+                      pub type Pair a = { first : a, second : a }\n\
+                      pub external zero : Int = \"0\"\n\
+                      trait Show a where\n    \
+                          let show (x: a) : Int\n\
+                      let swap (p: Pair Int) : Pair Int = Pair { first = p.second, second = p.first }\n\
+                      let isSome : Option Int -> Int\n    \
+                          | Option.Some _ => zero\n    \
+                          | Option.None => zero\n\
+                      mod Inner where\n    \
+                          let same (x: Int) : Int = x\n";
+
+fn symbols() -> Vec<SymbolInfo> {
+    let report = hash_reporter();
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), SOURCE);
+    let (mut programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    assert!(!report.has_errors());
+    outline(&programs.pop().unwrap())
+}
+
+fn text(symbol: &SymbolInfo) -> &'static str {
+    &SOURCE[symbol.span.start.0..symbol.span.end.0]
+}
+
+fn summary(symbols: &[SymbolInfo]) -> Vec<(SymbolKind, String, usize)> {
+    symbols
+        .iter()
+        .map(|symbol| (symbol.kind, symbol.name.get(), symbol.arity))
+        .collect()
+}
+
+#[test]
+fn outlines_every_declaration_in_order() {
+    let symbols = symbols();
+
+    assert_eq!(
+        summary(&symbols),
+        vec![
+            (SymbolKind::Type, "Int".to_string(), 0),
+            (SymbolKind::Type, "Option".to_string(), 1),
+            (SymbolKind::Type, "Pair".to_string(), 1),
+            (SymbolKind::External, "zero".to_string(), 0),
+            (SymbolKind::Trait, "Show".to_string(), 1),
+            (SymbolKind::Let, "swap".to_string(), 1),
+            (SymbolKind::Let, "isSome".to_string(), 1),
+            (SymbolKind::Module, "Inner".to_string(), 0),
+        ]
+    );
+
+    assert_eq!(
+        summary(&symbols[1].children),
+        vec![
+            (SymbolKind::Constructor, "Some".to_string(), 1),
+            (SymbolKind::Constructor, "None".to_string(), 0),
+        ]
+    );
+
+    assert_eq!(
+        summary(&symbols[2].children),
+        vec![
+            (SymbolKind::Field, "first".to_string(), 0),
+            (SymbolKind::Field, "second".to_string(), 0),
+        ]
+    );
+
+    assert_eq!(
+        summary(&symbols[4].children),
+        vec![(SymbolKind::Signature, "show".to_string(), 1)]
+    );
+
+    assert_eq!(
+        summary(&symbols[7].children),
+        vec![(SymbolKind::Let, "same".to_string(), 1)]
+    );
+}
+
+#[test]
+fn symbols_have_their_types_and_spans() {
+    let symbols = symbols();
+
+    let swap = &symbols[5];
+    assert_eq!(swap.detail.as_deref(), Some("Pair Int -> Pair Int"));
+    assert_eq!(
+        &SOURCE[swap.selection.start.0..swap.selection.end.0],
+        "swap"
+    );
+    assert!(text(swap).ends_with("second = p.first }"));
+
+    // All of the clauses belong to the same let.
+    let is_some = &symbols[6];
+    assert_eq!(is_some.detail.as_deref(), Some("Option Int -> Int"));
+    assert!(text(is_some).starts_with("isSome"));
+    assert!(text(is_some).ends_with("Option.None => zero"));
+
+    let option = &symbols[1];
+    assert_eq!(text(option), "Option a =\n    | pub Some a\n    | pub None");
+}
@@ -0,0 +1,39 @@
+use std::{cell::RefCell, rc::Rc};
+
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, sink::Sink, Diagnostic};
+use vulpi_vfs::path::Path;
+
+#[derive(Clone, Default)]
+struct Counter(Rc<RefCell<Vec<String>>>);
+
+impl Sink for Counter {
+    fn emit(&mut self, _: FileId, diagnostic: &Diagnostic) {
+        self.0.borrow_mut().push(diagnostic.message().to_string());
+    }
+}
+
+#[test]
+fn sinks_see_diagnostics_in_emission_order() {
+    let counter = Counter::default();
+    let report = hash_reporter().with_sink(counter.clone());
+
+    let source = "type Int\nlet a : Int = first\nlet b : Int = second\nlet c : Int = third\n";
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), source);
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    vulpi_resolver::resolve_modules(vec![(path, parsed)], report);
+
+    assert_eq!(
+        *counter.0.borrow(),
+        vec![
+            "cannot find 'first'",
+            "cannot find 'second'",
+            "cannot find 'third'",
+        ]
+    );
+}
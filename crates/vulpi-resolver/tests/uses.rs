@@ -0,0 +1,118 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, registry, Report, Severity};
+use vulpi_resolver::ResolveOptions;
+use vulpi_vfs::path::Path;
+
+const TYPES: &str = "pub type Int\n";
+
+const COLORS: &str = "pub type Color =\n    \
+                          | pub Red\n    \
+                          | pub Blue\n";
+
+const VALUES: &str = "use Tests.Types\n\
+                      pub external zero : Int = \"0\"\n";
+
+fn path(name: &str) -> Path {
+    Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern(name)],
+    }
+}
+
+fn resolve(source: &str) -> Report {
+    resolve_with_options(source, ResolveOptions::default())
+}
+
+fn resolve_with_options(source: &str, options: ResolveOptions) -> Report {
+    let report = hash_reporter();
+
+    let modules = [
+        ("Types", TYPES),
+        ("Colors", COLORS),
+        ("Values", VALUES),
+        ("Main", source),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (name, source))| {
+        (
+            path(name),
+            vulpi_parser::parse(report.clone(), FileId(i), source),
+        )
+    })
+    .collect();
+
+    vulpi_resolver::resolve_modules_with_options(modules, report.clone(), options);
+    report
+}
+
+fn with_prelude(name: &str) -> ResolveOptions {
+    ResolveOptions {
+        prelude: Some(path(name)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn uses_that_are_never_needed_are_warnings() {
+    let source = "use Tests.Types\n\
+                  use Tests.Colors\n\
+                  use Tests.Values\n\
+                  let pick (c: Tests.Colors.Color) (x: Int) (y: Int) : Int =\n    \
+                      when c is\n        \
+                          Color.Red => x\n        \
+                          Color.Blue => y\n";
+
+    let report = resolve(source);
+    let diagnostics = report.iter().collect::<Vec<_>>();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "nothing from the module 'Tests.Values' is used"
+    );
+    assert_eq!(diagnostics[0].code, Some(registry::UNUSED_USE));
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+    let location = &diagnostics[0].location;
+    assert_eq!(&source[location.start.0..location.end.0], "Tests.Values");
+}
+
+#[test]
+fn public_uses_are_always_needed() {
+    let report = resolve("pub use Tests.Values\n");
+    assert!(report.is_empty());
+}
+
+#[test]
+fn names_of_the_prelude_need_no_use() {
+    let source = "let pick (c: Color) : Color =\n    \
+                      when c is\n        \
+                          Color.Red => Color.Blue\n        \
+                          Color.Blue => Color.Red\n";
+
+    let report = resolve_with_options(source, with_prelude("Colors"));
+    assert!(report.is_empty());
+}
+
+#[test]
+fn declared_names_shadow_the_prelude() {
+    let report = resolve_with_options(
+        "type Int\nlet id (x: Int) : Int = x\n",
+        with_prelude("Types"),
+    );
+    assert!(report.is_empty());
+}
+
+#[test]
+fn modules_without_the_prelude_only_see_what_they_use() {
+    let options = ResolveOptions {
+        without_prelude: vec![path("Main")],
+        ..with_prelude("Types")
+    };
+
+    let report = resolve_with_options("let id (x: Int) : Int = x\n", options);
+    let messages = report.iter().map(|d| d.message).collect::<Vec<_>>();
+
+    assert_eq!(messages, ["cannot find 'Int'", "cannot find 'Int'"]);
+}
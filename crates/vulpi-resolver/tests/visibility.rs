@@ -0,0 +1,111 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, Report};
+use vulpi_vfs::path::Path;
+
+const LIB: &str = "pub type Int\n\
+                   pub external zero : Int = \"0\"\n\
+                   pub type Token =\n    \
+                       | Token Int\n\
+                   pub type Option a =\n    \
+                       | pub Some a\n    \
+                       | pub None\n\
+                   let secret : Int = secret\n\
+                   pub let token (x: Int) : Token = Token.Token x\n\
+                   pub let value (x: Token) : Int =\n    \
+                       when x is\n        \
+                           Token.Token y => y\n";
+
+fn resolve(source: &str) -> Report {
+    let report = hash_reporter();
+
+    let path = |name| Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern(name)],
+    };
+
+    let modules = vec![
+        (
+            path("Lib"),
+            vulpi_parser::parse(report.clone(), FileId(0), LIB),
+        ),
+        (
+            path("Main"),
+            vulpi_parser::parse(report.clone(), FileId(1), source),
+        ),
+    ];
+
+    vulpi_resolver::resolve_modules(modules, report.clone());
+    report
+}
+
+fn messages(report: &Report) -> Vec<String> {
+    report.iter().map(|diagnostic| diagnostic.message).collect()
+}
+
+#[test]
+fn private_items_are_visible_inside_of_their_module() {
+    let report = resolve("use Tests.Lib\npub let x : Int = value (token zero)\n");
+    assert!(report.is_empty(), "{:?}", messages(&report));
+}
+
+#[test]
+fn private_items_are_not_visible_from_other_modules() {
+    let source = "use Tests.Lib\n\
+                  let a : Int = secret\n\
+                  let b : Int = Tests.Lib.secret\n";
+    let report = resolve(source);
+
+    assert_eq!(
+        messages(&report),
+        vec!["private definition", "private definition"]
+    );
+
+    let diagnostic = &report.diagnostics(FileId(1))[0];
+    let definition = &diagnostic.markers()[0].position;
+
+    assert_eq!(definition.file, FileId(0));
+    assert_eq!(&LIB[definition.start.0..definition.end.0], "secret");
+}
+
+#[test]
+fn parents_cannot_use_the_private_items_of_their_submodules() {
+    let source = "use Tests.Lib\n\
+                  mod Inner where\n    \
+                      pub type Opt =\n        \
+                          | Some Int\n        \
+                          | None\n    \
+                      let secret : Int = zero\n    \
+                      pub let open : Int = secret\n\
+                  let a : Int = Inner.secret\n\
+                  let b : Inner.Opt = Inner.Opt.None\n\
+                  let c : Int = Inner.open\n";
+    let report = resolve(source);
+
+    let errors = report
+        .iter()
+        .map(|diagnostic| &source[diagnostic.location.start.0..diagnostic.location.end.0])
+        .collect::<Vec<_>>();
+
+    assert_eq!(errors, vec!["Inner.secret", "Inner.Opt.None"]);
+}
+
+#[test]
+fn abstract_types_hide_their_constructors() {
+    let source = "use Tests.Lib\n\
+                  let a (x: Token) : Int = value x\n\
+                  let b (x: Option Int) : Int =\n    \
+                      when x is\n        \
+                          Option.Some _ => value (token zero)\n        \
+                          Option.None => zero\n\
+                  let c (x: Token) : Int =\n    \
+                      when x is\n        \
+                          Token.Token y => y\n";
+    let report = resolve(source);
+
+    let errors = report
+        .iter()
+        .map(|diagnostic| &source[diagnostic.location.start.0..diagnostic.location.end.0])
+        .collect::<Vec<_>>();
+
+    assert_eq!(errors, vec!["Token.Token y"]);
+}
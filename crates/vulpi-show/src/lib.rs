@@ -4,6 +4,8 @@ use std::{
     ops::Range,
 };
 
+use indexmap::IndexMap;
+
 #[derive(Debug)]
 pub struct TreeDisplay {
     pub label: String,
@@ -103,6 +105,20 @@ impl<T: Show, U: Show> Show for HashMap<T, U> {
     }
 }
 
+impl<T: Show, U: Show> Show for IndexMap<T, U> {
+    fn show(&self) -> TreeDisplay {
+        let mut node = TreeDisplay::label("IndexMap");
+        for (key, value) in self {
+            node = node.with(
+                TreeDisplay::label("Entry")
+                    .with(key.show())
+                    .with(value.show()),
+            );
+        }
+        node
+    }
+}
+
 impl<T: Show> Show for HashSet<T> {
     fn show(&self) -> TreeDisplay {
         let mut node = TreeDisplay::label("HashSet");
@@ -132,6 +148,29 @@ impl<T: Show, U: Show, V: Show> Show for (T, U, V) {
     }
 }
 
+impl<T: Show, U: Show, V: Show, W: Show> Show for (T, U, V, W) {
+    fn show(&self) -> TreeDisplay {
+        let mut node = TreeDisplay::label("Tuple");
+        node = node.with(self.0.show());
+        node = node.with(self.1.show());
+        node = node.with(self.2.show());
+        node = node.with(self.3.show());
+        node
+    }
+}
+
+impl<T: Show, U: Show, V: Show, W: Show, X: Show> Show for (T, U, V, W, X) {
+    fn show(&self) -> TreeDisplay {
+        let mut node = TreeDisplay::label("Tuple");
+        node = node.with(self.0.show());
+        node = node.with(self.1.show());
+        node = node.with(self.2.show());
+        node = node.with(self.3.show());
+        node = node.with(self.4.show());
+        node
+    }
+}
+
 impl<T: std::fmt::Debug> Show for Range<T> {
     fn show(&self) -> TreeDisplay {
         TreeDisplay::label(&format!("Range({:?}..{:?})", self.start, self.end))
@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use vulpi_intern::Symbol;
 use vulpi_location::{Span, Spanned};
@@ -6,8 +6,8 @@ use vulpi_macros::Show;
 
 use vulpi_show::{Show, TreeDisplay};
 
-
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Qualified {
     pub path: Symbol,
     pub name: Symbol,
@@ -23,6 +23,19 @@ impl Qualified {
     pub fn to_string(&self) -> String {
         format!("{}.{}", self.path.get(), self.name.get())
     }
+
+    /// The name of a top level `let _`. The name cannot be written in the source, so the
+    /// declarations never collide with each other and cannot be used.
+    pub fn wildcard(path: Symbol, id: NodeId) -> Self {
+        Self {
+            path,
+            name: Symbol::intern(&format!("_.{}", id.0)),
+        }
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        self.name.as_str().starts_with("_.")
+    }
 }
 
 impl Show for Qualified {
@@ -33,7 +46,47 @@ impl Show for Qualified {
     }
 }
 
+/// Identifies a node of the abstract tree. The ids are given by the resolver while it desugars a
+/// program, so they are unique inside of the program and the same source always gets the same ids.
+/// Unlike spans, they are not shared by the nodes that are created by the desugaring.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NodeId(pub u32);
+
+impl Show for NodeId {
+    fn show(&self) -> TreeDisplay {
+        TreeDisplay::label(&format!("NodeId: {}", self.0))
+    }
+}
+
+/// A node of the abstract tree together with its location and its id.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Node<T> {
+    pub id: NodeId,
+    pub data: T,
+    pub span: Span,
+}
+
+impl<T> Node<T> {
+    pub fn new(id: NodeId, data: T, span: Span) -> Self {
+        Self { id, data, span }
+    }
+}
+
+impl<T: Show> Show for Node<T> {
+    fn show(&self) -> TreeDisplay {
+        TreeDisplay::label("Node")
+            .with(TreeDisplay::label(&format!(
+                "{}~{}",
+                self.span.start.0, self.span.end.0
+            )))
+            .with(self.data.show())
+    }
+}
+
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum KindType {
     Star,
     Constraint,
@@ -46,18 +99,21 @@ pub type Kind = Box<Spanned<KindType>>;
 // Types
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PiType {
     pub left: Type,
     pub right: Type,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeApplication {
     pub func: Type,
     pub args: Vec<Type>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TypeBinder {
     Implicit(Symbol),
     Explicit(Symbol, Kind),
@@ -73,12 +129,14 @@ impl TypeBinder {
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeForall {
     pub params: Vec<TypeBinder>,
     pub body: Type,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TypeKind {
     Arrow(PiType),
     Tuple(Vec<Type>),
@@ -91,62 +149,12 @@ pub enum TypeKind {
     Error,
 }
 
-pub type Type = Box<Spanned<TypeKind>>;
-
-impl TypeKind {
-    pub fn free_variables(&self) -> HashSet<Symbol> {
-        match self {
-            TypeKind::Arrow(pi) => {
-                let mut set = pi.left.data.free_variables();
-                set.extend(pi.right.data.free_variables());
-
-                set
-            }
-            TypeKind::Tuple(t) => {
-                let mut set = HashSet::new();
-
-                for typ in t {
-                    set.extend(typ.data.free_variables());
-                }
-
-                set
-            }
-            TypeKind::Application(app) => {
-                let mut set = app.func.data.free_variables();
-
-                for arg in &app.args {
-                    set.extend(arg.data.free_variables());
-                }
-
-                set
-            }
-            TypeKind::Forall(f) => {
-                let mut set = HashSet::new();
-
-                set.extend(f.body.data.free_variables());
-
-                for binder in &f.params {
-                    match binder {
-                        TypeBinder::Implicit(p) => set.remove(p),
-                        TypeBinder::Explicit(p, _) => set.remove(p),
-                    };
-                }
-
-                set
-            }
-            TypeKind::TypeVariable(v) => {
-                let mut set = HashSet::new();
-                set.insert(v.clone());
-                set
-            }
-            _ => HashSet::new(),
-        }
-    }
-}
+pub type Type = Box<Node<TypeKind>>;
 
 // Literal
 
-#[derive(Show)]
+#[derive(Show, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum LiteralKind {
     String(Symbol),
     Integer(Symbol),
@@ -160,21 +168,27 @@ pub type Literal = Box<Spanned<LiteralKind>>;
 // Statements
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LetSttm {
     pub pat: Pattern,
+
+    /// The type that the binding was annotated with, like the `Int` of `let x : Int = 1`.
+    pub typ: Option<Type>,
     pub expr: Expr,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SttmKind {
     Let(LetSttm),
     Expr(Expr),
     Error,
 }
 
-pub type Sttm = Spanned<SttmKind>;
+pub type Sttm = Node<SttmKind>;
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Block {
     pub sttms: Vec<Sttm>,
 }
@@ -182,24 +196,37 @@ pub struct Block {
 // Patterns
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PatAscription {
     pub pat: Pattern,
     pub typ: Type,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PatOr {
     pub left: Pattern,
     pub right: Pattern,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PatApplication {
     pub func: Qualified,
     pub args: Vec<Pattern>,
 }
 
+/// A pattern of a constructor with labeled fields. The fields can be in any order, and the ones
+/// that are missing match anything.
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PatLabeled {
+    pub func: Qualified,
+    pub fields: Vec<(Span, Symbol, Pattern)>,
+}
+
+#[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PatternKind {
     Wildcard,
     Variable(Symbol),
@@ -208,25 +235,29 @@ pub enum PatternKind {
     Ascription(PatAscription),
     Or(PatOr),
     Application(PatApplication),
+    Labeled(PatLabeled),
 
     Error,
 }
 
-pub type Pattern = Box<Spanned<PatternKind>>;
+pub type Pattern = Box<Node<PatternKind>>;
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LambdaExpr {
     pub param: Pattern,
     pub body: Expr,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum AppKind {
     Infix,
     Normal,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ApplicationExpr {
     pub app: AppKind,
     pub func: Expr,
@@ -234,55 +265,97 @@ pub struct ApplicationExpr {
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ProjectionExpr {
     pub expr: Expr,
     pub field: Symbol,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TupleProjectionExpr {
+    pub expr: Expr,
+    pub index: usize,
+}
+
+#[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PatternArm {
     pub patterns: Vec<Pattern>,
     pub expr: Expr,
     pub guard: Option<Expr>,
 }
 
+impl PatternArm {
+    /// The span that covers all the patterns of the arm, or the body if it has no patterns.
+    pub fn patterns_span(&self) -> Span {
+        match (self.patterns.first(), self.patterns.last()) {
+            (Some(first), Some(last)) => first.span.clone().mix(last.span.clone()),
+            _ => self.expr.span.clone(),
+        }
+    }
+}
+
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct WhenExpr {
     pub scrutinee: Vec<Expr>,
     pub arms: Vec<PatternArm>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AnnotationExpr {
     pub expr: Expr,
     pub typ: Type,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TypeApplicationExpr {
+    pub expr: Expr,
+    pub typ: Type,
+}
+
+#[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LetExpr {
     pub pattern: Pattern,
+
+    /// The type that the binding was annotated with. Only bindings that are not recursive have it.
+    pub typ: Option<Type>,
     pub body: Expr,
     pub value: Expr,
+
+    /// If the bindings of a `let rec` are in scope in their own bodies.
+    pub recursive: bool,
+
+    /// The bindings after `and` in a `let rec`. They are defined together with the first one.
+    pub group: Vec<(Pattern, Expr)>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RecordInstance {
     pub name: Qualified,
     pub fields: Vec<(Span, Symbol, Expr)>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RecordUpdate {
     pub expr: Expr,
     pub fields: Vec<(Span, Symbol, Expr)>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Tuple {
     pub exprs: Vec<Expr>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ExprKind {
     Lambda(LambdaExpr),
     Application(ApplicationExpr),
@@ -292,14 +365,22 @@ pub enum ExprKind {
     Function(Qualified),
 
     Projection(ProjectionExpr),
+    TupleProjection(TupleProjectionExpr),
     Let(LetExpr),
     When(WhenExpr),
     Do(Block),
     Literal(Literal),
+    Hole(Symbol),
 
     Annotation(AnnotationExpr),
+    TypeApplication(TypeApplicationExpr),
     RecordInstance(RecordInstance),
     RecordUpdate(RecordUpdate),
+
+    /// A constructor with labeled fields given by name, like `Shape.Circle { radius = 1 }`.
+    /// The name of the instance is the name of the constructor.
+    Labeled(RecordInstance),
+
     Tuple(Tuple),
 
     Error,
@@ -326,9 +407,10 @@ impl ExprKind {
     }
 }
 
-pub type Expr = Box<Spanned<ExprKind>>;
+pub type Expr = Box<Node<ExprKind>>;
 
 #[derive(Show, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Visibility {
     Public,
     Super,
@@ -346,12 +428,14 @@ impl From<crate::concrete::top_level::Visibility> for Visibility {
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Binder {
     pub pat: Pattern,
     pub typ: Type,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum LetBinder {
     Param(Binder),
     Trait(Type),
@@ -367,6 +451,7 @@ impl LetBinder {
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LetSignature {
     pub span: Span,
     pub visibility: Visibility,
@@ -376,7 +461,9 @@ pub struct LetSignature {
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TraitDecl {
+    pub id: NodeId,
     pub name: Qualified,
     pub supers: Vec<Type>,
     pub namespace: Symbol,
@@ -386,37 +473,90 @@ pub struct TraitDecl {
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TraitImpl {
+    pub id: NodeId,
     pub name: Qualified,
     pub binders: Vec<Type>,
     pub body: Vec<LetDecl>,
 }
 
+/// An attribute of a declaration, like `@deprecated("use bar")`.
+#[derive(Show, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Attribute {
+    pub name: Symbol,
+
+    /// The arguments between parenthesis, that are empty if the attribute has none.
+    pub args: Vec<Literal>,
+    pub span: Span,
+}
+
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LetDecl {
+    pub id: NodeId,
     pub signature: LetSignature,
+
+    /// The documentation comments written before the declaration.
+    pub docs: Option<Symbol>,
+    pub attributes: Vec<Attribute>,
     pub body: Vec<PatternArm>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_constant"))]
     pub constant: Option<HashMap<Qualified, Span>>,
 }
 
+/// Writes the names that a constant depends on as a list sorted by name, because the keys of the
+/// map are not strings and its order changes between runs.
+#[cfg(feature = "serde")]
+fn serialize_constant<S: serde::Serializer>(
+    constant: &Option<HashMap<Qualified, Span>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::Serialize;
+
+    let sorted = constant.as_ref().map(|names| {
+        let mut names = names.iter().collect::<Vec<_>>();
+        names.sort_by_key(|(name, _)| name.to_string());
+        names
+    });
+
+    sorted.serialize(serializer)
+}
+
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Constructor {
+    pub visibility: Visibility,
     pub name: Qualified,
+    pub docs: Option<Symbol>,
+    pub attributes: Vec<Attribute>,
     pub args: Vec<Type>,
+
+    /// The names of the arguments of a constructor with labeled fields, in the same order. It's
+    /// empty if the arguments are positional.
+    pub labels: Vec<(Symbol, Span)>,
+
     pub typ: Option<Type>,
+    pub span: Span,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SumDecl {
     pub constructors: Vec<Constructor>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RecordDecl {
-    pub fields: Vec<(Qualified, Type, Visibility)>,
+    /// The fields with their types, visibilities, the locations of their names and their
+    /// documentation.
+    pub fields: Vec<(Qualified, Type, Visibility, Span, Option<Symbol>)>,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum TypeDef {
     Sum(SumDecl),
     Record(RecordDecl),
@@ -424,29 +564,92 @@ pub enum TypeDef {
     Abstract,
 }
 
+/// A function that the compiler writes for a type that asks for it with `derive`.
+#[derive(Show, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Derive {
+    /// `eqFoo : Foo -> Foo -> Bool`, that compares two values by their constructors and fields.
+    Eq,
+
+    /// `showFoo : Foo -> String`, that writes a value like it's written in the source.
+    Show,
+}
+
+impl Derive {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Eq" => Some(Derive::Eq),
+            "Show" => Some(Derive::Show),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Derive::Eq => "Eq",
+            Derive::Show => "Show",
+        }
+    }
+
+    /// The name of the function for a type, like `eqFoo` for `Foo`.
+    pub fn function(&self, typ: &Symbol) -> Symbol {
+        let prefix = match self {
+            Derive::Eq => "eq",
+            Derive::Show => "show",
+        };
+
+        Symbol::intern(&format!("{prefix}{}", typ.get()))
+    }
+}
+
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TypeDecl {
+    pub id: NodeId,
     pub visibility: Visibility,
     pub name: Qualified,
+    pub docs: Option<Symbol>,
+    pub attributes: Vec<Attribute>,
     pub namespace: Symbol,
     pub binders: Vec<TypeBinder>,
     pub def: TypeDef,
+
+    /// The functions that are derived for the type, with the locations of their names.
+    pub derives: Vec<(Derive, Span)>,
+    pub span: Span,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ModuleDecl {
+    pub id: NodeId,
     pub visibility: Visibility,
     pub name: Symbol,
     pub decls: Option<Program>,
+    pub span: Span,
 }
 
 #[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ExtDecl {
+    pub id: NodeId,
     pub name: Qualified,
     pub visibility: Visibility,
     pub namespace: Symbol,
     pub typ: Type,
     pub ret: Symbol,
+    pub span: Span,
+}
+
+#[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UseDecl {
+    pub visibility: Visibility,
+    pub path: Symbol,
+
+    /// The name that the module is referred by, if it's used with `as`.
+    pub alias: Option<Symbol>,
+    pub span: Span,
 }
 
 pub enum TopLevel {
@@ -457,16 +660,48 @@ pub enum TopLevel {
     Trait(TraitDecl),
     Impl(Option<TraitImpl>),
     Command(Symbol, Symbol),
-    Use,
+    Use(UseDecl),
 }
 
-#[derive(Show, Default)]
+#[derive(Show)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Program {
+    pub name: Symbol,
+    pub uses: Vec<UseDecl>,
     pub lets: Vec<LetDecl>,
     pub types: Vec<TypeDecl>,
     pub modules: Vec<ModuleDecl>,
     pub traits: Vec<TraitDecl>,
     pub impls: Vec<TraitImpl>,
     pub externals: Vec<ExtDecl>,
-    pub commands: Vec<(Symbol, Symbol)>
+    pub commands: Vec<(Symbol, Symbol)>,
+
+    /// The first id that is not used by any node of the program. The inline modules share the ids
+    /// of the program of their file, so it's only set in the program of the file.
+    pub next: NodeId,
+}
+
+impl Program {
+    pub fn new(name: Symbol) -> Self {
+        Self {
+            name,
+            uses: Vec::new(),
+            lets: Vec::new(),
+            types: Vec::new(),
+            modules: Vec::new(),
+            traits: Vec::new(),
+            impls: Vec::new(),
+            externals: Vec::new(),
+            commands: Vec::new(),
+            next: NodeId::default(),
+        }
+    }
+
+    /// Gives an id that is not used by any other node of the program, for passes that create
+    /// new nodes.
+    pub fn next_id(&mut self) -> NodeId {
+        let id = self.next;
+        self.next = NodeId(id.0 + 1);
+        id
+    }
 }
@@ -84,6 +84,14 @@ pub struct ProjectionExpr {
     pub field: Lower,
 }
 
+/// An accessor of an element of a tuple by its position like `pair.0`.
+#[derive(Show, Clone)]
+pub struct TupleProjectionExpr {
+    pub expr: Box<Expr>,
+    pub dot: Token,
+    pub index: Token,
+}
+
 #[derive(Show, Clone)]
 pub struct BinaryExpr {
     pub left: Box<Expr>,
@@ -124,12 +132,31 @@ pub struct AnnotationExpr {
     pub typ: Box<Type>,
 }
 
+#[derive(Show, Clone)]
+pub struct TypeApplicationExpr {
+    pub expr: Box<Expr>,
+    pub at: Token,
+    pub typ: Box<Type>,
+}
+
+/// A binding that comes after `and` in a `let rec`.
+#[derive(Show, Clone)]
+pub struct LetAnd {
+    pub and: Token,
+    pub pattern: Box<Pattern>,
+    pub eq: Token,
+    pub body: Box<Expr>,
+}
+
 #[derive(Show, Clone)]
 pub struct LetExpr {
     pub let_: Token,
+    pub rec: Option<Token>,
     pub pattern: Box<Pattern>,
+    pub typ: Option<(Token, Box<Type>)>,
     pub eq: Token,
     pub body: Box<Expr>,
+    pub group: Vec<LetAnd>,
     pub in_: Token,
     pub value: Box<Expr>,
 }
@@ -156,8 +183,10 @@ pub struct HtmlNode {
 #[derive(Show, Clone)]
 pub struct RecordField {
     pub name: Lower,
-    pub eq: Token,
-    pub expr: Box<Expr>,
+
+    /// The value of the field. A field without one is a pun like `{ name }`, that takes the value
+    /// of the variable with the same name.
+    pub value: Option<(Token, Box<Expr>)>,
 }
 
 #[derive(Show, Clone)]
@@ -176,6 +205,12 @@ pub struct RecordUpdate {
     pub right_brace: Token,
 }
 
+#[derive(Show, Clone)]
+pub struct TypedHole {
+    pub question: Token,
+    pub name: Lower,
+}
+
 pub type Tuple = Parenthesis<Vec<(Box<Spanned<ExprKind>>, Option<Token>)>>;
 
 #[derive(Show, Clone)]
@@ -190,13 +225,16 @@ pub enum ExprKind {
     Function(Path<Lower>),
 
     Projection(ProjectionExpr),
+    TupleProjection(TupleProjectionExpr),
     Binary(BinaryExpr),
     Let(LetExpr),
     When(WhenExpr),
     Do(DoExpr),
     Literal(Literal),
+    Hole(TypedHole),
 
     Annotation(AnnotationExpr),
+    TypeApplication(TypeApplicationExpr),
     RecordInstance(RecordInstance),
     RecordUpdate(RecordUpdate),
 
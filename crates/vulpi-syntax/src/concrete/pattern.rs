@@ -18,6 +18,22 @@ pub struct PatApplication {
     pub args: Vec<Box<Pattern>>,
 }
 
+/// A field of a labeled pattern. A field without a pattern is a pun like `{ radius }`, that binds
+/// a variable with the name of the field.
+#[derive(Show, Clone)]
+pub struct PatField {
+    pub name: Lower,
+    pub pattern: Option<(Token, Box<Pattern>)>,
+}
+
+#[derive(Show, Clone)]
+pub struct PatRecord {
+    pub name: Path<Upper>,
+    pub left_brace: Token,
+    pub fields: Vec<(PatField, Option<Token>)>,
+    pub right_brace: Token,
+}
+
 #[derive(Show, Clone)]
 pub enum PatternKind {
     Wildcard(Token),
@@ -27,6 +43,7 @@ pub enum PatternKind {
     Annotation(PatAscription),
     Tuple(Vec<(Pattern, Option<Token>)>),
     Application(PatApplication),
+    Record(PatRecord),
     Parenthesis(Parenthesis<Box<Pattern>>),
 }
 
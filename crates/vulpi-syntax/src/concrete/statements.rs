@@ -3,12 +3,16 @@ use vulpi_macros::Show;
 
 use crate::tokens::Token;
 
-use super::{expr::Expr, tree::Pattern};
+use super::{
+    expr::Expr,
+    tree::{Pattern, Type},
+};
 
 #[derive(Show, Clone)]
 pub struct LetSttm {
     pub let_: Token,
     pub pattern: Box<Pattern>,
+    pub typ: Option<(Token, Box<Type>)>,
     pub eq: Token,
     pub expr: Box<Expr>,
 }
@@ -1,7 +1,7 @@
 use vulpi_intern::Symbol;
 use vulpi_macros::Show;
 
-use crate::tokens::Token;
+use crate::tokens::{Token, TokenData};
 
 #[derive(Show, Clone)]
 pub enum Visibility {
@@ -9,9 +9,21 @@ pub enum Visibility {
     Private,
 }
 
+impl Visibility {
+    /// The documentation of an item is attached to its first token, that is the `pub` if it has
+    /// one.
+    pub fn docs(&self, first: &Token) -> Option<Symbol> {
+        match self {
+            Visibility::Public(pub_) => pub_.docs(),
+            Visibility::Private => first.docs(),
+        }
+    }
+}
+
 use super::{
     expr::{Expr, PatternArm},
     kind::Kind,
+    literal::Literal,
     r#type::Type,
     tree::Pattern,
     Lower, Parenthesis, Path, Upper,
@@ -33,27 +45,50 @@ pub struct TraitBinder {
     pub right_bracket: Token,
 }
 
+#[derive(Show, Clone)]
+pub enum LetCaseKind {
+    Arm(Box<PatternArm>),
+
+    /// A clause that could not be parsed, with the tokens that were skipped.
+    Error(Vec<Token>),
+}
+
 #[derive(Show, Clone)]
 pub struct LetCase {
     pub pipe: Token,
-    pub arm: PatternArm,
+    pub arm: LetCaseKind,
 }
 
 #[derive(Show, Clone)]
 pub enum LetMode {
     Body(Token, Box<Expr>),
     Cases(Vec<LetCase>),
+
+    /// A body that could not be parsed, with the tokens that were skipped.
+    Error(Vec<Token>),
 }
 
 #[derive(Show, Clone)]
 pub struct LetSignature {
     pub visibility: Visibility,
     pub let_: Token,
+
+    /// The name of the declaration, or `_` for a declaration that is only checked.
     pub name: Lower,
     pub binders: Vec<LetBinder>,
     pub ret: Option<(Token, Box<Type>)>,
 }
 
+impl LetSignature {
+    pub fn docs(&self) -> Option<Symbol> {
+        self.visibility.docs(&self.let_)
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        self.name.0.kind == TokenData::Wildcard
+    }
+}
+
 #[derive(Show, Clone)]
 pub struct TraitDecl {
     pub visibility: Visibility,
@@ -75,20 +110,59 @@ pub struct TraitImpl {
     pub body: Vec<LetDecl>,
 }
 
+/// The literals between parenthesis that are given to an attribute.
+pub type AttributeArgs = Parenthesis<Vec<(Literal, Option<Token>)>>;
+
+/// An attribute that comes before a declaration, like `@deprecated("use bar")`.
+#[derive(Show, Clone)]
+pub struct DeclAttribute {
+    pub at: Token,
+    pub name: Lower,
+    pub args: Option<AttributeArgs>,
+}
+
+/// The documentation of a declaration with attributes is attached to the first one of them.
+fn docs(attributes: &[DeclAttribute], otherwise: Option<Symbol>) -> Option<Symbol> {
+    match attributes.first() {
+        Some(attribute) => attribute.at.docs(),
+        None => otherwise,
+    }
+}
+
 #[derive(Show, Clone)]
 pub struct LetDecl {
+    pub attributes: Vec<DeclAttribute>,
     pub signature: LetSignature,
     pub body: LetMode,
 }
 
+impl LetDecl {
+    pub fn docs(&self) -> Option<Symbol> {
+        docs(&self.attributes, self.signature.docs())
+    }
+}
+
 #[derive(Show, Clone)]
 pub struct Constructor {
+    pub attributes: Vec<DeclAttribute>,
     pub pipe: Token,
+    pub visibility: Visibility,
     pub name: Upper,
     pub args: Vec<Box<Type>>,
+
+    /// The labeled fields of a constructor like `Circle { radius : Float }`, that are used instead
+    /// of the arguments.
+    pub fields: Option<RecordDecl>,
+
     pub typ: Option<(Token, Box<Type>)>,
 }
 
+impl Constructor {
+    pub fn docs(&self) -> Option<Symbol> {
+        docs(&self.attributes, self.pipe.docs())
+    }
+}
+
 #[derive(Show, Clone)]
 pub struct SumDecl {
     pub constructors: Vec<Constructor>,
@@ -102,6 +176,12 @@ pub struct Field {
     pub typ: Box<Type>,
 }
 
+impl Field {
+    pub fn docs(&self) -> Option<Symbol> {
+        self.visibility.docs(&self.name.0)
+    }
+}
+
 #[derive(Show, Clone)]
 pub struct RecordDecl {
     pub left_brace: Token,
@@ -119,7 +199,7 @@ pub struct ExplicitTypeBinder {
 #[derive(Show, Clone)]
 pub enum TypeBinder {
     Implicit(Lower),
-    Explicit(Parenthesis<ExplicitTypeBinder>)
+    Explicit(Parenthesis<ExplicitTypeBinder>),
 }
 
 #[derive(Show, Clone)]
@@ -135,13 +215,28 @@ pub enum TypeDef {
     Synonym(Box<Type>),
 }
 
+/// The functions that the compiler writes for a type, like `derive (Eq, Show)`.
+#[derive(Show, Clone)]
+pub struct Deriving {
+    pub derive: Token,
+    pub names: Parenthesis<Vec<(Upper, Option<Token>)>>,
+}
+
 #[derive(Show, Clone)]
 pub struct TypeDecl {
+    pub attributes: Vec<DeclAttribute>,
     pub visibility: Visibility,
     pub type_: Token,
     pub name: Upper,
     pub binders: Vec<TypeBinder>,
     pub def: Option<(Token, TypeDef)>,
+    pub deriving: Option<Deriving>,
+}
+
+impl TypeDecl {
+    pub fn docs(&self) -> Option<Symbol> {
+        docs(&self.attributes, self.visibility.docs(&self.type_))
+    }
 }
 
 #[derive(Show, Clone)]
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use indexmap::IndexMap;
+
 use vulpi_intern::Symbol;
 use vulpi_location::{Span, Spanned};
 use vulpi_macros::Show;
@@ -34,7 +36,7 @@ pub type Statement<T> = SttmKind<T>;
 
 pub type Block<T> = Vec<Statement<T>>;
 
-#[derive(Show, Clone)]
+#[derive(Show, Clone, Debug)]
 pub struct PatOr {
     pub left: Pattern,
     pub right: Pattern,
@@ -53,6 +55,7 @@ pub enum PatternKind {
     Literal(Literal),
     Application(PatApplication),
     Tuple(Vec<Pattern>),
+    Or(PatOr),
     Error,
 }
 
@@ -83,6 +86,12 @@ pub struct ProjectionExpr<T> {
     pub expr: Expr<T>,
 }
 
+#[derive(Show, Clone)]
+pub struct TupleProjectionExpr<T> {
+    pub index: usize,
+    pub expr: Expr<T>,
+}
+
 #[derive(Show, Clone)]
 pub struct PatternArm<T> {
     pub patterns: Vec<Pattern>,
@@ -90,10 +99,61 @@ pub struct PatternArm<T> {
     pub guard: Option<Expr<T>>,
 }
 
+/// The place of a value inside of the scrutinees of a `when`. It's the index of the scrutinee
+/// followed by the indices of the arguments of the constructors and tuples that lead to the value.
+#[derive(Show, Clone, PartialEq, Eq, Debug)]
+pub struct Occurrence {
+    pub scrutinee: usize,
+    pub path: Vec<usize>,
+}
+
+impl Occurrence {
+    pub fn with(&self, index: usize) -> Occurrence {
+        let mut path = self.path.clone();
+        path.push(index);
+        Occurrence {
+            scrutinee: self.scrutinee,
+            path,
+        }
+    }
+}
+
+#[derive(Show, Clone, PartialEq, Debug)]
+pub enum Case {
+    Constructor(Qualified),
+    Literal(Literal),
+}
+
+/// The arms of a `when` compiled into tests of the scrutinees, so that each part of a value is
+/// tested at most once in every path to an arm.
+#[derive(Show, Clone, Debug)]
+pub enum DecisionTree {
+    /// Tests the value at the occurrence. The default tree is for the values that none of the cases
+    /// match, and it's missing if the cases are all of the constructors of the type.
+    Switch(
+        Occurrence,
+        Vec<(Case, DecisionTree)>,
+        Option<Box<DecisionTree>>,
+    ),
+
+    /// Binds a variable of the arm that is reached to the value at the occurrence.
+    Bind(Symbol, Occurrence, Box<DecisionTree>),
+
+    /// Checks the guard of an arm, and goes on with the tree if it does not hold.
+    Guard(usize, Box<DecisionTree>),
+
+    /// Runs the arm with the index.
+    Leaf(usize),
+
+    /// None of the arms match. It's only reachable if the arms are not exhaustive.
+    Fail,
+}
+
 #[derive(Show, Clone)]
 pub struct WhenExpr<T> {
     pub scrutinee: Vec<Expr<T>>,
     pub arms: Vec<PatternArm<T>>,
+    pub tree: DecisionTree,
 }
 
 #[derive(Show, Clone)]
@@ -101,6 +161,8 @@ pub struct LetExpr<T> {
     pub pattern: Pattern,
     pub body: Expr<T>,
     pub next: Expr<T>,
+    pub recursive: bool,
+    pub group: Vec<(Pattern, Expr<T>)>,
 }
 
 #[derive(Show, Clone)]
@@ -131,6 +193,7 @@ pub enum ExprKind<T> {
     Function(Qualified, T),
 
     Projection(ProjectionExpr<T>),
+    TupleProjection(TupleProjectionExpr<T>),
     Let(LetExpr<T>),
     When(WhenExpr<T>),
     Do(Block<T>),
@@ -167,22 +230,24 @@ pub struct ExternalDecl<T> {
     pub binding: Symbol,
 }
 
+/// An elaborated program. The declarations are kept in the order of the source, so everything that
+/// is generated from them is the same in every run.
 #[derive(Show, Clone)]
 pub struct Program<T> {
-    pub modules: HashMap<Symbol, Program<T>>,
-    pub lets: HashMap<Qualified, LetDecl<T>>,
-    pub types: HashMap<Qualified, TypeDecl>,
-    pub externals: HashMap<Qualified, ExternalDecl<T>>,
+    pub modules: IndexMap<Symbol, Program<T>>,
+    pub lets: IndexMap<Qualified, LetDecl<T>>,
+    pub types: IndexMap<Qualified, TypeDecl>,
+    pub externals: IndexMap<Qualified, ExternalDecl<T>>,
     pub commands: Vec<(Symbol, Symbol)>,
 }
 
 impl<T> Default for Program<T> {
     fn default() -> Self {
         Self {
-            modules: HashMap::new(),
-            lets: HashMap::new(),
-            types: HashMap::new(),
-            externals: HashMap::new(),
+            modules: IndexMap::new(),
+            lets: IndexMap::new(),
+            types: IndexMap::new(),
+            externals: IndexMap::new(),
             commands: Vec::new(),
         }
     }
@@ -0,0 +1,965 @@
+//! Formatting of the concrete tree. A program is turned into a [Doc] with a consistent indentation
+//! and the comments that are attached to its tokens.
+//!
+//! The language is sensitive to the layout, so the items of blocks always go on their own lines,
+//! every line break inside of an item is indented further than the item and the token that comes
+//! after the end of a block goes to a new line that is less indented than the block.
+
+use crate::{
+    concrete::{tree::*, Lower, Path, Upper},
+    pretty::Doc,
+    tokens::{Token, TokenData},
+};
+
+/// The number of spaces of each level of indentation.
+const INDENT: usize = 2;
+
+/// Where the next token is in relation to the items of the blocks.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Leading {
+    /// Inside of an item, so comments before the token are indented to not end the item.
+    Inside,
+
+    /// At the start of a line, like the first item of a block or the token after a block.
+    Line,
+
+    /// At the start of an item, so the blank lines before it are kept.
+    Item,
+}
+
+struct Formatter {
+    leading: Leading,
+}
+
+/// Formats a program trying to keep the lines shorter than the width.
+pub fn format(program: &Program, width: usize) -> String {
+    let mut formatter = Formatter {
+        leading: Leading::Inside,
+    };
+
+    formatter.program(program).render(width)
+}
+
+/// Checks if the last thing of an expression is a block, so nothing can be written after it in the
+/// same line.
+fn ends_with_block(expr: &Expr) -> bool {
+    match &expr.data {
+        ExprKind::When(when) => !when.arms.is_empty(),
+        ExprKind::Do(do_) => !do_.block.statements.is_empty(),
+        ExprKind::Lambda(lambda) => ends_with_block(&lambda.expr),
+        ExprKind::Let(let_) => ends_with_block(&let_.value),
+        ExprKind::Binary(binary) => ends_with_block(&binary.right),
+        _ => false,
+    }
+}
+
+fn escape(text: &str, quote: char) -> String {
+    let mut result = String::new();
+
+    for char in text.chars() {
+        match char {
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\0' => result.push_str("\\0"),
+            '\\' => result.push_str("\\\\"),
+            char if char == quote => {
+                result.push('\\');
+                result.push(char);
+            }
+            char => result.push(char),
+        }
+    }
+
+    result
+}
+
+fn operator_token(op: &Operator) -> &Token {
+    match op {
+        Operator::Add(token)
+        | Operator::Sub(token)
+        | Operator::Mul(token)
+        | Operator::Div(token)
+        | Operator::Rem(token)
+        | Operator::And(token)
+        | Operator::Or(token)
+        | Operator::Xor(token)
+        | Operator::Not(token)
+        | Operator::Eq(token)
+        | Operator::Neq(token)
+        | Operator::Lt(token)
+        | Operator::Gt(token)
+        | Operator::Le(token)
+        | Operator::Ge(token)
+        | Operator::Shl(token)
+        | Operator::Shr(token)
+        | Operator::Pipe(token)
+        | Operator::Concat(token) => token,
+    }
+}
+
+impl Formatter {
+    fn token(&mut self, token: &Token) -> Doc {
+        let leading = std::mem::replace(&mut self.leading, Leading::Inside);
+
+        let line = || match leading {
+            Leading::Inside => Doc::HardLine.nest(INDENT),
+            Leading::Line | Leading::Item => Doc::HardLine,
+        };
+
+        let blank = |whitespace: &str| {
+            if leading == Leading::Item && whitespace.matches('\n').count() > 1 {
+                Doc::BlankLine
+            } else {
+                Doc::Nil
+            }
+        };
+
+        let mut doc = Doc::Nil;
+
+        for comment in &token.comments {
+            let whitespace = comment.whitespace.data.get();
+            let text = comment.comment.data.get();
+
+            if whitespace.contains('\n') {
+                doc = doc + blank(&whitespace) + line() + Doc::Comment(text);
+            } else {
+                doc = doc + Doc::Trailing(text);
+            }
+
+            doc = doc + line();
+        }
+
+        let text = match token.kind {
+            TokenData::String => format!("\"{}\"", escape(&token.data(), '"')),
+            TokenData::Char => format!("'{}'", escape(&token.data(), '\'')),
+            _ => token.data(),
+        };
+
+        doc + blank(&token.whitespace.data.get()) + Doc::text(text)
+    }
+
+    fn tokens(&mut self, tokens: &[Token]) -> Doc {
+        let docs = tokens
+            .iter()
+            .filter(|token| {
+                !matches!(
+                    token.kind,
+                    TokenData::Begin | TokenData::Sep | TokenData::End
+                )
+            })
+            .map(|token| self.token(token))
+            .collect::<Vec<_>>();
+        Doc::join(docs, Doc::text(" "))
+    }
+
+    fn upper(&mut self, upper: &Upper) -> Doc {
+        self.token(&upper.0)
+    }
+
+    fn lower(&mut self, lower: &Lower) -> Doc {
+        self.token(&lower.0)
+    }
+
+    fn path<T>(&mut self, path: &Path<T>, last: impl FnOnce(&mut Self, &T) -> Doc) -> Doc {
+        let mut doc = Doc::Nil;
+
+        for (segment, dot) in &path.segments {
+            doc = doc + self.upper(segment) + self.token(dot);
+        }
+
+        doc + last(self, &path.last)
+    }
+
+    /// Goes to a new line after an expression that ends with a block.
+    fn close(&mut self, expr: &Expr) -> Doc {
+        if ends_with_block(expr) {
+            self.leading = Leading::Line;
+            Doc::HardLine
+        } else {
+            Doc::Nil
+        }
+    }
+
+    /// The space between an expression and the token after it.
+    fn space_after(&mut self, expr: &Expr) -> Doc {
+        if ends_with_block(expr) {
+            self.close(expr)
+        } else {
+            Doc::text(" ")
+        }
+    }
+
+    fn closed(&mut self, expr: &Expr) -> Doc {
+        let doc = self.expr(expr);
+        doc + self.close(expr)
+    }
+
+    /// The items of a block, each one of them in its own line.
+    fn block<T>(&mut self, items: &[T], mut item: impl FnMut(&mut Self, &T) -> Doc) -> Doc {
+        let mut doc = Doc::Nil;
+
+        for (i, value) in items.iter().enumerate() {
+            self.leading = if i == 0 { Leading::Line } else { Leading::Item };
+            doc = doc + Doc::HardLine + item(self, value);
+            self.leading = Leading::Inside;
+        }
+
+        doc.nest(INDENT)
+    }
+
+    /// Items separated by commas in a single line.
+    fn commas<T>(
+        &mut self,
+        items: &[(T, Option<Token>)],
+        mut item: impl FnMut(&mut Self, &T) -> Doc,
+    ) -> Doc {
+        let mut doc = Doc::Nil;
+
+        for (i, (value, sep)) in items.iter().enumerate() {
+            if i != 0 {
+                doc = doc + Doc::text(" ");
+            }
+
+            doc = doc + item(self, value);
+
+            if let Some(sep) = sep {
+                doc = doc + self.token(sep);
+            }
+        }
+
+        doc
+    }
+
+    /// Items separated by commas between delimiters. If they do not fit in a line, each one of
+    /// them goes to its own line with the comma before it.
+    fn delimited<T>(
+        &mut self,
+        left: &Token,
+        items: &[(T, Option<Token>)],
+        right: &Token,
+        padded: bool,
+        mut item: impl FnMut(&mut Self, &T) -> Doc,
+    ) -> Doc {
+        if items.is_empty() {
+            return self.token(left) + self.token(right);
+        }
+
+        // The lines are aligned with the delimiter, unless it starts an item of a block, where
+        // they would start new items.
+        let aligned = self.leading == Leading::Inside;
+
+        let (open, close) = if padded {
+            (Doc::text(" "), Doc::line())
+        } else {
+            (Doc::broken_space(), Doc::softline())
+        };
+
+        let mut doc = self.token(left) + open;
+
+        for (i, (value, sep)) in items.iter().enumerate() {
+            doc = doc + item(self, value);
+
+            if let Some(sep) = sep {
+                if i + 1 == items.len() {
+                    doc = doc + self.token(sep);
+                } else {
+                    doc = doc + Doc::softline() + self.token(sep) + Doc::text(" ");
+                }
+            }
+        }
+
+        let doc = doc + close + self.token(right);
+
+        if aligned {
+            doc.align().group()
+        } else {
+            doc.nest(INDENT).group()
+        }
+    }
+
+    /// The expression after an equal or an arrow. It goes to the next line if it does not fit,
+    /// but `do` blocks always start in the same line.
+    fn rhs(&mut self, expr: &Expr) -> Doc {
+        match &expr.data {
+            ExprKind::Do(_) => Doc::text(" ") + self.expr(expr),
+            _ => (Doc::line() + self.expr(expr)).nest(INDENT).group(),
+        }
+    }
+
+    fn program(&mut self, program: &Program) -> Doc {
+        let mut doc = Doc::Nil;
+
+        for top_level in &program.top_levels {
+            self.leading = Leading::Item;
+            doc = doc + self.top_level(top_level) + Doc::HardLine;
+        }
+
+        self.leading = Leading::Item;
+        doc + self.token(&program.eof)
+    }
+
+    fn top_level(&mut self, top_level: &TopLevel) -> Doc {
+        match top_level {
+            TopLevel::Let(decl) => self.let_decl(decl),
+            TopLevel::Type(decl) => self.type_decl(decl),
+            TopLevel::Use(decl) => self.use_decl(decl),
+            TopLevel::Impl(decl) => self.trait_impl(decl),
+            TopLevel::Trait(decl) => self.trait_decl(decl),
+            TopLevel::Module(decl) => self.module_decl(decl),
+            TopLevel::External(decl) => self.ext_decl(decl),
+            TopLevel::Command(decl) => {
+                // The code of commands keeps its lines.
+                let code = decl.name.get().replace('\\', "\\\\").replace('"', "\\\"");
+                Doc::text(format!("#{} \"{}\"", decl.command.get(), code))
+            }
+            TopLevel::Error(tokens) => self.tokens(tokens),
+        }
+    }
+
+    fn visibility(&mut self, visibility: &Visibility) -> Doc {
+        match visibility {
+            Visibility::Public(token) => self.token(token) + Doc::text(" "),
+            Visibility::Private => Doc::Nil,
+        }
+    }
+
+    fn type_binder(&mut self, binder: &TypeBinder) -> Doc {
+        match binder {
+            TypeBinder::Implicit(name) => self.lower(name),
+            TypeBinder::Explicit(binder) => {
+                self.token(&binder.left)
+                    + self.lower(&binder.data.name)
+                    + Doc::text(" ")
+                    + self.token(&binder.data.colon)
+                    + Doc::text(" ")
+                    + self.kind(&binder.data.kind)
+                    + self.token(&binder.right)
+            }
+        }
+    }
+
+    fn trait_binder(&mut self, binder: &TraitBinder) -> Doc {
+        self.token(&binder.left_bracket) + self.typ(&binder.typ) + self.token(&binder.right_bracket)
+    }
+
+    fn let_binder(&mut self, binder: &LetBinder) -> Doc {
+        match binder {
+            LetBinder::Param(binder) => {
+                self.token(&binder.left_paren)
+                    + self.pattern(&binder.pattern)
+                    + self.token(&binder.colon)
+                    + Doc::text(" ")
+                    + self.typ(&binder.typ)
+                    + self.token(&binder.right_paren)
+            }
+            LetBinder::Trait(binder) => self.trait_binder(binder),
+        }
+    }
+
+    fn let_signature(&mut self, signature: &LetSignature) -> Doc {
+        let head = self.visibility(&signature.visibility)
+            + self.token(&signature.let_)
+            + Doc::text(" ")
+            + self.lower(&signature.name);
+
+        let mut rest = Doc::Nil;
+
+        for binder in &signature.binders {
+            rest = rest + Doc::line() + self.let_binder(binder);
+        }
+
+        if let Some((colon, typ)) = &signature.ret {
+            rest = rest + Doc::line() + self.token(colon) + Doc::text(" ") + self.typ(typ);
+        }
+
+        (head + rest.nest(INDENT)).group()
+    }
+
+    /// The attributes of a declaration, each one in a line of its own.
+    fn attributes(&mut self, attributes: &[DeclAttribute]) -> Doc {
+        let mut doc = Doc::Nil;
+
+        for attribute in attributes {
+            doc = doc + self.token(&attribute.at) + self.lower(&attribute.name);
+
+            if let Some(args) = &attribute.args {
+                doc = doc
+                    + self.token(&args.left)
+                    + self.commas(&args.data, Self::literal)
+                    + self.token(&args.right);
+            }
+
+            doc = doc + Doc::HardLine;
+        }
+
+        doc
+    }
+
+    fn let_decl(&mut self, decl: &LetDecl) -> Doc {
+        let signature = self.attributes(&decl.attributes) + self.let_signature(&decl.signature);
+
+        match &decl.body {
+            LetMode::Body(eq, expr) => signature + Doc::text(" ") + self.token(eq) + self.rhs(expr),
+            LetMode::Cases(cases) => {
+                signature
+                    + self.block(cases, |this, case| {
+                        let arm = match &case.arm {
+                            LetCaseKind::Arm(arm) => this.pattern_arm(arm),
+                            LetCaseKind::Error(tokens) => this.tokens(tokens),
+                        };
+
+                        this.token(&case.pipe) + Doc::text(" ") + arm
+                    })
+            }
+            LetMode::Error(tokens) => signature + Doc::text(" ") + self.tokens(tokens),
+        }
+    }
+
+    fn trait_decl(&mut self, decl: &TraitDecl) -> Doc {
+        let mut doc = self.visibility(&decl.visibility) + self.token(&decl.trait_);
+
+        for binder in &decl.supers {
+            doc = doc + Doc::text(" ") + self.trait_binder(binder);
+        }
+
+        doc = doc + Doc::text(" ") + self.upper(&decl.name);
+
+        for binder in &decl.binders {
+            doc = doc + Doc::text(" ") + self.type_binder(binder);
+        }
+
+        doc + Doc::text(" ")
+            + self.token(&decl.where_)
+            + self.block(&decl.body, Self::let_signature)
+    }
+
+    fn trait_impl(&mut self, decl: &TraitImpl) -> Doc {
+        let mut doc = self.token(&decl.impl_);
+
+        for binder in &decl.supers {
+            doc = doc + Doc::text(" ") + self.trait_binder(binder);
+        }
+
+        doc = doc + Doc::text(" ") + self.path(&decl.name, Self::upper);
+
+        for typ in &decl.types {
+            doc = doc + Doc::text(" ") + self.typ(typ);
+        }
+
+        doc + Doc::text(" ") + self.token(&decl.where_) + self.block(&decl.body, Self::let_decl)
+    }
+
+    fn constructor(&mut self, constructor: &Constructor) -> Doc {
+        let mut doc = self.attributes(&constructor.attributes)
+            + self.token(&constructor.pipe)
+            + Doc::text(" ")
+            + self.visibility(&constructor.visibility)
+            + self.upper(&constructor.name);
+
+        for arg in &constructor.args {
+            doc = doc + Doc::text(" ") + self.typ(arg);
+        }
+
+        if let Some(record) = &constructor.fields {
+            doc = doc
+                + Doc::text(" ")
+                + self.delimited(
+                    &record.left_brace,
+                    &record.fields,
+                    &record.right_brace,
+                    true,
+                    Self::field,
+                );
+        }
+
+        if let Some((colon, typ)) = &constructor.typ {
+            doc = doc + Doc::text(" ") + self.token(colon) + Doc::text(" ") + self.typ(typ);
+        }
+
+        doc
+    }
+
+    fn field(&mut self, field: &Field) -> Doc {
+        self.visibility(&field.visibility)
+            + self.lower(&field.name)
+            + Doc::text(" ")
+            + self.token(&field.colon)
+            + Doc::text(" ")
+            + self.typ(&field.typ)
+    }
+
+    fn type_decl(&mut self, decl: &TypeDecl) -> Doc {
+        let mut doc = self.attributes(&decl.attributes)
+            + self.visibility(&decl.visibility)
+            + self.token(&decl.type_)
+            + Doc::text(" ")
+            + self.upper(&decl.name);
+
+        for binder in &decl.binders {
+            doc = doc + Doc::text(" ") + self.type_binder(binder);
+        }
+
+        if let Some((eq, def)) = &decl.def {
+            doc = doc + Doc::text(" ") + self.token(eq);
+
+            doc = match def {
+                TypeDef::Sum(sum) => doc + self.block(&sum.constructors, Self::constructor),
+                TypeDef::Record(record) => {
+                    let fields = self.delimited(
+                        &record.left_brace,
+                        &record.fields,
+                        &record.right_brace,
+                        true,
+                        Self::field,
+                    );
+
+                    doc + (Doc::line() + fields).nest(INDENT).group()
+                }
+                TypeDef::Synonym(typ) => doc + Doc::text(" ") + self.typ(typ),
+            };
+        }
+
+        let Some(deriving) = &decl.deriving else {
+            return doc;
+        };
+
+        let names = &deriving.names;
+
+        let deriving = self.token(&deriving.derive)
+            + Doc::text(" ")
+            + self.token(&names.left)
+            + self.commas(&names.data, Self::upper)
+            + self.token(&names.right);
+
+        // The constructors of a sum are in lines of their own, so the derive gets one too.
+        match &decl.def {
+            Some((_, TypeDef::Sum(_))) => doc + (Doc::HardLine + deriving).nest(INDENT),
+            _ => doc + Doc::text(" ") + deriving,
+        }
+    }
+
+    fn use_decl(&mut self, decl: &UseDecl) -> Doc {
+        let doc = self.visibility(&decl.visibility)
+            + self.token(&decl.use_)
+            + Doc::text(" ")
+            + self.path(&decl.path, Self::upper);
+
+        match &decl.alias {
+            Some(alias) => {
+                doc + Doc::text(" ")
+                    + self.token(&alias.as_)
+                    + Doc::text(" ")
+                    + self.upper(&alias.alias)
+            }
+            None => doc,
+        }
+    }
+
+    fn module_decl(&mut self, decl: &ModuleDecl) -> Doc {
+        let doc = self.visibility(&decl.visibility)
+            + self.token(&decl.mod_)
+            + Doc::text(" ")
+            + self.upper(&decl.name);
+
+        match &decl.part {
+            Some(part) => {
+                doc + Doc::text(" ")
+                    + self.token(&part.where_)
+                    + self.block(&part.top_levels, Self::top_level)
+            }
+            None => doc,
+        }
+    }
+
+    fn ext_decl(&mut self, decl: &ExtDecl) -> Doc {
+        self.visibility(&decl.visibility)
+            + self.token(&decl.external)
+            + Doc::text(" ")
+            + self.lower(&decl.name)
+            + Doc::text(" ")
+            + self.token(&decl.colon)
+            + Doc::text(" ")
+            + self.typ(&decl.typ)
+            + Doc::text(" ")
+            + self.token(&decl.equal)
+            + Doc::text(" ")
+            + self.token(&decl.str)
+    }
+
+    fn kind(&mut self, kind: &Kind) -> Doc {
+        match &kind.data {
+            KindType::Star(token) => self.token(token),
+            KindType::Variable(name) => self.upper(name),
+            KindType::Arrow(left, arrow, right) => {
+                self.kind(left)
+                    + Doc::text(" ")
+                    + self.token(arrow)
+                    + Doc::text(" ")
+                    + self.kind(right)
+            }
+            KindType::Parenthesis(parens) => {
+                self.token(&parens.left) + self.kind(&parens.data) + self.token(&parens.right)
+            }
+        }
+    }
+
+    fn typ(&mut self, typ: &Type) -> Doc {
+        match &typ.data {
+            TypeKind::Parenthesis(parens) => {
+                let mut doc = self.token(&parens.left) + self.typ(&parens.data.0);
+
+                if let Some(sep) = &parens.data.1 {
+                    doc = doc + self.token(sep);
+                }
+
+                doc + self.token(&parens.right)
+            }
+            TypeKind::Tuple(parens) => {
+                self.token(&parens.left)
+                    + self.commas(&parens.data, |this, typ| this.typ(typ))
+                    + self.token(&parens.right)
+            }
+            TypeKind::Type(path) => self.path(path, Self::upper),
+            TypeKind::TypeVariable(name) => self.lower(name),
+            TypeKind::Arrow(arrow) => {
+                self.typ(&arrow.left)
+                    + Doc::text(" ")
+                    + self.token(&arrow.arrow)
+                    + Doc::text(" ")
+                    + self.typ(&arrow.right)
+            }
+            TypeKind::Application(app) => {
+                let mut doc = self.typ(&app.func);
+
+                for arg in &app.args {
+                    doc = doc + Doc::text(" ") + self.typ(arg);
+                }
+
+                doc
+            }
+            TypeKind::Forall(forall) => {
+                let mut doc = self.token(&forall.forall);
+
+                for param in &forall.params {
+                    doc = doc + Doc::text(" ") + self.type_binder(param);
+                }
+
+                doc + self.token(&forall.dot) + Doc::text(" ") + self.typ(&forall.body)
+            }
+            TypeKind::Unit(token) => self.token(token),
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal) -> Doc {
+        match &literal.data {
+            LiteralKind::String(token)
+            | LiteralKind::Integer(token)
+            | LiteralKind::Float(token)
+            | LiteralKind::Char(token)
+            | LiteralKind::Unit(token) => self.token(token),
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) -> Doc {
+        match &pattern.data {
+            PatternKind::Wildcard(token) => self.token(token),
+            PatternKind::Constructor(path) => self.path(path, Self::upper),
+            PatternKind::Variable(name) => self.lower(name),
+            PatternKind::Literal(literal) => self.literal(literal),
+            PatternKind::Annotation(ascription) => {
+                self.pattern(&ascription.left)
+                    + Doc::text(" ")
+                    + self.token(&ascription.colon)
+                    + Doc::text(" ")
+                    + self.typ(&ascription.right)
+            }
+            PatternKind::Tuple(patterns) => self.commas(patterns, Self::pattern),
+            PatternKind::Application(app) => {
+                let mut doc = self.path(&app.func, Self::upper);
+
+                for arg in &app.args {
+                    doc = doc + Doc::text(" ") + self.pattern(arg);
+                }
+
+                doc
+            }
+            PatternKind::Record(record) => {
+                self.path(&record.name, Self::upper)
+                    + Doc::text(" ")
+                    + self.delimited(
+                        &record.left_brace,
+                        &record.fields,
+                        &record.right_brace,
+                        true,
+                        Self::pattern_field,
+                    )
+            }
+            PatternKind::Parenthesis(parens) => {
+                self.token(&parens.left) + self.pattern(&parens.data) + self.token(&parens.right)
+            }
+        }
+    }
+
+    fn pattern_field(&mut self, field: &PatField) -> Doc {
+        match &field.pattern {
+            Some((eq, pattern)) => {
+                self.lower(&field.name)
+                    + Doc::text(" ")
+                    + self.token(eq)
+                    + Doc::text(" ")
+                    + self.pattern(pattern)
+            }
+            None => self.lower(&field.name),
+        }
+    }
+
+    fn pattern_arm(&mut self, arm: &PatternArm) -> Doc {
+        let mut doc = self.commas(&arm.patterns, |this, pattern| this.pattern(pattern));
+
+        if let Some((if_, guard)) = &arm.guard {
+            doc = doc + Doc::text(" ") + self.token(if_) + Doc::text(" ") + self.expr(guard);
+            doc = doc + self.space_after(guard);
+        } else {
+            doc = doc + Doc::text(" ");
+        }
+
+        doc + self.token(&arm.arrow) + self.rhs(&arm.expr)
+    }
+
+    fn statement(&mut self, sttm: &Sttm) -> Doc {
+        match &sttm.data {
+            StatementKind::Let(let_) => {
+                self.token(&let_.let_)
+                    + Doc::text(" ")
+                    + self.pattern(&let_.pattern)
+                    + self.let_annotation(&let_.typ)
+                    + Doc::text(" ")
+                    + self.token(&let_.eq)
+                    + self.rhs(&let_.expr)
+            }
+            StatementKind::Expr(expr) => self.expr(expr),
+            StatementKind::Error(tokens) => self.tokens(tokens),
+        }
+    }
+
+    fn let_annotation(&mut self, typ: &Option<(Token, Box<Type>)>) -> Doc {
+        match typ {
+            Some((colon, typ)) => {
+                Doc::text(" ") + self.token(colon) + Doc::text(" ") + self.typ(typ)
+            }
+            None => Doc::Nil,
+        }
+    }
+
+    fn record_field(&mut self, field: &RecordField) -> Doc {
+        match &field.value {
+            Some((eq, expr)) => {
+                self.lower(&field.name)
+                    + Doc::text(" ")
+                    + self.token(eq)
+                    + Doc::text(" ")
+                    + self.closed(expr)
+            }
+            None => self.lower(&field.name),
+        }
+    }
+
+    fn html(&mut self, node: &HtmlNode) -> Doc {
+        let mut doc = self.token(&node.left_angle) + self.lower(&node.name);
+
+        for attribute in &node.attributes {
+            doc = doc
+                + Doc::text(" ")
+                + self.upper(&attribute.name)
+                + self.token(&attribute.eq)
+                + self.expr(&attribute.value);
+        }
+
+        doc = doc + self.token(&node.right_angle);
+
+        for child in &node.children {
+            doc = doc + self.html(child);
+        }
+
+        doc + self.token(&node.left_angle_slash)
+            + self.lower(&node.name_end)
+            + self.token(&node.right_angle_end)
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Doc {
+        match &expr.data {
+            ExprKind::Lambda(lambda) => {
+                let mut doc = self.token(&lambda.lambda);
+
+                for (i, pattern) in lambda.patterns.iter().enumerate() {
+                    if i != 0 {
+                        doc = doc + Doc::text(" ");
+                    }
+                    doc = doc + self.pattern(pattern);
+                }
+
+                doc + Doc::text(" ") + self.token(&lambda.arrow) + self.rhs(&lambda.expr)
+            }
+            ExprKind::List(list) => self.delimited(
+                &list.left_bracket,
+                &list.values,
+                &list.right_bracket,
+                false,
+                |this, value| this.closed(value),
+            ),
+            ExprKind::Application(app) => {
+                let func = self.expr(&app.func);
+                let mut args = Doc::Nil;
+
+                for arg in &app.args {
+                    args = args + Doc::line() + self.expr(arg);
+                }
+
+                (func + args.nest(INDENT)).group()
+            }
+            ExprKind::HtmlNode(node) => self.html(node),
+            ExprKind::Variable(name) => self.lower(name),
+            ExprKind::Constructor(path) => self.path(path, Self::upper),
+            ExprKind::Function(path) => self.path(path, Self::lower),
+            ExprKind::Projection(projection) => {
+                self.expr(&projection.expr)
+                    + self.token(&projection.dot)
+                    + self.lower(&projection.field)
+            }
+            ExprKind::TupleProjection(projection) => {
+                self.expr(&projection.expr)
+                    + self.token(&projection.dot)
+                    + self.token(&projection.index)
+            }
+            ExprKind::Binary(binary) => {
+                let left = self.expr(&binary.left);
+
+                if ends_with_block(&binary.left) {
+                    left + self.close(&binary.left)
+                        + self.token(operator_token(&binary.op))
+                        + Doc::text(" ")
+                        + self.expr(&binary.right)
+                } else {
+                    let right = Doc::line()
+                        + self.token(operator_token(&binary.op))
+                        + Doc::text(" ")
+                        + self.expr(&binary.right);
+
+                    (left + right.nest(INDENT)).group()
+                }
+            }
+            ExprKind::Let(let_) => {
+                let rec = match &let_.rec {
+                    Some(rec) => self.token(rec) + Doc::text(" "),
+                    None => Doc::Nil,
+                };
+
+                let mut head = self.token(&let_.let_)
+                    + Doc::text(" ")
+                    + rec
+                    + self.pattern(&let_.pattern)
+                    + self.let_annotation(&let_.typ)
+                    + Doc::text(" ")
+                    + self.token(&let_.eq)
+                    + self.rhs(&let_.body);
+
+                let mut last = &let_.body;
+
+                // Each binding of a group starts in its own line.
+                for binding in &let_.group {
+                    head = head
+                        + self.close(last)
+                        + Doc::HardLine
+                        + self.token(&binding.and)
+                        + Doc::text(" ")
+                        + self.pattern(&binding.pattern)
+                        + Doc::text(" ")
+                        + self.token(&binding.eq)
+                        + self.rhs(&binding.body);
+
+                    last = &binding.body;
+                }
+
+                if !let_.group.is_empty() {
+                    head + self.close(last)
+                        + Doc::HardLine
+                        + self.token(&let_.in_)
+                        + Doc::text(" ")
+                        + self.expr(&let_.value)
+                } else if ends_with_block(last) {
+                    head + self.close(last)
+                        + self.token(&let_.in_)
+                        + Doc::text(" ")
+                        + self.expr(&let_.value)
+                } else {
+                    let body = Doc::line()
+                        + self.token(&let_.in_)
+                        + Doc::text(" ")
+                        + self.expr(&let_.value);
+
+                    (head + body.nest(INDENT)).group()
+                }
+            }
+            ExprKind::When(when) => {
+                self.token(&when.when)
+                    + Doc::text(" ")
+                    + self.commas(&when.scrutinee, |this, expr| this.closed(expr))
+                    + Doc::text(" ")
+                    + self.token(&when.is)
+                    + self.block(&when.arms, Self::pattern_arm)
+            }
+            ExprKind::Do(do_) => {
+                self.token(&do_.do_) + self.block(&do_.block.statements, Self::statement)
+            }
+            ExprKind::Literal(literal) => self.literal(literal),
+            ExprKind::Hole(hole) => self.token(&hole.question) + self.lower(&hole.name),
+            ExprKind::Annotation(annotation) => {
+                self.expr(&annotation.expr)
+                    + Doc::text(" ")
+                    + self.token(&annotation.colon)
+                    + Doc::text(" ")
+                    + self.typ(&annotation.typ)
+            }
+            ExprKind::TypeApplication(app) => {
+                self.expr(&app.expr) + self.token(&app.at) + self.typ(&app.typ)
+            }
+            ExprKind::RecordInstance(instance) => {
+                self.path(&instance.name, Self::upper)
+                    + Doc::text(" ")
+                    + self.delimited(
+                        &instance.left_brace,
+                        &instance.fields,
+                        &instance.right_brace,
+                        true,
+                        Self::record_field,
+                    )
+            }
+            ExprKind::RecordUpdate(update) => {
+                self.expr(&update.expr)
+                    + Doc::text(" ")
+                    + self.delimited(
+                        &update.left_brace,
+                        &update.fields,
+                        &update.right_brace,
+                        true,
+                        Self::record_field,
+                    )
+            }
+            ExprKind::Parenthesis(parens) => {
+                let mut doc = self.token(&parens.left) + self.closed(&parens.data.0);
+
+                if let Some(sep) = &parens.data.1 {
+                    doc = doc + self.token(sep);
+                }
+
+                doc + self.token(&parens.right)
+            }
+            ExprKind::Tuple(tuple) => {
+                self.token(&tuple.left)
+                    + self.commas(&tuple.data, |this, expr| this.closed(expr))
+                    + self.token(&tuple.right)
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Free variables of the abstract tree. The variables are given in the order that they first appear
+//! from left to right, so everything that is built from them, like the implicit `forall` of a
+//! signature, does not depend on the order of a hash.
+
+use indexmap::IndexSet;
+use vulpi_intern::Symbol;
+
+use crate::{
+    r#abstract::{
+        Block, Expr, ExprKind, LetExpr, Node, PatternArm, PatternKind, Qualified, SttmKind, Type,
+        TypeKind,
+    },
+    visit::{walk_expr, walk_pattern_arm, walk_type, Visitor},
+};
+
+/// Collects the type variables that are not bound by a `forall`.
+#[derive(Default)]
+struct FreeTypes {
+    bound: Vec<Symbol>,
+    vars: IndexSet<Symbol>,
+}
+
+impl Visitor for FreeTypes {
+    fn visit_type(&mut self, typ: &Type) {
+        self.typ(typ)
+    }
+}
+
+impl FreeTypes {
+    fn typ(&mut self, typ: &Node<TypeKind>) {
+        match &typ.data {
+            TypeKind::Forall(forall) => {
+                let len = self.bound.len();
+                self.bound
+                    .extend(forall.params.iter().map(|param| param.name().clone()));
+                walk_type(self, typ);
+                self.bound.truncate(len);
+            }
+            TypeKind::TypeVariable(var) if !self.bound.contains(var) => {
+                self.vars.insert(var.clone());
+            }
+            _ => walk_type(self, typ),
+        }
+    }
+}
+
+/// Collects the local variables that are used but not bound by a pattern of the expression.
+#[derive(Default)]
+struct FreeTerms {
+    bound: Vec<Symbol>,
+    vars: IndexSet<Symbol>,
+}
+
+impl FreeTerms {
+    fn expr(&mut self, expr: &Node<ExprKind>) {
+        match &expr.data {
+            ExprKind::Variable(var) => {
+                if !self.bound.contains(var) {
+                    self.vars.insert(var.clone());
+                }
+            }
+            ExprKind::Lambda(lambda) => {
+                let len = self.bound.len();
+                self.bound.extend(lambda.param.bound_variables());
+                self.visit_expr(&lambda.body);
+                self.bound.truncate(len);
+            }
+            ExprKind::Let(let_) => self.let_expr(let_),
+            ExprKind::Do(block) => self.block(block),
+            _ => walk_expr(self, expr),
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        let len = self.bound.len();
+
+        for sttm in &block.sttms {
+            match &sttm.data {
+                SttmKind::Let(decl) => {
+                    self.visit_expr(&decl.expr);
+                    self.bound.extend(decl.pat.bound_variables());
+                }
+                SttmKind::Expr(expr) => self.visit_expr(expr),
+                SttmKind::Error => (),
+            }
+        }
+
+        self.bound.truncate(len);
+    }
+
+    fn let_expr(&mut self, let_: &LetExpr) {
+        let len = self.bound.len();
+
+        // The bindings of a `let rec` are in scope in their own bodies.
+        if let_.recursive {
+            self.bound.extend(let_.pattern.bound_variables());
+
+            for (pattern, _) in &let_.group {
+                self.bound.extend(pattern.bound_variables());
+            }
+        }
+
+        self.visit_expr(&let_.body);
+
+        for (_, body) in &let_.group {
+            self.visit_expr(body);
+        }
+
+        if !let_.recursive {
+            self.bound.extend(let_.pattern.bound_variables());
+        }
+
+        self.visit_expr(&let_.value);
+        self.bound.truncate(len);
+    }
+}
+
+impl Visitor for FreeTerms {
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.expr(expr)
+    }
+
+    fn visit_pattern_arm(&mut self, arm: &PatternArm) {
+        let len = self.bound.len();
+
+        for pattern in &arm.patterns {
+            self.bound.extend(pattern.bound_variables());
+        }
+
+        walk_pattern_arm(self, arm);
+        self.bound.truncate(len);
+    }
+}
+
+/// Collects the top level functions that are used by an expression.
+#[derive(Default)]
+struct Functions(IndexSet<Qualified>);
+
+impl Visitor for Functions {
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.expr(expr)
+    }
+}
+
+impl Functions {
+    fn expr(&mut self, expr: &Node<ExprKind>) {
+        if let ExprKind::Function(name) = &expr.data {
+            self.0.insert(name.clone());
+        }
+
+        walk_expr(self, expr)
+    }
+}
+
+impl Node<TypeKind> {
+    /// The type variables of the type that are not bound by one of its `forall`s.
+    pub fn free_type_variables(&self) -> IndexSet<Symbol> {
+        let mut free = FreeTypes::default();
+        free.typ(self);
+        free.vars
+    }
+}
+
+impl Node<PatternKind> {
+    /// The variables that are bound by the pattern. Both sides of an or pattern should bind the
+    /// same variables, and the ones of both sides are given if they don't.
+    pub fn bound_variables(&self) -> IndexSet<Symbol> {
+        let mut found = IndexSet::new();
+        bound_variables(self, &mut found);
+        found
+    }
+}
+
+fn bound_variables(pattern: &Node<PatternKind>, found: &mut IndexSet<Symbol>) {
+    match &pattern.data {
+        PatternKind::Variable(name) => {
+            found.insert(name.clone());
+        }
+        PatternKind::Tuple(patterns) => {
+            for pattern in patterns {
+                bound_variables(pattern, found);
+            }
+        }
+        PatternKind::Ascription(ascription) => bound_variables(&ascription.pat, found),
+        PatternKind::Or(or) => {
+            bound_variables(&or.left, found);
+            bound_variables(&or.right, found);
+        }
+        PatternKind::Application(app) => {
+            for pattern in &app.args {
+                bound_variables(pattern, found);
+            }
+        }
+        PatternKind::Labeled(labeled) => {
+            for (_, _, pattern) in &labeled.fields {
+                bound_variables(pattern, found);
+            }
+        }
+        PatternKind::Wildcard | PatternKind::Literal(_) | PatternKind::Error => (),
+    }
+}
+
+impl Node<ExprKind> {
+    /// The local variables that are used by the expression but are not bound inside of it by a
+    /// lambda, a `let`, a statement of a block or an arm of a `when`.
+    pub fn free_term_variables(&self) -> IndexSet<Symbol> {
+        let mut free = FreeTerms::default();
+        free.expr(self);
+        free.vars
+    }
+
+    /// The top level functions that are used by the expression.
+    pub fn functions(&self) -> IndexSet<Qualified> {
+        let mut functions = Functions::default();
+        functions.expr(self);
+        functions.0
+    }
+}
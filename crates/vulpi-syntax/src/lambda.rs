@@ -1,10 +1,10 @@
-use std::{collections::HashMap};
+use std::collections::HashMap;
 
 use vulpi_intern::Symbol;
 use vulpi_location::Span;
 use vulpi_macros::Show;
 
-use crate::{r#abstract::Qualified, elaborated::Literal};
+use crate::{elaborated::Literal, r#abstract::Qualified};
 
 #[derive(Show, Clone)]
 pub enum ConsDef {
@@ -38,7 +38,7 @@ pub enum TagType {
     Field(usize),
     Number(usize),
     Size,
-    None
+    None,
 }
 
 #[derive(Show, Clone)]
@@ -63,7 +63,6 @@ pub enum ExprKind {
     Tuple(Vec<Expr>),
 
     Switch(Symbol, Tree, Vec<Expr>),
-    
 }
 
 pub type Expr = Box<ExprKind>;
@@ -95,4 +94,4 @@ pub struct Program {
     pub externals: Vec<(Qualified, Symbol)>,
     pub commands: Vec<(Symbol, Symbol)>,
     pub definitions: HashMap<Qualified, (ConsDef, usize)>,
-}
\ No newline at end of file
+}
@@ -1,5 +1,13 @@
 pub mod r#abstract;
 pub mod concrete;
 pub mod elaborated;
+pub mod format;
+pub mod free;
 pub mod lambda;
+pub mod outline;
+pub mod pretty;
 pub mod tokens;
+pub mod visit;
+
+pub use format::format;
+pub use outline::outline;
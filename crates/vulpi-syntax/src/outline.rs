@@ -0,0 +1,320 @@
+//! The outline of a program, that editors show as the list of symbols of a file. It only looks at
+//! the abstract tree, so it works even for programs that do not type check.
+
+use vulpi_intern::Symbol;
+use vulpi_location::Span;
+
+use crate::{
+    r#abstract::{
+        Constructor, Expr, ExtDecl, LetBinder, LetDecl, LetSignature, ModuleDecl, Pattern, PiType,
+        Program, Sttm, TraitDecl, Type, TypeApplication, TypeDecl, TypeDef, TypeForall, TypeKind,
+    },
+    visit::{self, Visitor},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    Let,
+    External,
+    Type,
+    Constructor,
+    Field,
+    Trait,
+    Signature,
+    Module,
+}
+
+/// A declaration of the outline.
+#[derive(Clone, Debug)]
+pub struct SymbolInfo {
+    pub kind: SymbolKind,
+    pub name: Symbol,
+
+    /// The number of arguments of lets, signatures and constructors, and the number of binders of
+    /// types and traits.
+    pub arity: usize,
+
+    /// The declared type of lets, signatures, externals and fields. Lets without a return type
+    /// have none.
+    pub detail: Option<String>,
+
+    /// The location of the name of the declaration.
+    pub selection: Span,
+
+    /// The location of the whole declaration. It starts at the name, because the abstract tree
+    /// does not keep the keywords that come before it.
+    pub span: Span,
+
+    /// The constructors or fields of types, the signatures of traits and the declarations of
+    /// modules.
+    pub children: Vec<SymbolInfo>,
+}
+
+impl SymbolInfo {
+    fn new(kind: SymbolKind, name: Symbol, selection: Span) -> Self {
+        Self {
+            kind,
+            name,
+            arity: 0,
+            detail: None,
+            span: selection.clone(),
+            selection,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Grows a span to cover every type, pattern and expression that it visits.
+struct Extent(Span);
+
+impl Extent {
+    fn mix(&mut self, span: &Span) {
+        if span.file == self.0.file {
+            self.0 = self.0.clone().mix(span.clone());
+        }
+    }
+}
+
+impl Visitor for Extent {
+    fn visit_type(&mut self, typ: &Type) {
+        self.mix(&typ.span);
+        visit::walk_type(self, typ)
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        self.mix(&pattern.span);
+        visit::walk_pattern(self, pattern)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.mix(&expr.span);
+        visit::walk_expr(self, expr)
+    }
+
+    fn visit_sttm(&mut self, sttm: &Sttm) {
+        self.mix(&sttm.span);
+        visit::walk_sttm(self, sttm)
+    }
+}
+
+/// Shows a type in the way that it's written. Arrows and applications inside of other types are
+/// put between parenthesis.
+fn show_type(typ: &Type) -> String {
+    match &typ.data {
+        TypeKind::Arrow(PiType { left, right }) => {
+            format!("{} -> {}", show_domain(left), show_type(right))
+        }
+        TypeKind::Tuple(types) => {
+            let types = types.iter().map(show_type).collect::<Vec<_>>();
+            format!("({})", types.join(", "))
+        }
+        TypeKind::Application(TypeApplication { func, args }) => {
+            let mut text = show_argument(func);
+
+            for arg in args {
+                text.push(' ');
+                text.push_str(&show_argument(arg));
+            }
+
+            text
+        }
+        TypeKind::Forall(TypeForall { params, body }) => {
+            let params = params.iter().map(|x| x.name().get()).collect::<Vec<_>>();
+            format!("forall {}. {}", params.join(" "), show_type(body))
+        }
+        TypeKind::TypeVariable(name) => name.get(),
+        TypeKind::Type(name) => name.name.get(),
+        TypeKind::Unit => "()".to_string(),
+        TypeKind::Error => "<error>".to_string(),
+    }
+}
+
+/// Shows the type on the left of an arrow.
+fn show_domain(typ: &Type) -> String {
+    match &typ.data {
+        TypeKind::Arrow(_) | TypeKind::Forall(_) => format!("({})", show_type(typ)),
+        _ => show_type(typ),
+    }
+}
+
+/// Shows the type as an argument of an application.
+fn show_argument(typ: &Type) -> String {
+    match &typ.data {
+        TypeKind::Arrow(_) | TypeKind::Application(_) | TypeKind::Forall(_) => {
+            format!("({})", show_type(typ))
+        }
+        _ => show_type(typ),
+    }
+}
+
+/// The type of a signature as a function from the types of its parameters to its return type.
+fn signature_type(signature: &LetSignature) -> Option<String> {
+    let ret = signature.ret.as_ref()?;
+
+    let mut text = String::new();
+
+    for binder in &signature.binders {
+        match binder {
+            LetBinder::Param(binder) => {
+                text.push_str(&show_domain(&binder.typ));
+                text.push_str(" -> ");
+            }
+            LetBinder::Trait(typ) => {
+                text.push_str(&show_type(typ));
+                text.push_str(" => ");
+            }
+        }
+    }
+
+    text.push_str(&show_type(ret));
+
+    Some(text)
+}
+
+fn parameters(signature: &LetSignature) -> usize {
+    signature
+        .binders
+        .iter()
+        .filter(|binder| matches!(binder, LetBinder::Param(_)))
+        .count()
+}
+
+fn signature(signature: &LetSignature) -> SymbolInfo {
+    let mut extent = Extent(signature.span.clone());
+    extent.visit_let_signature(signature);
+
+    SymbolInfo {
+        arity: parameters(signature),
+        detail: signature_type(signature),
+        span: extent.0,
+        ..SymbolInfo::new(
+            SymbolKind::Signature,
+            signature.name.name.clone(),
+            signature.span.clone(),
+        )
+    }
+}
+
+fn let_decl(decl: &LetDecl) -> SymbolInfo {
+    let mut extent = Extent(decl.signature.span.clone());
+    extent.visit_let_decl(decl);
+
+    // The arguments of a let with many clauses are the patterns of the clauses.
+    let patterns = decl.body.first().map_or(0, |arm| arm.patterns.len());
+
+    SymbolInfo {
+        kind: SymbolKind::Let,
+        arity: parameters(&decl.signature) + patterns,
+        span: extent.0,
+        ..signature(&decl.signature)
+    }
+}
+
+fn ext_decl(decl: &ExtDecl) -> SymbolInfo {
+    let mut extent = Extent(decl.span.clone());
+    extent.visit_ext_decl(decl);
+
+    SymbolInfo {
+        detail: Some(show_type(&decl.typ)),
+        span: extent.0,
+        ..SymbolInfo::new(
+            SymbolKind::External,
+            decl.name.name.clone(),
+            decl.span.clone(),
+        )
+    }
+}
+
+fn constructor(constructor: &Constructor) -> SymbolInfo {
+    let mut extent = Extent(constructor.span.clone());
+    extent.visit_constructor(constructor);
+
+    SymbolInfo {
+        arity: constructor.args.len(),
+        span: extent.0,
+        ..SymbolInfo::new(
+            SymbolKind::Constructor,
+            constructor.name.name.clone(),
+            constructor.span.clone(),
+        )
+    }
+}
+
+fn type_decl(decl: &TypeDecl) -> SymbolInfo {
+    let mut extent = Extent(decl.span.clone());
+    extent.visit_type_decl(decl);
+
+    let children = match &decl.def {
+        TypeDef::Sum(sum) => sum.constructors.iter().map(constructor).collect(),
+        TypeDef::Record(record) => record
+            .fields
+            .iter()
+            .map(|(name, typ, _, span, _)| {
+                let mut extent = Extent(span.clone());
+                extent.visit_type(typ);
+
+                SymbolInfo {
+                    detail: Some(show_type(typ)),
+                    span: extent.0,
+                    ..SymbolInfo::new(SymbolKind::Field, name.name.clone(), span.clone())
+                }
+            })
+            .collect(),
+        TypeDef::Synonym(_) | TypeDef::Abstract => Vec::new(),
+    };
+
+    for child in &children {
+        extent.mix(&child.span);
+    }
+
+    SymbolInfo {
+        arity: decl.binders.len(),
+        span: extent.0,
+        children,
+        ..SymbolInfo::new(SymbolKind::Type, decl.name.name.clone(), decl.span.clone())
+    }
+}
+
+fn trait_decl(decl: &TraitDecl) -> SymbolInfo {
+    let mut extent = Extent(decl.span.clone());
+    extent.visit_trait_decl(decl);
+
+    let children = decl.body.iter().map(signature).collect();
+
+    SymbolInfo {
+        arity: decl.binders.len(),
+        span: extent.0,
+        children,
+        ..SymbolInfo::new(SymbolKind::Trait, decl.name.name.clone(), decl.span.clone())
+    }
+}
+
+fn module_decl(decl: &ModuleDecl) -> SymbolInfo {
+    let children = decl.decls.as_ref().map(outline).unwrap_or_default();
+
+    let mut extent = Extent(decl.span.clone());
+
+    for child in &children {
+        extent.mix(&child.span);
+    }
+
+    SymbolInfo {
+        span: extent.0,
+        children,
+        ..SymbolInfo::new(SymbolKind::Module, decl.name.clone(), decl.span.clone())
+    }
+}
+
+/// The declarations of a program in the order that they appear in the source.
+pub fn outline(program: &Program) -> Vec<SymbolInfo> {
+    let mut symbols = Vec::new();
+
+    symbols.extend(program.lets.iter().map(let_decl));
+    symbols.extend(program.externals.iter().map(ext_decl));
+    symbols.extend(program.types.iter().map(type_decl));
+    symbols.extend(program.traits.iter().map(trait_decl));
+    symbols.extend(program.modules.iter().map(module_decl));
+
+    symbols.sort_by_key(|symbol| symbol.selection.start.clone());
+    symbols
+}
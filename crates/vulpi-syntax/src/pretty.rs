@@ -0,0 +1,266 @@
+//! Documents for pretty printing in the style of Wadler's "A prettier printer". A document is made
+//! of text, line breaks, nesting and groups. A group is printed in a single line if it fits in the
+//! width of the page and, otherwise, all of its line breaks are broken.
+
+use std::ops::Add;
+
+#[derive(Clone, Debug)]
+pub enum Doc {
+    Nil,
+    Text(String),
+
+    /// A line break that is always broken. Groups that contain one are never printed flat.
+    HardLine,
+
+    /// An empty line. Many of them in a row are printed as a single one.
+    BlankLine,
+
+    /// A comment in its own line. It's not measured, because the line breaks around it already
+    /// decide if a group fits.
+    Comment(String),
+
+    /// A comment that goes at the end of the line before it, even if it was already broken.
+    Trailing(String),
+
+    /// The first document if the enclosing group is broken and the second one otherwise.
+    FlatAlt(Box<Doc>, Box<Doc>),
+
+    Nest(usize, Box<Doc>),
+
+    /// Indents the lines of the document to the column where it starts.
+    Align(Box<Doc>),
+
+    Group(Box<Doc>),
+    Concat(Vec<Doc>),
+}
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Self {
+        Doc::Text(text.into())
+    }
+
+    /// A line break that is a space when the group is flat.
+    pub fn line() -> Self {
+        Doc::FlatAlt(Box::new(Doc::HardLine), Box::new(Doc::text(" ")))
+    }
+
+    /// A line break that is nothing when the group is flat.
+    pub fn softline() -> Self {
+        Doc::FlatAlt(Box::new(Doc::HardLine), Box::new(Doc::Nil))
+    }
+
+    /// A space that is only printed when the group is broken.
+    pub fn broken_space() -> Self {
+        Doc::FlatAlt(Box::new(Doc::text(" ")), Box::new(Doc::Nil))
+    }
+
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Self {
+        docs.into_iter().fold(Doc::Nil, Add::add)
+    }
+
+    /// Joins documents with a separator between each of them.
+    pub fn join(docs: impl IntoIterator<Item = Doc>, sep: Doc) -> Self {
+        let mut result = Doc::Nil;
+
+        for (i, doc) in docs.into_iter().enumerate() {
+            if i != 0 {
+                result = result + sep.clone();
+            }
+            result = result + doc;
+        }
+
+        result
+    }
+
+    pub fn nest(self, indent: usize) -> Self {
+        Doc::Nest(indent, Box::new(self))
+    }
+
+    pub fn align(self) -> Self {
+        Doc::Align(Box::new(self))
+    }
+
+    pub fn group(self) -> Self {
+        Doc::Group(Box::new(self))
+    }
+
+    /// Prints the document trying to keep the lines shorter than the width.
+    pub fn render(&self, width: usize) -> String {
+        let mut printer = Printer {
+            out: String::new(),
+            column: 0,
+            indent: 0,
+            fresh: true,
+        };
+
+        let mut stack = vec![(0, Mode::Break, self)];
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil => {}
+                Doc::Text(text) | Doc::Comment(text) => printer.text(text),
+                Doc::HardLine => printer.newline(indent),
+                Doc::BlankLine => printer.blank(),
+                Doc::Trailing(text) => printer.trailing(text),
+                Doc::FlatAlt(broken, flat) => match mode {
+                    Mode::Break => stack.push((indent, mode, broken)),
+                    Mode::Flat => stack.push((indent, mode, flat)),
+                },
+                Doc::Nest(nested, doc) => stack.push((indent + nested, mode, doc)),
+                Doc::Align(doc) => stack.push((printer.current_column(), mode, doc)),
+                Doc::Group(doc) => {
+                    let space = width as isize - printer.current_column() as isize;
+
+                    let mode = if mode == Mode::Flat || fits(space, (indent, doc), &stack) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+
+                    stack.push((indent, mode, doc))
+                }
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+            }
+        }
+
+        let mut out = printer.out.trim_end().to_string();
+
+        if !out.is_empty() {
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+impl Add for Doc {
+    type Output = Doc;
+
+    fn add(self, rhs: Doc) -> Doc {
+        match (self, rhs) {
+            (Doc::Nil, doc) | (doc, Doc::Nil) => doc,
+            (Doc::Concat(mut docs), Doc::Concat(rest)) => {
+                docs.extend(rest);
+                Doc::Concat(docs)
+            }
+            (Doc::Concat(mut docs), doc) => {
+                docs.push(doc);
+                Doc::Concat(docs)
+            }
+            (doc, rhs) => Doc::Concat(vec![doc, rhs]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Checks if a document fits in the space that is left in the line when printed flat. The rest of
+/// the line is made of the documents that come after it, until the first line break. Line breaks
+/// before the first text, like the ones of the comments before a declaration, are not counted.
+fn fits(mut space: isize, (indent, doc): (usize, &Doc), rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut pending = vec![(indent, Mode::Flat, doc)];
+    let mut rest = rest.iter().rev();
+    let mut measured = false;
+
+    loop {
+        if space < 0 {
+            return false;
+        }
+
+        let Some((indent, mode, doc)) = pending.pop().or_else(|| rest.next().copied()) else {
+            return true;
+        };
+
+        match doc {
+            Doc::Nil | Doc::Comment(_) => {}
+            Doc::Text(text) => {
+                space -= text.chars().count() as isize;
+                measured |= !text.is_empty();
+            }
+            Doc::HardLine | Doc::BlankLine | Doc::Trailing(_) if !measured => {}
+            Doc::HardLine | Doc::BlankLine | Doc::Trailing(_) => return mode == Mode::Break,
+            Doc::FlatAlt(broken, flat) => match mode {
+                Mode::Break => pending.push((indent, mode, broken)),
+                Mode::Flat => pending.push((indent, mode, flat)),
+            },
+            Doc::Nest(nested, doc) => pending.push((indent + nested, mode, doc)),
+            Doc::Align(doc) | Doc::Group(doc) => pending.push((indent, mode, doc)),
+            Doc::Concat(docs) => pending.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+        }
+    }
+}
+
+/// The output of the rendering. The indentation of a line is only written with its first text,
+/// so empty lines have no trailing spaces, and many line breaks in a row make a single one.
+struct Printer {
+    out: String,
+    column: usize,
+    indent: usize,
+    fresh: bool,
+}
+
+impl Printer {
+    fn current_column(&self) -> usize {
+        if self.fresh {
+            self.indent
+        } else {
+            self.column
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        if self.fresh {
+            self.out.extend(std::iter::repeat_n(' ', self.indent));
+            self.column = self.indent;
+            self.fresh = false;
+        }
+
+        self.out.push_str(text);
+        self.column += text.chars().count();
+    }
+
+    fn newline(&mut self, indent: usize) {
+        if !self.fresh {
+            self.out.truncate(self.out.trim_end_matches(' ').len());
+            self.out.push('\n');
+            self.fresh = true;
+        }
+
+        self.indent = indent;
+    }
+
+    fn blank(&mut self) {
+        if self.out.is_empty() {
+            return;
+        }
+
+        self.newline(self.indent);
+
+        if !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn trailing(&mut self, text: &str) {
+        if self.out.is_empty() {
+            self.text(text);
+        } else if self.fresh {
+            self.out.truncate(self.out.trim_end_matches('\n').len());
+            self.out.push(' ');
+            self.out.push_str(text);
+            self.out.push('\n');
+        } else {
+            self.out.truncate(self.out.trim_end_matches(' ').len());
+            self.out.push(' ');
+            self.out.push_str(text);
+        }
+    }
+}
@@ -31,6 +31,10 @@ pub enum TokenData {
     External, // 'external' keyword
     Trait,    // 'trait' keyword
     Impl,     // 'impl' keyword
+    Rec,      // 'rec' keyword
+    Derive,   // 'derive' keyword
+
+    AndKeyword, // 'and' keyword, the operator `&&` is [TokenData::And]
 
     String, // String literal
     Int,    // Integer literal
@@ -72,6 +76,8 @@ pub enum TokenData {
     Caret,     // '^'
     Ampersand, // '&'
     Tilde,     // '~'
+    Question,  // '?'
+    At,        // '@'
 
     Greater,      // '>'
     Less,         // '<'
@@ -124,6 +130,41 @@ impl Token {
     pub fn symbol(&self) -> Symbol {
         self.value.data.clone()
     }
+
+    /// The documentation written right before the token in comments that start with `---`. The
+    /// lines are merged without their markers, and a blank line separates the documentation from
+    /// the token.
+    pub fn docs(&self) -> Option<Symbol> {
+        let mut lines = Vec::new();
+        let mut after = &self.whitespace;
+
+        for comment in self.comments.iter().rev() {
+            let text = comment.comment.data.get();
+
+            let Some(line) = text.strip_prefix("---") else {
+                break;
+            };
+
+            if after.data.get().matches('\n').count() > 1 {
+                break;
+            }
+
+            lines.push(
+                line.strip_prefix(' ')
+                    .unwrap_or(line)
+                    .trim_end()
+                    .to_string(),
+            );
+            after = &comment.whitespace;
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        lines.reverse();
+        Some(Symbol::intern(&lines.join("\n")))
+    }
 }
 
 impl Debug for Token {
@@ -157,6 +198,8 @@ impl ToString for Token {
             Caret => "^".to_string(),
             Ampersand => "&".to_string(),
             Tilde => "~".to_string(),
+            Question => "?".to_string(),
+            At => "@".to_string(),
             Greater => ">".to_string(),
             Less => "<".to_string(),
             GreaterEqual => ">=".to_string(),
@@ -186,6 +229,9 @@ impl ToString for Token {
             Forall => "forall".to_string(),
             Trait => "trait".to_string(),
             Impl => "impl".to_string(),
+            Rec => "rec".to_string(),
+            Derive => "derive".to_string(),
+            AndKeyword => "and".to_string(),
             In => "in".to_string(),
             LBrace => "{{".to_string(),
             RBrace => "}}".to_string(),
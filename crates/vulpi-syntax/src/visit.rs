@@ -0,0 +1,987 @@
+//! Traversals of the abstract tree. A [Visitor] has one method for each kind of node, and the
+//! default of every method calls the `walk_*` function of the node, that visits its children in the
+//! order that they are written. An analysis overrides the methods of the nodes that it cares about
+//! and calls the `walk_*` function where the children should be visited, so the code before the call
+//! runs when the node is entered and the code after it runs when the node is left.
+//!
+//! The `walk_*` functions match on every variant and destructure every field, so a new node or a
+//! new field does not compile until it's handled here. [VisitorMut] is the same traversal with
+//! mutable references, to rewrite the tree in place.
+
+use crate::r#abstract::*;
+
+/// A traversal of the abstract tree with shared references.
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_use_decl(&mut self, _decl: &UseDecl) {}
+
+    fn visit_let_decl(&mut self, decl: &LetDecl) {
+        walk_let_decl(self, decl)
+    }
+
+    fn visit_let_signature(&mut self, signature: &LetSignature) {
+        walk_let_signature(self, signature)
+    }
+
+    fn visit_let_binder(&mut self, binder: &LetBinder) {
+        walk_let_binder(self, binder)
+    }
+
+    fn visit_type_decl(&mut self, decl: &TypeDecl) {
+        walk_type_decl(self, decl)
+    }
+
+    fn visit_constructor(&mut self, constructor: &Constructor) {
+        walk_constructor(self, constructor)
+    }
+
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        walk_module_decl(self, decl)
+    }
+
+    fn visit_trait_decl(&mut self, decl: &TraitDecl) {
+        walk_trait_decl(self, decl)
+    }
+
+    fn visit_trait_impl(&mut self, decl: &TraitImpl) {
+        walk_trait_impl(self, decl)
+    }
+
+    fn visit_ext_decl(&mut self, decl: &ExtDecl) {
+        walk_ext_decl(self, decl)
+    }
+
+    fn visit_type_binder(&mut self, binder: &TypeBinder) {
+        walk_type_binder(self, binder)
+    }
+
+    fn visit_kind(&mut self, kind: &Kind) {
+        walk_kind(self, kind)
+    }
+
+    fn visit_type(&mut self, typ: &Type) {
+        walk_type(self, typ)
+    }
+
+    fn visit_literal(&mut self, _literal: &Literal) {}
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_sttm(&mut self, sttm: &Sttm) {
+        walk_sttm(self, sttm)
+    }
+
+    fn visit_pattern_arm(&mut self, arm: &PatternArm) {
+        walk_pattern_arm(self, arm)
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    let Program {
+        name: _,
+        uses,
+        lets,
+        types,
+        modules,
+        traits,
+        impls,
+        externals,
+        commands: _,
+        next: _,
+    } = program;
+
+    for decl in uses {
+        visitor.visit_use_decl(decl);
+    }
+
+    for decl in types {
+        visitor.visit_type_decl(decl);
+    }
+
+    for decl in traits {
+        visitor.visit_trait_decl(decl);
+    }
+
+    for decl in externals {
+        visitor.visit_ext_decl(decl);
+    }
+
+    for decl in lets {
+        visitor.visit_let_decl(decl);
+    }
+
+    for decl in impls {
+        visitor.visit_trait_impl(decl);
+    }
+
+    for decl in modules {
+        visitor.visit_module_decl(decl);
+    }
+}
+
+pub fn walk_let_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &LetDecl) {
+    let LetDecl {
+        id: _,
+        signature,
+        docs: _,
+        attributes: _,
+        body,
+        constant: _,
+    } = decl;
+
+    visitor.visit_let_signature(signature);
+
+    for arm in body {
+        visitor.visit_pattern_arm(arm);
+    }
+}
+
+pub fn walk_let_signature<V: Visitor + ?Sized>(visitor: &mut V, signature: &LetSignature) {
+    let LetSignature {
+        span: _,
+        visibility: _,
+        name: _,
+        binders,
+        ret,
+    } = signature;
+
+    for binder in binders {
+        visitor.visit_let_binder(binder);
+    }
+
+    if let Some(ret) = ret {
+        visitor.visit_type(ret);
+    }
+}
+
+pub fn walk_let_binder<V: Visitor + ?Sized>(visitor: &mut V, binder: &LetBinder) {
+    match binder {
+        LetBinder::Param(Binder { pat, typ }) => {
+            visitor.visit_pattern(pat);
+            visitor.visit_type(typ);
+        }
+        LetBinder::Trait(typ) => visitor.visit_type(typ),
+    }
+}
+
+pub fn walk_type_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &TypeDecl) {
+    let TypeDecl {
+        id: _,
+        visibility: _,
+        name: _,
+        docs: _,
+        attributes: _,
+        namespace: _,
+        binders,
+        def,
+        derives: _,
+        span: _,
+    } = decl;
+
+    for binder in binders {
+        visitor.visit_type_binder(binder);
+    }
+
+    match def {
+        TypeDef::Sum(SumDecl { constructors }) => {
+            for constructor in constructors {
+                visitor.visit_constructor(constructor);
+            }
+        }
+        TypeDef::Record(RecordDecl { fields }) => {
+            for (_, typ, _, _, _) in fields {
+                visitor.visit_type(typ);
+            }
+        }
+        TypeDef::Synonym(typ) => visitor.visit_type(typ),
+        TypeDef::Abstract => (),
+    }
+}
+
+pub fn walk_constructor<V: Visitor + ?Sized>(visitor: &mut V, constructor: &Constructor) {
+    let Constructor {
+        visibility: _,
+        name: _,
+        docs: _,
+        attributes: _,
+        args,
+        labels: _,
+        typ,
+        span: _,
+    } = constructor;
+
+    for arg in args {
+        visitor.visit_type(arg);
+    }
+
+    if let Some(typ) = typ {
+        visitor.visit_type(typ);
+    }
+}
+
+pub fn walk_module_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &ModuleDecl) {
+    let ModuleDecl {
+        id: _,
+        visibility: _,
+        name: _,
+        decls,
+        span: _,
+    } = decl;
+
+    if let Some(program) = decls {
+        visitor.visit_program(program);
+    }
+}
+
+pub fn walk_trait_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &TraitDecl) {
+    let TraitDecl {
+        id: _,
+        name: _,
+        supers,
+        namespace: _,
+        binders,
+        body,
+        span: _,
+    } = decl;
+
+    for binder in binders {
+        visitor.visit_type_binder(binder);
+    }
+
+    for typ in supers {
+        visitor.visit_type(typ);
+    }
+
+    for signature in body {
+        visitor.visit_let_signature(signature);
+    }
+}
+
+pub fn walk_trait_impl<V: Visitor + ?Sized>(visitor: &mut V, decl: &TraitImpl) {
+    let TraitImpl {
+        id: _,
+        name: _,
+        binders,
+        body,
+    } = decl;
+
+    for typ in binders {
+        visitor.visit_type(typ);
+    }
+
+    for decl in body {
+        visitor.visit_let_decl(decl);
+    }
+}
+
+pub fn walk_ext_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &ExtDecl) {
+    let ExtDecl {
+        id: _,
+        name: _,
+        visibility: _,
+        namespace: _,
+        typ,
+        ret: _,
+        span: _,
+    } = decl;
+
+    visitor.visit_type(typ);
+}
+
+pub fn walk_type_binder<V: Visitor + ?Sized>(visitor: &mut V, binder: &TypeBinder) {
+    match binder {
+        TypeBinder::Implicit(_) => (),
+        TypeBinder::Explicit(_, kind) => visitor.visit_kind(kind),
+    }
+}
+
+pub fn walk_kind<V: Visitor + ?Sized>(visitor: &mut V, kind: &Kind) {
+    match &kind.data {
+        KindType::Arrow(left, right) => {
+            visitor.visit_kind(left);
+            visitor.visit_kind(right);
+        }
+        KindType::Star | KindType::Constraint | KindType::Error => (),
+    }
+}
+
+pub fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, typ: &Node<TypeKind>) {
+    match &typ.data {
+        TypeKind::Arrow(PiType { left, right }) => {
+            visitor.visit_type(left);
+            visitor.visit_type(right);
+        }
+        TypeKind::Tuple(types) => {
+            for typ in types {
+                visitor.visit_type(typ);
+            }
+        }
+        TypeKind::Application(TypeApplication { func, args }) => {
+            visitor.visit_type(func);
+
+            for arg in args {
+                visitor.visit_type(arg);
+            }
+        }
+        TypeKind::Forall(TypeForall { params, body }) => {
+            for param in params {
+                visitor.visit_type_binder(param);
+            }
+
+            visitor.visit_type(body);
+        }
+        TypeKind::TypeVariable(_) | TypeKind::Type(_) | TypeKind::Unit | TypeKind::Error => (),
+    }
+}
+
+pub fn walk_pattern<V: Visitor + ?Sized>(visitor: &mut V, pattern: &Pattern) {
+    match &pattern.data {
+        PatternKind::Literal(literal) => visitor.visit_literal(literal),
+        PatternKind::Tuple(patterns) => {
+            for pattern in patterns {
+                visitor.visit_pattern(pattern);
+            }
+        }
+        PatternKind::Ascription(PatAscription { pat, typ }) => {
+            visitor.visit_pattern(pat);
+            visitor.visit_type(typ);
+        }
+        PatternKind::Or(PatOr { left, right }) => {
+            visitor.visit_pattern(left);
+            visitor.visit_pattern(right);
+        }
+        PatternKind::Application(PatApplication { func: _, args }) => {
+            for arg in args {
+                visitor.visit_pattern(arg);
+            }
+        }
+        PatternKind::Labeled(PatLabeled { func: _, fields }) => {
+            for (_, _, pattern) in fields {
+                visitor.visit_pattern(pattern);
+            }
+        }
+        PatternKind::Wildcard | PatternKind::Variable(_) | PatternKind::Error => (),
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Node<ExprKind>) {
+    match &expr.data {
+        ExprKind::Lambda(LambdaExpr { param, body }) => {
+            visitor.visit_pattern(param);
+            visitor.visit_expr(body);
+        }
+        ExprKind::Application(ApplicationExpr { app: _, func, args }) => {
+            visitor.visit_expr(func);
+
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprKind::Projection(ProjectionExpr { expr, field: _ }) => visitor.visit_expr(expr),
+        ExprKind::TupleProjection(TupleProjectionExpr { expr, index: _ }) => {
+            visitor.visit_expr(expr)
+        }
+        ExprKind::Let(LetExpr {
+            pattern,
+            typ,
+            body,
+            value,
+            recursive: _,
+            group,
+        }) => {
+            visitor.visit_expr(body);
+            visitor.visit_pattern(pattern);
+
+            if let Some(typ) = typ {
+                visitor.visit_type(typ);
+            }
+
+            for (pattern, body) in group {
+                visitor.visit_pattern(pattern);
+                visitor.visit_expr(body);
+            }
+
+            visitor.visit_expr(value);
+        }
+        ExprKind::When(WhenExpr { scrutinee, arms }) => {
+            for expr in scrutinee {
+                visitor.visit_expr(expr);
+            }
+
+            for arm in arms {
+                visitor.visit_pattern_arm(arm);
+            }
+        }
+        ExprKind::Do(Block { sttms }) => {
+            for sttm in sttms {
+                visitor.visit_sttm(sttm);
+            }
+        }
+        ExprKind::Literal(literal) => visitor.visit_literal(literal),
+        ExprKind::Annotation(AnnotationExpr { expr, typ }) => {
+            visitor.visit_expr(expr);
+            visitor.visit_type(typ);
+        }
+        ExprKind::TypeApplication(TypeApplicationExpr { expr, typ }) => {
+            visitor.visit_expr(expr);
+            visitor.visit_type(typ);
+        }
+        ExprKind::RecordInstance(RecordInstance { name: _, fields })
+        | ExprKind::Labeled(RecordInstance { name: _, fields }) => {
+            for (_, _, expr) in fields {
+                visitor.visit_expr(expr);
+            }
+        }
+        ExprKind::RecordUpdate(RecordUpdate { expr, fields }) => {
+            visitor.visit_expr(expr);
+
+            for (_, _, expr) in fields {
+                visitor.visit_expr(expr);
+            }
+        }
+        ExprKind::Tuple(Tuple { exprs }) => {
+            for expr in exprs {
+                visitor.visit_expr(expr);
+            }
+        }
+        ExprKind::Variable(_)
+        | ExprKind::Constructor(_)
+        | ExprKind::Function(_)
+        | ExprKind::Hole(_)
+        | ExprKind::Error => (),
+    }
+}
+
+pub fn walk_sttm<V: Visitor + ?Sized>(visitor: &mut V, sttm: &Sttm) {
+    match &sttm.data {
+        SttmKind::Let(LetSttm { pat, typ, expr }) => {
+            visitor.visit_pattern(pat);
+
+            if let Some(typ) = typ {
+                visitor.visit_type(typ);
+            }
+
+            visitor.visit_expr(expr);
+        }
+        SttmKind::Expr(expr) => visitor.visit_expr(expr),
+        SttmKind::Error => (),
+    }
+}
+
+pub fn walk_pattern_arm<V: Visitor + ?Sized>(visitor: &mut V, arm: &PatternArm) {
+    let PatternArm {
+        patterns,
+        expr,
+        guard,
+    } = arm;
+
+    for pattern in patterns {
+        visitor.visit_pattern(pattern);
+    }
+
+    if let Some(guard) = guard {
+        visitor.visit_expr(guard);
+    }
+
+    visitor.visit_expr(expr);
+}
+
+/// A traversal of the abstract tree with mutable references.
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program)
+    }
+
+    fn visit_use_decl_mut(&mut self, _decl: &mut UseDecl) {}
+
+    fn visit_let_decl_mut(&mut self, decl: &mut LetDecl) {
+        walk_let_decl_mut(self, decl)
+    }
+
+    fn visit_let_signature_mut(&mut self, signature: &mut LetSignature) {
+        walk_let_signature_mut(self, signature)
+    }
+
+    fn visit_let_binder_mut(&mut self, binder: &mut LetBinder) {
+        walk_let_binder_mut(self, binder)
+    }
+
+    fn visit_type_decl_mut(&mut self, decl: &mut TypeDecl) {
+        walk_type_decl_mut(self, decl)
+    }
+
+    fn visit_constructor_mut(&mut self, constructor: &mut Constructor) {
+        walk_constructor_mut(self, constructor)
+    }
+
+    fn visit_module_decl_mut(&mut self, decl: &mut ModuleDecl) {
+        walk_module_decl_mut(self, decl)
+    }
+
+    fn visit_trait_decl_mut(&mut self, decl: &mut TraitDecl) {
+        walk_trait_decl_mut(self, decl)
+    }
+
+    fn visit_trait_impl_mut(&mut self, decl: &mut TraitImpl) {
+        walk_trait_impl_mut(self, decl)
+    }
+
+    fn visit_ext_decl_mut(&mut self, decl: &mut ExtDecl) {
+        walk_ext_decl_mut(self, decl)
+    }
+
+    fn visit_type_binder_mut(&mut self, binder: &mut TypeBinder) {
+        walk_type_binder_mut(self, binder)
+    }
+
+    fn visit_kind_mut(&mut self, kind: &mut Kind) {
+        walk_kind_mut(self, kind)
+    }
+
+    fn visit_type_mut(&mut self, typ: &mut Type) {
+        walk_type_mut(self, typ)
+    }
+
+    fn visit_literal_mut(&mut self, _literal: &mut Literal) {}
+
+    fn visit_pattern_mut(&mut self, pattern: &mut Pattern) {
+        walk_pattern_mut(self, pattern)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr)
+    }
+
+    fn visit_sttm_mut(&mut self, sttm: &mut Sttm) {
+        walk_sttm_mut(self, sttm)
+    }
+
+    fn visit_pattern_arm_mut(&mut self, arm: &mut PatternArm) {
+        walk_pattern_arm_mut(self, arm)
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    let Program {
+        name: _,
+        uses,
+        lets,
+        types,
+        modules,
+        traits,
+        impls,
+        externals,
+        commands: _,
+        next: _,
+    } = program;
+
+    for decl in uses {
+        visitor.visit_use_decl_mut(decl);
+    }
+
+    for decl in types {
+        visitor.visit_type_decl_mut(decl);
+    }
+
+    for decl in traits {
+        visitor.visit_trait_decl_mut(decl);
+    }
+
+    for decl in externals {
+        visitor.visit_ext_decl_mut(decl);
+    }
+
+    for decl in lets {
+        visitor.visit_let_decl_mut(decl);
+    }
+
+    for decl in impls {
+        visitor.visit_trait_impl_mut(decl);
+    }
+
+    for decl in modules {
+        visitor.visit_module_decl_mut(decl);
+    }
+}
+
+pub fn walk_let_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut LetDecl) {
+    let LetDecl {
+        id: _,
+        signature,
+        docs: _,
+        attributes: _,
+        body,
+        constant: _,
+    } = decl;
+
+    visitor.visit_let_signature_mut(signature);
+
+    for arm in body {
+        visitor.visit_pattern_arm_mut(arm);
+    }
+}
+
+pub fn walk_let_signature_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    signature: &mut LetSignature,
+) {
+    let LetSignature {
+        span: _,
+        visibility: _,
+        name: _,
+        binders,
+        ret,
+    } = signature;
+
+    for binder in binders {
+        visitor.visit_let_binder_mut(binder);
+    }
+
+    if let Some(ret) = ret {
+        visitor.visit_type_mut(ret);
+    }
+}
+
+pub fn walk_let_binder_mut<V: VisitorMut + ?Sized>(visitor: &mut V, binder: &mut LetBinder) {
+    match binder {
+        LetBinder::Param(Binder { pat, typ }) => {
+            visitor.visit_pattern_mut(pat);
+            visitor.visit_type_mut(typ);
+        }
+        LetBinder::Trait(typ) => visitor.visit_type_mut(typ),
+    }
+}
+
+pub fn walk_type_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut TypeDecl) {
+    let TypeDecl {
+        id: _,
+        visibility: _,
+        name: _,
+        docs: _,
+        attributes: _,
+        namespace: _,
+        binders,
+        def,
+        derives: _,
+        span: _,
+    } = decl;
+
+    for binder in binders {
+        visitor.visit_type_binder_mut(binder);
+    }
+
+    match def {
+        TypeDef::Sum(SumDecl { constructors }) => {
+            for constructor in constructors {
+                visitor.visit_constructor_mut(constructor);
+            }
+        }
+        TypeDef::Record(RecordDecl { fields }) => {
+            for (_, typ, _, _, _) in fields {
+                visitor.visit_type_mut(typ);
+            }
+        }
+        TypeDef::Synonym(typ) => visitor.visit_type_mut(typ),
+        TypeDef::Abstract => (),
+    }
+}
+
+pub fn walk_constructor_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    constructor: &mut Constructor,
+) {
+    let Constructor {
+        visibility: _,
+        name: _,
+        docs: _,
+        attributes: _,
+        args,
+        labels: _,
+        typ,
+        span: _,
+    } = constructor;
+
+    for arg in args {
+        visitor.visit_type_mut(arg);
+    }
+
+    if let Some(typ) = typ {
+        visitor.visit_type_mut(typ);
+    }
+}
+
+pub fn walk_module_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut ModuleDecl) {
+    let ModuleDecl {
+        id: _,
+        visibility: _,
+        name: _,
+        decls,
+        span: _,
+    } = decl;
+
+    if let Some(program) = decls {
+        visitor.visit_program_mut(program);
+    }
+}
+
+pub fn walk_trait_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut TraitDecl) {
+    let TraitDecl {
+        id: _,
+        name: _,
+        supers,
+        namespace: _,
+        binders,
+        body,
+        span: _,
+    } = decl;
+
+    for binder in binders {
+        visitor.visit_type_binder_mut(binder);
+    }
+
+    for typ in supers {
+        visitor.visit_type_mut(typ);
+    }
+
+    for signature in body {
+        visitor.visit_let_signature_mut(signature);
+    }
+}
+
+pub fn walk_trait_impl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut TraitImpl) {
+    let TraitImpl {
+        id: _,
+        name: _,
+        binders,
+        body,
+    } = decl;
+
+    for typ in binders {
+        visitor.visit_type_mut(typ);
+    }
+
+    for decl in body {
+        visitor.visit_let_decl_mut(decl);
+    }
+}
+
+pub fn walk_ext_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut ExtDecl) {
+    let ExtDecl {
+        id: _,
+        name: _,
+        visibility: _,
+        namespace: _,
+        typ,
+        ret: _,
+        span: _,
+    } = decl;
+
+    visitor.visit_type_mut(typ);
+}
+
+pub fn walk_type_binder_mut<V: VisitorMut + ?Sized>(visitor: &mut V, binder: &mut TypeBinder) {
+    match binder {
+        TypeBinder::Implicit(_) => (),
+        TypeBinder::Explicit(_, kind) => visitor.visit_kind_mut(kind),
+    }
+}
+
+pub fn walk_kind_mut<V: VisitorMut + ?Sized>(visitor: &mut V, kind: &mut Kind) {
+    match &mut kind.data {
+        KindType::Arrow(left, right) => {
+            visitor.visit_kind_mut(left);
+            visitor.visit_kind_mut(right);
+        }
+        KindType::Star | KindType::Constraint | KindType::Error => (),
+    }
+}
+
+pub fn walk_type_mut<V: VisitorMut + ?Sized>(visitor: &mut V, typ: &mut Type) {
+    match &mut typ.data {
+        TypeKind::Arrow(PiType { left, right }) => {
+            visitor.visit_type_mut(left);
+            visitor.visit_type_mut(right);
+        }
+        TypeKind::Tuple(types) => {
+            for typ in types {
+                visitor.visit_type_mut(typ);
+            }
+        }
+        TypeKind::Application(TypeApplication { func, args }) => {
+            visitor.visit_type_mut(func);
+
+            for arg in args {
+                visitor.visit_type_mut(arg);
+            }
+        }
+        TypeKind::Forall(TypeForall { params, body }) => {
+            for param in params {
+                visitor.visit_type_binder_mut(param);
+            }
+
+            visitor.visit_type_mut(body);
+        }
+        TypeKind::TypeVariable(_) | TypeKind::Type(_) | TypeKind::Unit | TypeKind::Error => (),
+    }
+}
+
+pub fn walk_pattern_mut<V: VisitorMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    match &mut pattern.data {
+        PatternKind::Literal(literal) => visitor.visit_literal_mut(literal),
+        PatternKind::Tuple(patterns) => {
+            for pattern in patterns {
+                visitor.visit_pattern_mut(pattern);
+            }
+        }
+        PatternKind::Ascription(PatAscription { pat, typ }) => {
+            visitor.visit_pattern_mut(pat);
+            visitor.visit_type_mut(typ);
+        }
+        PatternKind::Or(PatOr { left, right }) => {
+            visitor.visit_pattern_mut(left);
+            visitor.visit_pattern_mut(right);
+        }
+        PatternKind::Application(PatApplication { func: _, args }) => {
+            for arg in args {
+                visitor.visit_pattern_mut(arg);
+            }
+        }
+        PatternKind::Labeled(PatLabeled { func: _, fields }) => {
+            for (_, _, pattern) in fields {
+                visitor.visit_pattern_mut(pattern);
+            }
+        }
+        PatternKind::Wildcard | PatternKind::Variable(_) | PatternKind::Error => (),
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match &mut expr.data {
+        ExprKind::Lambda(LambdaExpr { param, body }) => {
+            visitor.visit_pattern_mut(param);
+            visitor.visit_expr_mut(body);
+        }
+        ExprKind::Application(ApplicationExpr { app: _, func, args }) => {
+            visitor.visit_expr_mut(func);
+
+            for arg in args {
+                visitor.visit_expr_mut(arg);
+            }
+        }
+        ExprKind::Projection(ProjectionExpr { expr, field: _ }) => visitor.visit_expr_mut(expr),
+        ExprKind::TupleProjection(TupleProjectionExpr { expr, index: _ }) => {
+            visitor.visit_expr_mut(expr)
+        }
+        ExprKind::Let(LetExpr {
+            pattern,
+            typ,
+            body,
+            value,
+            recursive: _,
+            group,
+        }) => {
+            visitor.visit_expr_mut(body);
+            visitor.visit_pattern_mut(pattern);
+
+            if let Some(typ) = typ {
+                visitor.visit_type_mut(typ);
+            }
+
+            for (pattern, body) in group {
+                visitor.visit_pattern_mut(pattern);
+                visitor.visit_expr_mut(body);
+            }
+
+            visitor.visit_expr_mut(value);
+        }
+        ExprKind::When(WhenExpr { scrutinee, arms }) => {
+            for expr in scrutinee {
+                visitor.visit_expr_mut(expr);
+            }
+
+            for arm in arms {
+                visitor.visit_pattern_arm_mut(arm);
+            }
+        }
+        ExprKind::Do(Block { sttms }) => {
+            for sttm in sttms {
+                visitor.visit_sttm_mut(sttm);
+            }
+        }
+        ExprKind::Literal(literal) => visitor.visit_literal_mut(literal),
+        ExprKind::Annotation(AnnotationExpr { expr, typ }) => {
+            visitor.visit_expr_mut(expr);
+            visitor.visit_type_mut(typ);
+        }
+        ExprKind::TypeApplication(TypeApplicationExpr { expr, typ }) => {
+            visitor.visit_expr_mut(expr);
+            visitor.visit_type_mut(typ);
+        }
+        ExprKind::RecordInstance(RecordInstance { name: _, fields })
+        | ExprKind::Labeled(RecordInstance { name: _, fields }) => {
+            for (_, _, expr) in fields {
+                visitor.visit_expr_mut(expr);
+            }
+        }
+        ExprKind::RecordUpdate(RecordUpdate { expr, fields }) => {
+            visitor.visit_expr_mut(expr);
+
+            for (_, _, expr) in fields {
+                visitor.visit_expr_mut(expr);
+            }
+        }
+        ExprKind::Tuple(Tuple { exprs }) => {
+            for expr in exprs {
+                visitor.visit_expr_mut(expr);
+            }
+        }
+        ExprKind::Variable(_)
+        | ExprKind::Constructor(_)
+        | ExprKind::Function(_)
+        | ExprKind::Hole(_)
+        | ExprKind::Error => (),
+    }
+}
+
+pub fn walk_sttm_mut<V: VisitorMut + ?Sized>(visitor: &mut V, sttm: &mut Sttm) {
+    match &mut sttm.data {
+        SttmKind::Let(LetSttm { pat, typ, expr }) => {
+            visitor.visit_pattern_mut(pat);
+
+            if let Some(typ) = typ {
+                visitor.visit_type_mut(typ);
+            }
+
+            visitor.visit_expr_mut(expr);
+        }
+        SttmKind::Expr(expr) => visitor.visit_expr_mut(expr),
+        SttmKind::Error => (),
+    }
+}
+
+pub fn walk_pattern_arm_mut<V: VisitorMut + ?Sized>(visitor: &mut V, arm: &mut PatternArm) {
+    let PatternArm {
+        patterns,
+        expr,
+        guard,
+    } = arm;
+
+    for pattern in patterns {
+        visitor.visit_pattern_mut(pattern);
+    }
+
+    if let Some(guard) = guard {
+        visitor.visit_expr_mut(guard);
+    }
+
+    visitor.visit_expr_mut(expr);
+}
@@ -0,0 +1,110 @@
+//! The attributes of the declarations, like `@deprecated("use bar")` or `@keep`. The parser takes
+//! any name with literal arguments, and the type checker warns about the names that it does not
+//! know and reports the ones that are given the wrong number of arguments.
+//!
+//! The uses of deprecated items are found in the same places where names are recorded for
+//! go to definition, so [Context::refer] records the name and warns if its item is deprecated.
+
+use vulpi_intern::{well_known, Symbol};
+use vulpi_location::Span;
+use vulpi_report::Diagnostic;
+use vulpi_syntax::r#abstract::{Attribute, LiteralKind, Qualified};
+
+use crate::{
+    context::Context,
+    errors::{TypeError, TypeErrorKind},
+    resolution::ItemKind,
+};
+
+/// The attributes that the compiler knows, with the number of arguments that each one takes.
+const KNOWN: &[(Symbol, usize)] = &[(well_known::DEPRECATED, 1), (well_known::KEEP, 0)];
+
+impl Context {
+    fn report_attribute(&mut self, span: Span, kind: TypeErrorKind) {
+        self.reporter.report(Diagnostic::new(TypeError {
+            span,
+            kind,
+            budget: self.budget,
+        }));
+    }
+
+    /// Checks the names and the number of arguments of the attributes of a declaration, and marks
+    /// the item as deprecated if it has `@deprecated`.
+    pub(crate) fn check_attributes(
+        &mut self,
+        kind: ItemKind,
+        name: &Qualified,
+        attributes: &[Attribute],
+    ) {
+        if let Some(message) = deprecation(attributes) {
+            self.resolutions.deprecate(kind, name, message);
+        }
+
+        for attribute in attributes {
+            let Some((_, arity)) = KNOWN.iter().find(|(name, _)| *name == attribute.name) else {
+                let kind = TypeErrorKind::UnknownAttribute(attribute.name.clone());
+                self.report_attribute(attribute.span.clone(), kind);
+                continue;
+            };
+
+            if attribute.args.len() != *arity {
+                self.errored = true;
+
+                let kind = TypeErrorKind::AttributeArity {
+                    name: attribute.name.clone(),
+                    expected: *arity,
+                    found: attribute.args.len(),
+                };
+
+                self.report_attribute(attribute.span.clone(), kind);
+            }
+        }
+    }
+
+    /// Starts checking an item with the given attributes, and gives back if the previous item was
+    /// deprecated so it can be restored after.
+    pub(crate) fn enter_item(&mut self, attributes: &[Attribute]) -> bool {
+        let deprecated = deprecation(attributes).is_some();
+        std::mem::replace(&mut self.in_deprecated, deprecated)
+    }
+
+    /// Records a name that refers to an item, and warns if the item is deprecated. Deprecated
+    /// items can use each other without warnings.
+    pub(crate) fn refer(&mut self, span: Span, kind: ItemKind, name: &Qualified) {
+        self.resolutions.refer(span.clone(), kind, name);
+
+        if self.in_deprecated {
+            return;
+        }
+
+        let Some((message, site)) = self.resolutions.deprecation(kind, name) else {
+            return;
+        };
+
+        let site = site.map(|site| site.span.clone());
+
+        let diagnostic = Diagnostic::new(TypeError {
+            span,
+            kind: TypeErrorKind::Deprecated(name.name.clone(), message),
+            budget: self.budget,
+        });
+
+        self.reporter.report(match site {
+            Some(site) => diagnostic.with_label(site, "deprecated here"),
+            None => diagnostic,
+        });
+    }
+}
+
+/// The message of the `@deprecated` attribute, if the item has it. The message is missing if the
+/// argument of the attribute is not a string.
+fn deprecation(attributes: &[Attribute]) -> Option<Option<Symbol>> {
+    let attribute = attributes
+        .iter()
+        .find(|attribute| attribute.name == well_known::DEPRECATED)?;
+
+    Some(attribute.args.first().and_then(|arg| match &arg.data {
+        LiteralKind::String(message) => Some(message.clone()),
+        _ => None,
+    }))
+}
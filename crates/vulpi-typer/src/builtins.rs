@@ -0,0 +1,83 @@
+//! The types that the language needs to know about, like the type of the literals and of the
+//! guards. All of them but the unit type are defined by the prelude, so they are looked up there
+//! once and kept in [Builtins] for the rest of the checking.
+
+use vulpi_intern::{well_known, Symbol};
+use vulpi_syntax::r#abstract::Qualified;
+
+use crate::{intern::Interner, module::Modules, r#virtual::Virtual, Type};
+
+/// A type that is defined by the prelude and used by the type checker.
+#[derive(Clone, Copy)]
+pub enum Builtin {
+    Unit,
+    Bool,
+    Int,
+    Float,
+    String,
+    Char,
+}
+
+impl Builtin {
+    pub fn name(&self) -> Symbol {
+        match self {
+            Builtin::Unit => well_known::UNIT,
+            Builtin::Bool => well_known::BOOL,
+            Builtin::Int => well_known::INT,
+            Builtin::Float => well_known::FLOAT,
+            Builtin::String => well_known::STRING,
+            Builtin::Char => well_known::CHAR,
+        }
+    }
+}
+
+/// The builtin types that were found in the prelude.
+#[derive(Clone)]
+pub struct Builtins {
+    /// The unit type is the empty tuple, so every prelude has it.
+    pub unit: Type<Virtual>,
+    pub bool: Option<Type<Virtual>>,
+    pub int: Option<Type<Virtual>>,
+    pub float: Option<Type<Virtual>>,
+    pub string: Option<Type<Virtual>>,
+    pub char: Option<Type<Virtual>>,
+}
+
+impl Builtins {
+    /// Looks up the builtin types in the module of the prelude. They have to be declared already.
+    pub fn resolve(modules: &Modules, interner: &mut Interner, prelude: &Symbol) -> Self {
+        let unit = interner.tuple(Vec::new());
+
+        let mut find = |builtin: Builtin| {
+            let name = builtin.name();
+            let module = modules.get(prelude)?;
+
+            module.types.contains_key(&name).then(|| {
+                interner.variable(Qualified {
+                    path: prelude.clone(),
+                    name,
+                })
+            })
+        };
+
+        Self {
+            unit,
+            bool: find(Builtin::Bool),
+            int: find(Builtin::Int),
+            float: find(Builtin::Float),
+            string: find(Builtin::String),
+            char: find(Builtin::Char),
+        }
+    }
+
+    pub fn get(&self, builtin: Builtin) -> Option<&Type<Virtual>> {
+        match builtin {
+            Builtin::Unit => Some(&self.unit),
+            Builtin::Bool => self.bool.as_ref(),
+            Builtin::Int => self.int.as_ref(),
+            Builtin::Float => self.float.as_ref(),
+            Builtin::String => self.string.as_ref(),
+            Builtin::Char => self.char.as_ref(),
+        }
+    }
+}
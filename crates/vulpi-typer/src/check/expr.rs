@@ -3,7 +3,13 @@
 use vulpi_location::Spanned;
 use vulpi_syntax::{elaborated, r#abstract::Expr, r#abstract::ExprKind, r#abstract::Sttm};
 
-use crate::{context::Context, real::Real, Env, Type, TypeKind, Virtual};
+use crate::{
+    context::{Context, Expectation},
+    coverage::{Problem, Witness},
+    errors::{ArityOrigin, TypeErrorKind},
+    real::Real,
+    unused, Env, Type, TypeKind, Virtual,
+};
 
 use super::Check;
 use crate::infer::Infer;
@@ -19,6 +25,13 @@ impl Check for Expr {
         (ctx, mut env): Self::Context<'_>,
     ) -> Self::Return {
         env.set_current_span(self.span.clone());
+        env.depth += 1;
+
+        ctx.completions.scope(self.span.clone(), &env);
+
+        if ctx.exceeds_limit(&env) {
+            return Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone());
+        }
 
         let elem = match (&self.data, typ.deref().as_ref()) {
             (ExprKind::Do(block), _) => {
@@ -30,7 +43,8 @@ impl Check for Expr {
                         let (elab, new_env) = if is_last {
                             stmt.check(typ.clone(), (ctx, env.clone()))
                         } else {
-                            let (_, new_env, elab) = stmt.infer((ctx, &mut env.clone()));
+                            let (_, new_env, elab) = ctx
+                                .with_annotation(None, |ctx| stmt.infer((ctx, &mut env.clone())));
                             (elab, new_env)
                         };
 
@@ -40,8 +54,14 @@ impl Check for Expr {
                     }
                 }
 
+                ctx.report_unused(&unused::block_binders(block));
+
                 Box::new(elaborated::ExprKind::Do(stmts))
             }
+            (ExprKind::Hole(name), _) => {
+                ctx.typed_hole(&env, name.clone(), typ.clone());
+                Box::new(elaborated::ExprKind::Error)
+            }
             (_, TypeKind::Forall(l)) => {
                 let lvl_ty = Type::new(TypeKind::Bound(env.level));
                 self.check(
@@ -50,9 +70,127 @@ impl Check for Expr {
                 )
                 .data
             }
+            (ExprKind::Lambda(lam), TypeKind::Arrow(pi)) => {
+                // The parameter takes the type of the domain, so the errors in the body talk about
+                // the type that the context expects instead of about a hole.
+                let mut hashmap = Default::default();
+                let elab_pat = lam
+                    .param
+                    .check(pi.typ.clone(), (ctx, &mut hashmap, env.clone()));
+
+                for binding in hashmap {
+                    env.add_var(binding.0, binding.1)
+                }
+
+                let mut bound = Vec::new();
+                unused::binders(&lam.param, &mut bound);
+                ctx.add_binders(&mut env, &bound);
+
+                let elab_body = lam.body.check(pi.body.clone(), (ctx, env.clone()));
+                ctx.report_unused(&bound);
+
+                Box::new(elaborated::ExprKind::Lambda(elaborated::LambdaExpr {
+                    param: elab_pat,
+                    body: elab_body,
+                }))
+            }
+            (ExprKind::Tuple(tuple), TypeKind::Tuple(types))
+                if tuple.exprs.len() == types.len() =>
+            {
+                let exprs = tuple
+                    .exprs
+                    .iter()
+                    .zip(types.iter())
+                    .map(|(expr, typ)| expr.check(typ.clone(), (ctx, env.clone())))
+                    .collect();
+
+                Box::new(elaborated::ExprKind::Tuple(elaborated::Tuple { exprs }))
+            }
+            (ExprKind::When(when), _) => {
+                ctx.errored = false;
+
+                let mut types = Vec::new();
+                let mut elab_scrutinee = Vec::new();
+
+                for scrutinee in &when.scrutinee {
+                    let (typ, elab) =
+                        ctx.with_annotation(None, |ctx| scrutinee.infer((ctx, env.clone())));
+                    types.push(typ);
+                    elab_scrutinee.push(elab);
+                }
+
+                // Every arm is checked against the expected type, so each body is compared with
+                // it instead of with the body of the first arm.
+                let arms_type = Type::<Virtual>::function(types.clone(), typ.clone());
+
+                let scrutinees = when
+                    .scrutinee
+                    .first()
+                    .zip(when.scrutinee.last())
+                    .and_then(|(first, last)| first.span.merge(&last.span))
+                    .unwrap_or_else(|| self.span.clone());
+
+                // If the type is not known yet, the first arm decides it and the others are
+                // reported as the ones that differ from it.
+                let undecided = matches!(typ.deref().as_ref(), TypeKind::Hole(_));
+                let mut first = None;
+
+                let mut elab_arms = Vec::new();
+
+                if when.arms.is_empty() {
+                    ctx.report(&env, TypeErrorKind::EmptyCase);
+                }
+
+                for arm in &when.arms {
+                    if arm.patterns.len() != when.scrutinee.len() {
+                        env.set_current_span(arm.patterns_span());
+                        ctx.report(
+                            &env,
+                            TypeErrorKind::WrongArity {
+                                expected: when.scrutinee.len(),
+                                found: arm.patterns.len(),
+                                origin: Some(ArityOrigin::Scrutinees(scrutinees.clone())),
+                            },
+                        );
+                        continue;
+                    }
+
+                    let expectation = first.clone().filter(|_| undecided);
+                    let elab = ctx.with_expectation(expectation, |ctx| {
+                        arm.check(arms_type.clone(), (ctx, env.clone()))
+                    });
+
+                    elab_arms.push(elab);
+
+                    if first.is_none() {
+                        first = Some(Expectation::FirstArm(arm.expr.span.clone(), typ.clone()));
+                    }
+                }
+
+                let mut tree = elaborated::DecisionTree::Fail;
+
+                if !ctx.errored {
+                    let types = types.iter().map(|x| ctx.instantiate(&env, x)).collect();
+                    let problem = Problem::exhaustiveness(&elab_arms, types);
+
+                    if let Witness::NonExhaustive(case) = problem.exaustive(ctx, env.clone()) {
+                        env.set_current_span(self.span.clone());
+                        ctx.report(&env, TypeErrorKind::NonExhaustive(case));
+                    };
+
+                    tree = ctx.compile_match(&elab_arms);
+                }
+
+                Box::new(elaborated::ExprKind::When(elaborated::WhenExpr {
+                    scrutinee: elab_scrutinee,
+                    arms: elab_arms,
+                    tree,
+                }))
+            }
             _ => {
-                let (expr_ty, elab_expr) = self.infer((ctx, env.clone()));
-                ctx.subsumes(env, expr_ty, typ);
+                let (expr_ty, elab_expr) =
+                    ctx.with_annotation(None, |ctx| self.infer((ctx, env.clone())));
+                ctx.expect(env, typ, expr_ty);
                 elab_expr.data
             }
         };
@@ -69,9 +207,9 @@ impl Check for Sttm {
     fn check(&self, ann_ty: Type<Virtual>, (ctx, env): Self::Context<'_>) -> Self::Return {
         env.set_current_span(self.span.clone());
 
-        let (typ, env, elab) = self.infer((ctx, &mut env.clone()));
+        let (typ, env, elab) = ctx.with_annotation(None, |ctx| self.infer((ctx, &mut env.clone())));
 
-        ctx.subsumes(env.clone(), typ, ann_ty);
+        ctx.expect(env.clone(), ann_ty, typ);
         (elab, env)
     }
 }
@@ -8,7 +8,15 @@ use vulpi_syntax::{
 
 use crate::eval::Quote;
 use crate::infer::Infer;
-use crate::{context::Context, errors::TypeErrorKind, r#virtual::Virtual, real::Real, Env, Type};
+use crate::unused;
+use crate::{
+    builtins::Builtin,
+    context::Context,
+    errors::{ArityOrigin, TypeErrorKind},
+    r#virtual::Virtual,
+    real::Real,
+    Env, Type,
+};
 
 use super::Check;
 
@@ -26,7 +34,10 @@ impl Check for PatternArm {
             env.set_current_span(pat.span.clone());
 
             if let Some((left, right)) = ctx.as_function(&env, typ.clone()) {
-                let elab = pat.check(left, (ctx, &mut map, env.clone()));
+                // The patterns are compared with the values that are matched and not with the
+                // expected type of the body.
+                let elab =
+                    ctx.with_annotation(None, |ctx| pat.check(left, (ctx, &mut map, env.clone())));
                 elaborated_patterns.push(elab);
                 typ = right;
             } else {
@@ -46,17 +57,27 @@ impl Check for PatternArm {
             env.add_var(binding.0, binding.1);
         }
 
+        let mut bound = Vec::new();
+        for pat in &self.patterns {
+            unused::binders(pat, &mut bound);
+        }
+        ctx.add_binders(&mut env, &bound);
+
         let elab_expr = self.expr.check(typ, (ctx, env.clone()));
 
-        let guard = self.guard.as_ref().map(|g| g.infer((ctx, env.clone())));
+        // The guard is compared with Bool and not with the expected type of the body, so it's
+        // checked without the reason of that type.
+        let elab_guard = ctx.with_annotation(None, |ctx| {
+            self.guard.as_ref().map(|guard| {
+                let (typ, elab) = guard.infer((ctx, env.clone()));
+                env.set_current_span(guard.span.clone());
+                let bool = ctx.builtin(Builtin::Bool, &env);
+                ctx.expect(env.clone(), bool, typ);
+                elab
+            })
+        });
 
-        let elab_guard = if let Some((typ, guard)) = guard {
-            let bool = ctx.find_prelude_type("Bool", env.clone());
-            ctx.subsumes(env.clone(), typ, bool);
-            Some(guard)
-        } else {
-            None
-        };
+        ctx.report_unused(&bound);
 
         elaborated::PatternArm {
             patterns: elaborated_patterns,
@@ -85,8 +106,16 @@ impl Check for Vec<PatternArm> {
 
             for pat in self.iter().skip(1) {
                 if pat.patterns.len() != size {
-                    ctx.report(&env, TypeErrorKind::WrongArity(pat.patterns.len(), size));
-                    return vec![];
+                    env.set_current_span(pat.patterns_span());
+                    ctx.report(
+                        &env,
+                        TypeErrorKind::WrongArity {
+                            expected: size,
+                            found: pat.patterns.len(),
+                            origin: Some(ArityOrigin::FirstArm(self[0].patterns_span())),
+                        },
+                    );
+                    continue;
                 }
 
                 let elab_arm = pat.check(typ.clone(), (ctx, env.clone()));
@@ -114,7 +143,7 @@ impl Check for Pattern {
             }
             _ => {
                 let (typ, elab_pat) = self.infer((ctx, map, env.clone()));
-                ctx.subsumes(env, typ, ann_ty);
+                ctx.expect(env, ann_ty, typ);
                 elab_pat
             }
         }
@@ -0,0 +1,217 @@
+//! Completion of names. The environment of every expression is recorded while it's checked, so
+//! after the checking we can list the local variables that are in scope at a position, together
+//! with the top level items that the module of the position can refer to.
+
+use std::collections::HashMap;
+
+use vulpi_intern::Symbol;
+use vulpi_location::{Byte, FileId, Span};
+use vulpi_syntax::r#abstract::{Program, Qualified, TypeDef, Visibility};
+
+use crate::{module::Modules, resolution::ItemKind, Env};
+
+/// A name that can be written at a position, with its type or, for types, its kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub name: String,
+    pub kind: ItemKind,
+    pub typ: String,
+}
+
+/// A top level item of a module. The label is the name that the module itself uses for it.
+struct Item {
+    name: Qualified,
+    label: String,
+    kind: ItemKind,
+    public: bool,
+    typ: String,
+}
+
+/// The module of a file and the modules that it uses, with their aliases.
+struct FileScope {
+    module: Symbol,
+    uses: Vec<(Symbol, Option<Symbol>)>,
+}
+
+/// The scopes of the expressions and the items of the modules that were checked.
+#[derive(Default)]
+pub struct Completions {
+    scopes: Vec<(Span, Env)>,
+    items: HashMap<Symbol, Vec<Item>>,
+    files: HashMap<FileId, FileScope>,
+}
+
+impl Completions {
+    /// Records the environment that an expression is checked in.
+    pub(crate) fn scope(&mut self, span: Span, env: &Env) {
+        self.scopes.push((span, env.clone()));
+    }
+
+    /// Adds the scopes recorded by the completions of some programs that were checked on another
+    /// thread.
+    pub(crate) fn join(&mut self, other: Completions) {
+        self.scopes.extend(other.scopes);
+    }
+
+    /// Records the module of a file, the modules that it uses and its items. The types of the
+    /// items are only known after the file is defined, so they are filled by [Completions::fill].
+    pub(crate) fn add_file(&mut self, file: FileId, program: &Program) {
+        let uses = program
+            .uses
+            .iter()
+            .map(|decl| (decl.path.clone(), decl.alias.clone()))
+            .collect();
+
+        self.files.insert(
+            file,
+            FileScope {
+                module: program.name.clone(),
+                uses,
+            },
+        );
+
+        let public = |visibility: &Visibility| matches!(visibility, Visibility::Public);
+
+        let mut items = Vec::new();
+
+        let mut item = |name: &Qualified, label: String, kind, public| {
+            items.push(Item {
+                name: name.clone(),
+                label,
+                kind,
+                public,
+                typ: String::new(),
+            })
+        };
+
+        for decl in &program.lets {
+            let name = &decl.signature.name;
+            let visibility = &decl.signature.visibility;
+            item(name, name.name.get(), ItemKind::Let, public(visibility));
+        }
+
+        for decl in &program.externals {
+            let name = &decl.name;
+            item(
+                name,
+                name.name.get(),
+                ItemKind::External,
+                public(&decl.visibility),
+            );
+        }
+
+        for decl in &program.types {
+            let name = &decl.name;
+            item(
+                name,
+                name.name.get(),
+                ItemKind::Type,
+                public(&decl.visibility),
+            );
+
+            if let TypeDef::Sum(sum) = &decl.def {
+                for cons in &sum.constructors {
+                    let label = format!("{}.{}", name.name.get(), cons.name.name.get());
+                    item(
+                        &cons.name,
+                        label,
+                        ItemKind::Constructor,
+                        public(&cons.visibility),
+                    );
+                }
+            }
+        }
+
+        self.items
+            .entry(program.name.clone())
+            .or_default()
+            .extend(items);
+    }
+
+    /// Renders the types of the items after all of them were defined.
+    pub(crate) fn fill(&mut self, modules: &Modules) {
+        let env = Env::default();
+
+        for item in self.items.values_mut().flatten() {
+            let Some(module) = modules.get(&item.name.path) else {
+                continue;
+            };
+
+            let name = &item.name.name;
+
+            let typ = match item.kind {
+                ItemKind::Let | ItemKind::External => module
+                    .variables
+                    .get(name)
+                    .map(|def| def.typ.show(&env).to_string()),
+                ItemKind::Constructor => module
+                    .constructors
+                    .get(name)
+                    .map(|cons| cons.typ.show(&env).to_string()),
+                ItemKind::Type | ItemKind::Trait => module
+                    .types
+                    .get(name)
+                    .map(|data| data.kind.show(&env).to_string()),
+                ItemKind::Field | ItemKind::Variable => None,
+            };
+
+            item.typ = typ.unwrap_or_default();
+        }
+    }
+
+    /// The names that can be written at a byte of a file. These are the local variables of the
+    /// innermost expression around the byte, the items of the module of the file and the public
+    /// items of the modules that it uses, prefixed by their aliases. They are sorted by name.
+    pub fn completions_at(&self, file: FileId, byte: usize) -> Vec<Completion> {
+        let mut found = Vec::new();
+
+        let scope = self
+            .scopes
+            .iter()
+            .filter(|(span, _)| span.file == file && span.contains(Byte(byte)))
+            .min_by_key(|(span, _)| span.end.0 - span.start.0);
+
+        if let Some((_, env)) = scope {
+            for (name, typ) in env.vars.iter() {
+                found.push(Completion {
+                    name: name.get(),
+                    kind: ItemKind::Variable,
+                    typ: typ.show(env).to_string(),
+                });
+            }
+        }
+
+        if let Some(scope) = self.files.get(&file) {
+            let items = |module: &Symbol| self.items.get(module).into_iter().flatten();
+
+            for item in items(&scope.module) {
+                found.push(item.completion(None));
+            }
+
+            for (path, alias) in &scope.uses {
+                for item in items(path).filter(|item| item.public) {
+                    found.push(item.completion(alias.as_ref()));
+                }
+            }
+        }
+
+        found.sort_by(|a, b| (&a.name, a.kind, &a.typ).cmp(&(&b.name, b.kind, &b.typ)));
+        found.dedup();
+        found
+    }
+}
+
+impl Item {
+    fn completion(&self, alias: Option<&Symbol>) -> Completion {
+        let name = match alias {
+            Some(alias) => format!("{}.{}", alias.get(), self.label),
+            None => self.label.clone(),
+        };
+
+        Completion {
+            name,
+            kind: self.kind,
+            typ: self.typ.clone(),
+        }
+    }
+}
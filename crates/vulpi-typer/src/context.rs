@@ -1,73 +1,623 @@
 //! This file declares a mutable environment that is useful to keep track of information that does
 //! not need to be immutable like the Env.
 
-use vulpi_intern::Symbol;
-use vulpi_report::{Diagnostic, Report};
-use vulpi_syntax::{elaborated, r#abstract::Qualified};
+use std::collections::HashSet;
+
+use vulpi_intern::{well_known, Symbol};
+use vulpi_location::{FileId, Span};
+use vulpi_report::{hash_reporter, sink::Collector, Diagnostic, Report};
+use vulpi_syntax::{
+    elaborated,
+    r#abstract::{LetDecl, NodeId, Qualified},
+};
 
 use crate::{
+    builtins::{Builtin, Builtins},
+    completion::Completions,
+    elide::TypeBudget,
     errors::{TypeError, TypeErrorKind},
+    eval::{Eval, Quote},
+    hole::Slot,
+    intern::Interner,
     module::Modules,
     r#virtual::Env,
     r#virtual::Pi,
     r#virtual::Virtual,
-    real::Real,
-    HoleInner, State, Type, TypeKind,
+    real::{Forall, Real},
+    resolution::ResolutionMap,
+    trace::TraceEvent,
+    Hole, HoleInner, Holes, State, Type, TypeKind,
 };
 
+/// Where the expected type of the expression being checked comes from. Type mismatches point to it.
+#[derive(Clone)]
+pub(crate) enum Expectation {
+    /// A type annotation.
+    Annotation(Span),
+
+    /// The body of the first arm of a `when` whose type was not known before its arms, so the
+    /// first arm decided it.
+    FirstArm(Span, Type<Virtual>),
+}
+
+/// A hole written by the user in expression position (`?name`). They are only reported at the end
+/// of the type checking so the type that we show is as refined as possible.
+pub struct TypedHole {
+    pub name: Symbol,
+    pub env: Env,
+    pub typ: Type<Virtual>,
+}
+
 /// A mutable context that is used differently from [Env]. It is used to keep data between every
 /// thing inside the type checker.
 pub struct Context {
-    pub counter: usize,
     pub reporter: Report,
     pub modules: Modules,
     pub elaborated: elaborated::Program<Type<Real>>,
     pub errored: bool,
+    pub typed_holes: Vec<TypedHole>,
+
+    /// The table of the holes made by this context.
+    pub holes: Holes,
+
+    /// The entries of the tables of holes changed while speculating, together with their previous
+    /// content, so they can be restored afterwards.
+    trail: Option<Vec<(Hole, Slot)>>,
+
+    /// What gives the expected type of the expression being checked. Type mismatches point to it
+    /// as the reason of the expected type.
+    annotation: Option<Expectation>,
+
+    /// The types of the top level declarations that are being defined. They are generalized only
+    /// after all of them are checked, so local lets cannot generalize their holes.
+    pub(crate) monomorphic: Vec<Type<Virtual>>,
+
+    /// The functions derived by the types of the programs that are being checked, together with
+    /// the name of their program. They are written when the programs are declared and taken
+    /// when their programs are defined.
+    pub(crate) derived: Vec<(Symbol, LetDecl)>,
+
+    /// The table of types without holes, so each one of them is allocated only once.
+    pub interner: Interner,
+
+    /// The variables that were used, identified by the file and the id of the pattern that binds
+    /// them. The ids are only unique inside of a file.
+    pub(crate) used: HashSet<(FileId, NodeId)>,
+
+    /// The definitions that the names of the programs refer to.
+    pub resolutions: ResolutionMap,
+
+    /// The scopes of the expressions and the items of the modules, used to complete names.
+    pub completions: Completions,
+
+    /// Reports the variables that shadow other variables that are in scope.
+    pub warn_shadowing: bool,
+
+    /// Reports the private definitions that are not used by the public items of their modules.
+    pub warn_dead_code: bool,
+
+    /// The module that defines the builtin types.
+    pub prelude: Symbol,
+
+    /// The builtin types, looked up in the prelude the first time that one of them is needed.
+    builtins: Option<Builtins>,
+
+    /// The deepest that inference and unification can recurse. Deeper definitions are reported
+    /// and treated as errors instead of hanging or overflowing the stack.
+    pub limit: usize,
+
+    /// If the limit was exceeded in the definition that is being checked, so it's reported once.
+    pub(crate) exceeded: bool,
+
+    /// If the definition that is being checked is deprecated, so the deprecated items that it
+    /// uses are not reported.
+    pub(crate) in_deprecated: bool,
+
+    /// The decisions taken by the checker, if they are being traced.
+    trace: Option<Vec<TraceEvent>>,
+
+    /// How much of the types is shown in the diagnostics. They are shown whole if it's `None`.
+    pub budget: Option<TypeBudget>,
+}
+
+/// The default of [Context::limit]. It's far deeper than any definition written by hand.
+pub const DEFAULT_LIMIT: usize = 500;
+
+/// What a [Context] gives to another thread to define some programs there. The report cannot be
+/// sent between threads, so the context is only made inside of the thread by [Fork::run].
+pub(crate) struct Fork {
+    holes: Holes,
+    modules: Modules,
+    elaborated: elaborated::Program<Type<Real>>,
+    derived: Vec<(Symbol, LetDecl)>,
+    interner: Interner,
+    used: HashSet<(FileId, NodeId)>,
+    resolutions: ResolutionMap,
+    warn_shadowing: bool,
+    warn_dead_code: bool,
+    prelude: Symbol,
+    builtins: Option<Builtins>,
+    limit: usize,
+    trace: bool,
+    budget: Option<TypeBudget>,
+}
+
+/// What the context of a [Fork] found. It's given back to the original context by [Context::join].
+pub(crate) struct Joined {
+    modules: Modules,
+    used: HashSet<(FileId, NodeId)>,
+    resolutions: ResolutionMap,
+    completions: Completions,
+    typed_holes: Vec<TypedHole>,
+    trace: Option<Vec<TraceEvent>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Fork {
+    /// Makes the context of the fork, with a report of its own, and runs the function with it.
+    pub(crate) fn run<T>(self, fun: impl FnOnce(&mut Context) -> T) -> (T, Joined) {
+        let collector = Collector::new();
+        let mut ctx = Context::new(hash_reporter().with_sink(collector.clone()));
+
+        ctx.holes = self.holes;
+        ctx.modules = self.modules;
+        ctx.elaborated = self.elaborated;
+        ctx.derived = self.derived;
+        ctx.interner = self.interner;
+        ctx.used = self.used;
+        ctx.resolutions = self.resolutions;
+        ctx.warn_shadowing = self.warn_shadowing;
+        ctx.warn_dead_code = self.warn_dead_code;
+        ctx.prelude = self.prelude;
+        ctx.builtins = self.builtins;
+        ctx.limit = self.limit;
+        ctx.trace = self.trace.then(Vec::new);
+        ctx.budget = self.budget;
+
+        let result = fun(&mut ctx);
+
+        let joined = Joined {
+            modules: ctx.modules,
+            used: ctx.used,
+            resolutions: ctx.resolutions,
+            completions: ctx.completions,
+            typed_holes: ctx.typed_holes,
+            trace: ctx.trace,
+            diagnostics: collector.diagnostics(),
+        };
+
+        (result, joined)
     }
+}
 
 impl Context {
     pub fn new(reporter: Report) -> Self {
         Self {
-            counter: 0,
             reporter,
             modules: Default::default(),
             elaborated: Default::default(),
             errored: false,
+            typed_holes: Vec::new(),
+            holes: Holes::default(),
+            trail: None,
+            annotation: None,
+            monomorphic: Vec::new(),
+            derived: Vec::new(),
+            interner: Interner::default(),
+            used: HashSet::new(),
+            resolutions: ResolutionMap::default(),
+            completions: Completions::default(),
+            warn_shadowing: false,
+            warn_dead_code: false,
+            prelude: well_known::PRELUDE,
+            builtins: None,
+            limit: DEFAULT_LIMIT,
+            exceeded: false,
+            in_deprecated: false,
+            trace: None,
+            budget: Some(TypeBudget::default()),
+        }
+    }
+
+    /// Takes what is needed to define the programs with the given names on another thread. The
+    /// functions derived for them go with the fork.
+    pub(crate) fn fork(&mut self, names: &[&Symbol]) -> Fork {
+        let (derived, others) = std::mem::take(&mut self.derived)
+            .into_iter()
+            .partition(|(name, _)| names.contains(&name));
+
+        self.derived = others;
+
+        Fork {
+            holes: self.holes.fork(),
+            modules: self.modules.clone(),
+            elaborated: self.elaborated.clone(),
+            derived,
+            interner: self.interner.clone(),
+            used: self.used.clone(),
+            resolutions: self.resolutions.fork(),
+            warn_shadowing: self.warn_shadowing,
+            warn_dead_code: self.warn_dead_code,
+            prelude: self.prelude.clone(),
+            builtins: self.builtins.clone(),
+            limit: self.limit,
+            trace: self.trace.is_some(),
+            budget: self.budget,
         }
     }
 
+    /// Brings back what a fork found. Only the modules that the function says that belong to the
+    /// fork are taken, since the fork has a copy of all of them. The diagnostics are reported in
+    /// the order that the fork reported them.
+    pub(crate) fn join(&mut self, joined: Joined, owns: impl Fn(&Symbol) -> bool) {
+        for (name, module) in joined.modules.modules {
+            if owns(&name) {
+                self.modules.modules.insert(name, module);
+            }
+        }
+
+        for (name, def) in joined.modules.wildcards {
+            if owns(&name.path) {
+                self.modules.wildcards.insert(name, def);
+            }
+        }
+
+        self.used.extend(joined.used);
+        self.resolutions.join(joined.resolutions);
+        self.completions.join(joined.completions);
+        self.typed_holes.extend(joined.typed_holes);
+
+        if let (Some(trace), Some(events)) = (&mut self.trace, joined.trace) {
+            trace.extend(events);
+        }
+
+        for diagnostic in joined.diagnostics {
+            self.reporter.report(diagnostic);
+        }
+    }
+
+    /// Changes the module where the builtin types are looked up.
+    pub fn with_prelude(mut self, prelude: Symbol) -> Self {
+        self.prelude = prelude;
+        self.builtins = None;
+        self
+    }
+
+    /// Changes how deep inference and unification can recurse.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Shows the whole types in the diagnostics instead of eliding the parts of them that are far
+    /// from the place where they differ.
+    pub fn with_verbose_types(mut self) -> Self {
+        self.budget = None;
+        self
+    }
+
+    /// Records the decisions taken by the checker, so they can be retrieved with
+    /// [Context::take_trace].
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    /// Takes the events recorded since the last call. It's empty if tracing is disabled.
+    pub fn take_trace(&mut self) -> Vec<TraceEvent> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Records an event if tracing is enabled. The event is only built in that case, because
+    /// rendering the types is expensive.
+    pub(crate) fn trace(&mut self, event: impl FnOnce() -> TraceEvent) {
+        if let Some(trace) = &mut self.trace {
+            trace.push(event());
+        }
+    }
+
+    /// Checks if the environment is deeper than the limit. It's reported only once for each
+    /// definition, and the caller should give up on the current expression or type.
+    pub(crate) fn exceeds_limit(&mut self, env: &Env) -> bool {
+        if env.depth <= self.limit {
+            return false;
+        }
+
+        if !self.exceeded {
+            self.exceeded = true;
+            self.report(env, TypeErrorKind::CheckerLimitExceeded);
+        }
+
+        true
+    }
+
     pub fn report(&mut self, env: &Env, kind: TypeErrorKind) {
         self.errored = true;
-        self.reporter.report(Diagnostic::new(TypeError {
-            span: env.span.borrow().clone(),
+
+        let annotation = match &kind {
+            TypeErrorKind::TypeMismatch { .. } | TypeErrorKind::TupleArityMismatch { .. } => {
+                self.annotation.clone()
+            }
+            _ => None,
+        };
+
+        let mut diagnostic = Diagnostic::new(TypeError {
+            span: env.current_span(),
             kind,
-        }));
+            budget: self.budget,
+        });
+
+        match annotation {
+            Some(Expectation::Annotation(span)) => {
+                diagnostic = diagnostic.with_label(span, "expected because of this annotation");
+            }
+            Some(Expectation::FirstArm(span, typ)) => {
+                let typ = typ.quote(env.level).show(env).to_string();
+                let label = format!("the first arm has type {typ}");
+                diagnostic = diagnostic.with_label(span, label);
+            }
+            None => (),
+        }
+
+        self.reporter.report(diagnostic);
     }
 
-    fn inc_counter(&mut self) -> usize {
-        self.counter += 1;
-        self.counter - 1
+    /// Reports a name that is defined twice in the same namespace, pointing at the first definition.
+    pub(crate) fn report_duplicate(&mut self, name: Symbol, span: Span, first: Span) {
+        self.errored = true;
+
+        let diagnostic = Diagnostic::new(TypeError {
+            span,
+            kind: TypeErrorKind::DuplicateDefinition(name),
+            budget: self.budget,
+        });
+
+        self.reporter
+            .report(diagnostic.with_label(first, "first defined here"));
     }
 
-    pub fn find_prelude_type(&mut self, name: &str, env: Env) -> Type<Virtual> {
-        let path = Symbol::intern("Prelude");
-        let name = Symbol::intern(name);
-        if self.modules.get(&path).types.get(&name).is_some() {
-            Type::variable(Qualified { path, name })
+    /// Matches the fields given to a record or to a labeled constructor with the names that it
+    /// has, reporting the fields that are unknown or given twice. The names that are not given are
+    /// reported too if all of them are required. Gives the place of the name of each field, or
+    /// [None] if the field is wrong.
+    pub(crate) fn match_fields<T>(
+        &mut self,
+        env: &Env,
+        names: &[Symbol],
+        fields: &[(Span, Symbol, T)],
+        required: bool,
+    ) -> Vec<Option<usize>> {
+        let mut used = HashSet::new();
+        let mut places = Vec::new();
+
+        for (span, name, _) in fields {
+            env.set_current_span(span.clone());
+
+            let place = match names.iter().position(|x| x == name) {
+                None => {
+                    self.report(env, TypeErrorKind::NotFoundField);
+                    None
+                }
+                Some(place) if !used.insert(place) => {
+                    self.report(env, TypeErrorKind::DuplicatedField);
+                    None
+                }
+                place => place,
+            };
+
+            places.push(place);
+        }
+
+        if required {
+            for (place, name) in names.iter().enumerate() {
+                if !used.contains(&place) {
+                    self.report(env, TypeErrorKind::MissingField(name.clone()));
+                }
+            }
+        }
+
+        places
+    }
+
+    /// Reports a cycle between type synonyms at the first of them, pointing at the others.
+    pub(crate) fn report_cycle(&mut self, cycle: Vec<(Qualified, Span)>) {
+        self.errored = true;
+
+        let mut diagnostic = Diagnostic::new(TypeError {
+            span: cycle[0].1.clone(),
+            kind: TypeErrorKind::CyclicTypeSynonym(cycle.iter().map(|x| x.0.clone()).collect()),
+            budget: self.budget,
+        });
+
+        for (_, span) in cycle.iter().skip(1) {
+            diagnostic = diagnostic.with_label(span.clone(), "part of the cycle");
+        }
+
+        self.reporter.report(diagnostic);
+    }
+
+    /// Runs a function with the annotation that gives the expected type of what is checked inside
+    /// of it.
+    pub(crate) fn with_annotation<T>(
+        &mut self,
+        annotation: Option<Span>,
+        fun: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        self.with_expectation(annotation.map(Expectation::Annotation), fun)
+    }
+
+    /// Runs a function with the reason of the expected type of what is checked inside of it.
+    pub(crate) fn with_expectation<T>(
+        &mut self,
+        expectation: Option<Expectation>,
+        fun: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let old = std::mem::replace(&mut self.annotation, expectation);
+        let result = fun(self);
+        self.annotation = old;
+        result
+    }
+
+    /// Records the old content of entries of the tables of holes if we are speculating.
+    fn record(&mut self, changed: Vec<(Hole, Slot)>) {
+        if let Some(trail) = &mut self.trail {
+            trail.extend(changed);
+        }
+    }
+
+    /// Fills a hole recording the old content if we are speculating.
+    pub(crate) fn fill(&mut self, hole: &Hole, typ: Type<Virtual>) {
+        let root = hole.root();
+        self.record(vec![(root.clone(), root.slot())]);
+        root.fill(typ);
+    }
+
+    /// Fills an empty hole with the type that it stands for.
+    pub(crate) fn solve(&mut self, env: &Env, hole: &Hole, typ: Type<Virtual>) {
+        self.trace(|| TraceEvent::Solve {
+            hole: Symbol::intern(&format!("?{}", hole.index())),
+            solution: typ.show(env).to_string(),
+            span: env.current_span(),
+        });
+
+        self.fill(hole, typ);
+    }
+
+    /// Finds what a type stands for, following its hole to the root of its class and the types
+    /// that fill it. The path to the root is compressed, and the changes go through the trail, so
+    /// they are undone together with the speculation that caused them.
+    pub(crate) fn find(&mut self, typ: &Type<Virtual>) -> Type<Virtual> {
+        let TypeKind::Hole(hole) = typ.as_ref() else {
+            return typ.clone();
+        };
+
+        let (root, changed) = hole.compress();
+        self.record(changed);
+
+        match root.inner() {
+            HoleInner::Filled(next) => self.find(&next),
+            HoleInner::Empty(..) if hole.is_root() => typ.clone(),
+            HoleInner::Empty(..) => Type::new(TypeKind::Hole(root)),
+        }
+    }
+
+    /// Joins two empty holes. Holes of the same table are joined by rank. Holes of different
+    /// tables, that meet when a fork uses the holes of the context that it was forked from, are
+    /// joined by filling the one that was created in the innermost scope with the other, so the
+    /// joined hole cannot be filled with variables that only one of them can see.
+    pub(crate) fn union(&mut self, env: &Env, left: &Hole, right: &Hole) {
+        if left.same_table(right) {
+            self.trace(|| TraceEvent::Solve {
+                hole: Symbol::intern(&format!("?{}", left.index())),
+                solution: format!("?{}", right.index()),
+                span: env.current_span(),
+            });
+
+            let changed = left.union(right);
+            self.record(changed);
+            return;
+        }
+
+        let level = |hole: &Hole| match hole.inner() {
+            HoleInner::Empty(_, level) => level,
+            HoleInner::Filled(_) => unreachable!(),
+        };
+
+        if level(left) < level(right) {
+            self.solve(env, right, Type::new(TypeKind::Hole(left.clone())));
         } else {
-            self.report(&env, crate::errors::TypeErrorKind::CannotFind(name));
-            Type::error()
+            self.solve(env, left, Type::new(TypeKind::Hole(right.clone())));
+        }
+    }
+
+    /// Runs a function and undoes every hole filled by it, so we can ask questions to the unifier
+    /// without committing to the answer.
+    pub fn speculate<T>(&mut self, fun: impl FnOnce(&mut Self) -> T) -> T {
+        let old = self.trail.replace(Vec::new());
+        let result = fun(self);
+        let trail = std::mem::replace(&mut self.trail, old).unwrap_or_default();
+
+        for (hole, slot) in trail.into_iter().rev() {
+            hole.restore(slot);
+        }
+
+        result
+    }
+
+    /// Registers a hole written by the user in order to report its type later.
+    pub fn typed_hole(&mut self, env: &Env, name: Symbol, typ: Type<Virtual>) {
+        self.typed_holes.push(TypedHole {
+            name,
+            env: env.clone(),
+            typ,
+        });
+    }
+
+    /// Reports the type of every hole written by the user together with the variables in scope
+    /// that have a type that fits in it. The holes are kept so the driver knows that the program
+    /// cannot be compiled.
+    pub fn report_typed_holes(&mut self) {
+        let holes = std::mem::take(&mut self.typed_holes);
+
+        for hole in &holes {
+            let mut vars = hole.env.vars.iter().collect::<Vec<_>>();
+            vars.sort_by_key(|(name, _)| name.get());
+
+            let mut fits = Vec::new();
+
+            for (name, typ) in vars {
+                let fit = self.speculate(|ctx| {
+                    let typ = ctx.instantiate_all(&hole.env, typ);
+                    ctx.unify(hole.env.clone(), typ, hole.typ.clone()).is_ok()
+                });
+
+                if fit {
+                    fits.push(name.clone());
+                }
+            }
+
+            let typ = hole.typ.quote(hole.env.level);
+            let kind = TypeErrorKind::TypedHole(hole.name.clone(), hole.env.clone(), typ, fits);
+
+            self.report(&hole.env, kind);
         }
+
+        self.typed_holes = holes;
+    }
+
+    /// Returns one of the builtin types, reporting it at the current location if the prelude does
+    /// not define it.
+    pub fn builtin(&mut self, builtin: Builtin, env: &Env) -> Type<Virtual> {
+        let builtins = self.builtins.get_or_insert_with(|| {
+            Builtins::resolve(&self.modules, &mut self.interner, &self.prelude)
+        });
+
+        if let Some(typ) = builtins.get(builtin) {
+            return typ.clone();
+        }
+
+        let name = builtin.name();
+        self.report(
+            env,
+            TypeErrorKind::MissingBuiltin(name, self.prelude.clone()),
+        );
+        Type::error()
     }
 
-    /// Creates a new name with the prefix `t_` and a unique number.
-    pub fn new_name(&mut self) -> Symbol {
-        Symbol::intern(&format!("t_{}", self.inc_counter()))
+    /// Checks that the module of a referenced name was declared, reporting it at the current
+    /// location otherwise.
+    pub(crate) fn module_exists(&mut self, env: &Env, path: &Symbol) -> bool {
+        if self.modules.get(path).is_some() {
+            return true;
+        }
+
+        self.report(env, TypeErrorKind::UnknownModule(path.clone()));
+        false
     }
 
     /// Creates a new hole that is a type that is not yet known
     pub fn hole<S: State>(&mut self, env: &Env, kind: Type<Virtual>) -> Type<S> {
-        env.hole(kind, self.new_name())
+        Type::new(TypeKind::Hole(self.holes.empty(kind, env.level)))
     }
 
     pub fn as_function(
@@ -83,15 +633,19 @@ impl Context {
                 self.as_function(env, typ)
             }
             TypeKind::Hole(empty) => {
-                let hole_inner = empty.0.borrow().clone();
-                if let HoleInner::Empty(_, kind, _) = hole_inner {
+                let hole_inner = empty.inner();
+                if let HoleInner::Empty(kind, _) = hole_inner {
                     let hole_a = self.hole(env, kind.clone());
                     let hole_b = self.hole(env, kind);
 
-                    empty.fill(Type::new(TypeKind::Arrow(Pi {
-                        typ: hole_a.clone(),
-                        body: hole_b.clone(),
-                    })));
+                    self.solve(
+                        env,
+                        empty,
+                        Type::new(TypeKind::Arrow(Pi {
+                            typ: hole_a.clone(),
+                            body: hole_b.clone(),
+                        })),
+                    );
 
                     Some((hole_a, hole_b))
                 } else {
@@ -106,10 +660,18 @@ impl Context {
     pub fn instantiate(&mut self, env: &Env, typ: &Type<Virtual>) -> Type<Virtual> {
         match typ.deref().as_ref() {
             TypeKind::Forall(forall) => {
-                let arg = env.hole(forall.kind.clone(), forall.name.clone());
+                let arg = self.hole(env, forall.kind.clone());
                 let kind = forall.kind.clone();
                 // Applies the body using the hole argument.
-                forall.body.apply(Some(forall.name.clone()), arg, kind)
+                let result = forall.body.apply(Some(forall.name.clone()), arg, kind);
+
+                self.trace(|| TraceEvent::Instantiate {
+                    typ: typ.show(env).to_string(),
+                    result: result.show(env).to_string(),
+                    span: env.current_span(),
+                });
+
+                result
             }
             _ => typ.clone(),
         }
@@ -138,12 +700,86 @@ impl Context {
     }
 
     pub fn instantiate_all(&mut self, env: &Env, typ: &Type<Virtual>) -> Type<Virtual> {
-        match typ.deref().as_ref() {
-            TypeKind::Forall(_) => {
-                let res = self.instantiate(env, typ);
-                self.instantiate_all(env, &res)
-            }
-            _ => typ.clone(),
+        let mut result = typ.clone();
+
+        while let TypeKind::Forall(forall) = result.deref().as_ref() {
+            let arg = self.hole(env, forall.kind.clone());
+            let kind = forall.kind.clone();
+            result = forall.body.apply(Some(forall.name.clone()), arg, kind);
+        }
+
+        if matches!(typ.deref().as_ref(), TypeKind::Forall(_)) {
+            self.trace(|| TraceEvent::Instantiate {
+                typ: typ.show(env).to_string(),
+                result: result.show(env).to_string(),
+                span: env.current_span(),
+            });
         }
+
+        result
+    }
+
+    /// Generalizes a type, binding each one of the holes that are still empty in a `forall`. The
+    /// holes that appear in the types of the variables of the environment or of the declarations
+    /// that are being defined are kept, because they can still be filled by someone else. The
+    /// variables are named in the order that they first appear.
+    pub fn generalize(&mut self, env: &Env, typ: &Type<Virtual>) -> Type<Virtual> {
+        let real = typ.quote(env.level);
+
+        let mut holes = Vec::new();
+        real.holes(&mut holes);
+
+        // Most of the bindings have no holes left, so the environment is only scanned when there
+        // is something to generalize.
+        if holes.is_empty() {
+            return typ.clone();
+        }
+
+        let mut fixed = Vec::new();
+
+        for typ in env.vars.values().chain(self.monomorphic.iter()) {
+            typ.quote(env.level).holes(&mut fixed);
+        }
+
+        holes.retain(|hole| !fixed.contains(hole));
+
+        if holes.is_empty() {
+            return typ.clone();
+        }
+
+        let mut generalized = real.bind_holes(&holes, 0);
+
+        for (i, hole) in holes.iter().enumerate().rev() {
+            let HoleInner::Empty(kind, _) = hole.inner() else {
+                unreachable!()
+            };
+
+            generalized = Type::forall(Forall {
+                name: Symbol::intern(&variable_name(i)),
+                kind: kind.quote(env.level),
+                body: generalized,
+            });
+        }
+
+        let generalized = generalized.eval(env);
+
+        self.trace(|| TraceEvent::Generalize {
+            typ: typ.show(env).to_string(),
+            result: generalized.show(env).to_string(),
+            span: env.current_span(),
+        });
+
+        generalized
+    }
+}
+
+/// The name of the n-th generalized variable, going from `a` to `z` and then `a1`, `b1` and so on.
+fn variable_name(n: usize) -> String {
+    let letter = (b'a' + (n % 26) as u8) as char;
+
+    if n < 26 {
+        letter.to_string()
+    } else {
+        format!("{}{}", letter, n / 26)
     }
 }
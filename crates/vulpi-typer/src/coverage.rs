@@ -1,12 +1,12 @@
 //! Coverage checking algorithm for patterns. Based on the one described in the paper
 //! "Warnings for pattern matching" by Luc Maranget.
 
-use std::fmt::Display;
+use std::{collections::VecDeque, fmt::Display};
 
 use im_rc::HashSet;
 
 use vulpi_syntax::{
-    elaborated::{Literal, LiteralKind, Pattern, PatternArm, PatternKind},
+    elaborated::{Literal, LiteralKind, PatApplication, Pattern, PatternArm, PatternKind},
     r#abstract::Qualified,
 };
 
@@ -57,24 +57,20 @@ impl Display for Pat {
 }
 
 impl Pat {
-    pub fn from_pattern(pat: &Pattern) -> Option<Pat> {
+    pub fn from_pattern(pat: &Pattern) -> Pat {
         match &**pat {
-            PatternKind::Wildcard => Some(Pat::Wildcard),
-            PatternKind::Variable(_) => Some(Pat::Wildcard),
-            PatternKind::Tuple(args) => Some(Pat::Tuple(
-                args.iter()
-                    .map(Pat::from_pattern)
-                    .collect::<Option<Vec<_>>>()?,
-            )),
-            PatternKind::Literal(l) => Some(Pat::Literal(l.clone())),
-            PatternKind::Application(p) => Some(Pat::Constructor(
+            PatternKind::Wildcard => Pat::Wildcard,
+            PatternKind::Variable(_) => Pat::Wildcard,
+            PatternKind::Tuple(args) => Pat::Tuple(args.iter().map(Pat::from_pattern).collect()),
+            PatternKind::Literal(l) => Pat::Literal(l.clone()),
+            PatternKind::Application(p) => Pat::Constructor(
                 p.func.clone(),
-                p.args
-                    .iter()
-                    .map(Pat::from_pattern)
-                    .collect::<Option<Vec<_>>>()?,
-            )),
-            PatternKind::Error => None,
+                p.args.iter().map(Pat::from_pattern).collect(),
+            ),
+            // The error was already reported, so it matches everything to not report the cases
+            // that it would have matched as missing.
+            PatternKind::Error => Pat::Wildcard,
+            PatternKind::Or(_) => unreachable!("or patterns are split by `alternatives`"),
         }
     }
 
@@ -90,10 +86,58 @@ impl Pat {
     }
 }
 
+/// The rows without or patterns that match the same values as the patterns, in the order that
+/// they are tried. The alternatives of the first patterns change the slowest.
+pub fn alternatives(patterns: &[Pattern]) -> Vec<Vec<Pattern>> {
+    patterns.iter().fold(vec![vec![]], |rows, pattern| {
+        let choices = pattern_alternatives(pattern);
+
+        rows.iter()
+            .flat_map(|row| {
+                choices.iter().map(move |choice| {
+                    let mut row = row.clone();
+                    row.push(choice.clone());
+                    row
+                })
+            })
+            .collect()
+    })
+}
+
+fn pattern_alternatives(pattern: &Pattern) -> Vec<Pattern> {
+    match &**pattern {
+        PatternKind::Or(or) => {
+            let mut choices = pattern_alternatives(&or.left);
+            choices.extend(pattern_alternatives(&or.right));
+            choices
+        }
+        PatternKind::Tuple(args) => alternatives(args)
+            .into_iter()
+            .map(|args| Box::new(PatternKind::Tuple(args)))
+            .collect(),
+        PatternKind::Application(app) => alternatives(&app.args)
+            .into_iter()
+            .map(|args| {
+                Box::new(PatternKind::Application(PatApplication {
+                    func: app.func.clone(),
+                    args,
+                }))
+            })
+            .collect(),
+        _ => vec![pattern.clone()],
+    }
+}
+
 /// A line in the problem matrix. It's used to indicate that there's an answer to a open pattern
 /// problem.
 #[derive(Clone)]
-pub struct Row<T>(im_rc::Vector<T>);
+pub struct Row<T>(VecDeque<T>);
+
+impl<T: Clone> From<Vec<T>> for Row<T> {
+    fn from(items: Vec<T>) -> Self {
+        Row(items.into())
+    }
+}
 
 impl<T: Clone> Row<T> {
     /// Removes the first column of the row
@@ -116,12 +160,33 @@ impl<T: Clone> Row<T> {
     }
 
     pub fn split(&self, place: usize) -> (Self, Self) {
-        let (left, right) = self.0.clone().split_at(place);
+        let mut left = self.0.clone();
+        let right = left.split_off(place);
         (Row(left), Row(right))
     }
 
     pub fn first(&self) -> &T {
-        self.0.get(0).unwrap()
+        self.0.front().unwrap()
+    }
+
+    pub fn get(&self, place: usize) -> &T {
+        &self.0[place]
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.0.iter()
+    }
+
+    /// Moves the column at the place to the beggining of the row.
+    pub fn to_front(&self, place: usize) -> Self {
+        let mut line = self.clone();
+        let item = line.0.remove(place).unwrap();
+        line.0.push_front(item);
+        line
     }
 
     pub fn inline(&self, mut other: Vec<T>) -> Self {
@@ -146,6 +211,7 @@ impl Row<Pat> {
             }
 
             (Pat::Constructor(_, args), Pat::Wildcard) => vec![self.inline(wildcards(args.len()))],
+            (Pat::Tuple(args), Pat::Wildcard) => vec![self.inline(wildcards(args.len()))],
 
             (Pat::Tuple(a), Pat::Tuple(b)) if a.len() == b.len() => {
                 vec![self.inline(b.to_vec())]
@@ -201,7 +267,7 @@ impl Matrix<Pat> {
     }
 }
 
-fn wildcards(n: usize) -> Vec<Pat> {
+pub fn wildcards(n: usize) -> Vec<Pat> {
     vec![Pat::Wildcard; n]
 }
 
@@ -305,19 +371,16 @@ impl Display for Problem {
 
 impl Problem {
     pub fn exhaustiveness(arms: &[PatternArm<Type<Real>>], types: Vec<Type<Virtual>>) -> Self {
+        // A guard can be false, so the arms with one don't cover any case.
         let map = arms
             .iter()
-            .flat_map(|x| {
-                x.patterns
-                    .iter()
-                    .map(Pat::from_pattern)
-                    .collect::<Option<Vec<_>>>()
-                    .map(|x| Row(x.into()))
-            })
+            .filter(|x| x.guard.is_none())
+            .flat_map(|x| alternatives(&x.patterns))
+            .map(|x| Row(x.iter().map(Pat::from_pattern).collect()))
             .collect::<Vec<_>>();
 
         Self {
-            types: Row(types[0..map.get(0).map(|x| x.0.len()).unwrap_or_default()].into()),
+            types: Row(types[0..arms[0].patterns.len()].iter().cloned().collect()),
             case: Row(wildcards(arms[0].patterns.len()).into()),
             matrix: Matrix(map),
         }
@@ -372,7 +435,7 @@ impl Problem {
         case_pats: Vec<Pat>,
         args: Vec<Type<Virtual>>,
     ) -> Witness {
-        let (signature, _, _) = ctx.modules.constructor(&name);
+        let signature = ctx.modules.constructor(&name).typ;
         let signature = ctx.instantiate_with_arguments(&signature.eval(&env), args);
 
         let spine = signature.arrow_spine();
@@ -406,7 +469,7 @@ impl Problem {
     }
 
     pub fn synthetize(&self, ctx: &mut Context, name: Qualified) -> Pat {
-        let (_, args, _) = ctx.modules.constructor(&name);
+        let args = ctx.modules.constructor(&name).arity;
         Pat::Constructor(name.clone(), wildcards(args))
     }
 
@@ -428,10 +491,9 @@ impl Problem {
                     let witness = self.default_matrix().exaustive(ctx, env);
                     witness.preppend(pat)
                 }
-                Completeness::Incomplete(Finitude::Infinite) => {
-                    let witness = self.specialize_wildcard(ctx, env);
-                    witness.preppend(Pat::Wildcard)
-                }
+                // Types with infinite values like literals are only covered by a wildcard, which
+                // already puts the wildcard in the witness.
+                Completeness::Incomplete(Finitude::Infinite) => self.specialize_wildcard(ctx, env),
             }
         }
     }
@@ -447,7 +509,7 @@ impl Problem {
 
         if let crate::module::Def::Enum(constructors) = typ.def {
             for constructor in constructors {
-                let (_, size, _) = ctx.modules.constructor(&constructor);
+                let size = ctx.modules.constructor(&constructor).arity;
 
                 let witness = self.clone().specialize_cons(
                     ctx,
@@ -0,0 +1,195 @@
+//! Detection of the private definitions that nothing uses. The public items of the modules, the
+//! externals, the traits, the implementations and the entry points are used from outside, and a
+//! private let or type is dead if none of them reaches it through the functions, constructors and
+//! types that they mention. Items marked with `@keep` are used from outside too.
+
+use std::collections::{HashMap, HashSet};
+
+use vulpi_intern::well_known;
+use vulpi_report::Diagnostic;
+use vulpi_syntax::{
+    r#abstract::{
+        Attribute, Expr, ExprKind, Pattern, PatternKind, Program, Qualified, Type, TypeDef,
+        TypeKind, Visibility,
+    },
+    visit::{walk_expr, walk_pattern, walk_type, Visitor},
+};
+
+use crate::{
+    context::Context,
+    errors::{TypeError, TypeErrorKind},
+};
+
+fn kept(attributes: &[Attribute]) -> bool {
+    attributes
+        .iter()
+        .any(|attribute| attribute.name == well_known::KEEP)
+}
+
+/// A definition that can be used by other definitions.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Item {
+    Let(Qualified),
+    Type(Qualified),
+}
+
+/// Collects the definitions that are mentioned by a part of a program. Constructors stand for the
+/// types that declare them.
+struct References<'a> {
+    constructors: &'a HashMap<Qualified, Qualified>,
+    items: Vec<Item>,
+}
+
+impl References<'_> {
+    fn constructor(&mut self, name: &Qualified) {
+        if let Some(typ) = self.constructors.get(name) {
+            self.items.push(Item::Type(typ.clone()));
+        }
+    }
+}
+
+impl Visitor for References<'_> {
+    fn visit_type(&mut self, typ: &Type) {
+        if let TypeKind::Type(name) = &typ.data {
+            self.items.push(Item::Type(name.clone()));
+        }
+
+        walk_type(self, typ)
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        match &pattern.data {
+            PatternKind::Application(app) => self.constructor(&app.func),
+            PatternKind::Labeled(labeled) => self.constructor(&labeled.func),
+            _ => (),
+        }
+
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.data {
+            ExprKind::Function(name) => self.items.push(Item::Let(name.clone())),
+            ExprKind::Constructor(name) => self.constructor(name),
+            ExprKind::Labeled(instance) => self.constructor(&instance.name),
+            ExprKind::RecordInstance(instance) => {
+                self.items.push(Item::Type(instance.name.clone()))
+            }
+            _ => (),
+        }
+
+        walk_expr(self, expr)
+    }
+}
+
+impl Context {
+    /// Reports the private lets and types that cannot be reached from the items that are used
+    /// from outside of their modules. Names that start with `_` are unused on purpose.
+    pub(crate) fn report_dead_code(&mut self, roots: &[&Program], programs: &[&Program]) {
+        let constructors = programs
+            .iter()
+            .flat_map(|program| &program.types)
+            .flat_map(|decl| match &decl.def {
+                TypeDef::Sum(sum) => sum
+                    .constructors
+                    .iter()
+                    .map(|cons| (cons.name.clone(), decl.name.clone()))
+                    .collect(),
+                _ => Vec::new(),
+            })
+            .collect::<HashMap<_, _>>();
+
+        let references = |visit: &dyn Fn(&mut References)| {
+            let mut references = References {
+                constructors: &constructors,
+                items: Vec::new(),
+            };
+
+            visit(&mut references);
+            references.items
+        };
+
+        let mut uses = HashMap::new();
+        let mut reached = Vec::new();
+
+        for program in programs {
+            for decl in &program.lets {
+                let item = Item::Let(decl.signature.name.clone());
+                uses.insert(item.clone(), references(&|refs| refs.visit_let_decl(decl)));
+
+                if decl.signature.visibility == Visibility::Public
+                    || decl.signature.name.is_wildcard()
+                    || kept(&decl.attributes)
+                {
+                    reached.push(item);
+                }
+            }
+
+            for decl in &program.types {
+                let item = Item::Type(decl.name.clone());
+                uses.insert(item.clone(), references(&|refs| refs.visit_type_decl(decl)));
+
+                if decl.visibility == Visibility::Public || kept(&decl.attributes) {
+                    reached.push(item);
+                }
+            }
+
+            for decl in &program.externals {
+                reached.extend(references(&|refs| refs.visit_ext_decl(decl)));
+            }
+
+            for decl in &program.traits {
+                reached.extend(references(&|refs| refs.visit_trait_decl(decl)));
+            }
+
+            for decl in &program.impls {
+                reached.extend(references(&|refs| refs.visit_trait_impl(decl)));
+            }
+        }
+
+        for program in roots {
+            reached.extend(program.lets.iter().filter_map(|decl| {
+                let name = &decl.signature.name;
+                (name.name == well_known::MAIN).then(|| Item::Let(name.clone()))
+            }));
+        }
+
+        let mut alive = HashSet::new();
+
+        while let Some(item) = reached.pop() {
+            if alive.insert(item.clone()) {
+                reached.extend(uses.get(&item).into_iter().flatten().cloned());
+            }
+        }
+
+        let lets = programs
+            .iter()
+            .flat_map(|program| &program.lets)
+            .map(|decl| {
+                let signature = &decl.signature;
+                (
+                    Item::Let(signature.name.clone()),
+                    &signature.visibility,
+                    &signature.span,
+                )
+            });
+
+        let types = programs
+            .iter()
+            .flat_map(|program| &program.types)
+            .map(|decl| (Item::Type(decl.name.clone()), &decl.visibility, &decl.span));
+
+        for (item, visibility, span) in lets.chain(types) {
+            let (Item::Let(name) | Item::Type(name)) = &item;
+            let dead = *visibility == Visibility::Private && !alive.contains(&item);
+
+            if dead && !name.name.as_str().starts_with('_') {
+                self.reporter.report(Diagnostic::new(TypeError {
+                    span: span.clone(),
+                    kind: TypeErrorKind::UnusedDefinition(name.name.clone()),
+                    budget: self.budget,
+                }));
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
+use indexmap::IndexSet;
 use vulpi_intern::Symbol;
+use vulpi_location::Span;
 use vulpi_syntax::{
     elaborated::{self},
     r#abstract::{
-        LetBinder, Qualified, TraitDecl, {ExtDecl, LetDecl, TypeDef}, {Program, TypeDecl},
+        self, Constructor, LetBinder, Qualified, TraitDecl, Visibility,
+        {ExtDecl, LetDecl, TypeDef}, {Program, TypeDecl},
     },
 };
 
@@ -16,24 +19,51 @@ use crate::{
     eval::Eval,
     eval::Quote,
     infer::Infer,
-    module::{Def, LetDef, TraitData, TypeData},
+    module::{ConstructorData, Def, LetDef, TraitData, TypeData},
+    order,
     r#virtual::Virtual,
     real::{Forall, Real},
-    Env, Index, Kind, Type,
+    resolution::ItemKind,
+    unused, Env, Index, Kind, Type, TypeKind,
 };
 
-fn free_variables(let_sig: &vulpi_syntax::r#abstract::LetSignature) -> HashSet<Symbol> {
-    let mut fvs = let_sig
-        .ret
-        .as_ref()
-        .map(|x| x.data.free_variables())
-        .unwrap_or_default();
+/// If the result of a function type is a hole that none of the arguments mention. Nothing can tell
+/// what it is, like the result of `let loop (n: Int) = loop n`, so it's ambiguous.
+fn unconstrained_result(typ: &Type<Real>) -> bool {
+    let mut typ = typ.clone();
 
-    for arg in &let_sig.binders {
-        fvs.extend(arg.typ().data.free_variables());
+    while let TypeKind::Forall(forall) = typ.as_ref() {
+        typ = forall.body.clone();
     }
 
-    fvs
+    let mut spine = typ.arrow_spine();
+    let Some(result) = spine.pop() else {
+        return false;
+    };
+
+    let TypeKind::Hole(result) = result.as_ref() else {
+        return false;
+    };
+
+    let mut holes = Vec::new();
+
+    for arg in &spine {
+        arg.holes(&mut holes);
+    }
+
+    result.is_empty() && !holes.contains(result)
+}
+
+/// The free type variables of a signature in the order that they first appear, going through the
+/// binders from left to right and then through the return type. It's the order of the implicit
+/// `forall` of the signature.
+fn free_variables(let_sig: &vulpi_syntax::r#abstract::LetSignature) -> IndexSet<Symbol> {
+    let binders = let_sig.binders.iter().map(|arg| arg.typ());
+
+    binders
+        .chain(let_sig.ret.as_ref())
+        .flat_map(|typ| typ.free_type_variables())
+        .collect()
 }
 
 /// Trait for declaration of top level items inside the type checker.
@@ -85,7 +115,7 @@ impl<T: Declare> Declare for Option<T> {
 impl Declare for TraitDecl {
     type Return = ();
 
-    fn declare(&self, (ctx, mut env): (&mut Context, Env)) {        
+    fn declare(&self, (ctx, mut env): (&mut Context, Env)) {
         env.set_current_span(self.span.clone());
         let vec = &self.binders;
 
@@ -107,13 +137,17 @@ impl Declare for TraitDecl {
 
         let mut supers = vec![];
 
-        ctx.modules.get(&self.name.path).types.insert(
-            self.name.name.clone(),
+        declare_type(
+            ctx,
+            &self.name,
             TypeData {
                 kind: kind.clone(),
                 binders: names.into_iter().zip(binders.clone()).collect(),
                 module: self.namespace.clone(),
                 def: Def::Constraint,
+                span: self.span.clone(),
+                docs: None,
+                attributes: Vec::new(),
             },
         );
 
@@ -129,9 +163,8 @@ impl Declare for TraitDecl {
         for let_signature in &self.body {
             let mut env = env.clone();
 
-            let free_variables = &free_variables(let_signature);
-            let diff = HashSet::from_iter(fvs.iter().cloned());
-            let signature_fvs = free_variables.difference(&diff);
+            let free_variables = free_variables(let_signature);
+            let signature_fvs = free_variables.iter().filter(|fv| !fvs.contains(fv));
 
             let mut unbound = Vec::new();
 
@@ -139,7 +172,7 @@ impl Declare for TraitDecl {
                 let typ = ctx.hole(&env, Type::typ());
                 env = env.add(Some(fv.clone()), typ.clone());
                 unbound.push((fv, typ.quote(env.level)))
-            }            
+            }
 
             for fv in signature_fvs {
                 let typ = ctx.hole(&env, Type::typ());
@@ -152,26 +185,31 @@ impl Declare for TraitDecl {
             for arg in &let_signature.binders {
                 let (typ, kind) = arg.typ().infer((ctx, env.clone()));
                 env.set_current_span(arg.typ().span.clone());
-                ctx.subsumes(env.clone(), kind, Kind::typ());    
+                ctx.expect_type_kind(&env, kind);
                 args.push(typ);
             }
 
             let ret = if let Some(ret) = &let_signature.ret {
                 let (typ, kind) = ret.infer((ctx, env.clone()));
                 env.set_current_span(ret.span.clone());
-                ctx.subsumes(env.clone(), kind, Kind::typ());
-    
+                ctx.expect_type_kind(&env, kind);
+
                 typ
             } else {
                 ctx.hole(&env, Kind::typ())
             };
-            
+
             let mut typ = Type::<Real>::function(args.clone(), ret.clone());
-    
-            let fvs = fvs.iter().map(|x| {
-                let Some((index, _, _)) = env.find(x) else { unreachable!() };
-                Type::bound(Index(index))
-            }).collect();
+
+            let fvs = fvs
+                .iter()
+                .map(|x| {
+                    let Some((index, _, _)) = env.find(x) else {
+                        unreachable!()
+                    };
+                    Type::bound(Index(index))
+                })
+                .collect();
 
             let constraint = Type::<Real>::application(Type::variable(self.name.clone()), fvs);
 
@@ -188,7 +226,7 @@ impl Declare for TraitDecl {
             signatures.push((self.name.clone(), typ));
         }
 
-        ctx.modules.get(&self.name.path).traits.insert(
+        ctx.modules.get_or_create(&self.name.path).traits.insert(
             self.name.name.clone(),
             TraitData {
                 kind,
@@ -222,18 +260,52 @@ impl Declare for TypeDecl {
         let type_def = &self.def;
         let def = get_definition_of_type(type_def);
 
-        ctx.modules.get(&self.name.path).types.insert(
-            self.name.name.clone(),
+        match type_def {
+            TypeDef::Sum(sum) => {
+                for cons in &sum.constructors {
+                    let span = cons.span.clone();
+                    ctx.resolutions
+                        .define(ItemKind::Constructor, &cons.name, span);
+                    ctx.check_attributes(ItemKind::Constructor, &cons.name, &cons.attributes);
+                }
+            }
+            TypeDef::Record(record) => {
+                for (name, _, _, span, _) in &record.fields {
+                    ctx.resolutions.define(ItemKind::Field, name, span.clone());
+                }
+            }
+            TypeDef::Synonym(_) | TypeDef::Abstract => (),
+        }
+
+        if let Def::Record(fields) = &def {
+            for field in fields {
+                let records = ctx.modules.records.entry(field.name.clone()).or_default();
+
+                if !records.contains(&self.name) {
+                    records.push(self.name.clone());
+                }
+            }
+        }
+
+        declare_type(
+            ctx,
+            &self.name,
             TypeData {
                 kind,
                 binders: names.into_iter().zip(binders).collect(),
                 module: self.namespace.clone(),
                 def,
+                span: self.span.clone(),
+                docs: self.docs.clone(),
+                attributes: self.attributes.clone(),
             },
         );
+
+        ctx.check_attributes(ItemKind::Type, &self.name, &self.attributes);
     }
 
     fn define(&self, (ctx, mut env): (&mut Context, Env)) -> Self::Return {
+        let outer = ctx.enter_item(&self.attributes);
         let type_decl = ctx.modules.typ(&self.name);
 
         for (name, binder) in &type_decl.binders {
@@ -253,24 +325,40 @@ impl Declare for TypeDecl {
                 let mut constructors = Vec::new();
 
                 let mut cons_types = Vec::new();
+                let mut seen = HashMap::new();
 
                 for cons in &cons.constructors {
+                    check_duplicate(ctx, &mut seen, &cons.name.name, &cons.span);
                     constructors.push((cons.name.clone(), cons.args.len()));
 
+                    let mut labels = HashMap::new();
+                    for (label, span) in &cons.labels {
+                        check_duplicate(ctx, &mut labels, label, span);
+                    }
+
                     let mut types = Vec::new();
 
                     for arg in &cons.args {
                         env.set_current_span(arg.span.clone());
                         let (typ, kind) = arg.infer((ctx, env.clone()));
-                        ctx.subsumes(env.clone(), kind, Kind::typ());
+                        ctx.expect_type_kind(&env, kind);
                         types.push(typ);
                     }
 
-                    let typ = Type::<Real>::function(types, ret_type.clone());
-                    cons_types.push((cons.name.clone(), cons.args.len(), typ));
+                    let ret = match &cons.typ {
+                        Some(ret) => constructor_return_type(ctx, &env, &self.name, cons, ret)
+                            .unwrap_or_else(|| ret_type.clone()),
+                        None => ret_type.clone(),
+                    };
+
+                    let typ = Type::<Real>::function(types.clone(), ret);
+                    let labels = cons.labels.iter().map(|(label, _)| label.clone()).collect();
+                    let attributes = cons.attributes.clone();
+                    let docs = cons.docs.clone();
+                    cons_types.push((cons.name.clone(), types, labels, typ, docs, attributes));
                 }
 
-                for (name, arity, mut cons_typ) in cons_types {
+                for (name, args, labels, mut cons_typ, docs, attributes) in cons_types {
                     for (name, binder) in type_decl.binders.iter().rev() {
                         cons_typ = Type::forall(Forall {
                             name: name.clone(),
@@ -279,10 +367,18 @@ impl Declare for TypeDecl {
                         });
                     }
 
-                    ctx.modules
-                        .get(&name.path)
-                        .constructors
-                        .insert(name.name.clone(), (cons_typ, arity, self.name.clone()));
+                    ctx.modules.get_or_create(&name.path).constructors.insert(
+                        name.name.clone(),
+                        ConstructorData {
+                            typ: cons_typ,
+                            arity: args.len(),
+                            args,
+                            labels,
+                            type_name: self.name.clone(),
+                            docs,
+                            attributes,
+                        },
+                    );
                 }
 
                 elaborated::TypeDecl::Enum(constructors)
@@ -290,14 +386,16 @@ impl Declare for TypeDecl {
             TypeDef::Record(rec) => {
                 let mut types = Vec::new();
                 let mut names = Vec::new();
+                let mut seen = HashMap::new();
 
                 for field in &rec.fields {
+                    check_duplicate(ctx, &mut seen, &field.0.name, &field.3);
                     names.push(field.0.clone());
 
                     let (typ, kind) = field.1.infer((ctx, env.clone()));
                     env.set_current_span(field.1.span.clone());
 
-                    ctx.subsumes(env.clone(), kind, Kind::typ());
+                    ctx.expect_type_kind(&env, kind);
 
                     types.push(typ);
                 }
@@ -312,27 +410,137 @@ impl Declare for TypeDecl {
                     }
 
                     ctx.modules
-                        .get(&name.path)
+                        .get_or_create(&name.path)
                         .fields
                         .insert(name.name.clone(), typ);
                 }
 
+                for (name, _, _, _, docs) in &rec.fields {
+                    if let Some(docs) = docs {
+                        ctx.modules
+                            .get_or_create(&name.path)
+                            .field_docs
+                            .insert(name.name.clone(), docs.clone());
+                    }
+                }
+
                 elaborated::TypeDecl::Record(names)
             }
-            TypeDef::Synonym(_) => todo!(),
+            // Synonyms are not expanded yet, so they are treated as opaque types.
+            TypeDef::Synonym(_) => elaborated::TypeDecl::Abstract,
             TypeDef::Abstract => elaborated::TypeDecl::Abstract,
         };
 
+        // The kinds of the binders were inferred from their uses, and the binders that are not
+        // used in a way that tells their kind, like the `a` of `type Proxy a`, are types.
+        let mut holes = Vec::new();
+        type_decl.kind.quote(env.level).holes(&mut holes);
+
+        for hole in holes {
+            hole.fill(Type::typ());
+        }
+
+        if let Some(data) = ctx
+            .modules
+            .get_or_create(&self.name.path)
+            .types
+            .get_mut(&self.name.name)
+        {
+            data.kind = data.kind.zonk();
+
+            for (_, kind) in data.binders.iter_mut() {
+                *kind = kind.zonk();
+            }
+        }
+
+        ctx.in_deprecated = outer;
         (self.name.clone(), decl)
     }
 }
 
+/// Adds a type or a trait to its module. A name that was already declared is reported, and the
+/// later declaration replaces the earlier one so the references to it still find something.
+fn declare_type(ctx: &mut Context, name: &Qualified, data: TypeData) {
+    let span = data.span.clone();
+
+    let kind = match data.def {
+        Def::Constraint => ItemKind::Trait,
+        _ => ItemKind::Type,
+    };
+
+    ctx.resolutions.define(kind, name, span.clone());
+
+    let module = ctx.modules.get_or_create(&name.path);
+
+    if let Some(first) = module.types.insert(name.name.clone(), data) {
+        ctx.report_duplicate(name.name.clone(), span, first.span);
+    }
+}
+
+/// Adds a let or an external to its module. They share the namespace of values, so a name that was
+/// already declared by either of them is reported, and the later declaration replaces the earlier.
+fn declare_value(ctx: &mut Context, kind: ItemKind, name: &Qualified, span: Span, def: LetDef) {
+    if name.is_wildcard() {
+        ctx.modules.wildcards.insert(name.clone(), def);
+        return;
+    }
+
+    let first = ctx.resolutions.define(kind, name, span.clone());
+
+    let module = ctx.modules.get_or_create(&name.path);
+
+    if module.variables.insert(name.name.clone(), def).is_some() {
+        if let Some(first) = first {
+            ctx.report_duplicate(name.name.clone(), span, first.span);
+        }
+    }
+}
+
+/// Infers the return type that is written in a constructor like `| Lit Int : Expr Int`. It's
+/// [None] if the return type is not an application of the type being declared, because then the
+/// constructor would not build a value of it.
+fn constructor_return_type(
+    ctx: &mut Context,
+    env: &Env,
+    name: &Qualified,
+    cons: &Constructor,
+    ret: &r#abstract::Type,
+) -> Option<Type<Real>> {
+    env.set_current_span(ret.span.clone());
+    let (typ, kind) = ret.infer((ctx, env.clone()));
+    ctx.expect_type_kind(env, kind);
+
+    match typ.application_spine().0.as_ref() {
+        TypeKind::Variable(head) if head == name => Some(typ),
+        TypeKind::Error => None,
+        _ => {
+            env.set_current_span(cons.span.clone());
+            let kind =
+                TypeErrorKind::InvalidConstructorReturnType(cons.name.name.clone(), name.clone());
+            ctx.report(env, kind);
+            None
+        }
+    }
+}
+
+/// Reports a constructor or a field that has the same name as a previous one of the same type.
+fn check_duplicate(
+    ctx: &mut Context,
+    seen: &mut HashMap<Symbol, Span>,
+    name: &Symbol,
+    span: &Span,
+) {
+    if let Some(first) = seen.insert(name.clone(), span.clone()) {
+        ctx.report_duplicate(name.clone(), span.clone(), first);
+    }
+}
+
 fn get_definition_of_type(type_def: &TypeDef) -> Def {
     match type_def {
         TypeDef::Sum(cons) => Def::Enum(cons.constructors.iter().map(|x| x.name.clone()).collect()),
         TypeDef::Record(rec) => Def::Record(rec.fields.iter().map(|x| x.0.clone()).collect()),
-        TypeDef::Synonym(_) => Def::Type,
-        TypeDef::Abstract => Def::Type,
+        TypeDef::Synonym(_) => Def::Synonym,
+        TypeDef::Abstract => Def::Abstract,
     }
 }
 
@@ -340,7 +548,7 @@ impl Declare for ExtDecl {
     type Return = (Qualified, elaborated::ExternalDecl<Type<Real>>);
 
     fn declare(&self, (ctx, mut env): (&mut Context, Env)) {
-        let fvs = self.typ.data.free_variables();
+        let fvs = self.typ.free_type_variables();
 
         let start_env = env.clone();
 
@@ -353,19 +561,20 @@ impl Declare for ExtDecl {
         }
 
         let (typ, k) = self.typ.infer((ctx, env.clone()));
-        ctx.subsumes(env.clone(), k, Kind::typ());
+        ctx.expect_type_kind(&env, k);
 
-        let typ = typ.eval(&start_env);
+        let typ = ctx.interner.intern(&typ.eval(&start_env));
 
-        ctx.modules.get(&self.namespace).variables.insert(
-            self.name.name.clone(),
-            LetDef {
-                typ: typ.clone(),
-                unbound,
-                ret: typ.clone(),
-                args: vec![],
-            },
-        );
+        let def = LetDef {
+            typ: typ.clone(),
+            unbound,
+            ret: typ.clone(),
+            args: vec![],
+            docs: None,
+            attributes: Vec::new(),
+        };
+
+        declare_value(ctx, ItemKind::External, &self.name, self.span.clone(), def);
 
         ctx.elaborated.externals.insert(
             self.name.clone(),
@@ -395,18 +604,10 @@ impl Declare for LetDecl {
     type Return = (Qualified, elaborated::LetDecl<Type<Real>>);
 
     fn declare(&self, (ctx, mut env): (&mut Context, Env)) {
+        let outer = ctx.enter_item(&self.attributes);
         let start_env = env.clone();
 
-        let mut fvs = self
-            .signature
-            .ret
-            .as_ref()
-            .map(|x| x.data.free_variables())
-            .unwrap_or_default();
-
-        for arg in &self.signature.binders {
-            fvs.extend(arg.typ().data.free_variables());
-        }
+        let fvs = free_variables(&self.signature);
 
         let mut unbound = Vec::new();
 
@@ -422,7 +623,7 @@ impl Declare for LetDecl {
             let (typ, kind) = arg.typ().infer((ctx, env.clone()));
             env.set_current_span(arg.typ().span.clone());
 
-            ctx.subsumes(env.clone(), kind, Kind::typ());
+            ctx.expect_type_kind(&env, kind);
 
             args.push(typ);
         }
@@ -430,7 +631,7 @@ impl Declare for LetDecl {
         let ret = if let Some(ret) = &self.signature.ret {
             let (typ, kind) = ret.infer((ctx, env.clone()));
             env.set_current_span(ret.span.clone());
-            ctx.subsumes(env.clone(), kind, Kind::typ());
+            ctx.expect_type_kind(&env, kind);
 
             typ
         } else {
@@ -449,24 +650,28 @@ impl Declare for LetDecl {
             });
         }
 
-        ctx.modules
-            .get(&self.signature.name.path.clone())
-            .variables
-            .insert(
-                self.signature.name.name.clone(),
-                LetDef {
-                    typ: typ.eval(&start_env),
-                    unbound,
-                    ret: ret.eval(&env),
-                    args: func_args,
-                },
-            );
+        let def = LetDef {
+            typ: ctx.interner.intern(&typ.eval(&start_env)),
+            unbound,
+            ret: ctx.interner.intern(&ret.eval(&env)),
+            args: func_args,
+            docs: self.docs.clone(),
+            attributes: self.attributes.clone(),
+        };
+
+        let span = self.signature.span.clone();
+        declare_value(ctx, ItemKind::Let, &self.signature.name, span, def);
+
+        ctx.check_attributes(ItemKind::Let, &self.signature.name, &self.attributes);
+        ctx.in_deprecated = outer;
     }
 
     fn define(&self, (ctx, mut env): (&mut Context, Env)) -> Self::Return {
+        let outer = ctx.enter_item(&self.attributes);
         env.set_current_span(self.signature.span.clone());
+        ctx.exceeded = false;
 
-        let let_decl = ctx.modules.let_decl(&self.signature.name).clone();
+        let let_decl = ctx.modules.let_def(&self.signature.name).clone();
 
         for (fv, typ) in &let_decl.unbound {
             env = env.add(Some(fv.clone()), typ.eval(&env).clone());
@@ -489,25 +694,36 @@ impl Declare for LetDecl {
             env.add_var(binder.0, binder.1);
         }
 
+        let mut bound = Vec::new();
+        for binder in &self.signature.binders {
+            if let LetBinder::Param(binder) = binder {
+                unused::binders(&binder.pat, &mut bound);
+            }
+        }
+        ctx.add_binders(&mut env, &bound);
+
         let typ = let_decl.ret.clone();
         let binders = elab_binders;
 
         ctx.errored = false;
 
-        let body = self.body.check(typ.clone(), (ctx, env.clone()));
+        let annotation = self.signature.ret.as_ref().map(|ret| ret.span.clone());
+
+        let body = ctx.with_annotation(annotation, |ctx| {
+            self.body.check(typ.clone(), (ctx, env.clone()))
+        });
+        ctx.report_unused(&bound);
+
         let types = typ.arrow_spine();
 
         if !ctx.errored {
             let problem = Problem::exhaustiveness(&body, types);
-            let patterns = &self.body.last().unwrap().patterns;
+            let first = self.body.first().unwrap();
+            let last = self.body.last().unwrap();
 
-            if patterns.first().is_some() {
-                env.set_current_span(patterns
-                    .first()
-                    .unwrap()
-                    .span
-                    .clone()
-                    .mix(patterns.last().unwrap().span.clone()));
+            // The missing cases are about all of the clauses, not only about the last one.
+            if !last.patterns.is_empty() {
+                env.set_current_span(first.patterns_span().mix(last.patterns_span()));
 
                 if let Witness::NonExhaustive(case) = problem.exaustive(ctx, env.clone()) {
                     ctx.report(&env, TypeErrorKind::NonExhaustive(case));
@@ -515,6 +731,8 @@ impl Declare for LetDecl {
             }
         }
 
+        ctx.in_deprecated = outer;
+
         (
             self.signature.name.clone(),
             elaborated::LetDecl {
@@ -529,50 +747,238 @@ impl Declare for LetDecl {
 
 pub struct Programs(pub Vec<Program>);
 
+impl Programs {
+    /// The programs together with the programs of their inline modules. Each one comes before the
+    /// modules declared inside of it.
+    pub(crate) fn all(&self) -> Vec<&Program> {
+        fn go<'a>(program: &'a Program, all: &mut Vec<&'a Program>) {
+            all.push(program);
+
+            for module in &program.modules {
+                if let Some(decls) = &module.decls {
+                    go(decls, all);
+                }
+            }
+        }
+
+        let mut all = Vec::new();
+
+        for program in &self.0 {
+            go(program, &mut all);
+        }
+
+        all
+    }
+}
+
+/// Puts the elaborated programs of the inline modules inside of the program that declares them.
+/// The `elaborated` programs come in the order of [Programs::all].
+fn nest_modules(
+    program: &Program,
+    elaborated: &mut impl Iterator<Item = elaborated::Program<Type<Real>>>,
+) -> elaborated::Program<Type<Real>> {
+    let mut result = elaborated.next().unwrap();
+
+    for module in &program.modules {
+        if let Some(decls) = &module.decls {
+            let nested = nest_modules(decls, elaborated);
+            result.modules.insert(module.name.clone(), nested);
+        }
+    }
+
+    result
+}
+
 impl Declare for Programs {
     type Return = Vec<elaborated::Program<Type<Real>>>;
 
     fn declare(&self, (ctx, env): (&mut Context, Env)) {
-        for program in self.0.iter() {
+        let all = self.all();
+
+        for program in all.iter() {
             program.types.declare((ctx, env.clone()));
+
+            let cycles = order::synonym_cycles(&program.types);
+
+            // Synonyms are not expanded yet, so the ones that are not part of a cycle are reported
+            // as not implemented.
+            for (i, decl) in program.types.iter().enumerate() {
+                let cyclic = cycles.iter().flatten().any(|x| *x == i);
+
+                if matches!(decl.def, TypeDef::Synonym(_)) && !cyclic {
+                    env.set_current_span(decl.span.clone());
+                    ctx.report(&env, TypeErrorKind::NotImplemented);
+                }
+            }
+
+            for cycle in cycles {
+                let cycle = cycle
+                    .into_iter()
+                    .map(|i| (program.types[i].name.clone(), program.types[i].span.clone()))
+                    .collect();
+
+                ctx.report_cycle(cycle);
+            }
         }
 
-        for program in self.0.iter() {
+        for program in all.iter() {
             program.lets.declare((ctx, env.clone()));
         }
 
-        for program in self.0.iter() {
+        for program in all.iter() {
             program.externals.declare((ctx, env.clone()));
         }
 
-        for program in self.0.iter() {
+        // The derived functions are declared after the lets and externals, so they know which
+        // functions the types of the fields have.
+        let derived = ctx.derive(&env, &all);
+
+        for (_, decl) in &derived {
+            decl.declare((ctx, env.clone()));
+        }
+
+        ctx.derived = derived;
+
+        for program in all.iter() {
             program.traits.declare((ctx, env.clone()));
         }
     }
 
     fn define(&self, (context, env): (&mut Context, Env)) -> Self::Return {
-        let mut programs = vec![elaborated::Program::default(); self.0.len()];
+        let all = self.all();
+        let mut programs = vec![elaborated::Program::default(); all.len()];
 
-        for (i, program) in self.0.iter().enumerate() {
+        for (i, program) in all.iter().enumerate() {
             let typ = program.types.define((context, env.clone()));
             programs[i].types = typ.into_iter().collect();
         }
 
-        for (i, program) in self.0.iter().enumerate() {
-            let let_decl = program.lets.define((context, env.clone()));
-            programs[i].lets = let_decl.into_iter().collect();
+        // The programs are defined in groups, so only the functions derived by this group are
+        // taken and the others are left for their own groups.
+        let mut derived = Vec::new();
+
+        for (name, decl) in std::mem::take(&mut context.derived) {
+            match all.iter().position(|program| program.name == name) {
+                Some(i) => derived.push((i, decl)),
+                None => context.derived.push((name, decl)),
+            }
         }
 
-        for (i, program) in self.0.iter().enumerate() {
+        let lets = all
+            .iter()
+            .enumerate()
+            .flat_map(|(i, program)| program.lets.iter().map(move |decl| (i, decl)))
+            .chain(derived.iter().map(|(i, decl)| (*i, decl)))
+            .collect::<Vec<_>>();
+
+        let decls = lets.iter().map(|(_, decl)| *decl).collect::<Vec<_>>();
+
+        let mut defined = (0..lets.len()).map(|_| None).collect::<Vec<_>>();
+
+        // Each group is defined after the groups that it uses, so the types of the declarations
+        // of a group can be generalized as soon as all of them are checked.
+        for group in order::let_groups(&decls) {
+            let names = group
+                .iter()
+                .map(|index| lets[*index].1.signature.name.clone())
+                .collect::<Vec<_>>();
+
+            context.monomorphic = names
+                .iter()
+                .map(|name| context.modules.let_def(name).typ.clone())
+                .collect();
+
+            let mut errored = Vec::new();
+
+            for index in &group {
+                let (_, decl) = lets[*index];
+                defined[*index] = Some(decl.define((context, env.clone())));
+                errored.push(context.errored);
+            }
+
+            context.monomorphic.clear();
+
+            // A group is generalized only if all of its declarations are, because they share
+            // their holes.
+            let generalizes = group
+                .iter()
+                .all(|index| env.restriction.generalizes_decl(lets[*index].1));
+
+            for (i, name) in names.iter().enumerate().filter(|_| generalizes) {
+                let typ = context.modules.let_def(name).typ.clone();
+                let real = typ.quote(env.level);
+
+                // A body with errors leaves its result unknown, and that was already reported. The
+                // result is still generalized, so the uses of the function do not report it again.
+                if !errored[i] && unconstrained_result(&real) {
+                    env.set_current_span(lets[group[i]].1.signature.span.clone());
+                    let kind = TypeErrorKind::AmbiguousType(name.clone(), real, false);
+                    context.report(&env, kind);
+                }
+
+                context.modules.let_decl(name).typ = context.generalize(&env, &typ);
+            }
+        }
+
+        // The groups are in the order of their dependencies, but the declarations are kept in the
+        // order of the source.
+        for ((i, _), defined) in lets.iter().zip(defined) {
+            let (name, let_decl) = defined.unwrap();
+            programs[*i].lets.insert(name, let_decl);
+        }
+
+        for (i, program) in all.iter().enumerate() {
             let ext_decl = program.externals.define((context, env.clone()));
             programs[i].externals = ext_decl.into_iter().collect();
         }
 
-        for (i, program) in self.0.iter().enumerate() {
+        for (i, program) in all.iter().enumerate() {
             let _trait_decl = program.traits.define((context, env.clone()));
             programs[i].commands = program.commands.clone();
         }
 
-        programs
+        // Holes that survive until here are not solved by this group, so public definitions that
+        // contain them are reported before the interfaces are closed, and the users of the
+        // definitions see errors instead of holes that the first of them would fill.
+        for decl in all.iter().flat_map(|program| program.lets.iter()) {
+            if decl.signature.visibility != Visibility::Public {
+                continue;
+            }
+
+            let typ = context
+                .modules
+                .let_def(&decl.signature.name)
+                .typ
+                .quote(env.level);
+
+            let mut holes = Vec::new();
+            typ.holes(&mut holes);
+            typ.kind_holes(&mut holes);
+
+            if !holes.is_empty() {
+                env.set_current_span(decl.signature.span.clone());
+                let restricted = !env.restriction.generalizes_decl(decl);
+                let name = decl.signature.name.clone();
+                let kind = TypeErrorKind::AmbiguousType(name, typ, restricted);
+                context.report(&env, kind);
+            }
+        }
+
+        let names = all.iter().map(|program| &program.name).collect::<Vec<_>>();
+        context.modules.close(&names);
+
+        context.report_private_types(&all);
+        context.report_typed_holes();
+
+        if context.warn_dead_code {
+            context.report_dead_code(&self.0.iter().collect::<Vec<_>>(), &all);
+        }
+
+        let mut programs = programs.into_iter();
+
+        self.0
+            .iter()
+            .map(|program| nest_modules(program, &mut programs))
+            .collect()
     }
 }
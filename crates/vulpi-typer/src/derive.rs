@@ -0,0 +1,616 @@
+//! The functions that the compiler writes for the types that ask for them with `derive`. A type
+//! `Foo` that derives `Eq` gets `eqFoo : Foo -> Foo -> Bool`, and one that derives `Show` gets
+//! `showFoo : Foo -> String`. Each parameter of the type adds a parameter with the function for it
+//! before the others, so `eqList` takes an `a -> a -> Bool` to compare the elements of a `List a`.
+//!
+//! The functions are written as abstract declarations, so they are declared, checked and
+//! elaborated like the ones that the user writes.
+
+use std::collections::HashSet;
+
+use vulpi_intern::{well_known, Symbol};
+use vulpi_location::{Span, Spanned};
+use vulpi_report::Diagnostic;
+use vulpi_syntax::r#abstract::{
+    AppKind, ApplicationExpr, Binder, Derive, Expr, ExprKind, LetBinder, LetDecl, LetSignature,
+    LiteralKind, Node, NodeId, PatApplication, Pattern, PatternArm, PatternKind, PiType, Program,
+    ProjectionExpr, Qualified, Type, TypeApplication, TypeDecl, TypeDef, TypeKind, WhenExpr,
+};
+
+use crate::{
+    builtins::Builtin,
+    context::Context,
+    errors::{TypeError, TypeErrorKind, Underivable},
+    Env,
+};
+
+/// Builds the nodes of a derived function. All of them are at the location of the derive, and
+/// their ids come after the ones of the programs.
+struct Builder {
+    next: u32,
+    span: Span,
+
+    /// The ids of the variables that are bound, so they are never reported as unused.
+    bound: Vec<NodeId>,
+}
+
+impl Builder {
+    fn node<T>(&mut self, data: T) -> Box<Node<T>> {
+        self.next += 1;
+        Box::new(Node::new(NodeId(self.next - 1), data, self.span.clone()))
+    }
+
+    fn var(&mut self, name: &str) -> Expr {
+        self.node(ExprKind::Variable(Symbol::intern(name)))
+    }
+
+    fn apply(&mut self, func: Expr, args: Vec<Expr>) -> Expr {
+        self.node(ExprKind::Application(ApplicationExpr {
+            app: AppKind::Normal,
+            func,
+            args,
+        }))
+    }
+
+    fn text(&mut self, text: &str) -> Expr {
+        let literal = Spanned::new(LiteralKind::String(Symbol::intern(text)), self.span.clone());
+        self.node(ExprKind::Literal(Box::new(literal)))
+    }
+
+    fn bind(&mut self, name: &str) -> Pattern {
+        let pattern = self.node(PatternKind::Variable(Symbol::intern(name)));
+        self.bound.push(pattern.id);
+        pattern
+    }
+
+    fn constructor(&mut self, func: Qualified, args: Vec<Pattern>) -> Pattern {
+        self.node(PatternKind::Application(PatApplication { func, args }))
+    }
+
+    fn when(&mut self, scrutinee: Vec<Expr>, arms: Vec<(Vec<Pattern>, Expr)>) -> Expr {
+        let arms = arms
+            .into_iter()
+            .map(|(patterns, expr)| PatternArm {
+                patterns,
+                expr,
+                guard: None,
+            })
+            .collect();
+
+        self.node(ExprKind::When(WhenExpr { scrutinee, arms }))
+    }
+
+    fn arrow(&mut self, left: Type, right: Type) -> Type {
+        self.node(TypeKind::Arrow(PiType { left, right }))
+    }
+
+    fn named(&mut self, name: &Option<Qualified>) -> Type {
+        match name {
+            Some(name) => self.node(TypeKind::Type(name.clone())),
+            None => self.node(TypeKind::Error),
+        }
+    }
+
+    fn param(&mut self, name: &str, typ: Type) -> LetBinder {
+        LetBinder::Param(Binder {
+            pat: self.bind(name),
+            typ,
+        })
+    }
+}
+
+/// A part of the text written by a `show` function.
+enum Piece {
+    Text(String),
+    Expr(Expr),
+}
+
+/// Writes one derived function of a type.
+struct Deriver<'a> {
+    ctx: &'a mut Context,
+    build: Builder,
+    derive: Derive,
+    decl: &'a TypeDecl,
+
+    /// The functions that are derived by all of the programs.
+    derived: &'a HashSet<Qualified>,
+
+    /// If a field was reported, so the body of the function is an error.
+    failed: bool,
+}
+
+impl Deriver<'_> {
+    fn report(&mut self, span: Span, reason: Underivable) {
+        self.failed = true;
+
+        let kind = TypeErrorKind::CannotDerive(self.derive, self.decl.name.name.clone(), reason);
+
+        let diagnostic = Diagnostic::new(TypeError {
+            span,
+            kind,
+            budget: self.ctx.budget,
+        });
+
+        let span = self.build.span.clone();
+        self.ctx
+            .reporter
+            .report(diagnostic.with_label(span, "derived here"));
+    }
+
+    /// The name of the parameter with the function for a type parameter.
+    fn param_name(&self, binder: &Symbol) -> String {
+        format!(
+            "{}{}",
+            self.derive.function(&well_known::EMPTY).get(),
+            binder.get()
+        )
+    }
+
+    fn exists(&self, name: &Qualified) -> bool {
+        self.derived.contains(name)
+            || self
+                .ctx
+                .modules
+                .get(&name.path)
+                .is_some_and(|module| module.variables.contains_key(&name.name))
+    }
+
+    /// The function that does the work of the derive for a value of the type.
+    fn operation(&mut self, typ: &Type) -> Option<Expr> {
+        match &typ.data {
+            TypeKind::TypeVariable(name)
+                if self.decl.binders.iter().any(|binder| binder.name() == name) =>
+            {
+                let name = self.param_name(name);
+                Some(self.build.var(&name))
+            }
+            TypeKind::Type(name) => {
+                let function = Qualified {
+                    path: name.path.clone(),
+                    name: self.derive.function(&name.name),
+                };
+
+                if self.exists(&function) {
+                    Some(self.build.node(ExprKind::Function(function)))
+                } else {
+                    self.report(typ.span.clone(), Underivable::Missing(function));
+                    None
+                }
+            }
+            TypeKind::Application(app) if matches!(app.func.data, TypeKind::Type(_)) => {
+                let func = self.operation(&app.func)?;
+                let args = app
+                    .args
+                    .iter()
+                    .map(|arg| self.operation(arg))
+                    .collect::<Option<Vec<_>>>()?;
+
+                Some(self.build.apply(func, args))
+            }
+            _ => {
+                self.report(typ.span.clone(), Underivable::Unsupported);
+                None
+            }
+        }
+    }
+
+    /// The operations of all of the fields, that are applied to the given arguments.
+    fn operations(&mut self, fields: &[&Type], args: Vec<Vec<Expr>>) -> Vec<Expr> {
+        let mut result = Vec::new();
+
+        // Every field is looked at, so all of the ones that cannot be derived are reported.
+        for (field, args) in fields.iter().zip(args) {
+            if let Some(op) = self.operation(field) {
+                result.push(self.build.apply(op, args));
+            }
+        }
+
+        result
+    }
+
+    /// The type that the function is derived for, applied to its parameters.
+    fn applied(&mut self) -> Type {
+        let name = self.build.node(TypeKind::Type(self.decl.name.clone()));
+
+        if self.decl.binders.is_empty() {
+            return name;
+        }
+
+        let args = self
+            .decl
+            .binders
+            .iter()
+            .map(|binder| {
+                self.build
+                    .node(TypeKind::TypeVariable(binder.name().clone()))
+            })
+            .collect();
+
+        self.build
+            .node(TypeKind::Application(TypeApplication { func: name, args }))
+    }
+
+    /// The parameters with the functions for the type parameters, where each one takes `arity`
+    /// values and returns a value of the type `ret`.
+    fn params(&mut self, arity: usize, ret: &Option<Qualified>) -> Vec<LetBinder> {
+        let mut params = Vec::new();
+
+        for binder in &self.decl.binders {
+            let mut typ = self.build.named(ret);
+
+            for _ in 0..arity {
+                let var = self
+                    .build
+                    .node(TypeKind::TypeVariable(binder.name().clone()));
+                typ = self.build.arrow(var, typ);
+            }
+
+            let name = self.param_name(binder.name());
+            params.push(self.build.param(&name, typ));
+        }
+
+        params
+    }
+
+    fn constructor_of(&self, typ: &Option<Qualified>, name: Symbol) -> Option<Qualified> {
+        let constructors = self.ctx.modules.constructors_of(typ.as_ref()?)?;
+        constructors.iter().find(|x| x.name == name).cloned()
+    }
+
+    fn bool(&mut self, value: &Option<Qualified>) -> Expr {
+        match value {
+            Some(name) => self.build.node(ExprKind::Constructor(name.clone())),
+            None => self.build.node(ExprKind::Error),
+        }
+    }
+
+    /// The conjunction of the tests, that checks each one only if the ones before it are true.
+    fn all(&mut self, bool: &Option<Qualified>, tests: Vec<Expr>) -> Expr {
+        let true_ = self.constructor_of(bool, well_known::TRUE);
+        let false_ = self.constructor_of(bool, well_known::FALSE);
+
+        let mut tests = tests.into_iter().rev();
+
+        let Some(mut result) = tests.next() else {
+            return self.bool(&true_);
+        };
+
+        for test in tests {
+            let pattern = match &true_ {
+                Some(name) => self.build.constructor(name.clone(), Vec::new()),
+                None => self.build.node(PatternKind::Error),
+            };
+
+            let wildcard = self.build.node(PatternKind::Wildcard);
+            let otherwise = self.bool(&false_);
+
+            result = self.build.when(
+                vec![test],
+                vec![(vec![pattern], result), (vec![wildcard], otherwise)],
+            );
+        }
+
+        result
+    }
+
+    fn eq(&mut self, bool: Option<Qualified>) -> (Vec<LetBinder>, Type, Expr) {
+        let mut params = self.params(2, &bool);
+        let left = self.applied();
+        params.push(self.build.param("left", left));
+        let right = self.applied();
+        params.push(self.build.param("right", right));
+
+        let ret = self.build.named(&bool);
+
+        let body = match &self.decl.def {
+            TypeDef::Sum(sum) if !sum.constructors.is_empty() => {
+                let mut arms = Vec::new();
+
+                for cons in &sum.constructors {
+                    let names = (0..cons.args.len()).map(|i| (format!("x{i}"), format!("y{i}")));
+                    let (xs, ys): (Vec<_>, Vec<_>) = names.unzip();
+
+                    let args = xs
+                        .iter()
+                        .zip(&ys)
+                        .map(|(x, y)| vec![self.build.var(x), self.build.var(y)])
+                        .collect();
+
+                    let fields = cons.args.iter().collect::<Vec<_>>();
+                    let tests = self.operations(&fields, args);
+                    let expr = self.all(&bool, tests);
+
+                    let xs = xs.iter().map(|x| self.build.bind(x)).collect();
+                    let ys = ys.iter().map(|y| self.build.bind(y)).collect();
+                    let left = self.build.constructor(cons.name.clone(), xs);
+                    let right = self.build.constructor(cons.name.clone(), ys);
+
+                    arms.push((vec![left, right], expr));
+                }
+
+                if sum.constructors.len() > 1 {
+                    let false_ = self.constructor_of(&bool, well_known::FALSE);
+                    let wildcards = vec![
+                        self.build.node(PatternKind::Wildcard),
+                        self.build.node(PatternKind::Wildcard),
+                    ];
+                    let expr = self.bool(&false_);
+                    arms.push((wildcards, expr));
+                }
+
+                let scrutinee = vec![self.build.var("left"), self.build.var("right")];
+                self.build.when(scrutinee, arms)
+            }
+            TypeDef::Record(record) => {
+                let args = record
+                    .fields
+                    .iter()
+                    .map(|(name, ..)| {
+                        ["left", "right"]
+                            .into_iter()
+                            .map(|value| {
+                                let expr = self.build.var(value);
+                                self.build.node(ExprKind::Projection(ProjectionExpr {
+                                    expr,
+                                    field: name.name.clone(),
+                                }))
+                            })
+                            .collect()
+                    })
+                    .collect();
+
+                let fields = record.fields.iter().map(|x| &x.1).collect::<Vec<_>>();
+                let tests = self.operations(&fields, args);
+                self.all(&bool, tests)
+            }
+            _ => {
+                self.report(self.build.span.clone(), Underivable::Empty);
+                self.build.node(ExprKind::Error)
+            }
+        };
+
+        (params, ret, body)
+    }
+
+    /// Joins the pieces of a text with the `concat` of the prelude.
+    fn concat(&mut self, pieces: Vec<Piece>) -> Expr {
+        let mut merged: Vec<Piece> = Vec::new();
+
+        for piece in pieces {
+            match (merged.last_mut(), piece) {
+                (Some(Piece::Text(last)), Piece::Text(text)) => last.push_str(&text),
+                (_, piece) => merged.push(piece),
+            }
+        }
+
+        let concat = Qualified {
+            path: self.ctx.prelude.clone(),
+            name: well_known::CONCAT,
+        };
+
+        if merged.len() > 1 && !self.exists(&concat) {
+            self.report(self.build.span.clone(), Underivable::Missing(concat));
+            return self.build.node(ExprKind::Error);
+        }
+
+        let mut exprs = merged.into_iter().rev().map(|piece| match piece {
+            Piece::Text(text) => self.build.text(&text),
+            Piece::Expr(expr) => expr,
+        });
+
+        let mut result = exprs.next().unwrap();
+
+        for expr in exprs.collect::<Vec<_>>() {
+            let func = self.build.node(ExprKind::Function(concat.clone()));
+            result = self.build.apply(func, vec![expr, result]);
+        }
+
+        result
+    }
+
+    /// The pieces of the text of a value with the given name and fields, where the fields are
+    /// written by position if they have no labels.
+    fn pieces(name: &Symbol, labels: &[Symbol], fields: Vec<Expr>) -> Vec<Piece> {
+        if fields.is_empty() {
+            return vec![Piece::Text(name.get())];
+        }
+
+        let mut pieces = Vec::new();
+
+        for (i, field) in fields.into_iter().enumerate() {
+            let text = match (i, labels.get(i)) {
+                (0, Some(label)) => format!("{} {{ {} = ", name.get(), label.get()),
+                (0, None) => format!("{}(", name.get()),
+                (_, Some(label)) => format!(", {} = ", label.get()),
+                (_, None) => ", ".to_string(),
+            };
+
+            pieces.push(Piece::Text(text));
+            pieces.push(Piece::Expr(field));
+        }
+
+        let end = if labels.is_empty() { ")" } else { " }" };
+        pieces.push(Piece::Text(end.to_string()));
+
+        pieces
+    }
+
+    fn show(&mut self, string: Option<Qualified>) -> (Vec<LetBinder>, Type, Expr) {
+        let mut params = self.params(1, &string);
+        let value = self.applied();
+        params.push(self.build.param("value", value));
+
+        let ret = self.build.named(&string);
+
+        let body = match &self.decl.def {
+            TypeDef::Sum(sum) if !sum.constructors.is_empty() => {
+                let mut arms = Vec::new();
+
+                for cons in &sum.constructors {
+                    let xs = (0..cons.args.len())
+                        .map(|i| format!("x{i}"))
+                        .collect::<Vec<_>>();
+                    let args = xs.iter().map(|x| vec![self.build.var(x)]).collect();
+
+                    let fields = cons.args.iter().collect::<Vec<_>>();
+                    let shown = self.operations(&fields, args);
+
+                    let labels = cons.labels.iter().map(|x| x.0.clone()).collect::<Vec<_>>();
+                    let pieces = Self::pieces(&cons.name.name, &labels, shown);
+                    let expr = self.concat(pieces);
+
+                    let xs = xs.iter().map(|x| self.build.bind(x)).collect();
+                    let pattern = self.build.constructor(cons.name.clone(), xs);
+
+                    arms.push((vec![pattern], expr));
+                }
+
+                let scrutinee = vec![self.build.var("value")];
+                self.build.when(scrutinee, arms)
+            }
+            TypeDef::Record(record) => {
+                let args = record
+                    .fields
+                    .iter()
+                    .map(|(name, ..)| {
+                        let expr = self.build.var("value");
+                        vec![self.build.node(ExprKind::Projection(ProjectionExpr {
+                            expr,
+                            field: name.name.clone(),
+                        }))]
+                    })
+                    .collect();
+
+                let fields = record.fields.iter().map(|x| &x.1).collect::<Vec<_>>();
+                let shown = self.operations(&fields, args);
+
+                let labels = record
+                    .fields
+                    .iter()
+                    .map(|x| x.0.name.clone())
+                    .collect::<Vec<_>>();
+                let pieces = Self::pieces(&self.decl.name.name, &labels, shown);
+                self.concat(pieces)
+            }
+            _ => {
+                self.report(self.build.span.clone(), Underivable::Empty);
+                self.build.node(ExprKind::Error)
+            }
+        };
+
+        (params, ret, body)
+    }
+
+    fn derive(mut self, env: &Env) -> (LetDecl, Builder) {
+        let (binders, ret, body) = match self.derive {
+            Derive::Eq => {
+                let bool = self.builtin(env, Builtin::Bool);
+                self.eq(bool)
+            }
+            Derive::Show => {
+                let string = self.builtin(env, Builtin::String);
+                self.show(string)
+            }
+        };
+
+        let body = if self.failed {
+            self.build.node(ExprKind::Error)
+        } else {
+            body
+        };
+
+        let signature = LetSignature {
+            span: self.build.span.clone(),
+            visibility: self.decl.visibility.clone(),
+            name: Qualified {
+                path: self.decl.name.path.clone(),
+                name: self.derive.function(&self.decl.name.name),
+            },
+            binders,
+            ret: Some(ret),
+        };
+
+        // The functions of a deprecated type are deprecated too.
+        let attributes = self
+            .decl
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.name == well_known::DEPRECATED)
+            .cloned()
+            .collect();
+
+        let decl = LetDecl {
+            id: self.build.node(()).id,
+            signature,
+            docs: None,
+            attributes,
+            body: vec![PatternArm {
+                patterns: Vec::new(),
+                expr: body,
+                guard: None,
+            }],
+            constant: None,
+        };
+
+        (decl, self.build)
+    }
+
+    fn builtin(&mut self, env: &Env, builtin: Builtin) -> Option<Qualified> {
+        match self.ctx.builtin(builtin, env).as_ref() {
+            crate::TypeKind::Variable(name) => Some(name.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl Context {
+    /// Writes the functions that the types of the programs derive, together with the name of the
+    /// program of each one. The fields that cannot be derived are reported, and the functions
+    /// that need them are still written with an error as their body, so they can be used.
+    pub(crate) fn derive(&mut self, env: &Env, programs: &[&Program]) -> Vec<(Symbol, LetDecl)> {
+        let derived = programs
+            .iter()
+            .flat_map(|program| &program.types)
+            .flat_map(|decl| {
+                decl.derives.iter().map(|(derive, _)| Qualified {
+                    path: decl.name.path.clone(),
+                    name: derive.function(&decl.name.name),
+                })
+            })
+            .collect::<HashSet<_>>();
+
+        let mut next = programs.iter().map(|x| x.next.0).max().unwrap_or(0);
+        let mut lets = Vec::new();
+
+        for program in programs {
+            for decl in &program.types {
+                for (derive, span) in &decl.derives {
+                    env.set_current_span(span.clone());
+
+                    let deriver = Deriver {
+                        ctx: self,
+                        build: Builder {
+                            next,
+                            span: span.clone(),
+                            bound: Vec::new(),
+                        },
+                        derive: *derive,
+                        decl,
+                        derived: &derived,
+                        failed: false,
+                    };
+
+                    let (decl, build) = deriver.derive(env);
+
+                    next = build.next;
+                    let file = span.file;
+                    self.used
+                        .extend(build.bound.into_iter().map(|id| (file, id)));
+                    lets.push((program.name.clone(), decl));
+                }
+            }
+        }
+
+        lets
+    }
+}
@@ -0,0 +1,208 @@
+//! Shortening of the types that are shown in diagnostics. Comparing two big types would otherwise
+//! print both of them whole, so the parts that are far from the place where they differ are
+//! replaced by `…`.
+
+use vulpi_intern::{well_known, Symbol};
+use vulpi_syntax::r#abstract::Qualified;
+
+use crate::{
+    errors::Breadcrumb,
+    real::{Arrow, Forall, Real},
+    Env, Type, TypeKind,
+};
+
+/// How much of a type is shown in a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TypeBudget {
+    /// The number of types that are shown before the others are elided.
+    pub nodes: usize,
+
+    /// How deep inside of the type the types are shown.
+    pub depth: usize,
+
+    /// The number of characters of the rendered type. Longer ones are cut.
+    pub length: usize,
+}
+
+impl Default for TypeBudget {
+    fn default() -> Self {
+        Self {
+            nodes: 8,
+            depth: 6,
+            length: 160,
+        }
+    }
+}
+
+/// The type that stands for the parts that were elided. It's printed as `…`.
+fn ellipsis() -> Type<Real> {
+    Type::variable(Qualified {
+        path: well_known::EMPTY,
+        name: Symbol::intern("…"),
+    })
+}
+
+struct Elider<'a> {
+    budget: &'a TypeBudget,
+    used: usize,
+    elided: bool,
+}
+
+impl Elider<'_> {
+    /// Shortens a type. The types along the `path` are always shown, and the path is `None` for
+    /// the types that are not on it. Elided types return `None`.
+    fn go(
+        &mut self,
+        typ: &Type<Real>,
+        path: Option<&[Breadcrumb]>,
+        depth: usize,
+    ) -> Option<Type<Real>> {
+        if path.is_none() && (self.used >= self.budget.nodes || depth >= self.budget.depth) {
+            self.elided = true;
+            return None;
+        }
+
+        self.used += 1;
+
+        let next = path.and_then(|path| path.split_first());
+
+        // Gets the path of a child if the next step goes into it.
+        let child = |step: fn(&Breadcrumb) -> bool| match next {
+            Some((crumb, rest)) if step(crumb) => Some(rest),
+            _ => None,
+        };
+
+        let result = match typ.as_ref() {
+            TypeKind::Arrow(arrow) => {
+                let typ = self.go(
+                    &arrow.typ,
+                    child(|c| matches!(c, Breadcrumb::Argument)),
+                    depth + 1,
+                );
+                let body = self.go(
+                    &arrow.body,
+                    child(|c| matches!(c, Breadcrumb::Return)),
+                    depth + 1,
+                );
+
+                Type::new(TypeKind::Arrow(Arrow {
+                    typ: typ.unwrap_or_else(ellipsis),
+                    body: body.unwrap_or_else(ellipsis),
+                }))
+            }
+            TypeKind::Forall(forall) => {
+                // The unifier opens foralls without taking a step.
+                let kind = self.go(&forall.kind, None, depth + 1);
+                let body = self.go(&forall.body, path, depth + 1);
+
+                Type::forall(Forall {
+                    name: forall.name.clone(),
+                    kind: kind.unwrap_or_else(ellipsis),
+                    body: body.unwrap_or_else(ellipsis),
+                })
+            }
+            TypeKind::Tuple(types) => {
+                let types = types
+                    .iter()
+                    .enumerate()
+                    .map(|(i, typ)| {
+                        let path = match next {
+                            Some((Breadcrumb::TupleElement(n), rest)) if *n == i => Some(rest),
+                            _ => None,
+                        };
+                        self.go(typ, path, depth + 1)
+                    })
+                    .collect::<Vec<_>>();
+
+                Type::tuple(collapse(types))
+            }
+            TypeKind::Application(_, _) => {
+                let (head, args) = typ.application_spine();
+
+                // The head is always shown, because the arguments mean nothing without it.
+                let head = self
+                    .go(&head, Some(&[]), depth + 1)
+                    .unwrap_or_else(ellipsis);
+
+                let args = args
+                    .iter()
+                    .enumerate()
+                    .map(|(i, arg)| {
+                        let path = match next {
+                            Some((Breadcrumb::TypeArgument(n), rest)) if *n == i + 1 => Some(rest),
+                            _ => None,
+                        };
+                        self.go(arg, path, depth + 1)
+                    })
+                    .collect::<Vec<_>>();
+
+                Type::<Real>::application(head, collapse(args))
+            }
+            TypeKind::Qualified(constraint, body) => {
+                let constraint = self.go(
+                    constraint,
+                    child(|c| matches!(c, Breadcrumb::Constraint)),
+                    depth + 1,
+                );
+                let body = self.go(
+                    body,
+                    child(|c| matches!(c, Breadcrumb::Qualified)),
+                    depth + 1,
+                );
+
+                Type::qualified(
+                    constraint.unwrap_or_else(ellipsis),
+                    body.unwrap_or_else(ellipsis),
+                )
+            }
+            _ => typ.clone(),
+        };
+
+        Some(result)
+    }
+}
+
+/// Replaces each run of elided types by a single `…`.
+fn collapse(types: Vec<Option<Type<Real>>>) -> Vec<Type<Real>> {
+    let mut result = Vec::new();
+    let mut last_elided = false;
+
+    for typ in types {
+        let elided = typ.is_none();
+
+        match typ {
+            Some(typ) => result.push(typ),
+            None if last_elided => (),
+            None => result.push(ellipsis()),
+        }
+
+        last_elided = elided;
+    }
+
+    result
+}
+
+/// Shows a type inside of a budget. The `path` goes from the outermost to the innermost type and
+/// its types are never elided. Returns the text and if something was left out of it.
+pub(crate) fn show_elided(
+    env: &Env,
+    typ: &Type<Real>,
+    path: &[Breadcrumb],
+    budget: &TypeBudget,
+) -> (String, bool) {
+    let mut elider = Elider {
+        budget,
+        used: 0,
+        elided: false,
+    };
+
+    let shortened = elider.go(typ, Some(path), 0).unwrap_or_else(ellipsis);
+    let text = shortened.show(env).to_string();
+
+    if text.chars().count() > budget.length {
+        let cut = text.chars().take(budget.length).collect::<String>();
+        return (format!("{cut}…"), true);
+    }
+
+    (text, elider.elided)
+}
@@ -0,0 +1,137 @@
+//! Validation of the entry point of a program. The root module of a program that is compiled to
+//! run must define a public function that the runtime calls, and its signature is fixed by the
+//! program that embeds the compiler.
+
+use vulpi_intern::{well_known, Symbol};
+use vulpi_location::{FileId, Span};
+use vulpi_syntax::r#abstract::{Program, Qualified, Visibility};
+
+use crate::{errors::TypeErrorKind, eval::Quote, real::Real, Context, Env, Level, Type, TypeKind};
+
+/// The type that the entry point must return.
+#[derive(Clone)]
+pub enum EntryResult {
+    /// Any type is accepted.
+    Any,
+
+    /// The unit type `()`.
+    Unit,
+
+    /// A type declared by the program, applied to any arguments.
+    Named(Qualified),
+}
+
+impl EntryResult {
+    fn accepts(&self, typ: &Type<Real>) -> bool {
+        match (self, typ.as_ref()) {
+            (_, TypeKind::Error) | (EntryResult::Any, _) => true,
+            (EntryResult::Unit, TypeKind::Tuple(types)) => types.is_empty(),
+            (EntryResult::Named(name), _) => {
+                matches!(typ.application_spine().0.as_ref(), TypeKind::Variable(head) if head == name)
+            }
+            _ => false,
+        }
+    }
+
+    fn show(&self) -> String {
+        match self {
+            EntryResult::Any => "a".to_string(),
+            EntryResult::Unit => "()".to_string(),
+            EntryResult::Named(name) => name.name.get(),
+        }
+    }
+}
+
+/// The entry point that the root module of a program must define. It must be public, and it
+/// must be a constant or a function that takes a single `()`.
+#[derive(Clone)]
+pub struct EntryPoint {
+    pub module: Symbol,
+    pub name: Symbol,
+    pub result: EntryResult,
+}
+
+impl EntryPoint {
+    /// An entry point called `main` that returns `()`.
+    pub fn new(module: Symbol) -> Self {
+        Self {
+            module,
+            name: well_known::MAIN,
+            result: EntryResult::Unit,
+        }
+    }
+
+    pub fn with_name(mut self, name: Symbol) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_result(mut self, result: EntryResult) -> Self {
+        self.result = result;
+        self
+    }
+
+    /// The signature that is expected, as it's shown in the diagnostics.
+    fn expected(&self) -> String {
+        let result = self.result.show();
+        format!("{result} or () -> {result}")
+    }
+}
+
+/// Checks the type of the entry point. The parameter can be written as a binder or in the type, so
+/// the whole type is looked at instead of the arguments of the definition.
+fn accepts(result: &EntryResult, typ: &Type<Real>) -> bool {
+    match typ.as_ref() {
+        TypeKind::Forall(forall) => accepts(result, &forall.body),
+        TypeKind::Arrow(arrow) => {
+            let unit = matches!(arrow.typ.as_ref(), TypeKind::Tuple(types) if types.is_empty());
+            unit && !matches!(arrow.body.as_ref(), TypeKind::Arrow(_))
+                && result.accepts(&arrow.body)
+        }
+        _ => result.accepts(typ),
+    }
+}
+
+/// Checks the entry point of a program that was already defined. Programs of other modules are
+/// ignored. A missing entry point is reported at the start of the file of the module, because
+/// modules have no header to point at.
+pub fn check_entry_point(ctx: &mut Context, entry: &EntryPoint, file: FileId, program: &Program) {
+    if program.name != entry.module {
+        return;
+    }
+
+    let env = Env::default();
+
+    let decl = program
+        .lets
+        .iter()
+        .find(|decl| decl.signature.name.name == entry.name);
+
+    let Some(decl) = decl else {
+        env.set_current_span(Span::from_usize(file, 0, 0));
+        let kind = TypeErrorKind::MissingEntryPoint(entry.name.clone(), entry.module.clone());
+        ctx.report(&env, kind);
+        return;
+    };
+
+    env.set_current_span(decl.signature.span.clone());
+
+    if decl.signature.visibility != Visibility::Public {
+        ctx.report(&env, TypeErrorKind::PrivateEntryPoint(entry.name.clone()));
+    }
+
+    let Some(def) = ctx
+        .modules
+        .get(&decl.signature.name.path)
+        .and_then(|module| module.variables.get(&entry.name))
+    else {
+        return;
+    };
+
+    let typ = def.typ.quote(Level(0));
+
+    if !accepts(&entry.result, &typ) {
+        let kind = TypeErrorKind::InvalidEntryPoint(entry.name.clone(), typ, entry.expected());
+        ctx.report(&env, kind);
+    }
+}
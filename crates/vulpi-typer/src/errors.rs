@@ -2,27 +2,188 @@
 
 use vulpi_intern::Symbol;
 use vulpi_location::Span;
-use vulpi_report::{IntoDiagnostic, Text};
-use vulpi_syntax::r#abstract::Qualified;
+use vulpi_report::{IntoDiagnostic, Marker, Text};
+use vulpi_syntax::r#abstract::{Derive, Qualified};
 
 use crate::{
     coverage::{Pat, Row},
+    elide::{show_elided, TypeBudget},
     real::Real,
-    Env, Type,
+    Env, Type, TypeKind,
 };
 
+/// A step taken by the unifier inside of a type. It's used to describe where two types stop being
+/// equal.
+#[derive(Clone, Copy)]
+pub enum Breadcrumb {
+    Argument,
+    Return,
+    TupleElement(usize),
+    TypeArgument(usize),
+    Constraint,
+    Qualified,
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{n}{suffix}")
+}
+
+/// Describes the path that the unifier took from the innermost to the outermost type.
+fn describe_path(path: &[Breadcrumb]) -> String {
+    let mut phrases = Vec::new();
+    let mut returns = 0;
+
+    for crumb in path.iter().rev() {
+        match crumb {
+            Breadcrumb::Return => {
+                returns += 1;
+                continue;
+            }
+            Breadcrumb::Argument => {
+                phrases.push(format!(
+                    "the {} argument of the function type",
+                    ordinal(returns + 1)
+                ));
+                returns = 0;
+                continue;
+            }
+            _ => (),
+        }
+
+        if returns > 0 {
+            phrases.push("the return type of the function type".to_string());
+            returns = 0;
+        }
+
+        phrases.push(match crumb {
+            Breadcrumb::TupleElement(n) => format!("the {} element of the tuple", ordinal(n + 1)),
+            Breadcrumb::TypeArgument(n) => format!("the {} type argument", ordinal(*n)),
+            Breadcrumb::Constraint => "the constraint".to_string(),
+            Breadcrumb::Qualified => "the constrained type".to_string(),
+            Breadcrumb::Argument | Breadcrumb::Return => unreachable!(),
+        });
+    }
+
+    if returns > 0 {
+        phrases.push("the return type of the function type".to_string());
+    }
+
+    phrases
+        .iter()
+        .rev()
+        .map(|phrase| format!(" in {phrase}"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn tuple_size(typ: &Type<Real>) -> usize {
+    match typ.as_ref() {
+        TypeKind::Tuple(types) => types.len(),
+        _ => 0,
+    }
+}
+
+/// Checks if a tuple was expected where something that is not a tuple was found. The unit type is
+/// an empty tuple, but it's shown as a type of its own.
+fn expects_tuple(left: &Type<Real>, right: &Type<Real>) -> bool {
+    !matches!(left.as_ref(), TypeKind::Tuple(_)) && tuple_size(right) > 0
+}
+
+/// What sets the number of patterns that the arms of a pattern match must have.
+#[derive(Clone)]
+pub enum ArityOrigin {
+    /// The patterns of the first arm.
+    FirstArm(Span),
+
+    /// The values that are matched by a `when`.
+    Scrutinees(Span),
+}
+
+/// The whole types that were being compared when a [TypeErrorKind::TypeMismatch] happened.
+pub type Comparison = Box<(Env, Type<Real>, Type<Real>)>;
+
 pub enum TypeErrorKind {
     EmptyCase,
     UnboundTypeVariable(Symbol),
-    TypeMismatch(Env, Type<Real>, Type<Real>),
+
+    /// The `left` type was found where the `right` type was expected. Both of them are the
+    /// innermost types that differ; `path` tells where they are inside of the `full` types that
+    /// were being compared. The path is stored from the innermost to the outermost step.
+    TypeMismatch {
+        env: Env,
+        left: Type<Real>,
+        right: Type<Real>,
+        full: Option<Comparison>,
+        path: Vec<Breadcrumb>,
+    },
+
+    /// The `left` kind was found where the `right` kind was expected.
     KindMismatch(Env, Type<Real>, Type<Real>),
+
+    /// An argument of a type application does not have the kind that the `head` of the
+    /// application expects at that position.
+    ArgumentKindMismatch {
+        env: Env,
+        head: Type<Real>,
+        head_kind: Type<Real>,
+        position: usize,
+        expected: Type<Real>,
+        found: Type<Real>,
+    },
+
+    /// Two tuples with a different number of elements were compared. The `path` is the same as the
+    /// one of [TypeErrorKind::TypeMismatch].
+    TupleArityMismatch {
+        expected: usize,
+        found: usize,
+        path: Vec<Breadcrumb>,
+    },
     InfiniteType,
     CannotFind(Symbol),
     AtLeastOneArgument,
     EscapingScope,
-    NotAFunctionKind,
-    WrongArity(usize, usize),
+
+    /// A type was applied to more arguments than its kind takes. The `applied` type is the `head`
+    /// with the arguments before the one that is too many, and `kind` is its kind.
+    NotAFunctionKind {
+        env: Env,
+        head: Type<Real>,
+        head_kind: Type<Real>,
+        applied: Type<Real>,
+        kind: Type<Real>,
+    },
+    WrongArity {
+        expected: usize,
+        found: usize,
+        /// What established the expected number of patterns, if the error is about the arms of a
+        /// pattern match.
+        origin: Option<ArityOrigin>,
+    },
+    ConstructorArityMismatch {
+        expected: usize,
+        found: usize,
+        constructor: Qualified,
+
+        /// The name of the constructor in its declaration, if it's known.
+        declaration: Option<Span>,
+    },
     NotAFunction(Env, Type<Real>),
+
+    /// A function of type `typ` that takes `expected` arguments was applied to `found` of them.
+    TooManyArguments {
+        env: Env,
+        expected: usize,
+        found: usize,
+        typ: Type<Real>,
+    },
     NotImplemented,
     MissingLabel(Qualified),
     InvalidLabels(Vec<Qualified>),
@@ -30,47 +191,349 @@ pub enum TypeErrorKind {
     DuplicatedField,
     NotFoundField,
     NotARecord,
+
+    /// A tuple was accessed at a position like `t.2` that it does not have.
+    TupleIndexOutOfBounds {
+        index: usize,
+        arity: usize,
+    },
+    NotATuple(Env, Type<Real>),
     MissingField(Symbol),
     NonExhaustive(Row<Pat>),
+    TypedHole(Symbol, Env, Type<Real>, Vec<Symbol>),
+
+    /// The type of a public definition still has holes. The flag tells if the definition was not
+    /// generalized because of the value restriction.
+    AmbiguousType(Qualified, Type<Real>, bool),
+
+    CycleBetweenModules(Vec<Symbol>),
+    CyclicTypeSynonym(Vec<Qualified>),
+    UnknownModule(Symbol),
+    MissingBuiltin(Symbol, Symbol),
+    UnusedVariable(Symbol),
+    ShadowedVariable(Symbol),
+    DuplicateDefinition(Symbol),
+    AmbiguousField(Symbol, Vec<Qualified>),
+    InvalidConstructorReturnType(Symbol, Qualified),
+    FloatPattern,
+    TooManyTypeArguments(Env, Type<Real>),
+    CheckerLimitExceeded,
+    MissingEntryPoint(Symbol, Symbol),
+    PrivateEntryPoint(Symbol),
+    InvalidEntryPoint(Symbol, Type<Real>, String),
+    UnusedDefinition(Symbol),
+    PrivateTypeInPublicInterface(Symbol, Symbol),
+    UnlabeledConstructor(Symbol),
+    CannotDerive(Derive, Symbol, Underivable),
+    UnknownAttribute(Symbol),
+    AttributeArity {
+        name: Symbol,
+        expected: usize,
+        found: usize,
+    },
+    Deprecated(Symbol, Option<Symbol>),
+}
+
+/// The reason why a function cannot be derived for a type.
+pub enum Underivable {
+    /// A field needs a function that is not defined, like `eqInt` for a field of type `Int`.
+    Missing(Qualified),
+
+    /// The type of a field is not a type parameter or a named type applied to others.
+    Unsupported,
+
+    /// The type has no constructors or fields to derive the function from.
+    Empty,
+}
+
+impl TypeErrorKind {
+    pub(crate) fn mismatch(env: Env, left: Type<Real>, right: Type<Real>) -> Self {
+        TypeErrorKind::TypeMismatch {
+            env,
+            left,
+            right,
+            full: None,
+            path: Vec::new(),
+        }
+    }
+
+    /// Records that the error happened inside of a part of a type.
+    pub(crate) fn within(mut self, crumb: Breadcrumb) -> Self {
+        match &mut self {
+            TypeErrorKind::TypeMismatch { path, .. }
+            | TypeErrorKind::TupleArityMismatch { path, .. } => path.push(crumb),
+            _ => (),
+        }
+        self
+    }
+
+    /// Swaps the sides of a mismatch. It's used when the unifier changes the order of the types
+    /// (e.g. in contravariant positions).
+    pub(crate) fn flipped(self) -> Self {
+        match self {
+            TypeErrorKind::TypeMismatch {
+                env,
+                left,
+                right,
+                full,
+                path,
+            } => TypeErrorKind::TypeMismatch {
+                env,
+                left: right,
+                right: left,
+                full: full.map(|full| {
+                    let (env, left, right) = *full;
+                    Box::new((env, right, left))
+                }),
+                path,
+            },
+            TypeErrorKind::TupleArityMismatch {
+                expected,
+                found,
+                path,
+            } => TypeErrorKind::TupleArityMismatch {
+                expected: found,
+                found: expected,
+                path,
+            },
+            other => other,
+        }
+    }
 }
 
 pub struct TypeError {
     pub span: Span,
     pub kind: TypeErrorKind,
+
+    /// How much of the types is shown. They are shown whole if it's `None`.
+    pub budget: Option<TypeBudget>,
+}
+
+impl TypeError {
+    /// Shows a type of the diagnostic inside of the budget, returning if something was elided.
+    fn show_type(&self, env: &Env, typ: &Type<Real>, path: &[Breadcrumb]) -> (String, bool) {
+        match &self.budget {
+            Some(budget) => show_elided(env, typ, path, budget),
+            None => (typ.show(env).to_string(), false),
+        }
+    }
+
+    /// The types that are shown in the message and in the hint, named by their role, together with
+    /// the path from the outermost type to the part that must never be elided.
+    fn shown_types(&self) -> Vec<(&'static str, &Env, &Type<Real>, Vec<Breadcrumb>)> {
+        match &self.kind {
+            TypeErrorKind::TypeMismatch {
+                env,
+                left,
+                right,
+                full,
+                path,
+            } => {
+                let mut types = vec![
+                    ("expected", env, right, Vec::new()),
+                    ("found", env, left, Vec::new()),
+                ];
+
+                if let (Some(full), false) = (full, path.is_empty()) {
+                    let (env, left, right) = &**full;
+                    let path = path.iter().rev().copied().collect::<Vec<_>>();
+                    types.push(("full expected", env, right, path.clone()));
+                    types.push(("full found", env, left, path));
+                }
+
+                types
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+vulpi_report::codes! {
+    /// The code of each kind of type error.
+    TYPE_ERROR_CODES: TypeErrorKind {
+        EmptyCase => EMPTY_CASE,
+        UnboundTypeVariable => UNBOUND_TYPE_VARIABLE,
+        TypeMismatch => TYPE_MISMATCH,
+        KindMismatch => KIND_MISMATCH,
+        ArgumentKindMismatch => ARGUMENT_KIND_MISMATCH,
+        TupleArityMismatch => TUPLE_ARITY_MISMATCH,
+        InfiniteType => INFINITE_TYPE,
+        CannotFind => CANNOT_FIND_TYPE,
+        AtLeastOneArgument => AT_LEAST_ONE_ARGUMENT,
+        EscapingScope => ESCAPING_SCOPE,
+        NotAFunctionKind => NOT_A_FUNCTION_KIND,
+        WrongArity => WRONG_ARITY,
+        ConstructorArityMismatch => CONSTRUCTOR_ARITY_MISMATCH,
+        NotAFunction => NOT_A_FUNCTION,
+        TooManyArguments => TOO_MANY_ARGUMENTS,
+        NotImplemented => NOT_IMPLEMENTED,
+        MissingLabel => MISSING_LABEL,
+        InvalidLabels => INVALID_LABELS,
+        PatternsNotAllowedHere => PATTERNS_NOT_ALLOWED_HERE,
+        DuplicatedField => DUPLICATED_FIELD,
+        NotFoundField => NOT_FOUND_FIELD,
+        NotARecord => NOT_A_RECORD,
+        TupleIndexOutOfBounds => TUPLE_INDEX_OUT_OF_BOUNDS,
+        NotATuple => NOT_A_TUPLE,
+        MissingField => MISSING_FIELD,
+        NonExhaustive => NON_EXHAUSTIVE,
+        TypedHole => TYPED_HOLE,
+        AmbiguousType => AMBIGUOUS_TYPE,
+        CycleBetweenModules => CYCLE_BETWEEN_MODULES,
+        CyclicTypeSynonym => CYCLIC_TYPE_SYNONYM,
+        UnknownModule => UNKNOWN_MODULE,
+        MissingBuiltin => MISSING_BUILTIN,
+        UnusedVariable => UNUSED_VARIABLE,
+        ShadowedVariable => SHADOWED_VARIABLE,
+        DuplicateDefinition => DUPLICATE_DEFINITION,
+        AmbiguousField => AMBIGUOUS_FIELD,
+        InvalidConstructorReturnType => INVALID_CONSTRUCTOR_RETURN_TYPE,
+        FloatPattern => FLOAT_PATTERN,
+        TooManyTypeArguments => TOO_MANY_TYPE_ARGUMENTS,
+        CheckerLimitExceeded => CHECKER_LIMIT_EXCEEDED,
+        MissingEntryPoint => MISSING_ENTRY_POINT,
+        PrivateEntryPoint => PRIVATE_ENTRY_POINT,
+        InvalidEntryPoint => INVALID_ENTRY_POINT,
+        UnusedDefinition => UNUSED_DEFINITION,
+        PrivateTypeInPublicInterface => PRIVATE_TYPE_IN_PUBLIC_INTERFACE,
+        UnlabeledConstructor => UNLABELED_CONSTRUCTOR,
+        CannotDerive => CANNOT_DERIVE,
+        UnknownAttribute => UNKNOWN_ATTRIBUTE,
+        AttributeArity => ATTRIBUTE_ARITY,
+        Deprecated => DEPRECATED,
+    }
 }
 
 impl IntoDiagnostic for TypeError {
+    fn code(&self) -> Option<usize> {
+        Some(self.kind.code())
+    }
+
     fn message(&self) -> Text {
         match &self.kind {
-            TypeErrorKind::TypeMismatch(env, left, right) => Text::from(format!(
-                "type mismatch: {} != {}",
-                left.show(env),
-                right.show(env)
+            TypeErrorKind::TypeMismatch {
+                env,
+                left,
+                right,
+                path,
+                ..
+            } if expects_tuple(left, right) => Text::from(format!(
+                "type mismatch: expected a tuple of {} elements but found {}{}",
+                tuple_size(right),
+                self.show_type(env, left, &[]).0,
+                describe_path(path)
+            )),
+            TypeErrorKind::TypeMismatch {
+                env,
+                left,
+                right,
+                path,
+                ..
+            } => Text::from(format!(
+                "type mismatch: expected {} but found {}{}",
+                self.show_type(env, right, &[]).0,
+                self.show_type(env, left, &[]).0,
+                describe_path(path)
+            )),
+            TypeErrorKind::TupleArityMismatch {
+                expected,
+                found,
+                path,
+            } => Text::from(format!(
+                "type mismatch: expected a tuple of {} elements but found one with {}{}",
+                expected,
+                found,
+                describe_path(path)
             )),
             TypeErrorKind::EmptyCase => Text::from("empty case".to_string()),
             TypeErrorKind::KindMismatch(env, left, right) => Text::from(format!(
-                "kind mismatch: {} != {}",
-                left.show(env),
+                "kind mismatch: expected {} but found {}",
                 right.show(env),
+                left.show(env),
+            )),
+            TypeErrorKind::ArgumentKindMismatch {
+                env,
+                head,
+                position,
+                expected,
+                found,
+                ..
+            } => Text::from(format!(
+                "the {} argument of '{}' should have kind {} but it has kind {}",
+                ordinal(position + 1),
+                head.show(env),
+                expected.show(env),
+                found.show(env),
             )),
             TypeErrorKind::InfiniteType => Text::from("infinite type".to_string()),
             TypeErrorKind::EscapingScope => Text::from("escaping scope".to_string()),
-            TypeErrorKind::NotAFunctionKind => Text::from("not a function kind".to_string()),
+            TypeErrorKind::NotAFunctionKind {
+                env, applied, kind, ..
+            } => Text::from(format!(
+                "'{}' has kind {}, it cannot be applied to another argument",
+                applied.show(env),
+                kind.show(env),
+            )),
             TypeErrorKind::UnboundTypeVariable(name) => {
                 Text::from(format!("unbound type variable: {}", name.get()))
             }
-            TypeErrorKind::WrongArity(expected, found) => Text::from(format!(
-                "wrong arity: expected {} arguments, found {}",
-                expected, found
+            TypeErrorKind::WrongArity {
+                expected,
+                found,
+                origin,
+            } => Text::from(format!(
+                "wrong arity: expected {} {}, found {}",
+                expected,
+                if origin.is_some() {
+                    "patterns"
+                } else {
+                    "arguments"
+                },
+                found
+            )),
+            TypeErrorKind::ConstructorArityMismatch {
+                expected,
+                found,
+                constructor,
+                ..
+            } => Text::from(format!(
+                "the constructor {} expects {} {} but the pattern has {}",
+                constructor.name.get(),
+                expected,
+                if *expected == 1 {
+                    "argument"
+                } else {
+                    "arguments"
+                },
+                found
             )),
             TypeErrorKind::NotAFunction(env, ty) => {
                 Text::from(format!("not a function: {}", ty.show(env)))
             }
+            TypeErrorKind::TooManyArguments {
+                expected, found, ..
+            } => Text::from(format!(
+                "the function takes {} {} but {} were given",
+                expected,
+                if *expected == 1 {
+                    "argument"
+                } else {
+                    "arguments"
+                },
+                found
+            )),
             TypeErrorKind::CannotFind(name) => Text::from(format!("cannot find: {}", name.get())),
             TypeErrorKind::NotImplemented => Text::from("not implemented".to_string()),
             TypeErrorKind::DuplicatedField => Text::from("duplicated field".to_string()),
             TypeErrorKind::NotFoundField => Text::from("not found field".to_string()),
             TypeErrorKind::NotARecord => Text::from("not a record".to_string()),
+            TypeErrorKind::TupleIndexOutOfBounds { index, arity } => Text::from(format!(
+                "the tuple has {} elements, so there is no element {}",
+                arity, index
+            )),
+            TypeErrorKind::NotATuple(env, ty) => {
+                Text::from(format!("not a tuple: {}", ty.show(env)))
+            }
             TypeErrorKind::MissingField(name) => {
                 Text::from(format!("missing field: {}", name.get()))
             }
@@ -96,11 +559,300 @@ impl IntoDiagnostic for TypeError {
             TypeErrorKind::NonExhaustive(row) => {
                 Text::from(format!("non-exhaustive patterns: {}", row))
             }
+            TypeErrorKind::TypedHole(name, env, typ, _) => Text::from(format!(
+                "found hole ?{} of type {}",
+                name.get(),
+                typ.show(env)
+            )),
+            TypeErrorKind::AmbiguousType(name, typ, _) => Text::from(format!(
+                "the type {} of '{}' is ambiguous",
+                typ.show(&Env::default()),
+                name.to_string()
+            )),
+            TypeErrorKind::CycleBetweenModules(cycle) => Text::from(format!(
+                "cycle between the modules '{}'",
+                cycle
+                    .iter()
+                    .map(|name| name.get())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            )),
+            TypeErrorKind::CyclicTypeSynonym(cycle) => Text::from(format!(
+                "cycle between the type synonyms {}",
+                cycle
+                    .iter()
+                    .map(|name| format!("'{}'", name.name.get()))
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            )),
+            TypeErrorKind::UnknownModule(path) => {
+                Text::from(format!("cannot find the module '{}'", path.get()))
+            }
+            TypeErrorKind::MissingBuiltin(name, prelude) => Text::from(format!(
+                "the prelude '{}' does not define the builtin type '{}'",
+                prelude.get(),
+                name.get()
+            )),
+            TypeErrorKind::UnusedVariable(name) => {
+                Text::from(format!("the variable '{}' is never used", name.get()))
+            }
+            TypeErrorKind::ShadowedVariable(name) => Text::from(format!(
+                "the variable '{}' shadows another variable",
+                name.get()
+            )),
+            TypeErrorKind::DuplicateDefinition(name) => {
+                Text::from(format!("'{}' is defined more than once", name.get()))
+            }
+            TypeErrorKind::AmbiguousField(name, records) => Text::from(format!(
+                "the field '{}' is ambiguous, it's defined in {}",
+                name.get(),
+                records
+                    .iter()
+                    .map(|record| format!("'{}'", record.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            )),
+            TypeErrorKind::InvalidConstructorReturnType(name, typ) => Text::from(format!(
+                "the constructor '{}' must return the type '{}'",
+                name.get(),
+                typ.to_string()
+            )),
+            TypeErrorKind::FloatPattern => {
+                Text::from("float literals cannot be used as patterns".to_string())
+            }
+            TypeErrorKind::TooManyTypeArguments(env, ty) => Text::from(format!(
+                "the type '{}' does not have a forall to apply the type argument",
+                ty.show(env)
+            )),
+            TypeErrorKind::CheckerLimitExceeded => {
+                Text::from("this definition is too deeply nested to be checked".to_string())
+            }
+            TypeErrorKind::MissingEntryPoint(name, module) => Text::from(format!(
+                "the module '{}' does not define '{}'",
+                module.get(),
+                name.get()
+            )),
+            TypeErrorKind::PrivateEntryPoint(name) => {
+                Text::from(format!("'{}' must be public", name.get()))
+            }
+            TypeErrorKind::InvalidEntryPoint(name, typ, expected) => Text::from(format!(
+                "'{}' has type {}, expected {}",
+                name.get(),
+                typ.show(&Env::default()),
+                expected
+            )),
+            TypeErrorKind::UnusedDefinition(name) => {
+                Text::from(format!("'{}' is private and never used", name.get()))
+            }
+            TypeErrorKind::PrivateTypeInPublicInterface(name, typ) => Text::from(format!(
+                "'{}' is public but its type mentions the private type '{}'",
+                name.get(),
+                typ.get()
+            )),
+            TypeErrorKind::UnlabeledConstructor(name) => Text::from(format!(
+                "the constructor '{}' does not have labeled fields",
+                name.get()
+            )),
+            TypeErrorKind::CannotDerive(derive, typ, reason) => {
+                let reason = match reason {
+                    Underivable::Missing(name) => {
+                        format!(
+                            "this field needs '{}', which is not defined",
+                            name.name.get()
+                        )
+                    }
+                    Underivable::Unsupported => "the type of this field is not supported".into(),
+                    Underivable::Empty => "it has no constructors or fields".into(),
+                };
+
+                Text::from(format!(
+                    "cannot derive {} for '{}', {reason}",
+                    derive.name(),
+                    typ.get()
+                ))
+            }
+            TypeErrorKind::UnknownAttribute(name) => Text::from(format!(
+                "unknown attribute '@{}', it is ignored",
+                name.get()
+            )),
+            TypeErrorKind::AttributeArity {
+                name,
+                expected,
+                found,
+            } => Text::from(format!(
+                "the attribute '@{}' takes {} {} but {} were given",
+                name.get(),
+                expected,
+                if *expected == 1 {
+                    "argument"
+                } else {
+                    "arguments"
+                },
+                found
+            )),
+            TypeErrorKind::Deprecated(name, message) => match message {
+                Some(message) => {
+                    Text::from(format!("'{}' is deprecated: {}", name.get(), message.get()))
+                }
+                None => Text::from(format!("'{}' is deprecated", name.get())),
+            },
+        }
+    }
+
+    fn hint(&self) -> Option<Text> {
+        match &self.kind {
+            TypeErrorKind::TypeMismatch {
+                full: Some(full),
+                path,
+                ..
+            } if !path.is_empty() => {
+                let (env, left, right) = &**full;
+                let path = path.iter().rev().copied().collect::<Vec<_>>();
+                Some(Text::from(format!(
+                    "while comparing the expected type {} with {}",
+                    self.show_type(env, right, &path).0,
+                    self.show_type(env, left, &path).0
+                )))
+            }
+            TypeErrorKind::KindMismatch(_, left, right)
+                if matches!(right.as_ref(), TypeKind::Type) && left.arrow_spine().len() > 1 =>
+            {
+                let missing = left.arrow_spine().len() - 1;
+                Some(Text::from(format!(
+                    "the type needs {} more {}",
+                    missing,
+                    if missing == 1 {
+                        "argument"
+                    } else {
+                        "arguments"
+                    }
+                )))
+            }
+            TypeErrorKind::ArgumentKindMismatch {
+                env,
+                head,
+                head_kind,
+                ..
+            } => Some(Text::from(format!(
+                "'{}' has kind {}",
+                head.show(env),
+                head_kind.show(env)
+            ))),
+            TypeErrorKind::NotAFunctionKind {
+                env,
+                head,
+                head_kind,
+                ..
+            } if head_kind.arrow_spine().len() > 1 => {
+                let arity = head_kind.arrow_spine().len() - 1;
+                Some(Text::from(format!(
+                    "'{}' has kind {}, so it takes {} {}",
+                    head.show(env),
+                    head_kind.show(env),
+                    arity,
+                    if arity == 1 { "argument" } else { "arguments" }
+                )))
+            }
+            TypeErrorKind::TypedHole(_, _, _, fits) if !fits.is_empty() => {
+                Some(Text::from(format!(
+                    "variables that fit: {}",
+                    fits.iter().map(|x| x.get()).collect::<Vec<_>>().join(", ")
+                )))
+            }
+            TypeErrorKind::ConstructorArityMismatch {
+                expected, found, ..
+            } if found < expected => Some(Text::from(
+                "constructors must be fully applied inside of patterns",
+            )),
+            TypeErrorKind::AmbiguousType(_, _, true) => Some(Text::from(
+                "it's not generalized because its value is not a function or a constructor, add a \
+                 type annotation to the definition",
+            )),
+            TypeErrorKind::AmbiguousType(..) => {
+                Some(Text::from("add a type annotation to the definition"))
+            }
+            TypeErrorKind::PrivateEntryPoint(_) => Some(Text::from("add `pub` before `let`")),
+            _ => None,
+        }
+    }
+
+    fn markers(&self) -> Vec<Marker> {
+        match &self.kind {
+            TypeErrorKind::WrongArity {
+                origin: Some(ArityOrigin::FirstArm(first)),
+                expected,
+                ..
+            } => vec![Marker {
+                position: first.clone(),
+                subtitle: Some(Text::from(format!(
+                    "the first arm has {} patterns",
+                    expected
+                ))),
+            }],
+            TypeErrorKind::WrongArity {
+                origin: Some(ArityOrigin::Scrutinees(scrutinees)),
+                expected,
+                ..
+            } => vec![Marker {
+                position: scrutinees.clone(),
+                subtitle: Some(Text::from(format!("{} values are matched", expected))),
+            }],
+            TypeErrorKind::ConstructorArityMismatch {
+                declaration: Some(declaration),
+                constructor,
+                ..
+            } => vec![Marker {
+                position: declaration.clone(),
+                subtitle: Some(Text::from(format!(
+                    "the constructor {} is declared here",
+                    constructor.name.get()
+                ))),
+            }],
+            _ => Vec::new(),
         }
     }
 
+    fn notes(&self) -> Vec<Text> {
+        let mut notes = Vec::new();
+
+        if let TypeErrorKind::TooManyArguments { env, typ, .. } = &self.kind {
+            notes.push(Text::from(format!(
+                "the function has type {}",
+                typ.show(env)
+            )));
+        }
+
+        let elided = self
+            .shown_types()
+            .into_iter()
+            .any(|(_, env, typ, path)| self.show_type(env, typ, &path).1);
+
+        if elided {
+            notes.push(Text::from(
+                "type truncated, use --verbose-types for the full type",
+            ));
+        }
+
+        notes
+    }
+
+    fn details(&self) -> Vec<(String, String)> {
+        self.shown_types()
+            .into_iter()
+            .map(|(role, env, typ, _)| (role.to_string(), typ.show(env).to_string()))
+            .collect()
+    }
+
     fn severity(&self) -> vulpi_report::Severity {
-        vulpi_report::Severity::Error
+        match &self.kind {
+            TypeErrorKind::TypedHole(..) => vulpi_report::Severity::Info,
+            TypeErrorKind::UnusedVariable(_)
+            | TypeErrorKind::ShadowedVariable(_)
+            | TypeErrorKind::UnusedDefinition(_)
+            | TypeErrorKind::UnknownAttribute(_)
+            | TypeErrorKind::Deprecated(..) => vulpi_report::Severity::Warning,
+            _ => vulpi_report::Severity::Error,
+        }
     }
 
     fn location(&self) -> Span {
@@ -0,0 +1,89 @@
+//! Private types that escape through the signatures of public items. Other modules can use the
+//! public item, but they cannot write the type of what it takes or returns.
+
+use std::collections::HashMap;
+
+use vulpi_report::Diagnostic;
+use vulpi_syntax::r#abstract::{Program, TypeDef, Visibility};
+
+use crate::{
+    context::Context,
+    errors::{TypeError, TypeErrorKind},
+    eval::Quote,
+    Level,
+};
+
+impl Context {
+    /// Reports the private types that are mentioned by the zonked types of the public lets and
+    /// externals, and by the arguments of the public constructors.
+    pub(crate) fn report_private_types(&mut self, programs: &[&Program]) {
+        let private = programs
+            .iter()
+            .flat_map(|program| &program.types)
+            .filter(|decl| decl.visibility != Visibility::Public)
+            .map(|decl| (decl.name.clone(), decl.span.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let mut items = Vec::new();
+
+        for program in programs {
+            let lets = program
+                .lets
+                .iter()
+                .filter(|decl| decl.signature.visibility == Visibility::Public)
+                .map(|decl| (&decl.signature.name, &decl.signature.span));
+
+            let externals = program
+                .externals
+                .iter()
+                .filter(|decl| decl.visibility == Visibility::Public)
+                .map(|decl| (&decl.name, &decl.span));
+
+            for (name, span) in lets.chain(externals) {
+                let typ = self.modules.let_def(name).typ.quote(Level(0));
+                items.push((name.name.clone(), span.clone(), vec![typ]));
+            }
+
+            for decl in &program.types {
+                let TypeDef::Sum(sum) = &decl.def else {
+                    continue;
+                };
+
+                if decl.visibility != Visibility::Public {
+                    continue;
+                }
+
+                for cons in &sum.constructors {
+                    if cons.visibility == Visibility::Public {
+                        let args = self.modules.constructor(&cons.name).args;
+                        items.push((cons.name.name.clone(), cons.span.clone(), args));
+                    }
+                }
+            }
+        }
+
+        for (name, span, types) in items {
+            let mut names = Vec::new();
+
+            for typ in &types {
+                typ.type_names(&mut names);
+            }
+
+            for typ in names {
+                let Some(declared) = private.get(&typ) else {
+                    continue;
+                };
+
+                let diagnostic = Diagnostic::new(TypeError {
+                    span: span.clone(),
+                    kind: TypeErrorKind::PrivateTypeInPublicInterface(name.clone(), typ.name),
+                    budget: self.budget,
+                });
+
+                self.reporter.report(
+                    diagnostic.with_label(declared.clone(), "the type is declared private here"),
+                );
+            }
+        }
+    }
+}
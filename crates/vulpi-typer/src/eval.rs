@@ -56,27 +56,16 @@ impl Eval<Vec<Type<Virtual>>> for Vec<Type<Real>> {
     }
 }
 
-impl Eval<Type<Virtual>> for Hole<Real> {
-    fn eval(&self, env: &Env) -> Type<Virtual> {
-        match &*self.0.borrow() {
-            HoleInner::Empty(s, k, l) => {
-                Type::new(TypeKind::Hole(Hole::empty(s.clone(), k.eval(env), *l)))
-            }
-            HoleInner::Filled(f) => f.clone().eval(env),
-        }
-    }
-}
-
 /// Quotation of types.
 pub trait Quote<T> {
     fn quote(&self, lvl: Level) -> T;
 }
 
-impl Quote<Type<Real>> for Hole<Virtual> {
+impl Quote<Type<Real>> for Hole {
     fn quote(&self, depth: Level) -> Type<Real> {
-        match &*self.0.borrow() {
-            HoleInner::Empty(_, _, _) => Type::new(TypeKind::Hole(self.clone())),
-            HoleInner::Filled(f) => f.clone().quote(depth),
+        match self.inner() {
+            HoleInner::Empty(..) => Type::new(TypeKind::Hole(self.clone())),
+            HoleInner::Filled(f) => f.quote(depth),
         }
     }
 }
@@ -0,0 +1,243 @@
+//! Holes are types that are not known yet. They live in a union-find table that belongs to the
+//! [crate::Context] that made them: a hole is an index in the table, the holes that were unified
+//! with each other point to a parent, and only the root of a class knows what the class stands
+//! for. Classes are joined by rank and the paths to the roots are compressed, so finding the root
+//! of a hole takes nearly constant time.
+
+use std::{
+    hash::Hash,
+    sync::{Arc, RwLock},
+};
+
+use crate::{r#virtual::Virtual, Kind, Level, Type};
+
+/// The inside of a hole. It contains a Level in the Empty in order to avoid infinite loops and
+/// the hole to go out of scope.
+#[derive(Clone)]
+pub enum HoleInner {
+    Empty(Kind<Virtual>, Level),
+    Filled(Type<Virtual>),
+}
+
+/// An entry of the table. The content is only meaningful in the roots.
+#[derive(Clone)]
+pub(crate) struct Slot {
+    parent: usize,
+    rank: usize,
+    inner: HoleInner,
+}
+
+#[derive(Default)]
+struct Table {
+    /// The index of the first hole of the table. The tables of forks start after the holes of the
+    /// table that they were forked from, so their holes are not named like the older ones.
+    base: usize,
+    slots: Vec<Slot>,
+}
+
+impl Table {
+    fn slot(&self, index: usize) -> &Slot {
+        &self.slots[index - self.base]
+    }
+
+    fn slot_mut(&mut self, index: usize) -> &mut Slot {
+        &mut self.slots[index - self.base]
+    }
+
+    fn root(&self, mut index: usize) -> usize {
+        while self.slot(index).parent != index {
+            index = self.slot(index).parent;
+        }
+
+        index
+    }
+}
+
+/// The table of the holes made by a context. Holes keep a handle to their table, so types can be
+/// read without the context, but only the context changes the shape of the classes.
+#[derive(Clone, Default)]
+pub struct Holes(Arc<RwLock<Table>>);
+
+impl Holes {
+    /// A new table for a fork of the context, with indices after the ones of this table.
+    pub(crate) fn fork(&self) -> Holes {
+        let table = self.0.read().unwrap();
+
+        Holes(Arc::new(RwLock::new(Table {
+            base: table.base + table.slots.len(),
+            slots: Vec::new(),
+        })))
+    }
+
+    /// Makes a new hole that is alone in its class.
+    pub(crate) fn empty(&self, kind: Kind<Virtual>, level: Level) -> Hole {
+        let mut table = self.0.write().unwrap();
+        let index = table.base + table.slots.len();
+
+        table.slots.push(Slot {
+            parent: index,
+            rank: 0,
+            inner: HoleInner::Empty(kind, level),
+        });
+
+        Hole {
+            holes: self.clone(),
+            index,
+        }
+    }
+}
+
+/// A hole is a type that is not yet known. It is used for type inference.
+#[derive(Clone)]
+pub struct Hole {
+    holes: Holes,
+    index: usize,
+}
+
+impl Hash for Hole {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.index().hash(state);
+    }
+}
+
+/// Two holes are the same if they are in the same class.
+impl PartialEq for Hole {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_table(other) && self.index() == other.index()
+    }
+}
+
+impl Eq for Hole {}
+
+impl Hole {
+    fn at(&self, index: usize) -> Hole {
+        Hole {
+            holes: self.holes.clone(),
+            index,
+        }
+    }
+
+    pub(crate) fn same_table(&self, other: &Hole) -> bool {
+        Arc::ptr_eq(&self.holes.0, &other.holes.0)
+    }
+
+    /// The index of the root of the class of the hole. It's what names the hole in the messages.
+    pub fn index(&self) -> usize {
+        self.holes.0.read().unwrap().root(self.index)
+    }
+
+    /// The root of the class of the hole.
+    pub fn root(&self) -> Hole {
+        self.at(self.index())
+    }
+
+    pub(crate) fn is_root(&self) -> bool {
+        self.depth() == 0
+    }
+
+    /// How many parents are followed to find the root of the class of the hole.
+    pub fn depth(&self) -> usize {
+        let table = self.holes.0.read().unwrap();
+        let mut index = self.index;
+        let mut depth = 0;
+
+        while table.slot(index).parent != index {
+            index = table.slot(index).parent;
+            depth += 1;
+        }
+
+        depth
+    }
+
+    /// What the class of the hole stands for.
+    pub fn inner(&self) -> HoleInner {
+        let table = self.holes.0.read().unwrap();
+        table.slot(table.root(self.index)).inner.clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self.inner(), HoleInner::Empty(..))
+    }
+
+    /// Fills the class of the hole with a type.
+    pub(crate) fn fill(&self, typ: Type<Virtual>) {
+        let mut table = self.holes.0.write().unwrap();
+        let root = table.root(self.index);
+        table.slot_mut(root).inner = HoleInner::Filled(typ);
+    }
+
+    /// The entry of the hole in the table, so it can be restored after speculating.
+    pub(crate) fn slot(&self) -> Slot {
+        self.holes.0.read().unwrap().slot(self.index).clone()
+    }
+
+    pub(crate) fn restore(&self, slot: Slot) {
+        *self.holes.0.write().unwrap().slot_mut(self.index) = slot;
+    }
+
+    /// Makes every hole in the path to the root point directly to it. The entries that changed
+    /// are given back with their old content.
+    pub(crate) fn compress(&self) -> (Hole, Vec<(Hole, Slot)>) {
+        let mut table = self.holes.0.write().unwrap();
+        let root = table.root(self.index);
+
+        let mut changed = Vec::new();
+        let mut index = self.index;
+
+        while table.slot(index).parent != root {
+            let slot = table.slot(index).clone();
+            table.slot_mut(index).parent = root;
+            changed.push((self.at(index), slot.clone()));
+            index = slot.parent;
+        }
+
+        (self.at(root), changed)
+    }
+
+    /// Joins the classes of two empty holes of the same table. The root with the lower rank goes
+    /// below the other one, and the joined class has the outermost level of both, so it cannot be
+    /// filled with variables that only one of them can see. The entries that changed are given
+    /// back with their old content.
+    pub(crate) fn union(&self, other: &Hole) -> Vec<(Hole, Slot)> {
+        let mut table = self.holes.0.write().unwrap();
+
+        let mut root = table.root(self.index);
+        let mut child = table.root(other.index);
+
+        if root == child {
+            return Vec::new();
+        }
+
+        if table.slot(root).rank < table.slot(child).rank {
+            std::mem::swap(&mut root, &mut child);
+        }
+
+        let changed = vec![
+            (self.at(root), table.slot(root).clone()),
+            (self.at(child), table.slot(child).clone()),
+        ];
+
+        let (HoleInner::Empty(_, outer), HoleInner::Empty(_, inner)) =
+            (&table.slot(root).inner, &table.slot(child).inner)
+        else {
+            unreachable!()
+        };
+
+        let level = (*outer).min(*inner);
+        let rank = table.slot(child).rank;
+
+        table.slot_mut(child).parent = root;
+
+        let slot = table.slot_mut(root);
+
+        if slot.rank == rank {
+            slot.rank += 1;
+        }
+
+        if let HoleInner::Empty(_, outer) = &mut slot.inner {
+            *outer = level;
+        }
+
+        changed
+    }
+}
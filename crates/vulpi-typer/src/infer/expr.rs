@@ -1,21 +1,19 @@
 //! Inference of expressions
 
-use crate::coverage::Problem;
-use crate::coverage::Witness;
+use crate::builtins::Builtin;
 use crate::r#virtual;
 use crate::real::Real;
+use crate::resolution::ItemKind;
+use crate::unused;
 use crate::TypeKind;
 
 use crate::check::Check;
-use im_rc::HashMap;
-use im_rc::HashSet;
 use vulpi_intern::Symbol;
 use vulpi_location::Spanned;
 use vulpi_syntax::elaborated;
-use vulpi_syntax::r#abstract::Qualified;
 use vulpi_syntax::{
     r#abstract::Sttm,
-    r#abstract::{Expr, ExprKind, SttmKind},
+    r#abstract::{Expr, ExprKind, LetExpr, Pattern, PatternKind, SttmKind},
 };
 
 use crate::eval::Eval;
@@ -28,16 +26,27 @@ impl Infer for Expr {
     type Return = (Type<Virtual>, elaborated::Expr<Type<Real>>);
 
     type Context<'a> = (&'a mut Context, Env);
-    
+
     fn infer(&self, (ctx, mut env): Self::Context<'_>) -> Self::Return {
         env.set_current_span(self.span.clone());
-        
+        env.depth += 1;
+
+        ctx.completions.scope(self.span.clone(), &env);
+
+        if ctx.exceeds_limit(&env) {
+            return (
+                Type::error(),
+                Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone()),
+            );
+        }
+
         let elem = match &self.data {
             ExprKind::Application(app) => {
                 let (mut typ, func_elab) = app.func.infer((ctx, env.clone()));
+                let function = typ.clone();
                 let mut elab_args = Vec::new();
 
-                for arg in &app.args {
+                for (given, arg) in app.args.iter().enumerate() {
                     env.set_current_span(arg.span.clone());
 
                     if let Some((left, right)) = ctx.as_function(&env, typ.deref()) {
@@ -45,65 +54,84 @@ impl Infer for Expr {
                         elab_args.push(arg);
                         typ = right;
                     } else {
-                        ctx.report(
-                            &env,
-                            TypeErrorKind::NotAFunction(env.clone(), typ.quote(env.level)),
-                        );
+                        // A function that took some of the arguments is blamed for all of the
+                        // extra ones, instead of its result for not being a function.
+                        if given > 0 && !typ.is_error() {
+                            let last = app.args.last().unwrap();
+                            env.set_current_span(arg.span.clone().mix(last.span.clone()));
+
+                            let kind = TypeErrorKind::TooManyArguments {
+                                env: env.clone(),
+                                expected: given,
+                                found: app.args.len(),
+                                typ: function.quote(env.level),
+                            };
+
+                            ctx.report(&env, kind);
+                        } else if !typ.is_error() {
+                            ctx.report(
+                                &env,
+                                TypeErrorKind::NotAFunction(env.clone(), typ.quote(env.level)),
+                            );
+                        }
                         return (
                             Type::error(),
                             Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone()),
                         );
                     }
                 }
-                
 
                 (
                     typ.clone(),
-                    elab_args.into_iter().fold(func_elab, |acc, arg| {
-                        Spanned::new(
-                            Box::new(elaborated::ExprKind::Application(
-                                elaborated::ApplicationExpr {
-                                    typ: typ.quote(env.level),
-                                    func: acc,
-                                    args: arg,
-                                },
-                            )),
-                            self.span.clone(),
-                        )
-                    }).data,
+                    elab_args
+                        .into_iter()
+                        .fold(func_elab, |acc, arg| {
+                            Spanned::new(
+                                Box::new(elaborated::ExprKind::Application(
+                                    elaborated::ApplicationExpr {
+                                        typ: typ.quote(env.level),
+                                        func: acc,
+                                        args: arg,
+                                    },
+                                )),
+                                self.span.clone(),
+                            )
+                        })
+                        .data,
                 )
             }
-            ExprKind::Variable(m) => (
-                env.vars.get(m).unwrap().clone(),
-                Box::new(elaborated::ExprKind::Variable(m.clone())),
-            ),
-            ExprKind::Constructor(n) => (
-                ctx.modules.constructor(n).0.eval(&env),
-                Box::new(elaborated::ExprKind::Constructor(
-                    ctx.modules.constructor(n).2,
-                    n.clone(),
-                )),
-            ),
-            ExprKind::Function(n) => (
-                ctx.modules.let_decl(n).typ.clone(),
-                Box::new(elaborated::ExprKind::Function(
-                    n.clone(),
-                    ctx.modules.let_decl(n).typ.clone().quote(env.level),
-                )),
-            ),
+            ExprKind::Variable(_)
+            | ExprKind::Constructor(_)
+            | ExprKind::Function(_)
+            | ExprKind::TypeApplication(_) => {
+                let (typ, elab) = infer_reference(ctx, &env, self);
+                (ctx.instantiate_all(&env, &typ), elab)
+            }
+            ExprKind::Let(e) if e.recursive => infer_recursive_let(ctx, env, e),
             ExprKind::Let(e) => {
-                let (val_ty, body_elab) = e.body.infer((ctx, env.clone()));
+                let (val_ty, body_elab) = infer_binding(ctx, &env, e.typ.as_ref(), &e.body);
 
                 let mut hashmap = Default::default();
                 let (pat_ty, pat_elab) = e.pattern.infer((ctx, &mut hashmap, env.clone()));
 
-                ctx.subsumes(env.clone(), pat_ty, val_ty);
+                ctx.expect(env.clone(), pat_ty, val_ty.clone());
+
+                if e.typ.is_some() {
+                    bind_annotation(&e.pattern, val_ty, &mut hashmap);
+                } else {
+                    generalize_binding(ctx, &env, (&e.pattern, &e.body), val_ty, &mut hashmap);
+                }
 
                 for binding in hashmap {
                     env.add_var(binding.0, binding.1)
                 }
 
+                let mut bound = Vec::new();
+                unused::binders(&e.pattern, &mut bound);
+                ctx.add_binders(&mut env, &bound);
+
                 let (typ, value_elab) = e.value.infer((ctx, env.clone()));
+                ctx.report_unused(&bound);
 
                 (
                     typ,
@@ -111,6 +139,8 @@ impl Infer for Expr {
                         pattern: pat_elab,
                         next: value_elab,
                         body: body_elab,
+                        recursive: false,
+                        group: Vec::new(),
                     })),
                 )
             }
@@ -125,55 +155,27 @@ impl Infer for Expr {
                 }
 
                 (
-                    Type::tuple(types),
+                    ctx.interner.tuple(types),
                     Box::new(elaborated::ExprKind::Tuple(
                         vulpi_syntax::elaborated::Tuple { exprs: elaborated },
                     )),
                 )
             }
             ExprKind::Error => (Type::error(), Box::new(elaborated::ExprKind::Error)),
-            ExprKind::When(when) => {
-                // TODO: Check mode
-                ctx.errored = false;
-
-                let (_, arms, ret, elab_arms) = when.arms.infer((ctx, env.clone()));
-                let perform = !ctx.errored;
-
-                if arms.len() != when.scrutinee.len() {
-                    ctx.report(
-                        &env,
-                        TypeErrorKind::WrongArity(arms.len(), when.scrutinee.len()),
-                    );
-                }
-
-                let mut elab_scrutinee = Vec::new();
-
-                for (arm, scrutinee) in arms.iter().cloned().zip(when.scrutinee.iter()) {
-                    let (typ, elab) = scrutinee.infer((ctx, env.clone()));
-                    ctx.subsumes(env.clone(), arm, typ);
-                    elab_scrutinee.push(elab);
-                }
-
-                if perform {
-                    let arms = arms.iter().map(|x| ctx.instantiate(&env, x)).collect();
-
-                    let problem = Problem::exhaustiveness(&elab_arms, arms);
-
-                    if let Witness::NonExhaustive(case) = problem.exaustive(ctx, env.clone()) {
-                        ctx.report(&env, TypeErrorKind::NonExhaustive(case));
-                    };
-                }
-
-                (
-                    ret,
-                    Box::new(elaborated::ExprKind::When(elaborated::WhenExpr {
-                        scrutinee: elab_scrutinee,
-                        arms: elab_arms,
-                    })),
-                )
+            ExprKind::Hole(name) => {
+                let typ = ctx.hole(&env, Type::typ());
+                ctx.typed_hole(&env, name.clone(), typ.clone());
+                (typ, Box::new(elaborated::ExprKind::Error))
+            }
+            ExprKind::When(_) => {
+                // The arms are checked against a hole, so each one of them is compared with the
+                // scrutinees instead of with the first arm.
+                let ret = ctx.hole(&env, Type::typ());
+                let elab = self.check(ret.clone(), (ctx, env.clone()));
+                (ret, elab.data)
             }
             ExprKind::Do(block) => {
-                let mut typ = Type::tuple(vec![]);
+                let mut typ = ctx.builtin(Builtin::Unit, &env);
                 let mut stmts = Vec::new();
 
                 for stmt in &block.sttms {
@@ -184,6 +186,8 @@ impl Infer for Expr {
                     stmts.push(stmt);
                 }
 
+                ctx.report_unused(&unused::block_binders(block));
+
                 (typ, Box::new(elaborated::ExprKind::Do(stmts)))
             }
             ExprKind::Literal(n) => {
@@ -191,10 +195,15 @@ impl Infer for Expr {
                 (typ, Box::new(elaborated::ExprKind::Literal(elab)))
             }
             ExprKind::Annotation(ann) => {
-                let (expr_typ, elab_expr) = ann.expr.infer((ctx, env.clone()));
                 let (typ, _) = ann.typ.infer((ctx, env.clone()));
                 let right = typ.eval(&env);
-                ctx.subsumes(env.clone(), expr_typ, right.clone());
+
+                // Checking against the annotation lets a `forall` in it bind its variables in the
+                // expression, and mismatches point to the annotation.
+                let elab_expr = ctx.with_annotation(Some(ann.typ.span.clone()), |ctx| {
+                    ann.expr.check(right.clone(), (ctx, env.clone()))
+                });
+
                 (right, elab_expr.data)
             }
             ExprKind::Lambda(lam) => {
@@ -205,7 +214,12 @@ impl Infer for Expr {
                     env.add_var(binding.0, binding.1)
                 }
 
+                let mut bound = Vec::new();
+                unused::binders(&lam.param, &mut bound);
+                ctx.add_binders(&mut env, &bound);
+
                 let (body, elab_body) = lam.body.infer((ctx, env.clone()));
+                ctx.report_unused(&bound);
 
                 (
                     Type::new(TypeKind::Arrow(r#virtual::Pi { typ: pat_ty, body })),
@@ -217,10 +231,13 @@ impl Infer for Expr {
             }
             ExprKind::Projection(expr) => {
                 let (ty, elab_expr) = expr.expr.infer((ctx, env.clone()));
+                let ty = record_of_field(ctx, &env, &expr.field, ty);
                 let (head, spine) = ty.application_spine();
 
                 let TypeKind::Variable(name) = head.as_ref() else {
-                    ctx.report(&env, TypeErrorKind::NotARecord);
+                    if !ty.is_error() {
+                        ctx.report(&env, TypeErrorKind::NotARecord);
+                    }
                     return (
                         Type::error(),
                         Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone()),
@@ -245,6 +262,8 @@ impl Infer for Expr {
                     );
                 };
 
+                ctx.refer(self.span.clone(), ItemKind::Field, field_name);
+
                 let field = ctx.modules.field(field_name);
 
                 let eval_ty = field.eval(&env);
@@ -259,7 +278,49 @@ impl Infer for Expr {
                     )),
                 )
             }
+            ExprKind::TupleProjection(projection) => {
+                let (ty, elab_expr) = projection.expr.infer((ctx, env.clone()));
+
+                let typ = match ty.deref().as_ref() {
+                    TypeKind::Tuple(types) if projection.index < types.len() => {
+                        types[projection.index].clone()
+                    }
+                    TypeKind::Tuple(types) => {
+                        let kind = TypeErrorKind::TupleIndexOutOfBounds {
+                            index: projection.index,
+                            arity: types.len(),
+                        };
+                        ctx.report(&env, kind);
+                        Type::error()
+                    }
+                    _ if ty.is_error() => Type::error(),
+                    _ => {
+                        let kind = TypeErrorKind::NotATuple(env.clone(), ty.quote(env.level));
+                        ctx.report(&env, kind);
+                        Type::error()
+                    }
+                };
+
+                (
+                    typ,
+                    Box::new(elaborated::ExprKind::TupleProjection(
+                        elaborated::TupleProjectionExpr {
+                            index: projection.index,
+                            expr: elab_expr,
+                        },
+                    )),
+                )
+            }
             ExprKind::RecordInstance(instance) => {
+                if !ctx.module_exists(&env, &instance.name.path) {
+                    return (
+                        Type::error(),
+                        Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone()),
+                    );
+                }
+
+                ctx.refer(self.span.clone(), ItemKind::Type, &instance.name);
+
                 let typ = ctx.modules.typ(&instance.name);
 
                 let crate::module::Def::Record(rec) = typ.def else {
@@ -270,10 +331,7 @@ impl Infer for Expr {
                     );
                 };
 
-                let iter = rec.into_iter().map(|x| (x.name.clone(), x));
-
-                let available: HashMap<Symbol, Qualified> = HashMap::from_iter(iter);
-                let mut used = HashSet::<Symbol>::default();
+                let names = rec.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
 
                 let binders = typ
                     .binders
@@ -282,24 +340,23 @@ impl Infer for Expr {
                     .collect::<Vec<_>>();
 
                 let ret_type = Type::<Virtual>::application(
-                    Type::variable(instance.name.clone()),
+                    ctx.interner.variable(instance.name.clone()),
                     binders.clone(),
                 );
 
+                let places = ctx.match_fields(&env, &names, &instance.fields, true);
                 let mut elab_fields = Vec::new();
 
-                for (span, name, expr) in &instance.fields {
-                    env.set_current_span(span.clone());
-
-                    let Some(qualified) = available.get(name) else {
-                        ctx.report(&env, TypeErrorKind::NotFoundField);
+                for ((span, name, expr), place) in instance.fields.iter().zip(places) {
+                    let Some(place) = place else {
                         continue;
                     };
 
-                    if used.contains(name) {
-                        ctx.report(&env, TypeErrorKind::DuplicatedField);
-                        continue;
-                    }
+                    env.set_current_span(span.clone());
+
+                    let qualified = &rec[place];
+
+                    ctx.refer(span.clone(), ItemKind::Field, qualified);
 
                     let field = ctx.modules.field(qualified).eval(&env);
                     let inst_field = ctx.instantiate_with_arguments(&field, binders.clone());
@@ -307,18 +364,6 @@ impl Infer for Expr {
                     let elab_expr = expr.check(inst_field.clone(), (ctx, env.clone()));
 
                     elab_fields.push((name.clone(), elab_expr));
-
-                    used.insert(name.clone());
-                }
-
-                let diff = available
-                    .keys()
-                    .cloned()
-                    .collect::<HashSet<_>>()
-                    .difference(used);
-
-                for key in diff {
-                    ctx.report(&env, TypeErrorKind::MissingField(key));
                 }
 
                 (
@@ -331,19 +376,110 @@ impl Infer for Expr {
                     )),
                 )
             }
+            ExprKind::Labeled(instance) => {
+                let exists = ctx.module_exists(&env, &instance.name.path);
+
+                if exists {
+                    ctx.refer(self.span.clone(), ItemKind::Constructor, &instance.name);
+                }
+
+                let cons = exists.then(|| ctx.modules.constructor(&instance.name));
+
+                let Some(cons) = cons.filter(|cons| !cons.labels.is_empty()) else {
+                    if exists {
+                        let name = instance.name.name.clone();
+                        ctx.report(&env, TypeErrorKind::UnlabeledConstructor(name));
+                    }
+
+                    for (_, _, expr) in &instance.fields {
+                        expr.infer((ctx, env.clone()));
+                    }
+
+                    return (
+                        Type::error(),
+                        Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone()),
+                    );
+                };
+
+                let typ = cons.typ.eval(&env);
+                let mut typ = ctx.instantiate_all(&env, &typ);
+                let mut params = Vec::new();
+
+                for _ in 0..cons.arity {
+                    let Some((param_ty, rest)) = ctx.as_function(&env, typ) else {
+                        unreachable!()
+                    };
+
+                    params.push(param_ty);
+                    typ = rest;
+                }
+
+                let places = ctx.match_fields(&env, &cons.labels, &instance.fields, true);
+                let mut args = vec![None; cons.arity];
+
+                for ((span, _, expr), place) in instance.fields.iter().zip(places) {
+                    env.set_current_span(span.clone());
+
+                    let Some(place) = place else {
+                        expr.infer((ctx, env.clone()));
+                        continue;
+                    };
+
+                    args[place] = Some(expr.check(params[place].clone(), (ctx, env.clone())));
+                }
+
+                // The constructor is applied to the fields in the order that they were declared,
+                // so the rest of the compiler never sees the labels.
+                let Some(args) = args.into_iter().collect::<Option<Vec<_>>>() else {
+                    return (
+                        Type::error(),
+                        Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone()),
+                    );
+                };
+
+                let func = Spanned::new(
+                    Box::new(elaborated::ExprKind::Constructor(
+                        cons.type_name,
+                        instance.name.clone(),
+                    )),
+                    self.span.clone(),
+                );
+
+                let elab = args.into_iter().fold(func, |acc, arg| {
+                    Spanned::new(
+                        Box::new(elaborated::ExprKind::Application(
+                            elaborated::ApplicationExpr {
+                                typ: typ.quote(env.level),
+                                func: acc,
+                                args: arg,
+                            },
+                        )),
+                        self.span.clone(),
+                    )
+                });
+
+                (typ, elab.data)
+            }
             ExprKind::RecordUpdate(update) => {
                 let (typ, elab_expr) = update.expr.infer((ctx, env.clone()));
                 let (head, binders) = typ.deref().application_spine();
 
                 let TypeKind::Variable(name) = head.as_ref() else {
-                    ctx.report(&env, TypeErrorKind::NotARecord);
+                    if !typ.is_error() {
+                        ctx.report(&env, TypeErrorKind::NotARecord);
+                    }
                     return (
                         Type::error(),
                         Spanned::new(Box::new(elaborated::ExprKind::Error), self.span.clone()),
                     );
                 };
 
-                let Some(typ) = ctx.modules.get(&name.path).types.get(&name.name).cloned() else {
+                let typ = ctx
+                    .modules
+                    .get(&name.path)
+                    .and_then(|module| module.types.get(&name.name).cloned());
+
+                let Some(typ) = typ else {
                     ctx.report(&env, TypeErrorKind::NotARecord);
                     return (
                         Type::error(),
@@ -359,28 +495,26 @@ impl Infer for Expr {
                     );
                 };
 
-                let iter = rec.iter().map(|x| (x.name.clone(), x.clone()));
-
-                let available: HashMap<Symbol, Qualified> = HashMap::from_iter(iter);
-                let mut used = HashSet::<Symbol>::default();
+                let names = rec.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
 
-                let ret_type =
-                    Type::<Virtual>::application(Type::variable(name.clone()), binders.clone());
+                let ret_type = Type::<Virtual>::application(
+                    ctx.interner.variable(name.clone()),
+                    binders.clone(),
+                );
 
+                let places = ctx.match_fields(&env, &names, &update.fields, false);
                 let mut elab_fields = Vec::new();
 
-                for (span, name, expr) in &update.fields {
-                    env.set_current_span(span.clone());
-
-                    let Some(qualified) = available.get(name) else {
-                        ctx.report(&env, TypeErrorKind::NotFoundField);
+                for ((span, name, expr), place) in update.fields.iter().zip(places) {
+                    let Some(place) = place else {
                         continue;
                     };
 
-                    if used.contains(name) {
-                        ctx.report(&env, TypeErrorKind::DuplicatedField);
-                        continue;
-                    }
+                    env.set_current_span(span.clone());
+
+                    let qualified = &rec[place];
+
+                    ctx.refer(span.clone(), ItemKind::Field, qualified);
 
                     let field = ctx.modules.field(qualified).eval(&env);
                     let inst_field = ctx.instantiate_with_arguments(&field, binders.clone());
@@ -388,8 +522,6 @@ impl Infer for Expr {
                     let elab = expr.check(inst_field.clone(), (ctx, env.clone()));
 
                     elab_fields.push((name.clone(), elab));
-
-                    used.insert(name.clone());
                 }
 
                 (
@@ -409,6 +541,243 @@ impl Infer for Expr {
     }
 }
 
+/// Finds the record of a projection whose expression has a type that is not known yet, looking at
+/// the records that have a field with the projected name. Other types are returned as they are.
+fn record_of_field(
+    ctx: &mut Context,
+    env: &Env,
+    field: &Symbol,
+    typ: Type<Virtual>,
+) -> Type<Virtual> {
+    let typ = typ.deref();
+
+    if !matches!(typ.as_ref(), TypeKind::Hole(_)) {
+        return typ;
+    }
+
+    let records = ctx.modules.records_with_field(field).to_vec();
+
+    match records.as_slice() {
+        [] => typ,
+        [name] => {
+            let binders = ctx.modules.typ(name).binders;
+
+            let args = binders
+                .into_iter()
+                .map(|(_, kind)| ctx.hole::<Virtual>(env, kind))
+                .collect::<Vec<_>>();
+
+            let record = Type::<Virtual>::application(ctx.interner.variable(name.clone()), args);
+            ctx.expect(env.clone(), record.clone(), typ);
+            record
+        }
+        _ => {
+            ctx.report(env, TypeErrorKind::AmbiguousField(field.clone(), records));
+            Type::error()
+        }
+    }
+}
+
+/// Generalizes the type of a variable bound by a local let, so it can be used at different types.
+/// Other patterns can fail to match, so the variables bound by them stay monomorphic, and so do the
+/// ones whose values are not generalized by the value restriction.
+fn generalize_binding(
+    ctx: &mut Context,
+    env: &Env,
+    (pattern, value): (&Pattern, &Expr),
+    typ: Type<Virtual>,
+    bindings: &mut std::collections::HashMap<Symbol, Type<Virtual>>,
+) {
+    if !env.restriction.generalizes(value) {
+        return;
+    }
+
+    if let PatternKind::Variable(name) = &pattern.data {
+        let typ = ctx.generalize(env, &typ);
+        bindings.insert(name.clone(), typ);
+    }
+}
+
+/// Infers the value of a local binding. An annotated value is checked against its annotation, and
+/// the annotation is its type.
+fn infer_binding(
+    ctx: &mut Context,
+    env: &Env,
+    annotation: Option<&vulpi_syntax::r#abstract::Type>,
+    value: &Expr,
+) -> (Type<Virtual>, elaborated::Expr<Type<Real>>) {
+    let Some(annotation) = annotation else {
+        return value.infer((ctx, env.clone()));
+    };
+
+    let (typ, _) = annotation.infer((ctx, env.clone()));
+    let typ = typ.eval(env);
+
+    let elab = ctx.with_annotation(Some(annotation.span.clone()), |ctx| {
+        value.check(typ.clone(), (ctx, env.clone()))
+    });
+
+    (typ, elab)
+}
+
+/// An annotated variable has the type of its annotation, that can be polymorphic, instead of the
+/// generalized type of its value.
+fn bind_annotation(
+    pattern: &Pattern,
+    typ: Type<Virtual>,
+    bindings: &mut std::collections::HashMap<Symbol, Type<Virtual>>,
+) {
+    if let PatternKind::Variable(name) = &pattern.data {
+        bindings.insert(name.clone(), typ);
+    }
+}
+
+/// Infers a `let rec`. The names of the group are bound to the types of their patterns before any
+/// of the bodies is inferred, so the bodies can refer to each other, and they are only generalized
+/// after all of the bodies were inferred.
+fn infer_recursive_let(
+    ctx: &mut Context,
+    mut env: Env,
+    e: &LetExpr,
+) -> (Type<Virtual>, Box<elaborated::ExprKind<Type<Real>>>) {
+    let bindings = std::iter::once((&e.pattern, &e.body))
+        .chain(e.group.iter().map(|(pattern, body)| (pattern, body)))
+        .collect::<Vec<_>>();
+
+    let mut bound = Vec::new();
+    for (pattern, _) in &bindings {
+        unused::binders(pattern, &mut bound);
+    }
+    ctx.add_binders(&mut env, &bound);
+
+    let mut hashmap = Default::default();
+
+    let patterns = bindings
+        .iter()
+        .map(|(pattern, _)| pattern.infer((ctx, &mut hashmap, env.clone())))
+        .collect::<Vec<_>>();
+
+    let mut inner = env.clone();
+
+    for (name, typ) in &hashmap {
+        inner.add_var(name.clone(), typ.clone());
+    }
+
+    let mut elaborated = Vec::new();
+
+    for (binding, (pat_ty, pat_elab)) in bindings.into_iter().zip(patterns) {
+        let (val_ty, body_elab) = binding.1.infer((ctx, inner.clone()));
+        ctx.expect(inner.clone(), pat_ty, val_ty.clone());
+        elaborated.push((binding, val_ty, pat_elab, body_elab));
+    }
+
+    let mut group = Vec::new();
+
+    for (binding, val_ty, pat_elab, body_elab) in elaborated {
+        generalize_binding(ctx, &env, binding, val_ty, &mut hashmap);
+        group.push((pat_elab, body_elab));
+    }
+
+    for binding in hashmap {
+        env.add_var(binding.0, binding.1)
+    }
+
+    let (typ, next) = e.value.infer((ctx, env.clone()));
+    ctx.report_unused(&bound);
+
+    let mut group = group.into_iter();
+    let (pattern, body) = group.next().unwrap();
+
+    (
+        typ,
+        Box::new(elaborated::ExprKind::Let(elaborated::LetExpr {
+            pattern,
+            body,
+            next,
+            recursive: true,
+            group: group.collect(),
+        })),
+    )
+}
+
+/// Infers the type of a reference to a variable, function or constructor without instantiating its
+/// outer `forall`s, so each explicit type argument like `@Int` applies one of them.
+fn infer_reference(
+    ctx: &mut Context,
+    env: &Env,
+    expr: &Expr,
+) -> (Type<Virtual>, Box<elaborated::ExprKind<Type<Real>>>) {
+    env.set_current_span(expr.span.clone());
+
+    match &expr.data {
+        ExprKind::Variable(m) => {
+            ctx.use_variable(env, m);
+
+            if let Some((_, binder)) = env.binders.get(m) {
+                ctx.resolutions
+                    .refer_local(expr.span.clone(), binder.clone());
+            }
+
+            (
+                env.vars.get(m).unwrap().clone(),
+                Box::new(elaborated::ExprKind::Variable(m.clone())),
+            )
+        }
+        ExprKind::Constructor(n) | ExprKind::Function(n) if !ctx.module_exists(env, &n.path) => {
+            (Type::error(), Box::new(elaborated::ExprKind::Error))
+        }
+        ExprKind::Constructor(n) => {
+            ctx.refer(expr.span.clone(), ItemKind::Constructor, n);
+
+            (
+                ctx.modules.constructor(n).typ.eval(env),
+                Box::new(elaborated::ExprKind::Constructor(
+                    ctx.modules.constructor(n).type_name,
+                    n.clone(),
+                )),
+            )
+        }
+        ExprKind::Function(n) => {
+            ctx.refer(expr.span.clone(), ItemKind::Let, n);
+
+            (
+                ctx.modules.let_def(n).typ.clone(),
+                Box::new(elaborated::ExprKind::Function(
+                    n.clone(),
+                    ctx.modules.let_def(n).typ.clone().quote(env.level),
+                )),
+            )
+        }
+        ExprKind::TypeApplication(app) => {
+            let (typ, elab) = infer_reference(ctx, env, &app.expr);
+            let (arg, kind) = app.typ.infer((ctx, env.clone()));
+
+            env.set_current_span(app.typ.span.clone());
+
+            match typ.deref().as_ref() {
+                TypeKind::Forall(forall) => {
+                    ctx.subsumes(env.clone(), kind, forall.kind.clone());
+                    let name = Some(forall.name.clone());
+                    let typ = forall.body.apply(name, arg.eval(env), forall.kind.clone());
+                    (typ, elab)
+                }
+                TypeKind::Error => (typ, elab),
+                _ => {
+                    ctx.report(
+                        env,
+                        TypeErrorKind::TooManyTypeArguments(env.clone(), typ.quote(env.level)),
+                    );
+                    (Type::error(), Box::new(elaborated::ExprKind::Error))
+                }
+            }
+        }
+        _ => {
+            let (typ, elab) = expr.infer((ctx, env.clone()));
+            (typ, elab.data)
+        }
+    }
+}
+
 impl Infer for Sttm {
     type Return = (Type<Virtual>, Env, elaborated::Statement<Type<Real>>);
 
@@ -421,14 +790,27 @@ impl Infer for Sttm {
                 let mut hashmap = Default::default();
                 let (pat_ty, elab_pat) = decl.pat.infer((ctx, &mut hashmap, env.clone()));
 
-                let elab_expr = decl.expr.check(pat_ty, (ctx, env.clone()));
+                let elab_expr = if decl.typ.is_some() {
+                    let (typ, elab) = infer_binding(ctx, env, decl.typ.as_ref(), &decl.expr);
+                    ctx.expect(env.clone(), pat_ty, typ.clone());
+                    bind_annotation(&decl.pat, typ, &mut hashmap);
+                    elab
+                } else {
+                    let elab = decl.expr.check(pat_ty.clone(), (ctx, env.clone()));
+                    generalize_binding(ctx, env, (&decl.pat, &decl.expr), pat_ty, &mut hashmap);
+                    elab
+                };
 
                 for binding in hashmap {
                     env.add_var(binding.0, binding.1)
                 }
 
+                let mut bound = Vec::new();
+                unused::binders(&decl.pat, &mut bound);
+                ctx.add_binders(env, &bound);
+
                 (
-                    Type::tuple(vec![]),
+                    ctx.builtin(Builtin::Unit, env),
                     env.clone(),
                     elaborated::SttmKind::Let(elaborated::LetStatement {
                         pattern: elab_pat,
@@ -3,7 +3,7 @@
 use vulpi_syntax::{elaborated, r#abstract::Literal, r#abstract::LiteralKind};
 
 use super::Infer;
-use crate::{context::Context, r#virtual::Virtual, Env, Type};
+use crate::{builtins::Builtin, context::Context, r#virtual::Virtual, Env, Type};
 
 impl Infer for Literal {
     type Return = (Type<Virtual>, elaborated::Literal);
@@ -15,22 +15,25 @@ impl Infer for Literal {
 
         match &self.data {
             LiteralKind::String(n) => (
-                ctx.find_prelude_type("String", env),
+                ctx.builtin(Builtin::String, &env),
                 Box::new(elaborated::LiteralKind::String(n.clone())),
             ),
             LiteralKind::Integer(n) => (
-                ctx.find_prelude_type("Int", env),
+                ctx.builtin(Builtin::Int, &env),
                 Box::new(elaborated::LiteralKind::Integer(n.clone())),
             ),
             LiteralKind::Float(n) => (
-                ctx.find_prelude_type("Float", env),
+                ctx.builtin(Builtin::Float, &env),
                 Box::new(elaborated::LiteralKind::Float(n.clone())),
             ),
             LiteralKind::Char(n) => (
-                ctx.find_prelude_type("Char", env),
+                ctx.builtin(Builtin::Char, &env),
                 Box::new(elaborated::LiteralKind::Char(n.clone())),
             ),
-            LiteralKind::Unit => (Type::tuple(vec![]), Box::new(elaborated::LiteralKind::Unit)),
+            LiteralKind::Unit => (
+                ctx.builtin(Builtin::Unit, &env),
+                Box::new(elaborated::LiteralKind::Unit),
+            ),
         }
     }
 }
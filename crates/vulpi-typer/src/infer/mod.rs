@@ -42,4 +42,4 @@ impl<T: Infer> Infer for Spanned<T> {
     fn infer(&self, context: Self::Context<'_>) -> Self::Return {
         self.data.infer(context)
     }
-}
\ No newline at end of file
+}
@@ -7,14 +7,16 @@ use vulpi_syntax::{
     elaborated::{self, PatApplication},
     r#abstract::Pattern,
     r#abstract::PatternArm,
-    r#abstract::PatternKind,
+    r#abstract::{LiteralKind, PatternKind},
 };
 
 use crate::{
+    builtins::Builtin,
     context::Context,
-    errors::TypeErrorKind,
+    errors::{ArityOrigin, TypeErrorKind},
     real::Real,
-    Env, Kind, Type,
+    resolution::ItemKind,
+    unused, Env, Kind, Type,
     {eval::Eval, r#virtual::Virtual},
 };
 
@@ -44,17 +46,25 @@ impl Infer for PatternArm {
             env.add_var(binding.0, binding.1);
         }
 
+        let mut bound = Vec::new();
+        for pat in &self.patterns {
+            unused::binders(pat, &mut bound);
+        }
+        ctx.add_binders(&mut env, &bound);
+
         let (typ, elab_expr) = self.expr.infer((ctx, env.clone()));
 
-        let guard = self.guard.as_ref().map(|g| g.infer((ctx, env.clone())));
+        let elab_guard = ctx.with_annotation(None, |ctx| {
+            self.guard.as_ref().map(|guard| {
+                let (typ, elab) = guard.infer((ctx, env.clone()));
+                env.set_current_span(guard.span.clone());
+                let bool = ctx.builtin(Builtin::Bool, &env);
+                ctx.expect(env.clone(), bool, typ);
+                elab
+            })
+        });
 
-        let elab_guard = if let Some((typ, guard)) = guard {
-            let bool = ctx.find_prelude_type("Bool", env.clone());
-            ctx.subsumes(env.clone(), typ, bool);
-            Some(guard)
-        } else {
-            None
-        };
+        ctx.report_unused(&bound);
 
         (
             patterns,
@@ -91,28 +101,33 @@ impl Infer for Vec<PatternArm> {
 
             let (types, fst_type, elab_arm) = self[0].infer((ctx, env.clone()));
 
-            ctx.subsumes(env.clone(), fst_type.clone(), ret.clone());
+            ctx.expect(env.clone(), ret.clone(), fst_type.clone());
 
             let mut elab_arms = vec![elab_arm];
 
             for pat in self.iter().skip(1) {
                 let (new_types, new_ret_type, elab_arm) = pat.infer((ctx, env.clone()));
 
-                elab_arms.push(elab_arm);
+                ctx.expect(env.clone(), ret.clone(), new_ret_type.clone());
 
                 if new_types.len() != types.len() {
+                    env.set_current_span(pat.patterns_span());
                     ctx.report(
                         &env,
-                        TypeErrorKind::WrongArity(new_types.len(), types.len()),
+                        TypeErrorKind::WrongArity {
+                            expected: types.len(),
+                            found: new_types.len(),
+                            origin: Some(ArityOrigin::FirstArm(self[0].patterns_span())),
+                        },
                     );
-                    return (Type::error(), types, Type::error(), vec![]);
+                    continue;
                 }
 
+                elab_arms.push(elab_arm);
+
                 for (old, new) in types.iter().zip(new_types) {
-                    ctx.subsumes(env.clone(), old.clone(), new);
+                    ctx.expect(env.clone(), old.clone(), new);
                 }
-
-                ctx.subsumes(env.clone(), new_ret_type.clone(), ret.clone());
             }
 
             (
@@ -149,7 +164,7 @@ impl Infer for Pattern {
                 }
 
                 (
-                    Type::tuple(types),
+                    ctx.interner.tuple(types),
                     Box::new(elaborated::PatternKind::Tuple(elab_pats)),
                 )
             }
@@ -168,6 +183,11 @@ impl Infer for Pattern {
                 )
             }
             PatternKind::Literal(lit) => {
+                // Comparing floats for equality depends on rounding, so they cannot be matched.
+                if let LiteralKind::Float(_) = &lit.data {
+                    ctx.report(&env, TypeErrorKind::FloatPattern);
+                }
+
                 let (typ, lit) = lit.infer((ctx, env));
                 (typ, Box::new(elaborated::PatternKind::Literal(lit)))
             }
@@ -175,19 +195,69 @@ impl Infer for Pattern {
                 let (typ, _) = ann.typ.infer((ctx, env.clone()));
                 let eval_typ = typ.eval(&env);
                 let (value, pat) = ann.pat.infer((ctx, map, env.clone()));
-                ctx.subsumes(env, eval_typ.clone(), value);
+
+                // The pattern is instantiated from the annotation, but the annotation is the type
+                // that is expected.
+                if let Err(kind) = ctx.try_subsumes(env.clone(), eval_typ.clone(), value) {
+                    ctx.report(&env, kind.flipped());
+                }
+
                 (eval_typ, pat)
             }
-            PatternKind::Or(_) => {
-                unimplemented!("Or patterns are not yet implemented")
+            PatternKind::Or(or) => {
+                // Both sides share the map, so a variable that they bind gets the same type.
+                let (left_ty, left) = or.left.infer((ctx, map, env.clone()));
+                let (right_ty, right) = or.right.infer((ctx, map, env.clone()));
+                ctx.expect(env, left_ty.clone(), right_ty);
+
+                (
+                    left_ty,
+                    Box::new(elaborated::PatternKind::Or(elaborated::PatOr {
+                        left,
+                        right,
+                    })),
+                )
             }
             PatternKind::Application(app) => {
-                let (typ, arity, _) = ctx.modules.constructor(&app.func);
+                if !ctx.module_exists(&env, &app.func.path) {
+                    for arg in &app.args {
+                        arg.infer((ctx, map, env.clone()));
+                    }
+
+                    return (Type::error(), Box::new(elaborated::PatternKind::Error));
+                }
+
+                ctx.refer(self.span.clone(), ItemKind::Constructor, &app.func);
 
-                let mut typ = typ.eval(&env);
+                let cons = ctx.modules.constructor(&app.func);
+                let arity = cons.arity;
+
+                // Constructors without arguments would otherwise escape with their type variables
+                // still bound by the `forall`.
+                let typ = cons.typ.eval(&env);
+                let mut typ = ctx.instantiate_all(&env, &typ);
 
                 if arity != app.args.len() {
-                    ctx.report(&env, TypeErrorKind::WrongArity(arity, app.args.len()));
+                    let declaration = ctx
+                        .resolutions
+                        .definition(ItemKind::Constructor, &app.func)
+                        .map(|site| site.span.clone());
+
+                    ctx.report(
+                        &env,
+                        TypeErrorKind::ConstructorArityMismatch {
+                            expected: arity,
+                            found: app.args.len(),
+                            constructor: app.func.clone(),
+                            declaration,
+                        },
+                    );
+
+                    // The sub patterns still bind variables that the body of the arm refers to.
+                    for arg in &app.args {
+                        arg.infer((ctx, map, env.clone()));
+                    }
+
                     return (Type::error(), Box::new(elaborated::PatternKind::Error));
                 }
 
@@ -206,7 +276,7 @@ impl Infer for Pattern {
 
                     typ = rest;
 
-                    ctx.subsumes(env.clone(), arg_ty, param_ty);
+                    ctx.expect(env.clone(), param_ty, arg_ty);
                 }
 
                 (
@@ -217,6 +287,62 @@ impl Infer for Pattern {
                     })),
                 )
             }
+            PatternKind::Labeled(labeled) => {
+                let exists = ctx.module_exists(&env, &labeled.func.path);
+
+                if exists {
+                    ctx.refer(self.span.clone(), ItemKind::Constructor, &labeled.func);
+                }
+
+                let cons = exists.then(|| ctx.modules.constructor(&labeled.func));
+
+                let Some(cons) = cons.filter(|cons| !cons.labels.is_empty()) else {
+                    if exists {
+                        let name = labeled.func.name.clone();
+                        ctx.report(&env, TypeErrorKind::UnlabeledConstructor(name));
+                    }
+
+                    for (_, _, pattern) in &labeled.fields {
+                        pattern.infer((ctx, map, env.clone()));
+                    }
+
+                    return (Type::error(), Box::new(elaborated::PatternKind::Error));
+                };
+
+                let typ = cons.typ.eval(&env);
+                let mut typ = ctx.instantiate_all(&env, &typ);
+                let mut params = Vec::new();
+
+                for _ in 0..cons.arity {
+                    let Some((param_ty, rest)) = ctx.as_function(&env, typ) else {
+                        unreachable!()
+                    };
+
+                    params.push(param_ty);
+                    typ = rest;
+                }
+
+                // The fields that are left out of the pattern match anything.
+                let mut args = vec![Box::new(elaborated::PatternKind::Wildcard); cons.arity];
+                let places = ctx.match_fields(&env, &cons.labels, &labeled.fields, false);
+
+                for ((_, _, pattern), place) in labeled.fields.iter().zip(places) {
+                    let (arg_ty, elab_arg) = pattern.infer((ctx, map, env.clone()));
+
+                    if let Some(place) = place {
+                        ctx.expect(env.clone(), params[place].clone(), arg_ty);
+                        args[place] = elab_arg;
+                    }
+                }
+
+                (
+                    typ,
+                    Box::new(elaborated::PatternKind::Application(PatApplication {
+                        func: labeled.func.clone(),
+                        args,
+                    })),
+                )
+            }
             PatternKind::Error => (Type::error(), Box::new(elaborated::PatternKind::Error)),
         }
     }
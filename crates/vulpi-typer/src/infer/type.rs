@@ -4,9 +4,11 @@ use crate::{
     context::Context,
     errors::TypeErrorKind,
     eval::{Eval, Quote},
+    module::Def,
     r#virtual::Env,
     r#virtual::Virtual,
     real::{self, Real},
+    resolution::ItemKind,
     Index, Kind, Type,
 };
 
@@ -25,11 +27,11 @@ impl Infer for r#abstract::Type {
             TypeKind::Arrow(pi) => {
                 let (typ, kind) = pi.left.infer((ctx, env.clone()));
                 env.set_current_span(pi.left.span.clone());
-                ctx.subsumes(env.clone(), kind, Kind::typ());
+                ctx.expect_type_kind(&env, kind);
 
                 let (body, kind) = pi.right.infer((ctx, env.clone()));
                 env.set_current_span(pi.right.span.clone());
-                ctx.subsumes(env.clone(), kind, Kind::typ());
+                ctx.expect_type_kind(&env, kind);
 
                 let typ = Type::new(crate::TypeKind::Arrow(real::Arrow { typ, body }));
                 (typ, Kind::typ())
@@ -39,34 +41,58 @@ impl Infer for r#abstract::Type {
 
                 for ty in t {
                     let (ty, kind) = ty.infer((ctx, env.clone()));
-                    ctx.subsumes(env.clone(), kind, Kind::typ());
+                    ctx.expect_type_kind(&env, kind);
                     types.push(ty);
                 }
 
                 (Type::tuple(types), Kind::typ())
             }
             TypeKind::Application(app) => {
-                let (ty, mut k) = app.func.infer((ctx, env.clone()));
+                let (ty, head_kind) = app.func.infer((ctx, env.clone()));
 
+                let mut k = head_kind.clone();
                 let mut args = Vec::new();
 
-                for arg in &app.args {
+                for (position, arg) in app.args.iter().enumerate() {
                     env.set_current_span(arg.span.clone());
 
                     let (arg_ty, arg_kind) = arg.infer((ctx, env.clone()));
 
-                    args.push(arg_ty);
+                    let Some((left, right)) = ctx.as_function(&env, k.deref()) else {
+                        if !k.is_error() {
+                            let applied = Type::<Real>::application(ty.clone(), args);
+
+                            let kind = TypeErrorKind::NotAFunctionKind {
+                                env: env.clone(),
+                                head: ty,
+                                head_kind: head_kind.quote(env.level),
+                                applied,
+                                kind: k.quote(env.level),
+                            };
+
+                            ctx.report(&env, kind);
+                        }
 
-                    if let Some((left, right)) = ctx.as_function(&env, k.deref()) {
-                        ctx.subsumes(env.clone(), arg_kind, left);
-                        k = right;
-                    } else {
-                        ctx.report(
-                            &env,
-                            TypeErrorKind::NotAFunction(env.clone(), k.quote(env.level)),
-                        );
                         return (Type::error(), Kind::error());
+                    };
+
+                    let result = ctx.try_subsumes(env.clone(), arg_kind.clone(), left.clone());
+
+                    if result.is_err() {
+                        let kind = TypeErrorKind::ArgumentKindMismatch {
+                            env: env.clone(),
+                            head: ty.clone(),
+                            head_kind: head_kind.quote(env.level),
+                            position,
+                            expected: left.quote(env.level),
+                            found: arg_kind.quote(env.level),
+                        };
+
+                        ctx.report(&env, kind);
                     }
+
+                    args.push(arg_ty);
+                    k = right;
                 }
 
                 (Type::<Real>::application(ty, args), k)
@@ -97,7 +123,21 @@ impl Infer for r#abstract::Type {
 
                 (Type::bound(Index(index)), kind)
             }
-            TypeKind::Type(name) => (Type::variable(name.clone()), ctx.modules.typ(name).kind),
+            TypeKind::Type(name) if !ctx.module_exists(&env, &name.path) => {
+                (Type::error(), Kind::error())
+            }
+            TypeKind::Type(name) => {
+                let typ = ctx.modules.typ(name);
+
+                let kind = match typ.def {
+                    Def::Constraint => ItemKind::Trait,
+                    _ => ItemKind::Type,
+                };
+
+                ctx.refer(self.span.clone(), kind, name);
+
+                (Type::variable(name.clone()), typ.kind)
+            }
             TypeKind::Unit => (Type::tuple(Vec::new()), Kind::typ()),
             TypeKind::Error => (Type::error(), Kind::error()),
         }
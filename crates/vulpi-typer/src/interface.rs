@@ -0,0 +1,585 @@
+//! Interface files of modules. After a module is defined, the types of its declarations can be
+//! written as text and loaded by another run of the checker, so the modules that use it are checked
+//! without declaring it again. The file is a list of S-expressions where every atom is a quoted
+//! string, so symbols are stored as text and interned again when they are loaded.
+//!
+//! The checker keeps the private declarations of a module in its interface too, and they are
+//! written as well because the resolver is the one that hides them.
+
+use std::{iter::Peekable, str::Chars};
+
+use vulpi_intern::Symbol;
+use vulpi_location::{stable_hash, Byte, FileId, Span};
+use vulpi_syntax::r#abstract::Qualified;
+
+use crate::{
+    context::Context,
+    eval::{Eval, Quote},
+    module::{ConstructorData, Def, Interface, LetDef, TypeData},
+    real::{self, Real},
+    Env, Index, Level, Type, TypeKind,
+};
+
+/// The version of the format. Interfaces written with other versions are rejected.
+pub const VERSION: usize = 2;
+
+const MAGIC: &str = "vulpi-interface";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InterfaceError {
+    /// The interface was written by another version of the format.
+    Version(String),
+
+    /// The source of the module changed after the interface was written.
+    Stale,
+
+    /// The text is not an interface.
+    Malformed,
+}
+
+type Result<T> = std::result::Result<T, InterfaceError>;
+
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+impl Sexp {
+    fn atom(text: impl ToString) -> Sexp {
+        Sexp::Atom(text.to_string())
+    }
+
+    fn tagged(tag: &str, mut items: Vec<Sexp>) -> Sexp {
+        items.insert(0, Sexp::atom(tag));
+        Sexp::List(items)
+    }
+
+    fn write(&self, out: &mut String) {
+        match self {
+            Sexp::Atom(text) => {
+                out.push('"');
+
+                for char in text.chars() {
+                    match char {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        _ => out.push(char),
+                    }
+                }
+
+                out.push('"');
+            }
+            Sexp::List(items) => {
+                out.push('(');
+
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 {
+                        out.push(' ');
+                    }
+                    item.write(out);
+                }
+
+                out.push(')');
+            }
+        }
+    }
+
+    fn parse(chars: &mut Peekable<Chars>) -> Result<Sexp> {
+        while chars.next_if(|char| char.is_whitespace()).is_some() {}
+
+        match chars.next() {
+            Some('(') => {
+                let mut items = Vec::new();
+
+                loop {
+                    while chars.next_if(|char| char.is_whitespace()).is_some() {}
+
+                    if chars.next_if_eq(&')').is_some() {
+                        return Ok(Sexp::List(items));
+                    }
+
+                    items.push(Sexp::parse(chars)?);
+                }
+            }
+            Some('"') => {
+                let mut text = String::new();
+
+                loop {
+                    match chars.next().ok_or(InterfaceError::Malformed)? {
+                        '"' => return Ok(Sexp::Atom(text)),
+                        '\\' => match chars.next().ok_or(InterfaceError::Malformed)? {
+                            'n' => text.push('\n'),
+                            char => text.push(char),
+                        },
+                        char => text.push(char),
+                    }
+                }
+            }
+            _ => Err(InterfaceError::Malformed),
+        }
+    }
+
+    fn as_atom(&self) -> Result<&str> {
+        match self {
+            Sexp::Atom(text) => Ok(text),
+            Sexp::List(_) => Err(InterfaceError::Malformed),
+        }
+    }
+
+    fn as_list(&self) -> Result<&[Sexp]> {
+        match self {
+            Sexp::List(items) => Ok(items),
+            Sexp::Atom(_) => Err(InterfaceError::Malformed),
+        }
+    }
+
+    /// Splits a list that starts with an atom into the atom and the rest of it.
+    fn as_tagged(&self) -> Result<(&str, &[Sexp])> {
+        match self.as_list()? {
+            [tag, rest @ ..] => Ok((tag.as_atom()?, rest)),
+            [] => Err(InterfaceError::Malformed),
+        }
+    }
+
+    fn as_symbol(&self) -> Result<Symbol> {
+        self.as_atom().map(Symbol::intern)
+    }
+
+    fn as_number(&self) -> Result<usize> {
+        self.as_atom()?
+            .parse()
+            .map_err(|_| InterfaceError::Malformed)
+    }
+}
+
+/// A hash of the source of a module that does not change between runs of the compiler.
+fn source_hash(source: &str) -> String {
+    format!("{:016x}", stable_hash(source.as_bytes()))
+}
+
+fn qualified(name: &Qualified) -> Sexp {
+    Sexp::List(vec![
+        Sexp::atom(name.path.get()),
+        Sexp::atom(name.name.get()),
+    ])
+}
+
+fn read_qualified(sexp: &Sexp) -> Result<Qualified> {
+    match sexp.as_list()? {
+        [path, name] => Ok(Qualified {
+            path: path.as_symbol()?,
+            name: name.as_symbol()?,
+        }),
+        _ => Err(InterfaceError::Malformed),
+    }
+}
+
+fn typ(typ: &Type<Real>) -> Sexp {
+    match typ.as_ref() {
+        TypeKind::Type => Sexp::tagged("type", vec![]),
+        TypeKind::Constraint => Sexp::tagged("constraint", vec![]),
+        TypeKind::Arrow(arrow) => {
+            Sexp::tagged("arrow", vec![self::typ(&arrow.typ), self::typ(&arrow.body)])
+        }
+        TypeKind::Forall(forall) => Sexp::tagged(
+            "forall",
+            vec![
+                Sexp::atom(forall.name.get()),
+                self::typ(&forall.kind),
+                self::typ(&forall.body),
+            ],
+        ),
+        TypeKind::Variable(name) => Sexp::tagged("variable", vec![qualified(name)]),
+        TypeKind::Bound(index) => Sexp::tagged("bound", vec![Sexp::atom(index.0)]),
+        TypeKind::Tuple(types) => Sexp::tagged("tuple", types.iter().map(self::typ).collect()),
+        TypeKind::Application(func, arg) => {
+            Sexp::tagged("application", vec![self::typ(func), self::typ(arg)])
+        }
+        TypeKind::Qualified(constraint, body) => {
+            Sexp::tagged("qualified", vec![self::typ(constraint), self::typ(body)])
+        }
+        // Holes that survive until the end of a module cannot be filled by another module, and
+        // they were already reported as ambiguous types.
+        TypeKind::Hole(_) | TypeKind::Error => Sexp::tagged("error", vec![]),
+    }
+}
+
+fn read_type(sexp: &Sexp) -> Result<Type<Real>> {
+    let kind = match sexp.as_tagged()? {
+        ("type", []) => TypeKind::Type,
+        ("constraint", []) => TypeKind::Constraint,
+        ("arrow", [typ, body]) => TypeKind::Arrow(real::Arrow {
+            typ: read_type(typ)?,
+            body: read_type(body)?,
+        }),
+        ("forall", [name, kind, body]) => TypeKind::Forall(real::Forall {
+            name: name.as_symbol()?,
+            kind: read_type(kind)?,
+            body: read_type(body)?,
+        }),
+        ("variable", [name]) => TypeKind::Variable(read_qualified(name)?),
+        ("bound", [index]) => TypeKind::Bound(Index(index.as_number()?)),
+        ("tuple", types) => TypeKind::Tuple(types.iter().map(read_type).collect::<Result<_>>()?),
+        ("application", [func, arg]) => TypeKind::Application(read_type(func)?, read_type(arg)?),
+        ("qualified", [constraint, body]) => {
+            TypeKind::Qualified(read_type(constraint)?, read_type(body)?)
+        }
+        ("error", []) => TypeKind::Error,
+        _ => return Err(InterfaceError::Malformed),
+    };
+
+    Ok(Type::new(kind))
+}
+
+fn types(types: &[Type<Real>]) -> Sexp {
+    Sexp::List(types.iter().map(typ).collect())
+}
+
+fn read_types(sexp: &Sexp) -> Result<Vec<Type<Real>>> {
+    sexp.as_list()?.iter().map(read_type).collect()
+}
+
+fn binders(binders: &[(Symbol, Type<Real>)]) -> Sexp {
+    Sexp::List(
+        binders
+            .iter()
+            .map(|(name, kind)| Sexp::List(vec![Sexp::atom(name.get()), typ(kind)]))
+            .collect(),
+    )
+}
+
+fn read_binders(sexp: &Sexp) -> Result<Vec<(Symbol, Type<Real>)>> {
+    sexp.as_list()?
+        .iter()
+        .map(|binder| match binder.as_list()? {
+            [name, kind] => Ok((name.as_symbol()?, read_type(kind)?)),
+            _ => Err(InterfaceError::Malformed),
+        })
+        .collect()
+}
+
+fn def(def: &Def) -> Sexp {
+    let names = |names: &[Qualified]| names.iter().map(qualified).collect();
+
+    match def {
+        Def::Enum(constructors) => Sexp::tagged("enum", names(constructors)),
+        Def::Record(fields) => Sexp::tagged("record", names(fields)),
+        Def::Effect(operations) => Sexp::tagged("effect", names(operations)),
+        Def::Synonym => Sexp::tagged("synonym", vec![]),
+        Def::Abstract => Sexp::tagged("abstract", vec![]),
+        Def::Constraint => Sexp::tagged("constraint", vec![]),
+    }
+}
+
+fn read_def(sexp: &Sexp) -> Result<Def> {
+    let names = |names: &[Sexp]| names.iter().map(read_qualified).collect::<Result<Vec<_>>>();
+
+    match sexp.as_tagged()? {
+        ("enum", constructors) => Ok(Def::Enum(names(constructors)?)),
+        ("record", fields) => Ok(Def::Record(names(fields)?)),
+        ("effect", operations) => Ok(Def::Effect(names(operations)?)),
+        ("synonym", []) => Ok(Def::Synonym),
+        ("abstract", []) => Ok(Def::Abstract),
+        ("constraint", []) => Ok(Def::Constraint),
+        _ => Err(InterfaceError::Malformed),
+    }
+}
+
+fn span(span: &Span) -> Sexp {
+    Sexp::List(vec![
+        Sexp::atom(span.file.0),
+        Sexp::atom(span.start.0),
+        Sexp::atom(span.end.0),
+    ])
+}
+
+fn read_span(sexp: &Sexp) -> Result<Span> {
+    match sexp.as_list()? {
+        [file, start, end] => Ok(Span {
+            file: FileId(file.as_number()?),
+            start: Byte(start.as_number()?),
+            end: Byte(end.as_number()?),
+        }),
+        _ => Err(InterfaceError::Malformed),
+    }
+}
+
+/// Sorts the entries of a table by name, so the same interface is always written in the same way.
+fn sorted<T>(table: &std::collections::HashMap<Symbol, T>) -> Vec<(&Symbol, &T)> {
+    let mut entries = table.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(name, _)| name.get());
+    entries
+}
+
+/// The entries of an interface. Without the spans, they are only what other modules can see.
+fn entries(interface: &Interface, spans: bool) -> Vec<Sexp> {
+    let mut entries = Vec::new();
+
+    for (name, data) in sorted(&interface.types) {
+        let data_binders = data
+            .binders
+            .iter()
+            .map(|(name, kind)| (name.clone(), kind.quote(Level(0))))
+            .collect::<Vec<_>>();
+
+        let mut items = vec![
+            Sexp::atom(name.get()),
+            typ(&data.kind.quote(Level(0))),
+            binders(&data_binders),
+            Sexp::atom(data.module.get()),
+            def(&data.def),
+        ];
+
+        if spans {
+            items.push(span(&data.span));
+        }
+
+        entries.push(Sexp::tagged("type", items));
+    }
+
+    for (name, data) in sorted(&interface.constructors) {
+        entries.push(Sexp::tagged(
+            "constructor",
+            vec![
+                Sexp::atom(name.get()),
+                typ(&data.typ),
+                types(&data.args),
+                Sexp::atom(data.arity),
+                qualified(&data.type_name),
+                Sexp::List(
+                    data.labels
+                        .iter()
+                        .map(|label| Sexp::atom(label.get()))
+                        .collect(),
+                ),
+            ],
+        ));
+    }
+
+    for (name, field) in sorted(&interface.fields) {
+        entries.push(Sexp::tagged(
+            "field",
+            vec![Sexp::atom(name.get()), typ(field)],
+        ));
+    }
+
+    for (name, def) in sorted(&interface.variables) {
+        let depth = Level(def.unbound.len());
+
+        entries.push(Sexp::tagged(
+            "let",
+            vec![
+                Sexp::atom(name.get()),
+                typ(&def.typ.quote(Level(0))),
+                binders(&def.unbound),
+                types(&def.args),
+                typ(&def.ret.quote(depth)),
+            ],
+        ));
+    }
+
+    entries
+}
+
+/// Writes the interface of a module that was already defined. The source is the text of the
+/// module, and it's used to detect interfaces that are older than their module.
+pub fn write_interface(ctx: &Context, module: &Symbol, source: &str) -> Option<String> {
+    ctx.modules.get(module)?;
+
+    let header = Sexp::List(vec![
+        Sexp::atom(MAGIC),
+        Sexp::atom(VERSION),
+        Sexp::atom(source_hash(source)),
+        Sexp::atom(module.get()),
+    ]);
+
+    let mut out = String::new();
+    header.write(&mut out);
+    out.push('\n');
+    out.push_str(&body(ctx, module, true));
+
+    Some(out)
+}
+
+/// A hash of the part of the interface of a module that other modules can see. It does not change
+/// when only the source or the spans of the module change, so the modules that use it do not have
+/// to be checked again.
+pub fn interface_hash(ctx: &Context, module: &Symbol) -> u64 {
+    stable_hash(body(ctx, module, false).as_bytes())
+}
+
+/// The entries of the interface of a module, after the header.
+fn body(ctx: &Context, module: &Symbol, spans: bool) -> String {
+    // The constructors and the fields of a type are in a module named after the type, and inline
+    // modules have their own interfaces too, so all of them are written after their name.
+    let prefix = format!("{}.", module.get());
+
+    let mut modules = ctx
+        .modules
+        .modules
+        .iter()
+        .filter(|(name, _)| *name == module || name.get().starts_with(&prefix))
+        .collect::<Vec<_>>();
+
+    modules.sort_by_key(|(name, _)| name.get());
+
+    let mut out = String::new();
+
+    for (name, interface) in modules {
+        let start = Sexp::tagged("module", vec![Sexp::atom(name.get())]);
+
+        for entry in std::iter::once(start).chain(entries(interface, spans)) {
+            entry.write(&mut out);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+fn read_entry(ctx: &mut Context, module: &Symbol, entry: &Sexp) -> Result<()> {
+    let env = Env::default();
+
+    match entry.as_tagged()? {
+        ("type", [name, kind, data_binders, data_module, data_def, data_span]) => {
+            let data = TypeData {
+                kind: read_type(kind)?.eval(&env),
+                binders: read_binders(data_binders)?
+                    .into_iter()
+                    .map(|(name, kind)| (name, kind.eval(&env)))
+                    .collect(),
+                module: data_module.as_symbol()?,
+                def: read_def(data_def)?,
+                span: read_span(data_span)?,
+                // The documentation and the attributes are read from the sources, so interfaces
+                // do not keep them.
+                docs: None,
+                attributes: Vec::new(),
+            };
+
+            let name = name.as_symbol()?;
+
+            if let Def::Record(fields) = &data.def {
+                let record = Qualified {
+                    path: module.clone(),
+                    name: name.clone(),
+                };
+
+                for field in fields {
+                    let records = ctx.modules.records.entry(field.name.clone()).or_default();
+
+                    if !records.contains(&record) {
+                        records.push(record.clone());
+                    }
+                }
+            }
+
+            ctx.modules.get_or_create(module).types.insert(name, data);
+        }
+        ("constructor", [name, typ, args, arity, type_name, labels]) => {
+            let data = ConstructorData {
+                typ: read_type(typ)?,
+                args: read_types(args)?,
+                arity: arity.as_number()?,
+                labels: labels
+                    .as_list()?
+                    .iter()
+                    .map(Sexp::as_symbol)
+                    .collect::<Result<_>>()?,
+                type_name: read_qualified(type_name)?,
+                docs: None,
+                attributes: Vec::new(),
+            };
+
+            let name = name.as_symbol()?;
+            ctx.modules
+                .get_or_create(module)
+                .constructors
+                .insert(name, data);
+        }
+        ("field", [name, typ]) => {
+            let typ = read_type(typ)?;
+            let name = name.as_symbol()?;
+            ctx.modules.get_or_create(module).fields.insert(name, typ);
+        }
+        ("let", [name, typ, unbound, args, ret]) => {
+            let unbound = read_binders(unbound)?;
+
+            let mut ret_env = env.clone();
+
+            for (name, kind) in &unbound {
+                ret_env = ret_env.add(Some(name.clone()), kind.eval(&ret_env));
+            }
+
+            let def = LetDef {
+                typ: ctx.interner.intern(&read_type(typ)?.eval(&env)),
+                unbound,
+                args: read_types(args)?,
+                ret: ctx.interner.intern(&read_type(ret)?.eval(&ret_env)),
+                docs: None,
+                attributes: Vec::new(),
+            };
+
+            let name = name.as_symbol()?;
+            ctx.modules
+                .get_or_create(module)
+                .variables
+                .insert(name, def);
+        }
+        _ => return Err(InterfaceError::Malformed),
+    }
+
+    Ok(())
+}
+
+/// Loads an interface written by [write_interface] and returns the name of its module. The source
+/// is the current text of the module; if it changed since the interface was written, the interface
+/// is [InterfaceError::Stale] and nothing is loaded.
+pub fn read_interface(ctx: &mut Context, text: &str, source: &str) -> Result<Symbol> {
+    let mut chars = text.chars().peekable();
+    let mut sexps = Vec::new();
+
+    loop {
+        while chars.next_if(|char| char.is_whitespace()).is_some() {}
+
+        if chars.peek().is_none() {
+            break;
+        }
+
+        sexps.push(Sexp::parse(&mut chars)?);
+    }
+
+    let Some((header, entries)) = sexps.split_first() else {
+        return Err(InterfaceError::Malformed);
+    };
+
+    let (magic, [version, hash, module]) = header.as_tagged()? else {
+        return Err(InterfaceError::Malformed);
+    };
+
+    if magic != MAGIC {
+        return Err(InterfaceError::Malformed);
+    }
+
+    if version.as_atom()? != VERSION.to_string() {
+        return Err(InterfaceError::Version(version.as_atom()?.to_string()));
+    }
+
+    if hash.as_atom()? != source_hash(source) {
+        return Err(InterfaceError::Stale);
+    }
+
+    let module = module.as_symbol()?;
+    let mut current = None;
+
+    for entry in entries {
+        match (entry.as_tagged()?, &current) {
+            (("module", [name]), _) => current = Some(name.as_symbol()?),
+            (_, Some(current)) => read_entry(ctx, current, entry)?,
+            (_, None) => return Err(InterfaceError::Malformed),
+        }
+    }
+
+    Ok(module)
+}
@@ -0,0 +1,145 @@
+//! Hash consing of types. Types without holes and without bound variables like `Int` or
+//! `Int -> Int` are built again and again while checking a module, so the [Interner] keeps a single
+//! allocation for each one of them. Two interned types are the same if and only if they are the same pointer.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use vulpi_syntax::r#abstract::Qualified;
+
+use crate::{
+    r#virtual::{Pi, Virtual},
+    Type, TypeKind,
+};
+
+/// The structure of an interned type. The children are identified by their pointers because they
+/// were interned before.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Type,
+    Constraint,
+    Variable(Qualified),
+    Arrow(usize, usize),
+    Application(usize, usize),
+    Tuple(Vec<usize>),
+}
+
+/// The table of interned types.
+#[derive(Clone, Default)]
+pub struct Interner {
+    table: HashMap<Key, Type<Virtual>>,
+    interned: HashSet<usize>,
+
+    /// Number of types that were found in the table instead of being allocated.
+    pub hits: usize,
+
+    /// Number of types that were allocated and added to the table.
+    pub misses: usize,
+}
+
+fn address(typ: &Type<Virtual>) -> usize {
+    Arc::as_ptr(&typ.0) as usize
+}
+
+impl Interner {
+    fn is_interned(&self, typ: &Type<Virtual>) -> bool {
+        self.interned.contains(&address(typ))
+    }
+
+    fn get_or_insert(&mut self, key: Key, build: impl FnOnce() -> Type<Virtual>) -> Type<Virtual> {
+        if let Some(typ) = self.table.get(&key) {
+            self.hits += 1;
+            return typ.clone();
+        }
+
+        self.misses += 1;
+
+        let typ = build();
+        self.interned.insert(address(&typ));
+        self.table.insert(key, typ.clone());
+        typ
+    }
+
+    pub fn variable(&mut self, name: Qualified) -> Type<Virtual> {
+        self.get_or_insert(Key::Variable(name.clone()), || Type::variable(name))
+    }
+
+    pub fn arrow(&mut self, typ: Type<Virtual>, body: Type<Virtual>) -> Type<Virtual> {
+        let (typ, body) = (typ.deref(), body.deref());
+
+        if !self.is_interned(&typ) || !self.is_interned(&body) {
+            return Type::new(TypeKind::Arrow(Pi { typ, body }));
+        }
+
+        let key = Key::Arrow(address(&typ), address(&body));
+        self.get_or_insert(key, || Type::new(TypeKind::Arrow(Pi { typ, body })))
+    }
+
+    pub fn app(&mut self, func: Type<Virtual>, arg: Type<Virtual>) -> Type<Virtual> {
+        let (func, arg) = (func.deref(), arg.deref());
+
+        if !self.is_interned(&func) || !self.is_interned(&arg) {
+            return Type::new(TypeKind::Application(func, arg));
+        }
+
+        let key = Key::Application(address(&func), address(&arg));
+        self.get_or_insert(key, || Type::new(TypeKind::Application(func, arg)))
+    }
+
+    pub fn tuple(&mut self, types: Vec<Type<Virtual>>) -> Type<Virtual> {
+        let types = types.iter().map(|typ| typ.deref()).collect::<Vec<_>>();
+
+        if !types.iter().all(|typ| self.is_interned(typ)) {
+            return Type::tuple(types);
+        }
+
+        let key = Key::Tuple(types.iter().map(address).collect());
+        self.get_or_insert(key, || Type::tuple(types))
+    }
+
+    /// Interns a type that was built somewhere else, like the result of an evaluation. The parts
+    /// of it that have holes, bound variables or quantifiers are kept as they are.
+    pub fn intern(&mut self, typ: &Type<Virtual>) -> Type<Virtual> {
+        let typ = typ.deref();
+
+        if self.is_interned(&typ) {
+            return typ;
+        }
+
+        match typ.as_ref() {
+            TypeKind::Type => self.get_or_insert(Key::Type, Type::typ),
+            TypeKind::Constraint => self.get_or_insert(Key::Constraint, Type::constraint),
+            TypeKind::Variable(name) => self.variable(name.clone()),
+            TypeKind::Arrow(pi) => {
+                let (left, right) = (self.intern(&pi.typ), self.intern(&pi.body));
+
+                if self.is_interned(&left) && self.is_interned(&right) {
+                    self.arrow(left, right)
+                } else {
+                    typ
+                }
+            }
+            TypeKind::Application(func, arg) => {
+                let (func, arg) = (self.intern(func), self.intern(arg));
+
+                if self.is_interned(&func) && self.is_interned(&arg) {
+                    self.app(func, arg)
+                } else {
+                    typ
+                }
+            }
+            TypeKind::Tuple(types) => {
+                let types = types.iter().map(|t| self.intern(t)).collect::<Vec<_>>();
+
+                if types.iter().all(|t| self.is_interned(t)) {
+                    self.tuple(types)
+                } else {
+                    typ
+                }
+            }
+            _ => typ,
+        }
+    }
+}
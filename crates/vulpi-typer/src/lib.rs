@@ -11,26 +11,232 @@
 //! This module in specific re-exports the type checker and the type inference algorithm.
 //! but defines what is a Type in the language.
 
-mod errors;
+mod attribute;
+mod builtins;
 mod check;
+mod completion;
 mod context;
 mod coverage;
+mod dead;
+mod derive;
+mod elide;
+mod entry;
+mod errors;
+mod escape;
 mod eval;
+mod hole;
 mod infer;
+mod interface;
+mod intern;
+mod match_compile;
 mod module;
+mod order;
+mod project;
+mod resolution;
+#[cfg(feature = "serde")]
+mod serialize;
+mod trace;
 mod unify;
+mod unused;
+mod zonk;
 
 pub mod declare;
+pub mod value;
+
+pub use builtins::Builtin;
+pub use completion::{Completion, Completions};
+pub use context::{Context, DEFAULT_LIMIT};
+pub use elide::TypeBudget;
+pub use entry::{check_entry_point, EntryPoint, EntryResult};
+pub use errors::TYPE_ERROR_CODES;
+pub use hole::{Hole, HoleInner, Holes};
+pub use interface::{interface_hash, read_interface, write_interface, InterfaceError};
+pub use project::{
+    check_project, check_project_with_options, check_project_with_prelude, ProjectOptions,
+    ProjectOutput,
+};
+pub use resolution::{DefinitionSite, ItemKind, ResolutionMap};
+#[cfg(feature = "serde")]
+pub use serialize::{serialize_module_types, ModuleTypes, Signature, TypeSchema};
+pub use trace::{show_trace, TraceEvent, TraceKind};
+
+use std::{collections::HashMap, sync::Arc};
+
+use eval::Quote;
+use r#virtual::Virtual;
+use vulpi_intern::{well_known, Symbol};
+use vulpi_report::Report;
+use vulpi_syntax::{elaborated, r#abstract, r#abstract::Qualified};
 
-pub use context::Context;
+pub use r#virtual::Env;
 
-use std::{cell::RefCell, hash::Hash, rc::Rc};
+/// The options of [check].
+pub struct CheckOptions {
+    /// The module that defines the builtin types.
+    pub prelude: Symbol,
 
-use r#virtual::Virtual;
-use vulpi_intern::Symbol;
-use vulpi_syntax::r#abstract::Qualified;
+    /// Reports the variables that shadow other variables that are in scope.
+    pub warn_shadowing: bool,
 
-pub use r#virtual::Env;
+    /// Reports the private definitions that are not used by the public items of their modules.
+    pub warn_dead_code: bool,
+
+    /// How deep the checker can go in the recursion of inference and unification.
+    pub limit: usize,
+
+    /// Shows the whole types in the diagnostics.
+    pub verbose_types: bool,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        Self {
+            prelude: well_known::PRELUDE,
+            warn_shadowing: false,
+            warn_dead_code: false,
+            limit: DEFAULT_LIMIT,
+            verbose_types: false,
+        }
+    }
+}
+
+/// How many diagnostics of each severity were reported while checking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// The signature of a module after it was checked. The types are zonked and quoted, so they do not
+/// share any hole with the checker.
+#[derive(Clone, Default)]
+pub struct ModuleSignature {
+    /// The types of the lets and of the externals.
+    pub variables: HashMap<Symbol, Type<real::Real>>,
+
+    /// The types of the constructors.
+    pub constructors: HashMap<Symbol, Type<real::Real>>,
+
+    /// The kinds of the types.
+    pub types: HashMap<Symbol, Type<real::Real>>,
+
+    /// The types of the fields of the records.
+    pub fields: HashMap<Symbol, Type<real::Real>>,
+}
+
+impl ModuleSignature {
+    fn new(interface: &module::Interface) -> Self {
+        let quote = |typ: &Type<Virtual>| typ.quote(Level(0));
+
+        let variables = interface
+            .variables
+            .iter()
+            .map(|(name, def)| (name.clone(), quote(&def.typ)))
+            .collect();
+
+        let constructors = interface
+            .constructors
+            .iter()
+            .map(|(name, data)| (name.clone(), data.typ.clone()))
+            .collect();
+
+        let types = interface
+            .types
+            .iter()
+            .map(|(name, data)| (name.clone(), quote(&data.kind)))
+            .collect();
+
+        Self {
+            variables,
+            constructors,
+            types,
+            fields: interface.fields.clone(),
+        }
+    }
+}
+
+/// The result of [check]. Everything in it is owned, so it can be kept after the checker is gone.
+pub struct CheckedModule {
+    /// The signatures of the modules, by their names.
+    pub modules: HashMap<Symbol, ModuleSignature>,
+
+    /// The types of the top level lets and externals, by their names.
+    pub types: HashMap<Qualified, Type<real::Real>>,
+
+    /// The definitions that the names of the programs refer to.
+    pub resolutions: ResolutionMap,
+
+    /// The elaborated programs, in the same order as the given ones. It's [None] if any error or
+    /// typed hole was reported, because the elaborated tree of such a program has holes in it.
+    pub elaborated: Option<Vec<elaborated::Program<Type<real::Real>>>>,
+
+    /// The diagnostics reported by this check, without the ones that were already in the report.
+    pub summary: Summary,
+}
+
+/// Type checks and elaborates a set of programs. The diagnostics go to the report, and the
+/// [CheckedModule] says how many of them were reported.
+pub fn check(
+    programs: Vec<r#abstract::Program>,
+    report: Report,
+    options: CheckOptions,
+) -> CheckedModule {
+    let mut ctx = Context::new(report.clone())
+        .with_prelude(options.prelude)
+        .with_limit(options.limit);
+
+    ctx.warn_shadowing = options.warn_shadowing;
+    ctx.warn_dead_code = options.warn_dead_code;
+
+    if options.verbose_types {
+        ctx = ctx.with_verbose_types();
+    }
+
+    let env = Env::default();
+
+    let (errors, warnings) = (report.error_count(), report.warning_count());
+
+    let programs = declare::Programs(programs);
+
+    declare::Declare::declare(&programs, (&mut ctx, env.clone()));
+    let elaborated = declare::Declare::define(&programs, (&mut ctx, env));
+
+    let modules = ctx
+        .modules
+        .modules
+        .iter()
+        .map(|(path, interface)| (path.clone(), ModuleSignature::new(interface)))
+        .collect::<HashMap<_, _>>();
+
+    let types = modules
+        .iter()
+        .flat_map(|(path, signature)| {
+            signature.variables.iter().map(|(name, typ)| {
+                let name = Qualified {
+                    path: path.clone(),
+                    name: name.clone(),
+                };
+
+                (name, typ.clone())
+            })
+        })
+        .collect();
+
+    let summary = Summary {
+        errors: report.error_count() - errors,
+        warnings: report.warning_count() - warnings,
+    };
+
+    let complete = summary.errors == 0 && ctx.typed_holes.is_empty();
+
+    CheckedModule {
+        modules,
+        types,
+        resolutions: ctx.resolutions,
+        elaborated: complete.then_some(elaborated),
+        summary,
+    }
+}
 
 /// The level of the type. It is used for type checking and type inference.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
@@ -96,7 +302,7 @@ pub enum TypeKind<S: State> {
     Forall(S::Forall),
 
     /// The type of holes.
-    Hole(Hole<Virtual>),
+    Hole(Hole),
 
     /// Type for types that are defined by the user.
     Variable(Qualified),
@@ -117,16 +323,24 @@ pub enum TypeKind<S: State> {
     Error,
 }
 
-/// The type of types. It is used for type checking and type inference.
+/// The type of types. It is used for type checking and type inference. Types are shared with
+/// atomic reference counting, so the parts of a project that are checked on different threads can
+/// use the same types.
 #[derive(Clone)]
-pub struct Type<S: State>(Rc<TypeKind<S>>);
+pub struct Type<S: State>(Arc<TypeKind<S>>);
 
 /// A type of a type is the same as a type!
 pub type Kind<S> = Type<S>;
 
 impl<S: State> Type<S> {
     pub fn new(kind: TypeKind<S>) -> Self {
-        Self(Rc::new(kind))
+        Self(Arc::new(kind))
+    }
+
+    /// Checks if both types are the same allocation. Interned types are equal if and only if
+    /// they are the same allocation.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
     }
 
     pub(crate) fn forall(forall: S::Forall) -> Self {
@@ -168,75 +382,61 @@ impl<S: State> AsRef<TypeKind<S>> for Type<S> {
     }
 }
 
-/// The inside of a hole. It contains a Level in the Empty in order to avoid infinite loops and
-/// the hole to go out of scope.
-#[derive(Clone)]
-pub enum HoleInner<S: State> {
-    Empty(Symbol, Kind<S>, Level),
-    Filled(Type<S>),
-}
-
-/// A hole is a type that is not yet known. It is used for type inference.
-#[derive(Clone)]
-pub struct Hole<S: State>(pub Rc<RefCell<HoleInner<S>>>);
-
-impl<S: State> Hash for Hole<S> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        let s = (self.0.as_ptr()) as usize;
-        s.hash(state);
-    }
-}
-
-impl<S: State> PartialEq for Hole<S> {
-    fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.0, &other.0)
-    }
-}
-
-impl<S: State> Eq for Hole<S> {}
-
-impl<S: State> Hole<S> {
-    pub fn is_empty(&self) -> bool {
-        matches!(&*self.0.borrow(), HoleInner::Empty(_, _, _))
-    }
-}
-
-impl<S: State> Hole<S> {
-    pub fn new(hole_inner: HoleInner<S>) -> Self {
-        Self(Rc::new(RefCell::new(hole_inner)))
-    }
-
-    pub fn empty(name: Symbol, kind: Kind<S>, level: Level) -> Self {
-        Self(Rc::new(RefCell::new(HoleInner::Empty(name, kind, level))))
-    }
-
-    pub fn fill(&self, typ: Type<S>) {
-        *self.0.borrow_mut() = HoleInner::Filled(typ);
-    }
-}
-
 pub mod r#virtual {
-    use std::cell::RefCell;
+    use std::sync::RwLock;
 
     use vulpi_intern::Symbol;
     use vulpi_location::Span;
+    use vulpi_syntax::r#abstract::NodeId;
 
-    use super::{eval::Eval, real::Real, Hole, HoleInner, Kind, Level, State, Type, TypeKind};
+    use super::{
+        eval::{Eval, Quote},
+        real::Real,
+        HoleInner, Level, State, Type, TypeKind,
+    };
 
     /// The virtual state is used as label for the [State] trait as a way to express that the type
     /// contains closures and can be executed.
     #[derive(Clone)]
     pub struct Virtual;
 
-    /// The typing environment is used for type checking and type inference.
-    #[derive(Clone, Default)]
+    /// The typing environment is used for type checking and type inference. The collections are
+    /// persistent, so cloning and extending it is cheap and it can be sent to other threads.
+    #[derive(Default)]
     pub struct Env {
-        pub names: im_rc::Vector<Option<Symbol>>,
-        pub types: im_rc::Vector<Type<Virtual>>,
-        pub kinds: im_rc::Vector<Type<Virtual>>,
-        pub vars: im_rc::HashMap<Symbol, Type<Virtual>>,
+        pub names: im::Vector<Option<Symbol>>,
+        pub types: im::Vector<Type<Virtual>>,
+        pub kinds: im::Vector<Type<Virtual>>,
+        pub vars: im::HashMap<Symbol, Type<Virtual>>,
+
+        /// The patterns that bound the variables in scope, by their ids and locations.
+        pub binders: im::HashMap<Symbol, (NodeId, Span)>,
+
         pub level: Level,
-        pub span: RefCell<Span>,
+        pub span: RwLock<Span>,
+
+        /// How deep the checker is in the recursion of inference and unification. It's compared
+        /// with [crate::Context::limit].
+        pub depth: usize,
+
+        /// Which bindings are generalized.
+        pub restriction: crate::value::ValueRestriction,
+    }
+
+    impl Clone for Env {
+        fn clone(&self) -> Self {
+            Self {
+                names: self.names.clone(),
+                types: self.types.clone(),
+                kinds: self.kinds.clone(),
+                vars: self.vars.clone(),
+                binders: self.binders.clone(),
+                level: self.level,
+                span: RwLock::new(self.current_span()),
+                depth: self.depth,
+                restriction: self.restriction,
+            }
+        }
     }
 
     impl Env {
@@ -244,9 +444,19 @@ pub mod r#virtual {
             self.vars.insert(name, typ);
         }
 
+        /// Records the pattern that bound a variable.
+        pub(crate) fn add_binder(&mut self, name: Symbol, binder: (NodeId, Span)) {
+            self.binders.insert(name, binder);
+        }
+
         /// Sets the location of the environment. It is used for error reporting.
         pub fn set_current_span(&self, span: Span) {
-            *self.span.borrow_mut() = span;
+            *self.span.write().unwrap() = span;
+        }
+
+        /// The location of the environment.
+        pub fn current_span(&self) -> Span {
+            self.span.read().unwrap().clone()
         }
 
         pub fn find(&self, name: &Symbol) -> Option<(usize, Type<Virtual>, Type<Virtual>)> {
@@ -283,7 +493,12 @@ pub mod r#virtual {
             clone
         }
 
-        pub fn define(&self, name: Option<Symbol>, typ: Type<Virtual>, kind: Type<Virtual>) -> Self {
+        pub fn define(
+            &self,
+            name: Option<Symbol>,
+            typ: Type<Virtual>,
+            kind: Type<Virtual>,
+        ) -> Self {
             let mut clone = self.clone();
             clone.names.push_front(name);
             clone.types.push_front(typ);
@@ -291,10 +506,6 @@ pub mod r#virtual {
             clone.level = clone.level.inc();
             clone
         }
-
-        pub fn hole<S: State>(&self, kind: Kind<Virtual>, label: Symbol) -> Type<S> {
-            Type::new(TypeKind::Hole(Hole::empty(label, kind, self.level)))
-        }
     }
 
     /// A simulation of a closure in a type. It contains the environment and the body of the closure.
@@ -368,9 +579,15 @@ pub mod r#virtual {
             spine
         }
 
+        /// Checks if the type is an error or an application of an error. These types were already
+        /// reported and should not produce new diagnostics.
+        pub fn is_error(&self) -> bool {
+            matches!(self.application_spine().0.deref().as_ref(), TypeKind::Error)
+        }
+
         pub fn deref(&self) -> Type<Virtual> {
             match self.as_ref() {
-                TypeKind::Hole(h) => match h.0.borrow().clone() {
+                TypeKind::Hole(h) => match h.inner() {
                     HoleInner::Filled(typ) => typ.deref(),
                     _ => self.clone(),
                 },
@@ -385,10 +602,14 @@ pub mod r#virtual {
         }
 
         pub(crate) fn function(right: Vec<Self>, ret: Self) -> Self {
-            right
-                .into_iter()
-                .rev()
-                .fold(ret, |body, typ| Type::new(TypeKind::Arrow(Pi { typ: typ, body })))
+            right.into_iter().rev().fold(ret, |body, typ| {
+                Type::new(TypeKind::Arrow(Pi { typ: typ, body }))
+            })
+        }
+
+        /// Shows the type with the names of the variables of the environment.
+        pub fn show(&self, env: &Env) -> crate::real::Show {
+            self.quote(env.level).show(env)
         }
     }
 }
@@ -396,12 +617,12 @@ pub mod r#virtual {
 pub mod real {
     use std::fmt::Display;
 
-    use crate::Virtual;
     use vulpi_intern::Symbol;
     use vulpi_show::Show as OShow;
 
     use super::{
-        eval::Quote, r#virtual::Env, Hole, HoleInner, Index, Level, State, Type, TypeKind,
+        eval::Quote, r#virtual::Env, Hole, HoleInner, Index, Level, Qualified, State, Type,
+        TypeKind,
     };
 
     /// The real state is used as label for the [State] trait as a way to express that the type
@@ -431,7 +652,7 @@ pub mod real {
 
     /// Environment of names that is useful for pretty printing.
     #[derive(Clone)]
-    struct NameEnv(im_rc::Vector<Option<Symbol>>);
+    struct NameEnv(im::Vector<Option<Symbol>>);
 
     impl From<Env> for NameEnv {
         fn from(env: Env) -> Self {
@@ -497,16 +718,126 @@ pub mod real {
                 Type::new(TypeKind::Arrow(Arrow { typ, body }))
             })
         }
+
+        /// Collects the empty holes of the type in the order that they first appear. The kinds of
+        /// the variables of a `forall` are not visited, so only holes that stand for types are
+        /// collected.
+        pub(crate) fn holes(&self, holes: &mut Vec<Hole>) {
+            match self.as_ref() {
+                TypeKind::Arrow(pi) => {
+                    pi.typ.holes(holes);
+                    pi.body.holes(holes);
+                }
+                TypeKind::Forall(forall) => forall.body.holes(holes),
+                TypeKind::Hole(hole) if hole.is_empty() && !holes.contains(hole) => {
+                    holes.push(hole.root())
+                }
+                TypeKind::Tuple(types) => {
+                    for typ in types {
+                        typ.holes(holes);
+                    }
+                }
+                TypeKind::Application(left, right) | TypeKind::Qualified(left, right) => {
+                    left.holes(holes);
+                    right.holes(holes);
+                }
+                _ => (),
+            }
+        }
+
+        /// Collects the names of the types declared by the user that the type mentions, in the
+        /// order that they first appear.
+        pub(crate) fn type_names(&self, names: &mut Vec<Qualified>) {
+            match self.as_ref() {
+                TypeKind::Arrow(pi) => {
+                    pi.typ.type_names(names);
+                    pi.body.type_names(names);
+                }
+                TypeKind::Forall(forall) => forall.body.type_names(names),
+                TypeKind::Variable(name) if !names.contains(name) => names.push(name.clone()),
+                TypeKind::Tuple(types) => {
+                    for typ in types {
+                        typ.type_names(names);
+                    }
+                }
+                TypeKind::Application(left, right) | TypeKind::Qualified(left, right) => {
+                    left.type_names(names);
+                    right.type_names(names);
+                }
+                _ => (),
+            }
+        }
+
+        /// Collects the empty holes of the kinds of the variables bound by a `forall`.
+        pub(crate) fn kind_holes(&self, holes: &mut Vec<Hole>) {
+            match self.as_ref() {
+                TypeKind::Arrow(pi) => {
+                    pi.typ.kind_holes(holes);
+                    pi.body.kind_holes(holes);
+                }
+                TypeKind::Forall(forall) => {
+                    forall.kind.holes(holes);
+                    forall.kind.kind_holes(holes);
+                    forall.body.kind_holes(holes);
+                }
+                TypeKind::Tuple(types) => {
+                    for typ in types {
+                        typ.kind_holes(holes);
+                    }
+                }
+                TypeKind::Application(left, right) | TypeKind::Qualified(left, right) => {
+                    left.kind_holes(holes);
+                    right.kind_holes(holes);
+                }
+                _ => (),
+            }
+        }
+
+        /// Replaces each one of the holes by the variable of a `forall` that is outside of the
+        /// type. The first hole is bound by the outermost `forall`, and the variables that are
+        /// bound outside of the type are shifted over the new binders.
+        pub(crate) fn bind_holes(&self, holes: &[Hole], depth: usize) -> Self {
+            match self.as_ref() {
+                TypeKind::Arrow(pi) => Type::new(TypeKind::Arrow(Arrow {
+                    typ: pi.typ.bind_holes(holes, depth),
+                    body: pi.body.bind_holes(holes, depth),
+                })),
+                TypeKind::Forall(forall) => Type::new(TypeKind::Forall(Forall {
+                    name: forall.name.clone(),
+                    kind: forall.kind.bind_holes(holes, depth),
+                    body: forall.body.bind_holes(holes, depth + 1),
+                })),
+                TypeKind::Hole(hole) => match holes.iter().position(|h| h == hole) {
+                    Some(i) => Type::new(TypeKind::Bound(Index(holes.len() - 1 - i + depth))),
+                    None => self.clone(),
+                },
+                TypeKind::Bound(index) if index.0 >= depth => {
+                    Type::new(TypeKind::Bound(Index(index.0 + holes.len())))
+                }
+                TypeKind::Tuple(types) => Type::new(TypeKind::Tuple(
+                    types.iter().map(|t| t.bind_holes(holes, depth)).collect(),
+                )),
+                TypeKind::Application(left, right) => Type::new(TypeKind::Application(
+                    left.bind_holes(holes, depth),
+                    right.bind_holes(holes, depth),
+                )),
+                TypeKind::Qualified(left, right) => Type::new(TypeKind::Qualified(
+                    left.bind_holes(holes, depth),
+                    right.bind_holes(holes, depth),
+                )),
+                _ => self.clone(),
+            }
+        }
     }
 
     trait Formattable {
         fn format(&self, env: &NameEnv, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
     }
 
-    impl Formattable for Hole<Virtual> {
+    impl Formattable for Hole {
         fn format(&self, env: &NameEnv, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            match self.0.borrow().clone() {
-                HoleInner::Empty(s, _, _) => write!(f, "{}", s.get()),
+            match self.inner() {
+                HoleInner::Empty(..) => write!(f, "?{}", self.index()),
                 HoleInner::Filled(forall) => forall.quote(Level(env.0.len())).format(env, f),
             }
         }
@@ -0,0 +1,242 @@
+//! Compilation of the arms of a `when` into a [DecisionTree]. It's the algorithm described in the
+//! paper "Compiling Pattern Matching to Good Decision Trees" by Luc Maranget, and it specializes
+//! the same rows as the coverage checking, so the tree has a reachable [DecisionTree::Fail] exactly
+//! when the arms are not exhaustive.
+
+use vulpi_intern::Symbol;
+use vulpi_syntax::{
+    elaborated::{Case, DecisionTree, LiteralKind, Occurrence, Pattern, PatternArm, PatternKind},
+    r#abstract::Qualified,
+};
+
+use crate::{
+    context::Context,
+    coverage::{alternatives, wildcards, Pat, Row},
+};
+
+/// A row of the matrix together with the arm that it comes from.
+#[derive(Clone)]
+struct Clause {
+    row: Row<Pat>,
+    arm: usize,
+    guarded: bool,
+    binds: Vec<(Symbol, Occurrence)>,
+}
+
+impl Clause {
+    fn with(&self, row: Row<Pat>) -> Clause {
+        Clause {
+            row,
+            ..self.clone()
+        }
+    }
+}
+
+/// The variables of a pattern without or patterns together with the places of their values.
+fn binds(pattern: &Pattern, occurrence: Occurrence, found: &mut Vec<(Symbol, Occurrence)>) {
+    match &**pattern {
+        PatternKind::Variable(name) => found.push((name.clone(), occurrence)),
+        PatternKind::Tuple(args) => {
+            for (i, arg) in args.iter().enumerate() {
+                binds(arg, occurrence.with(i), found);
+            }
+        }
+        PatternKind::Application(app) => {
+            for (i, arg) in app.args.iter().enumerate() {
+                binds(arg, occurrence.with(i), found);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn specialize(clauses: &[Clause], case: Pat) -> Vec<Clause> {
+    clauses
+        .iter()
+        .flat_map(|clause| {
+            let rows = clause.row.specialize(case.clone());
+            rows.into_iter().map(|row| clause.with(row))
+        })
+        .collect()
+}
+
+fn default_clauses(clauses: &[Clause]) -> Vec<Clause> {
+    clauses
+        .iter()
+        .flat_map(|clause| {
+            let rows = clause.row.clone().default_row();
+            rows.into_iter().map(|row| clause.with(row))
+        })
+        .collect()
+}
+
+/// The occurrences of the arguments of the first column followed by the other columns.
+fn expand(occurrences: &[Occurrence], size: usize) -> Vec<Occurrence> {
+    let mut expanded = (0..size)
+        .map(|i| occurrences[0].with(i))
+        .collect::<Vec<_>>();
+    expanded.extend(occurrences[1..].iter().cloned());
+    expanded
+}
+
+impl Context {
+    /// Compiles the arms of a `when` into a decision tree. The arms must be well typed, and each
+    /// of them must have a pattern for each scrutinee.
+    pub fn compile_match<T>(&mut self, arms: &[PatternArm<T>]) -> DecisionTree {
+        let clauses = arms
+            .iter()
+            .enumerate()
+            .flat_map(|(arm, x)| {
+                alternatives(&x.patterns).into_iter().map(move |patterns| {
+                    let mut found = Vec::new();
+
+                    for (scrutinee, pattern) in patterns.iter().enumerate() {
+                        let path = Vec::new();
+                        binds(pattern, Occurrence { scrutinee, path }, &mut found);
+                    }
+
+                    Clause {
+                        row: patterns
+                            .iter()
+                            .map(Pat::from_pattern)
+                            .collect::<Vec<_>>()
+                            .into(),
+                        arm,
+                        guarded: x.guard.is_some(),
+                        binds: found,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let size = arms.first().map_or(0, |arm| arm.patterns.len());
+
+        let occurrences = (0..size)
+            .map(|scrutinee| Occurrence {
+                scrutinee,
+                path: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        self.compile(occurrences, clauses)
+    }
+
+    fn compile(&mut self, mut occurrences: Vec<Occurrence>, clauses: Vec<Clause>) -> DecisionTree {
+        let Some(first) = clauses.first() else {
+            return DecisionTree::Fail;
+        };
+
+        // The first clause that is left is the one that is tried now, so only the columns that
+        // it tests need to be tested.
+        let Some(column) = first.row.iter().position(|pat| !pat.is_wildcard()) else {
+            return self.leaf(occurrences, clauses);
+        };
+
+        let occurrence = occurrences.remove(column);
+        occurrences.insert(0, occurrence.clone());
+
+        let clauses = clauses
+            .iter()
+            .map(|clause| clause.with(clause.row.to_front(column)))
+            .collect::<Vec<_>>();
+
+        let heads = clauses
+            .iter()
+            .map(|clause| clause.row.first().clone())
+            .collect::<Vec<_>>();
+
+        match &heads[0] {
+            // Tuples have a single shape, so they are not tested.
+            Pat::Tuple(args) => {
+                let size = args.len();
+                let clauses = specialize(&clauses, Pat::Tuple(wildcards(size)));
+                self.compile(expand(&occurrences, size), clauses)
+            }
+            Pat::Constructor(name, _) => {
+                let type_name = self.modules.constructor(name).type_name;
+
+                let mut used = Vec::<Qualified>::new();
+                for name in heads.iter().filter_map(Pat::constructor) {
+                    if !used.contains(&name) {
+                        used.push(name);
+                    }
+                }
+
+                let complete = self
+                    .modules
+                    .constructors_of(&type_name)
+                    .is_some_and(|siblings| siblings.iter().all(|x| used.contains(x)));
+
+                // The only constructor of a type does not need to be tested either.
+                if complete && used.len() == 1 {
+                    let size = self.modules.constructor(&used[0]).arity;
+                    let case = Pat::Constructor(used[0].clone(), wildcards(size));
+                    return self.compile(expand(&occurrences, size), specialize(&clauses, case));
+                }
+
+                let mut cases = Vec::new();
+
+                for name in used {
+                    let size = self.modules.constructor(&name).arity;
+                    let case = Pat::Constructor(name.clone(), wildcards(size));
+                    let tree = self.compile(expand(&occurrences, size), specialize(&clauses, case));
+                    cases.push((Case::Constructor(name), tree));
+                }
+
+                let default = (!complete).then(|| self.default(&occurrences, &clauses));
+                DecisionTree::Switch(occurrence, cases, default)
+            }
+            Pat::Literal(_) => {
+                let mut used = Vec::new();
+                for head in &heads {
+                    if let Pat::Literal(literal) = head {
+                        if !used.contains(literal) {
+                            used.push(literal.clone());
+                        }
+                    }
+                }
+
+                // The unit literal is the only value of its type.
+                let complete = used.iter().any(|x| matches!(&**x, LiteralKind::Unit));
+
+                let mut cases = Vec::new();
+
+                for literal in used {
+                    let case = Pat::Literal(literal.clone());
+                    let tree = self.compile(expand(&occurrences, 0), specialize(&clauses, case));
+                    cases.push((Case::Literal(literal), tree));
+                }
+
+                let default = (!complete).then(|| self.default(&occurrences, &clauses));
+                DecisionTree::Switch(occurrence, cases, default)
+            }
+            Pat::Wildcard => unreachable!("the column was chosen because it is not a wildcard"),
+        }
+    }
+
+    fn default(&mut self, occurrences: &[Occurrence], clauses: &[Clause]) -> Box<DecisionTree> {
+        let tree = self.compile(occurrences[1..].to_vec(), default_clauses(clauses));
+        Box::new(tree)
+    }
+
+    /// The first clause matches everything that is left, so its arm is taken. If it has a guard,
+    /// the other clauses are tried when the guard does not hold.
+    fn leaf(&mut self, occurrences: Vec<Occurrence>, clauses: Vec<Clause>) -> DecisionTree {
+        let first = &clauses[0];
+
+        let tree = if first.guarded {
+            let otherwise = self.compile(occurrences, clauses[1..].to_vec());
+            DecisionTree::Guard(first.arm, Box::new(otherwise))
+        } else {
+            DecisionTree::Leaf(first.arm)
+        };
+
+        first
+            .binds
+            .iter()
+            .rev()
+            .fold(tree, |tree, (name, occurrence)| {
+                DecisionTree::Bind(name.clone(), occurrence.clone(), Box::new(tree))
+            })
+    }
+}
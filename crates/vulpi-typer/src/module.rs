@@ -2,20 +2,22 @@
 //! module is the [Module] structure that is responsible for storing the types of the top level
 //! items.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use vulpi_intern::Symbol;
-use vulpi_syntax::r#abstract::Qualified;
+use vulpi_location::Span;
+use vulpi_syntax::r#abstract::{Attribute, Qualified};
 
-use crate::{r#virtual::Virtual, real::Real, Type};
+use crate::{r#virtual::Virtual, real::Real, Level, Type};
 
 #[derive(Clone)]
 pub enum Def {
     Enum(Vec<Qualified>),
     Record(Vec<Qualified>),
     Effect(Vec<Qualified>),
-    Type,
-    Constraint
+    Synonym,
+    Abstract,
+    Constraint,
 }
 
 #[derive(Clone)]
@@ -24,6 +26,13 @@ pub struct TypeData {
     pub binders: Vec<(Symbol, Type<Virtual>)>,
     pub module: Symbol,
     pub def: Def,
+
+    /// The name of the declaration.
+    pub span: Span,
+
+    /// The documentation comments of the declaration.
+    pub docs: Option<Symbol>,
+    pub attributes: Vec<Attribute>,
 }
 
 #[derive(Clone)]
@@ -32,8 +41,27 @@ pub struct TraitData {
     pub binders: Vec<Type<Virtual>>,
     pub supers: Vec<Type<Real>>,
     pub signatures: Vec<(Qualified, Type<Real>)>,
+}
+
+#[derive(Clone)]
+pub struct ConstructorData {
+    /// The curried function type of the constructor.
+    pub typ: Type<Real>,
+
+    /// The types of the arguments, under the binders of the type.
+    pub args: Vec<Type<Real>>,
+
+    pub arity: usize,
+
+    /// The names of the arguments of a constructor with labeled fields. It's empty if the
+    /// arguments are positional.
+    pub labels: Vec<Symbol>,
 
+    /// The name of the type that the constructor belongs to.
+    pub type_name: Qualified,
 
+    pub docs: Option<Symbol>,
+    pub attributes: Vec<Attribute>,
 }
 
 #[derive(Clone)]
@@ -42,15 +70,17 @@ pub struct LetDef {
     pub unbound: Vec<(Symbol, Type<Real>)>,
     pub args: Vec<Type<Real>>,
     pub ret: Type<Virtual>,
+    pub docs: Option<Symbol>,
+    pub attributes: Vec<Attribute>,
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Interface {
     /// The types of the functions.
     pub variables: HashMap<Symbol, LetDef>,
 
-    /// The types of the functions.
-    pub constructors: HashMap<Symbol, (Type<Real>, usize, Qualified)>,
+    /// The types of the constructors.
+    pub constructors: HashMap<Symbol, ConstructorData>,
 
     /// The types of the types.
     pub types: HashMap<Symbol, TypeData>,
@@ -58,44 +88,174 @@ pub struct Interface {
     /// The fields of the records.
     pub fields: HashMap<Symbol, Type<Real>>,
 
+    /// The documentation comments of the fields that have them.
+    pub field_docs: HashMap<Symbol, Symbol>,
+
     /// Traits.
     pub traits: HashMap<Symbol, TraitData>,
 }
 
-#[derive(Default)]
+impl Interface {
+    /// Closes every type of the interface, so the consumers of it do not have to follow chains of
+    /// holes and cannot fill the holes that are left.
+    pub fn close(&mut self) {
+        for def in self.variables.values_mut() {
+            def.typ = def.typ.close();
+            def.ret = def.ret.close();
+
+            for (i, (_, kind)) in def.unbound.iter_mut().enumerate() {
+                *kind = kind.close(Level(i + 1));
+            }
+
+            let depth = Level(def.unbound.len());
+
+            for arg in def.args.iter_mut() {
+                *arg = arg.close(depth);
+            }
+        }
+
+        for cons in self.constructors.values_mut() {
+            cons.typ = cons.typ.close(Level(0));
+
+            for arg in cons.args.iter_mut() {
+                *arg = arg.close(Level(0));
+            }
+        }
+
+        for typ in self.fields.values_mut() {
+            *typ = typ.close(Level(0));
+        }
+    }
+}
+
+/// The interfaces of the modules. They are shared between the clones, and an interface is copied
+/// only when it's changed, so the parts of a project that are defined on other threads can take a
+/// clone of them.
+#[derive(Clone, Default)]
 pub struct Modules {
     /// The modules.
-    pub modules: HashMap<Symbol, Interface>,
+    pub modules: HashMap<Symbol, Arc<Interface>>,
+
+    /// The records that have a field with each name, in the order that they were declared.
+    pub records: HashMap<Symbol, Vec<Qualified>>,
+
+    /// The top level declarations that are written `let _`. They are checked like the others, but
+    /// they are not part of their modules.
+    pub wildcards: HashMap<Qualified, LetDef>,
 }
 
 impl Modules {
     pub fn new() -> Self {
         Self {
             modules: Default::default(),
+            records: Default::default(),
+            wildcards: Default::default(),
         }
     }
 
-    pub fn typ(&mut self, qualified: &Qualified) -> TypeData {
-        let module = self.get(&qualified.path);
+    pub fn typ(&self, qualified: &Qualified) -> TypeData {
+        let module = self.modules.get(&qualified.path).unwrap();
         module.types.get(&qualified.name).unwrap().clone()
     }
 
-    pub fn constructor(&mut self, qualified: &Qualified) -> (Type<Real>, usize, Qualified) {
-        let module = self.get(&qualified.path);
+    pub fn constructor(&self, qualified: &Qualified) -> ConstructorData {
+        let module = self.modules.get(&qualified.path).unwrap();
         module.constructors.get(&qualified.name).unwrap().clone()
     }
 
+    pub fn let_def(&self, qualified: &Qualified) -> &LetDef {
+        if qualified.is_wildcard() {
+            return self.wildcards.get(qualified).unwrap();
+        }
+
+        let module = self.modules.get(&qualified.path).unwrap();
+        module.variables.get(&qualified.name).unwrap()
+    }
+
+    /// The definition of a let to change it. The interface of its module is copied if it's shared.
     pub fn let_decl(&mut self, qualified: &Qualified) -> &mut LetDef {
-        let module = self.get(&qualified.path);
-        module.variables.get_mut(&qualified.name).unwrap()
+        if qualified.is_wildcard() {
+            return self.wildcards.get_mut(qualified).unwrap();
+        }
+
+        let module = self.modules.get_mut(&qualified.path).unwrap();
+        Arc::make_mut(module)
+            .variables
+            .get_mut(&qualified.name)
+            .unwrap()
     }
 
-    pub fn field(&mut self, qualified: &Qualified) -> Type<Real> {
-        let module = self.get(&qualified.path);
+    pub fn field(&self, qualified: &Qualified) -> Type<Real> {
+        let module = self.modules.get(&qualified.path).unwrap();
         module.fields.get(&qualified.name).unwrap().clone()
     }
 
-    pub fn get(&mut self, id: &Symbol) -> &mut Interface {
-        self.modules.entry(id.clone()).or_default()
+    /// The constructors of a type, in the order that they were declared. It's [None] if the type
+    /// does not exist or if it's not an enum.
+    pub fn constructors_of(&self, qualified: &Qualified) -> Option<&[Qualified]> {
+        match &self
+            .modules
+            .get(&qualified.path)?
+            .types
+            .get(&qualified.name)?
+            .def
+        {
+            Def::Enum(constructors) => Some(constructors),
+            _ => None,
+        }
+    }
+
+    /// The fields of a record, in the order that they were declared. It's [None] if the type does
+    /// not exist or if it's not a record.
+    pub fn fields_of(&self, qualified: &Qualified) -> Option<&[Qualified]> {
+        match &self
+            .modules
+            .get(&qualified.path)?
+            .types
+            .get(&qualified.name)?
+            .def
+        {
+            Def::Record(fields) => Some(fields),
+            _ => None,
+        }
     }
+
+    /// The records that have a field with the given name.
+    pub fn records_with_field(&self, field: &Symbol) -> &[Qualified] {
+        self.records
+            .get(field)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Closes the interfaces of the given modules and of the modules inside of them, like the ones
+    /// of their types. The other interfaces are left shared.
+    pub fn close(&mut self, names: &[&Symbol]) {
+        for (name, module) in self.modules.iter_mut() {
+            if names.iter().any(|outer| inside(name, outer)) {
+                Arc::make_mut(module).close();
+            }
+        }
+    }
+
+    /// The interface of a module if it was declared.
+    pub fn get(&self, id: &Symbol) -> Option<&Interface> {
+        self.modules.get(id).map(Arc::as_ref)
+    }
+
+    /// The interface of a module, creating an empty one if it does not exist. It should only be
+    /// used by declarations that introduce names in their own module; references must use
+    /// [Modules::get] so a wrong path does not become an empty module.
+    pub fn get_or_create(&mut self, id: &Symbol) -> &mut Interface {
+        Arc::make_mut(self.modules.entry(id.clone()).or_default())
+    }
+}
+
+/// If a module is the outer one or one of the modules inside of it.
+pub fn inside(module: &Symbol, outer: &Symbol) -> bool {
+    let (module, outer) = (module.get(), outer.get());
+
+    module
+        .strip_prefix(&outer)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
 }
@@ -0,0 +1,103 @@
+//! Ordering of the top level let declarations. The declarations are split into groups of mutually
+//! recursive functions and the groups are sorted so every group is checked after the groups that
+//! it uses. It makes possible to generalize a group as soon as it's checked. The same is done with
+//! the type synonyms, to find the ones that are defined in terms of themselves.
+
+use std::collections::HashMap;
+
+use petgraph::graph::DiGraph;
+use vulpi_syntax::{
+    r#abstract::{LetDecl, Qualified, Type, TypeDecl, TypeDef, TypeKind},
+    visit::{walk_type, Visitor},
+};
+
+/// Splits the declarations into groups of mutually recursive declarations. The groups are sorted
+/// so the dependencies of a group come before it, and the declarations inside of each group are
+/// in the same order as they were given.
+pub fn let_groups(lets: &[&LetDecl]) -> Vec<Vec<usize>> {
+    let mut graph = DiGraph::<usize, ()>::new();
+
+    let nodes = lets
+        .iter()
+        .enumerate()
+        .map(|(i, decl)| (decl.signature.name.clone(), graph.add_node(i)))
+        .collect::<HashMap<_, _>>();
+
+    for decl in lets {
+        let from = nodes[&decl.signature.name];
+        let used = decl.body.iter().flat_map(|arm| {
+            let guard = arm.guard.iter().flat_map(|guard| guard.functions());
+            arm.expr.functions().into_iter().chain(guard)
+        });
+
+        for name in used {
+            if let Some(to) = nodes.get(&name) {
+                graph.add_edge(from, *to, ());
+            }
+        }
+    }
+
+    petgraph::algo::tarjan_scc(&graph)
+        .into_iter()
+        .map(|group| {
+            let mut group = group.into_iter().map(|n| graph[n]).collect::<Vec<_>>();
+            group.sort();
+            group
+        })
+        .collect()
+}
+
+/// Collects the names of the types that are used by a type.
+struct TypeReferences(Vec<Qualified>);
+
+impl Visitor for TypeReferences {
+    fn visit_type(&mut self, typ: &Type) {
+        if let TypeKind::Type(name) = &typ.data {
+            self.0.push(name.clone());
+        }
+
+        walk_type(self, typ)
+    }
+}
+
+/// Finds the synonyms that are defined in terms of themselves, because expanding them would never
+/// end. Only the uses that go through other synonyms are followed, so recursion through the
+/// constructors of a sum or a record is fine. Each cycle is given in the order of the declarations.
+pub fn synonym_cycles(types: &[TypeDecl]) -> Vec<Vec<usize>> {
+    let mut graph = DiGraph::<usize, ()>::new();
+
+    let nodes = types
+        .iter()
+        .enumerate()
+        .filter(|(_, decl)| matches!(decl.def, TypeDef::Synonym(_)))
+        .map(|(i, decl)| (decl.name.clone(), graph.add_node(i)))
+        .collect::<HashMap<_, _>>();
+
+    for decl in types {
+        let TypeDef::Synonym(typ) = &decl.def else {
+            continue;
+        };
+
+        let mut used = TypeReferences(Vec::new());
+        used.visit_type(typ);
+
+        for name in used.0 {
+            if let Some(to) = nodes.get(&name) {
+                graph.update_edge(nodes[&decl.name], *to, ());
+            }
+        }
+    }
+
+    let mut cycles = petgraph::algo::tarjan_scc(&graph)
+        .into_iter()
+        .filter(|group| group.len() > 1 || graph.contains_edge(group[0], group[0]))
+        .map(|group| {
+            let mut group = group.into_iter().map(|n| graph[n]).collect::<Vec<_>>();
+            group.sort();
+            group
+        })
+        .collect::<Vec<_>>();
+
+    cycles.sort();
+    cycles
+}
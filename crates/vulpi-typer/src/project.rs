@@ -0,0 +1,386 @@
+//! Checking of a project made of many files. Every file is declared before anything is defined,
+//! so files can refer to the types of each other, and then the files are defined in the order of
+//! their `use` declarations. The files that do not use each other are defined in parallel.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use petgraph::{
+    graph::{DiGraph, NodeIndex},
+    visit::EdgeRef,
+};
+use vulpi_intern::{well_known, Symbol};
+use vulpi_location::{FileId, Span};
+use vulpi_report::Report;
+use vulpi_syntax::{
+    elaborated,
+    r#abstract::{Program, UseDecl},
+};
+
+use crate::{
+    context::{Fork, Joined},
+    declare::{Declare, Programs},
+    entry::{check_entry_point, EntryPoint},
+    errors::TypeErrorKind,
+    real::Real,
+    resolution::ResolutionMap,
+    trace::TraceEvent,
+    Completions, Context, Env, Type,
+};
+
+/// The result of checking a project. The programs are in the same order as the files that were
+/// given to [check_project].
+pub struct ProjectOutput {
+    pub programs: Vec<(FileId, elaborated::Program<Type<Real>>)>,
+    pub report: Report,
+
+    /// The definitions that the names of the programs refer to.
+    pub resolutions: ResolutionMap,
+
+    /// The names that can be written at each position of the programs.
+    pub completions: Completions,
+
+    /// The decisions taken by the checker, if [ProjectOptions::trace] is set.
+    pub trace: Vec<TraceEvent>,
+}
+
+/// Registers the name of a program and of its inline modules as belonging to a file.
+fn owners(program: &Program, file: usize, map: &mut HashMap<Symbol, usize>) {
+    map.insert(program.name.clone(), file);
+
+    for module in &program.modules {
+        if let Some(decls) = &module.decls {
+            owners(decls, file, map);
+        }
+    }
+}
+
+/// The file of the program that a module belongs to. The modules of the types of a program are
+/// inside of it, so it's the innermost program whose name contains the one of the module.
+fn owner(module: &Symbol, map: &HashMap<Symbol, usize>) -> Option<usize> {
+    let mut name = module.get();
+
+    loop {
+        if let Some(file) = map.get(&Symbol::intern(&name)) {
+            return Some(*file);
+        }
+
+        name.truncate(name.rfind('.')?);
+    }
+}
+
+/// Collects the `use` declarations of a program and of its inline modules.
+fn uses<'a>(program: &'a Program, found: &mut Vec<&'a UseDecl>) {
+    found.extend(program.uses.iter());
+
+    for module in &program.modules {
+        if let Some(decls) = &module.decls {
+            uses(decls, found);
+        }
+    }
+}
+
+/// Splits the files into groups that can be defined together, and the groups into levels. Every
+/// group comes in a level after the groups of the files that it uses, so the groups of a level do
+/// not depend on each other. The groups inside of a level are sorted by the name of their first
+/// module, so the order of the diagnostics does not depend on the order of the files. Groups with
+/// more than one file are cycles, and they are reported.
+fn file_levels(ctx: &mut Context, env: &Env, programs: &[Program]) -> Vec<Vec<Vec<usize>>> {
+    let mut map = HashMap::new();
+
+    for (i, program) in programs.iter().enumerate() {
+        owners(program, i, &mut map);
+    }
+
+    let mut graph = DiGraph::<usize, Span>::new();
+
+    let nodes = (0..programs.len())
+        .map(|i| graph.add_node(i))
+        .collect::<Vec<NodeIndex>>();
+
+    for (i, program) in programs.iter().enumerate() {
+        let mut used = Vec::new();
+        uses(program, &mut used);
+
+        // Paths that do not lead to any file were already reported by the resolver.
+        for decl in used {
+            match map.get(&decl.path) {
+                Some(to) if *to != i => {
+                    graph.add_edge(nodes[i], nodes[*to], decl.span.clone());
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let groups = petgraph::algo::tarjan_scc(&graph)
+        .into_iter()
+        .map(|group| {
+            let mut group = group.into_iter().map(|n| graph[n]).collect::<Vec<_>>();
+            group.sort();
+            group
+        })
+        .collect::<Vec<_>>();
+
+    for group in &groups {
+        if group.len() < 2 {
+            continue;
+        }
+
+        let first = nodes[group[0]];
+
+        let span = graph
+            .edges(first)
+            .find(|edge| group.contains(&graph[edge.target()]))
+            .map(|edge| edge.weight().clone())
+            .unwrap();
+
+        let cycle = group
+            .iter()
+            .map(|i| programs[*i].name.clone())
+            .collect::<Vec<_>>();
+
+        env.set_current_span(span);
+        ctx.report(env, TypeErrorKind::CycleBetweenModules(cycle));
+    }
+
+    let mut group_of = vec![0; programs.len()];
+
+    for (g, group) in groups.iter().enumerate() {
+        for i in group {
+            group_of[*i] = g;
+        }
+    }
+
+    // The groups come after the groups that they use, so the depth of the used ones is known.
+    let mut depths = vec![0; groups.len()];
+
+    for (g, group) in groups.iter().enumerate() {
+        for i in group {
+            for edge in graph.edges(nodes[*i]) {
+                let to = group_of[graph[edge.target()]];
+
+                if to != g {
+                    depths[g] = depths[g].max(depths[to] + 1);
+                }
+            }
+        }
+    }
+
+    let mut levels = vec![Vec::new(); depths.iter().max().map_or(0, |depth| depth + 1)];
+
+    for (group, depth) in groups.into_iter().zip(depths) {
+        levels[depth].push(group);
+    }
+
+    for level in &mut levels {
+        level.sort_by_key(|group| programs[group[0]].name.get());
+    }
+
+    levels
+}
+
+/// Type checks and elaborates a project. All of the files are declared first, and then they are
+/// defined level by level after the files that they use, so the definitions of a file are already
+/// generalized when another file uses them.
+pub fn check_project(files: Vec<(FileId, Program)>, report: Report) -> ProjectOutput {
+    check_project_with_prelude(files, report, well_known::PRELUDE)
+}
+
+/// Same as [check_project], but the builtin types are looked up in the given module instead of
+/// `Prelude`.
+pub fn check_project_with_prelude(
+    files: Vec<(FileId, Program)>,
+    report: Report,
+    prelude: Symbol,
+) -> ProjectOutput {
+    let options = ProjectOptions {
+        prelude,
+        ..Default::default()
+    };
+
+    check_project_with_options(files, report, options)
+}
+
+/// The options of [check_project_with_options].
+pub struct ProjectOptions {
+    /// The module that defines the builtin types.
+    pub prelude: Symbol,
+
+    /// The entry point that the root module must define. Libraries have none.
+    pub entry: Option<EntryPoint>,
+
+    /// Records the decisions taken by the checker in [ProjectOutput::trace].
+    pub trace: bool,
+
+    /// Shows the whole types in the diagnostics.
+    pub verbose_types: bool,
+
+    /// How many threads define the files that do not use each other. With one, everything is
+    /// defined on the current thread. The result is the same with any number of them.
+    pub threads: usize,
+}
+
+impl Default for ProjectOptions {
+    fn default() -> Self {
+        Self {
+            prelude: well_known::PRELUDE,
+            entry: None,
+            trace: false,
+            verbose_types: false,
+            threads: std::thread::available_parallelism().map_or(1, usize::from),
+        }
+    }
+}
+
+/// The stack of the threads that define the files. It's as big as the one of the main thread,
+/// because checking is deeply recursive.
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// A group of programs defined by a fork of the context.
+type Defined = (Programs, Vec<elaborated::Program<Type<Real>>>, Joined);
+
+/// Defines the groups of a level, each one with its own fork of the context. They are spread over
+/// the given number of threads, and the results are in the order of the groups either way.
+fn define_level(jobs: Vec<(Programs, Fork)>, threads: usize) -> Vec<Defined> {
+    let define = |(programs, fork): (Programs, Fork)| {
+        let (defined, joined) = fork.run(|ctx| Declare::define(&programs, (ctx, Env::default())));
+
+        (programs, defined, joined)
+    };
+
+    if threads <= 1 || jobs.len() <= 1 {
+        return jobs.into_iter().map(define).collect();
+    }
+
+    let count = jobs.len();
+    let queue = Mutex::new(jobs.into_iter().enumerate());
+    let mut results = (0..count).map(|_| None).collect::<Vec<_>>();
+
+    std::thread::scope(|scope| {
+        let workers = (0..threads.min(count))
+            .map(|_| {
+                let work = || {
+                    let mut done = Vec::new();
+
+                    // The lock is released before the job is defined.
+                    loop {
+                        let Some((i, job)) = queue.lock().unwrap().next() else {
+                            break;
+                        };
+
+                        done.push((i, define(job)));
+                    }
+
+                    done
+                };
+
+                std::thread::Builder::new()
+                    .stack_size(STACK_SIZE)
+                    .spawn_scoped(scope, work)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        for worker in workers {
+            for (i, defined) in worker.join().unwrap() {
+                results[i] = Some(defined);
+            }
+        }
+    });
+
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+/// Same as [check_project], but with the given options.
+pub fn check_project_with_options(
+    files: Vec<(FileId, Program)>,
+    report: Report,
+    options: ProjectOptions,
+) -> ProjectOutput {
+    let mut ctx = Context::new(report.clone()).with_prelude(options.prelude);
+
+    if options.trace {
+        ctx = ctx.with_trace();
+    }
+
+    if options.verbose_types {
+        ctx = ctx.with_verbose_types();
+    }
+    let env = Env::default();
+
+    let (ids, programs): (Vec<_>, Vec<_>) = files.into_iter().unzip();
+
+    let levels = file_levels(&mut ctx, &env, &programs);
+
+    for (id, program) in ids.iter().zip(&programs) {
+        ctx.completions.add_file(*id, program);
+    }
+
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, env.clone()));
+
+    let mut map = HashMap::new();
+
+    for (i, program) in programs.0.iter().enumerate() {
+        owners(program, i, &mut map);
+    }
+
+    let mut remaining = programs.0.into_iter().map(Some).collect::<Vec<_>>();
+    let mut elaborated = vec![None; remaining.len()];
+
+    // Every group of a level is forked from the context as it was before the level, so the result
+    // is the same whatever the number of threads.
+    for level in levels {
+        let jobs = level
+            .iter()
+            .map(|group| {
+                let programs = group
+                    .iter()
+                    .map(|i| remaining[*i].take().unwrap())
+                    .collect::<Vec<_>>();
+
+                let programs = Programs(programs);
+                let names = programs
+                    .all()
+                    .into_iter()
+                    .map(|program| &program.name)
+                    .collect::<Vec<_>>();
+                let fork = ctx.fork(&names);
+
+                (programs, fork)
+            })
+            .collect::<Vec<_>>();
+
+        let done = define_level(jobs, options.threads);
+
+        for (group, (programs, defined, joined)) in level.into_iter().zip(done) {
+            let owns = |module: &Symbol| owner(module, &map).is_some_and(|i| group.contains(&i));
+            ctx.join(joined, owns);
+
+            if let Some(entry) = &options.entry {
+                for (i, program) in group.iter().zip(&programs.0) {
+                    check_entry_point(&mut ctx, entry, ids[*i], program);
+                }
+            }
+
+            for (i, program) in group.into_iter().zip(defined) {
+                elaborated[i] = Some(program);
+            }
+        }
+    }
+
+    ctx.completions.fill(&ctx.modules);
+
+    let programs = ids
+        .into_iter()
+        .zip(elaborated.into_iter().map(Option::unwrap))
+        .collect();
+
+    ProjectOutput {
+        programs,
+        report,
+        trace: ctx.take_trace(),
+        resolutions: ctx.resolutions,
+        completions: ctx.completions,
+    }
+}
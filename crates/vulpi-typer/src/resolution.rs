@@ -0,0 +1,158 @@
+//! Tracking of the definitions that the names of the programs refer to. Every name that the type
+//! checker resolves is recorded with the place where it was defined, so an editor can go from a
+//! name to its definition and from a definition to all of its references.
+
+use std::collections::HashMap;
+
+use vulpi_intern::Symbol;
+use vulpi_location::{Byte, FileId, Span};
+use vulpi_syntax::r#abstract::Qualified;
+
+/// The kind of the item that a name refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ItemKind {
+    Type,
+    Trait,
+    Constructor,
+    Field,
+    Let,
+    External,
+    Variable,
+}
+
+/// The namespace of an item. Items of different kinds can share a name if they are in different
+/// namespaces, like a type and its constructor.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Namespace {
+    Type,
+    Value,
+    Constructor,
+    Field,
+}
+
+impl ItemKind {
+    fn namespace(self) -> Namespace {
+        match self {
+            ItemKind::Type | ItemKind::Trait => Namespace::Type,
+            ItemKind::Constructor => Namespace::Constructor,
+            ItemKind::Field => Namespace::Field,
+            ItemKind::Let | ItemKind::External | ItemKind::Variable => Namespace::Value,
+        }
+    }
+}
+
+/// The place where an item was defined. The span is the one of the name of the item.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DefinitionSite {
+    pub file: FileId,
+    pub span: Span,
+    pub kind: ItemKind,
+}
+
+impl DefinitionSite {
+    pub fn new(span: Span, kind: ItemKind) -> Self {
+        Self {
+            file: span.file,
+            span,
+            kind,
+        }
+    }
+}
+
+/// The definitions of the items and the names that refer to them.
+#[derive(Default)]
+pub struct ResolutionMap {
+    definitions: HashMap<(Namespace, Qualified), DefinitionSite>,
+    references: Vec<(Span, DefinitionSite)>,
+
+    /// The items that are marked with `@deprecated`, with the message of the attribute.
+    deprecated: HashMap<(Namespace, Qualified), Option<Symbol>>,
+}
+
+impl ResolutionMap {
+    /// Records the place where an item was defined. It returns the place of the previous item
+    /// with the same name in the same namespace, if there's one.
+    pub(crate) fn define(
+        &mut self,
+        kind: ItemKind,
+        name: &Qualified,
+        span: Span,
+    ) -> Option<DefinitionSite> {
+        let site = DefinitionSite::new(span, kind);
+        self.definitions
+            .insert((kind.namespace(), name.clone()), site)
+    }
+
+    /// Records a name that refers to an item. Items whose definition is not known, like the ones
+    /// that come from interfaces, are skipped.
+    pub(crate) fn refer(&mut self, span: Span, kind: ItemKind, name: &Qualified) {
+        if let Some(site) = self.definitions.get(&(kind.namespace(), name.clone())) {
+            self.references.push((span, site.clone()));
+        }
+    }
+
+    /// Marks an item as deprecated, so the names that refer to it are warned about.
+    pub(crate) fn deprecate(&mut self, kind: ItemKind, name: &Qualified, message: Option<Symbol>) {
+        self.deprecated
+            .insert((kind.namespace(), name.clone()), message);
+    }
+
+    /// The message of an item that is deprecated and the place where it was defined, if it's
+    /// known.
+    pub(crate) fn deprecation(
+        &self,
+        kind: ItemKind,
+        name: &Qualified,
+    ) -> Option<(Option<Symbol>, Option<&DefinitionSite>)> {
+        let key = (kind.namespace(), name.clone());
+        let message = self.deprecated.get(&key)?;
+        Some((message.clone(), self.definitions.get(&key)))
+    }
+
+    /// The place where an item was defined, if it's known.
+    pub(crate) fn definition(&self, kind: ItemKind, name: &Qualified) -> Option<&DefinitionSite> {
+        self.definitions.get(&(kind.namespace(), name.clone()))
+    }
+
+    /// A map with the same definitions and without references, to check some programs on another
+    /// thread. Their references are brought back with [ResolutionMap::join].
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            definitions: self.definitions.clone(),
+            references: Vec::new(),
+            deprecated: self.deprecated.clone(),
+        }
+    }
+
+    /// Adds the references found by a fork of the map.
+    pub(crate) fn join(&mut self, fork: Self) {
+        self.references.extend(fork.references);
+    }
+
+    /// Records a name that refers to a local variable bound by a pattern.
+    pub(crate) fn refer_local(&mut self, span: Span, binder: Span) {
+        let site = DefinitionSite::new(binder, ItemKind::Variable);
+        self.references.push((span, site));
+    }
+
+    /// The definition of the name under a byte of a file. Names can be inside of other names, like
+    /// a variable inside of a projection, so the innermost one is taken.
+    pub fn definition_at(&self, file: FileId, byte: usize) -> Option<&DefinitionSite> {
+        self.references
+            .iter()
+            .filter(|(span, _)| span.file == file && span.contains(Byte(byte)))
+            .min_by_key(|(span, _)| span.end.0 - span.start.0)
+            .map(|(_, site)| site)
+    }
+
+    /// The locations of the names that refer to a definition, in the order that they were checked.
+    pub fn references_of(&self, def: &DefinitionSite) -> Vec<Span> {
+        self.references
+            .iter()
+            .filter(|(_, site)| site == def)
+            .map(|(span, _)| span.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,188 @@
+//! Machine readable signatures of the public items of a module. They are made for tools that want
+//! the types found by the checker, so the types are written as plain trees instead of the
+//! S-expressions of the interface files, together with the text that the checker shows for them.
+
+use serde::{Deserialize, Serialize};
+use vulpi_intern::Symbol;
+use vulpi_syntax::r#abstract::{Program, Qualified, TypeDef, Visibility};
+
+use crate::{
+    eval::Quote,
+    real::{Real, Show},
+    Context, Env, ItemKind, Level, Type, TypeKind,
+};
+
+/// A type of the checker after it was zonked. Bound variables are de Bruijn indices that point to
+/// the `forall` that binds them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "tag", rename_all = "lowercase")]
+pub enum TypeSchema {
+    Type,
+    Constraint,
+    Arrow {
+        from: Box<TypeSchema>,
+        to: Box<TypeSchema>,
+    },
+    Forall {
+        name: Symbol,
+        kind: Box<TypeSchema>,
+        body: Box<TypeSchema>,
+    },
+    Variable {
+        name: Qualified,
+    },
+    Bound {
+        index: usize,
+    },
+    Tuple {
+        types: Vec<TypeSchema>,
+    },
+    Application {
+        func: Box<TypeSchema>,
+        arg: Box<TypeSchema>,
+    },
+    Qualified {
+        constraint: Box<TypeSchema>,
+        body: Box<TypeSchema>,
+    },
+    Error,
+}
+
+impl From<&Type<Real>> for TypeSchema {
+    fn from(typ: &Type<Real>) -> Self {
+        let schema = |typ: &Type<Real>| Box::new(TypeSchema::from(typ));
+
+        match typ.as_ref() {
+            TypeKind::Type => TypeSchema::Type,
+            TypeKind::Constraint => TypeSchema::Constraint,
+            TypeKind::Arrow(arrow) => TypeSchema::Arrow {
+                from: schema(&arrow.typ),
+                to: schema(&arrow.body),
+            },
+            TypeKind::Forall(forall) => TypeSchema::Forall {
+                name: forall.name.clone(),
+                kind: schema(&forall.kind),
+                body: schema(&forall.body),
+            },
+            TypeKind::Variable(name) => TypeSchema::Variable { name: name.clone() },
+            TypeKind::Bound(index) => TypeSchema::Bound { index: index.0 },
+            TypeKind::Tuple(types) => TypeSchema::Tuple {
+                types: types.iter().map(TypeSchema::from).collect(),
+            },
+            TypeKind::Application(func, arg) => TypeSchema::Application {
+                func: schema(func),
+                arg: schema(arg),
+            },
+            TypeKind::Qualified(constraint, body) => TypeSchema::Qualified {
+                constraint: schema(constraint),
+                body: schema(body),
+            },
+            // Holes that survive the zonking were already reported as ambiguous types.
+            TypeKind::Hole(_) | TypeKind::Error => TypeSchema::Error,
+        }
+    }
+}
+
+/// The type of a public item of a module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pub name: Qualified,
+    pub item: ItemKind,
+    pub typ: TypeSchema,
+
+    /// The type as it's shown in the messages of the checker.
+    pub text: String,
+}
+
+/// The signatures of the public items of a module, in the order that they were declared. Types are
+/// followed by their constructors and fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleTypes {
+    pub module: Symbol,
+    pub items: Vec<Signature>,
+}
+
+/// Collects the signatures of the public items of a program that was already defined by the
+/// context. The program is only used to know which items are public and their order.
+pub fn serialize_module_types(ctx: &Context, program: &Program) -> ModuleTypes {
+    let env = Env::default();
+    let public = |visibility: &Visibility| *visibility == Visibility::Public;
+
+    let mut items = Vec::new();
+
+    let mut push = |name: &Qualified, item: ItemKind, typ: Option<(Type<Real>, Show)>| {
+        if let Some((typ, text)) = typ {
+            items.push(Signature {
+                name: name.clone(),
+                item,
+                typ: TypeSchema::from(&typ),
+                text: text.to_string(),
+            });
+        }
+    };
+
+    let modules = &ctx.modules;
+
+    for decl in program.types.iter().filter(|decl| public(&decl.visibility)) {
+        let typ = modules
+            .get(&decl.name.path)
+            .and_then(|module| module.types.get(&decl.name.name))
+            .map(|data| (data.kind.quote(Level(0)), data.kind.show(&env)));
+
+        push(&decl.name, ItemKind::Type, typ);
+
+        match &decl.def {
+            TypeDef::Sum(sum) => {
+                for cons in sum.constructors.iter().filter(|c| public(&c.visibility)) {
+                    let typ = modules
+                        .get(&cons.name.path)
+                        .and_then(|module| module.constructors.get(&cons.name.name))
+                        .map(|data| (data.typ.clone(), data.typ.show(&env)));
+
+                    push(&cons.name, ItemKind::Constructor, typ);
+                }
+            }
+            TypeDef::Record(record) => {
+                for (name, _, visibility, _, _) in &record.fields {
+                    if !public(visibility) {
+                        continue;
+                    }
+
+                    let typ = modules
+                        .get(&name.path)
+                        .and_then(|module| module.fields.get(&name.name))
+                        .map(|typ| (typ.clone(), typ.show(&env)));
+
+                    push(name, ItemKind::Field, typ);
+                }
+            }
+            TypeDef::Synonym(_) | TypeDef::Abstract => {}
+        }
+    }
+
+    let lets = program
+        .lets
+        .iter()
+        .filter(|decl| public(&decl.signature.visibility) && !decl.signature.name.is_wildcard())
+        .map(|decl| (&decl.signature.name, ItemKind::Let));
+
+    let externals = program
+        .externals
+        .iter()
+        .filter(|decl| public(&decl.visibility))
+        .map(|decl| (&decl.name, ItemKind::External));
+
+    for (name, item) in lets.chain(externals) {
+        let typ = modules
+            .get(&name.path)
+            .and_then(|module| module.variables.get(&name.name))
+            .map(|def| (def.typ.quote(Level(0)), def.typ.show(&env)));
+
+        push(name, item, typ);
+    }
+
+    ModuleTypes {
+        module: program.name.clone(),
+        items,
+    }
+}
@@ -0,0 +1,106 @@
+//! A log of the decisions taken by the checker, made to find out why some type was inferred. It's
+//! only recorded when the [crate::Context] was created with [crate::Context::with_trace], and the
+//! types are rendered only in that case.
+
+use std::fmt::Display;
+
+use vulpi_intern::Symbol;
+use vulpi_location::Span;
+
+/// One decision of the checker, with the types already rendered and the location of the
+/// expression that was being checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// Two types were unified.
+    Unify {
+        left: String,
+        right: String,
+        span: Span,
+    },
+
+    /// A type was checked to be at least as polymorphic as another one.
+    Subsume {
+        left: String,
+        right: String,
+        span: Span,
+    },
+
+    /// A hole was filled with a type.
+    Solve {
+        hole: Symbol,
+        solution: String,
+        span: Span,
+    },
+
+    /// The outer `forall` of a type was replaced by a new hole.
+    Instantiate {
+        typ: String,
+        result: String,
+        span: Span,
+    },
+
+    /// The holes of a type were bound in `forall`s.
+    Generalize {
+        typ: String,
+        result: String,
+        span: Span,
+    },
+}
+
+/// The kinds of [TraceEvent], used to look at the sequence of events without the types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceKind {
+    Unify,
+    Subsume,
+    Solve,
+    Instantiate,
+    Generalize,
+}
+
+impl TraceEvent {
+    pub fn kind(&self) -> TraceKind {
+        match self {
+            TraceEvent::Unify { .. } => TraceKind::Unify,
+            TraceEvent::Subsume { .. } => TraceKind::Subsume,
+            TraceEvent::Solve { .. } => TraceKind::Solve,
+            TraceEvent::Instantiate { .. } => TraceKind::Instantiate,
+            TraceEvent::Generalize { .. } => TraceKind::Generalize,
+        }
+    }
+
+    pub fn span(&self) -> &Span {
+        match self {
+            TraceEvent::Unify { span, .. }
+            | TraceEvent::Subsume { span, .. }
+            | TraceEvent::Solve { span, .. }
+            | TraceEvent::Instantiate { span, .. }
+            | TraceEvent::Generalize { span, .. } => span,
+        }
+    }
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let span = self.span();
+        write!(f, "{}..{}: ", span.start.0, span.end.0)?;
+
+        match self {
+            TraceEvent::Unify { left, right, .. } => write!(f, "unify {left} ~ {right}"),
+            TraceEvent::Subsume { left, right, .. } => write!(f, "subsume {left} <: {right}"),
+            TraceEvent::Solve { hole, solution, .. } => {
+                write!(f, "solve {} := {solution}", hole.get())
+            }
+            TraceEvent::Instantiate { typ, result, .. } => {
+                write!(f, "instantiate {typ} => {result}")
+            }
+            TraceEvent::Generalize { typ, result, .. } => {
+                write!(f, "generalize {typ} => {result}")
+            }
+        }
+    }
+}
+
+/// Pretty prints a trace, one event per line.
+pub fn show_trace(trace: &[TraceEvent]) -> String {
+    trace.iter().map(|event| format!("{event}\n")).collect()
+}
@@ -1,23 +1,85 @@
 //! Module for unification and subsumption of types.
 
-#![allow(clippy::only_used_in_recursion)]
+#![allow(clippy::only_used_in_recursion, clippy::result_large_err)]
 
-use crate::{context::Context, errors::TypeErrorKind};
+use crate::{
+    context::Context,
+    errors::{Breadcrumb, TypeErrorKind},
+    trace::TraceEvent,
+};
 
 use super::{
     eval::Quote,
     r#virtual::Pi,
     r#virtual::{Env, Virtual},
-    Hole, HoleInner, Level, Type, TypeKind,
+    Hole, HoleInner, Kind, Level, Type, TypeKind,
 };
 
 type Result<T = ()> = std::result::Result<T, TypeErrorKind>;
 
 impl Context {
     pub fn subsumes(&mut self, env: Env, left: Type<Virtual>, right: Type<Virtual>) {
-        fn go(ctx: &mut Context, env: Env, left: Type<Virtual>, right: Type<Virtual>) -> Result {
-            let l = left.deref();
-            let r = right.deref();
+        if let Err(kind) = self.try_subsumes(env.clone(), left, right) {
+            self.report(&env, kind)
+        }
+    }
+
+    /// Checks that the type `found` of an expression or a pattern can be used where the context or
+    /// an annotation expects the type `expected`. Mismatches read "expected ... but found ...", in
+    /// the same order as the arguments.
+    pub fn expect(&mut self, env: Env, expected: Type<Virtual>, found: Type<Virtual>) {
+        self.subsumes(env, found, expected)
+    }
+
+    /// Checks that a kind is the kind of complete types, and reports a kind mismatch otherwise.
+    pub(crate) fn expect_type_kind(&mut self, env: &Env, kind: Kind<Virtual>) {
+        let typ = Kind::typ();
+
+        if self
+            .try_subsumes(env.clone(), kind.clone(), typ.clone())
+            .is_err()
+        {
+            let kind = TypeErrorKind::KindMismatch(
+                env.clone(),
+                kind.quote(env.level),
+                typ.quote(env.level),
+            );
+
+            self.report(env, kind)
+        }
+    }
+
+    /// Same as [Context::subsumes], but the error is given back instead of reported.
+    pub(crate) fn try_subsumes(
+        &mut self,
+        env: Env,
+        left: Type<Virtual>,
+        right: Type<Virtual>,
+    ) -> Result {
+        fn go(
+            ctx: &mut Context,
+            mut env: Env,
+            left: Type<Virtual>,
+            right: Type<Virtual>,
+        ) -> Result {
+            env.depth += 1;
+
+            ctx.trace(|| TraceEvent::Subsume {
+                left: left.show(&env).to_string(),
+                right: right.show(&env).to_string(),
+                span: env.current_span(),
+            });
+
+            if ctx.exceeds_limit(&env) {
+                return Ok(());
+            }
+
+            let l = ctx.find(&left);
+            let r = ctx.find(&right);
+
+            if l.is_error() || r.is_error() {
+                return Ok(());
+            }
 
             match (l.as_ref(), r.as_ref()) {
                 (TypeKind::Hole(n), _) if n.is_empty() => {
@@ -28,8 +90,10 @@ impl Context {
                 }
                 (TypeKind::Arrow(m), TypeKind::Arrow(n)) => {
                     // Change due to variance.
-                    go(ctx, env.clone(), n.typ.clone(), m.typ.clone())?;
+                    go(ctx, env.clone(), n.typ.clone(), m.typ.clone())
+                        .map_err(|err| err.flipped().within(Breadcrumb::Argument))?;
                     go(ctx, env, m.body.clone(), n.body.clone())
+                        .map_err(|err| err.within(Breadcrumb::Return))
                 }
                 (_, TypeKind::Forall(forall)) => {
                     let lvl_ty = Type::new(TypeKind::Bound(env.level));
@@ -48,24 +112,20 @@ impl Context {
             }
         }
 
-        let result = go(self, env.clone(), left.clone(), right.clone());
-
-        if let Err(kind) = result {
-            match kind {
-                TypeErrorKind::TypeMismatch(_, _, _) => self.report(
-                    &env,
-                    TypeErrorKind::TypeMismatch(
-                        env.clone(),
-                        left.quote(env.level),
-                        right.quote(env.level),
-                    ),
-                ),
-                _ => self.report(&env, kind),
+        go(self, env.clone(), left.clone(), right.clone()).map_err(|mut kind| {
+            if let TypeErrorKind::TypeMismatch { full, .. } = &mut kind {
+                *full = Some(Box::new((
+                    env.clone(),
+                    left.quote(env.level),
+                    right.quote(env.level),
+                )));
             }
-        }
+
+            kind
+        })
     }
 
-    fn sub_hole_type(&mut self, env: Env, left: Hole<Virtual>, right: Type<Virtual>) -> Result {
+    fn sub_hole_type(&mut self, env: Env, left: Hole, right: Type<Virtual>) -> Result {
         match right.deref().as_ref() {
             TypeKind::Forall(forall) => {
                 let lvl_ty = Type::new(TypeKind::Bound(env.level));
@@ -76,17 +136,23 @@ impl Context {
                 )
             }
             TypeKind::Arrow(pi) => {
-                let HoleInner::Empty(_, kind, _) = left.0.borrow().clone() else {
+                let HoleInner::Empty(kind, _) = left.inner() else {
                     unreachable!()
                 };
 
+                self.occurs_in(&env, &left, &right)?;
+
                 let hole_a = self.hole(&env, kind.clone());
                 let hole_b = self.hole(&env, kind);
 
-                left.fill(Type::new(TypeKind::Arrow(Pi {
-                    typ: hole_a.clone(),
-                    body: hole_b.clone(),
-                })));
+                self.solve(
+                    &env,
+                    &left,
+                    Type::new(TypeKind::Arrow(Pi {
+                        typ: hole_a.clone(),
+                        body: hole_b.clone(),
+                    })),
+                );
 
                 let a = pi.typ.clone();
                 let b = pi.body.clone();
@@ -105,7 +171,7 @@ impl Context {
         }
     }
 
-    fn sub_type_hole(&mut self, env: Env, left: Type<Virtual>, right: Hole<Virtual>) -> Result {
+    fn sub_type_hole(&mut self, env: Env, left: Type<Virtual>, right: Hole) -> Result {
         let deref = &left.deref();
         match deref.as_ref() {
             TypeKind::Forall(_) => {
@@ -113,17 +179,23 @@ impl Context {
                 self.sub_type_hole(env, left, right)
             }
             TypeKind::Arrow(pi) => {
-                let HoleInner::Empty(_, kind, _) = right.0.borrow().clone() else {
+                let HoleInner::Empty(kind, _) = right.inner() else {
                     unreachable!()
                 };
 
+                self.occurs_in(&env, &right, deref)?;
+
                 let hole_a = self.hole(&env, kind.clone());
                 let hole_b = self.hole(&env, kind);
 
-                right.fill(Type::new(TypeKind::Arrow(Pi {
-                    typ: hole_a.clone(),
-                    body: hole_b.clone(),
-                })));
+                self.solve(
+                    &env,
+                    &right,
+                    Type::new(TypeKind::Arrow(Pi {
+                        typ: hole_a.clone(),
+                        body: hole_b.clone(),
+                    })),
+                );
 
                 let a = pi.typ.clone();
                 let b = pi.body.clone();
@@ -138,7 +210,9 @@ impl Context {
                 self.sub_hole_type(env.clone(), hole_a.clone(), a)?;
                 self.sub_type_hole(env, b, hole_b.clone())
             }
-            _ => self.unify_hole(env, right, left),
+            _ => self
+                .unify_hole(env, right, left)
+                .map_err(TypeErrorKind::flipped),
         }
     }
 
@@ -147,31 +221,106 @@ impl Context {
         result.is_ok()
     }
 
-    pub fn unify(&mut self, env: Env, left: Type<Virtual>, right: Type<Virtual>) -> Result {
-        let l = left.deref();
-        let r = right.deref();
+    pub fn unify(&mut self, mut env: Env, left: Type<Virtual>, right: Type<Virtual>) -> Result {
+        env.depth += 1;
+
+        self.trace(|| TraceEvent::Unify {
+            left: left.show(&env).to_string(),
+            right: right.show(&env).to_string(),
+            span: env.current_span(),
+        });
+
+        // The limit was already reported, so the types are treated like errors.
+        if self.exceeds_limit(&env) {
+            return Ok(());
+        }
+
+        let l = self.find(&left);
+        let r = self.find(&right);
+
+        // Interned types are equal only if they are the same allocation.
+        if l.ptr_eq(&r) {
+            return Ok(());
+        }
+
+        // Errors were already reported, so they are compatible with everything in order to avoid
+        // a cascade of diagnostics.
+        if l.is_error() || r.is_error() {
+            return Ok(());
+        }
+
         match (l.as_ref(), r.as_ref()) {
             (TypeKind::Tuple(x), TypeKind::Tuple(y)) if x.len() == y.len() => x
                 .iter()
                 .zip(y.iter())
-                .try_for_each(|(x, y)| self.unify(env.clone(), x.clone(), y.clone())),
+                .enumerate()
+                .try_for_each(|(i, (x, y))| {
+                    self.unify(env.clone(), x.clone(), y.clone())
+                        .map_err(|err| err.within(Breadcrumb::TupleElement(i)))
+                }),
+            // The unit type is an empty tuple, but comparing it with a tuple is not about the size.
+            (TypeKind::Tuple(x), TypeKind::Tuple(y)) if !x.is_empty() && !y.is_empty() => {
+                Err(TypeErrorKind::TupleArityMismatch {
+                    expected: y.len(),
+                    found: x.len(),
+                    path: Vec::new(),
+                })
+            }
+            (TypeKind::Arrow(m), TypeKind::Arrow(n)) => {
+                self.unify(env.clone(), m.typ.clone(), n.typ.clone())
+                    .map_err(|err| err.within(Breadcrumb::Argument))?;
+                self.unify(env, m.body.clone(), n.body.clone())
+                    .map_err(|err| err.within(Breadcrumb::Return))
+            }
             (TypeKind::Application(f, a), TypeKind::Application(g, b)) => {
                 self.unify(env.clone(), f.clone(), g.clone())?;
+                let position = l.application_spine().1.len();
                 self.unify(env, a.clone(), b.clone())
+                    .map_err(|err| err.within(Breadcrumb::TypeArgument(position)))
             }
             (TypeKind::Qualified(f, u), TypeKind::Qualified(f1, u1)) => {
-                self.unify(env.clone(), f.clone(), f1.clone())?;
+                self.unify(env.clone(), f.clone(), f1.clone())
+                    .map_err(|err| err.within(Breadcrumb::Constraint))?;
                 self.unify(env, u.clone(), u1.clone())
+                    .map_err(|err| err.within(Breadcrumb::Qualified))
+            }
+            // Foralls are equal up to the names of their binders, so both bodies are opened with
+            // the same rigid variable.
+            (TypeKind::Forall(m), TypeKind::Forall(n)) => {
+                if self
+                    .unify(env.clone(), m.kind.clone(), n.kind.clone())
+                    .is_err()
+                {
+                    return Err(TypeErrorKind::KindMismatch(
+                        env.clone(),
+                        m.kind.quote(env.level),
+                        n.kind.quote(env.level),
+                    ));
+                }
+
+                let rigid = Type::new(TypeKind::Bound(env.level));
+                let inner = env.add(Some(m.name.clone()), m.kind.clone());
+
+                self.unify(
+                    inner,
+                    m.body.apply_local(None, rigid.clone()),
+                    n.body.apply_local(None, rigid),
+                )
             }
             (TypeKind::Hole(n), TypeKind::Hole(m)) if n == m => Ok(()),
+            (TypeKind::Hole(n), TypeKind::Hole(m)) if n.is_empty() && m.is_empty() => {
+                self.union(&env, n, m);
+                Ok(())
+            }
             (TypeKind::Hole(m), _) => self.unify_hole(env, m.clone(), r),
-            (_, TypeKind::Hole(m)) => self.unify_hole(env, m.clone(), l),
+            (_, TypeKind::Hole(m)) => self
+                .unify_hole(env, m.clone(), l)
+                .map_err(TypeErrorKind::flipped),
             (TypeKind::Bound(x), TypeKind::Bound(y)) if x == y => Ok(()),
             (TypeKind::Variable(x), TypeKind::Variable(y)) if x == y => Ok(()),
             (TypeKind::Type, TypeKind::Type) => Ok(()),
             (TypeKind::Constraint, TypeKind::Constraint) => Ok(()),
-            (TypeKind::Error, _) | (_, TypeKind::Error) => Ok(()),
-            (_, _) => Err(TypeErrorKind::TypeMismatch(
+            (_, _) => Err(TypeErrorKind::mismatch(
                 env.clone(),
                 left.quote(env.level),
                 right.quote(env.level),
@@ -179,7 +328,7 @@ impl Context {
         }
     }
 
-    fn occurs(&self, env: Env, scope: &Level, hole: Hole<Virtual>, typ: Type<Virtual>) -> Result {
+    fn occurs(&self, env: Env, scope: &Level, hole: Hole, typ: Type<Virtual>) -> Result {
         match typ.deref().as_ref() {
             TypeKind::Arrow(pi) => {
                 self.occurs(env.clone(), scope, hole.clone(), pi.typ.clone())?;
@@ -202,14 +351,24 @@ impl Context {
         }
     }
 
-    fn unify_hole(&mut self, env: Env, hole: Hole<Virtual>, right: Type<Virtual>) -> Result {
-        let borrow = hole.0.borrow().clone();
-        match borrow {
-            HoleInner::Empty(_, _, lvl) => match right.deref().as_ref() {
+    /// Fails if the hole appears inside of the type, so a hole is not split into an arrow that
+    /// contains it forever. The scope is not checked, because the parts of the arrow are unified
+    /// with holes that check it.
+    fn occurs_in(&self, env: &Env, hole: &Hole, typ: &Type<Virtual>) -> Result {
+        self.occurs(env.clone(), &Level(usize::MAX), hole.clone(), typ.clone())
+    }
+
+    fn unify_hole(&mut self, env: Env, hole: Hole, right: Type<Virtual>) -> Result {
+        match hole.inner() {
+            HoleInner::Empty(_, lvl) => match right.deref().as_ref() {
                 TypeKind::Hole(hole1) if hole == hole1.clone() => Ok(()),
+                TypeKind::Hole(hole1) if hole1.is_empty() => {
+                    self.union(&env, &hole, hole1);
+                    Ok(())
+                }
                 _ => {
-                    self.occurs(env, &lvl, hole.clone(), right.clone())?;
-                    hole.fill(right);
+                    self.occurs(env.clone(), &lvl, hole.clone(), right.clone())?;
+                    self.solve(&env, &hole, right);
                     Ok(())
                 }
             },
@@ -0,0 +1,107 @@
+//! Tracking of the variables that are never used or that shadow other variables. Every variable is
+//! identified by the file and the id of the pattern that binds it, so a variable that shadows
+//! another one is tracked apart from it, even when the desugaring gives both of them the same
+//! location.
+
+use vulpi_intern::Symbol;
+use vulpi_location::Span;
+use vulpi_report::Diagnostic;
+use vulpi_syntax::r#abstract::{Block, NodeId, Pattern, PatternKind, SttmKind};
+
+use crate::{
+    context::Context,
+    errors::{TypeError, TypeErrorKind},
+    Env,
+};
+
+/// A variable bound by a pattern, with the id and the location of the pattern.
+pub(crate) type Bound = (Symbol, NodeId, Span);
+
+/// Collects the variables bound by a pattern together with their patterns. Both sides of an or
+/// pattern bind the same variables and share the same body, so only the left one is collected.
+pub(crate) fn binders(pattern: &Pattern, found: &mut Vec<Bound>) {
+    match &pattern.data {
+        PatternKind::Variable(name) => found.push((name.clone(), pattern.id, pattern.span.clone())),
+        PatternKind::Tuple(patterns) => {
+            for pattern in patterns {
+                binders(pattern, found);
+            }
+        }
+        PatternKind::Ascription(ascription) => binders(&ascription.pat, found),
+        PatternKind::Or(or) => binders(&or.left, found),
+        PatternKind::Application(app) => {
+            for pattern in &app.args {
+                binders(pattern, found);
+            }
+        }
+        PatternKind::Labeled(labeled) => {
+            for (_, _, pattern) in &labeled.fields {
+                binders(pattern, found);
+            }
+        }
+        PatternKind::Wildcard | PatternKind::Literal(_) | PatternKind::Error => (),
+    }
+}
+
+/// Collects the variables bound by the let statements of a block. Their scope is the rest of the
+/// block.
+pub(crate) fn block_binders(block: &Block) -> Vec<Bound> {
+    let mut found = Vec::new();
+
+    for sttm in &block.sttms {
+        if let SttmKind::Let(decl) = &sttm.data {
+            binders(&decl.pat, &mut found);
+        }
+    }
+
+    found
+}
+
+impl Context {
+    /// Remembers where the variables are bound, so their uses can be tracked. If shadowing warnings
+    /// are enabled, the variables that shadow another one of the enclosing scopes are reported.
+    pub(crate) fn add_binders(&mut self, env: &mut Env, binders: &[Bound]) {
+        if self.warn_shadowing {
+            for (name, _, span) in binders {
+                if let Some((_, shadowed)) = env.binders.get(name) {
+                    let diagnostic = Diagnostic::new(TypeError {
+                        span: span.clone(),
+                        kind: TypeErrorKind::ShadowedVariable(name.clone()),
+                        budget: self.budget,
+                    });
+
+                    self.reporter.report(
+                        diagnostic.with_label(shadowed.clone(), "shadowed variable bound here"),
+                    );
+                }
+            }
+        }
+
+        for (name, id, span) in binders {
+            env.add_binder(name.clone(), (*id, span.clone()));
+        }
+    }
+
+    /// Marks the variable that the name refers to as used.
+    pub(crate) fn use_variable(&mut self, env: &Env, name: &Symbol) {
+        if let Some((id, span)) = env.binders.get(name) {
+            self.used.insert((span.file, *id));
+        }
+    }
+
+    /// Reports the variables that were never used after their scope was checked. Variables that
+    /// start with `_` are unused on purpose.
+    pub(crate) fn report_unused(&mut self, binders: &[Bound]) {
+        for (name, id, span) in binders {
+            if name.get().starts_with('_') || self.used.contains(&(span.file, *id)) {
+                continue;
+            }
+
+            self.reporter.report(Diagnostic::new(TypeError {
+                span: span.clone(),
+                kind: TypeErrorKind::UnusedVariable(name.clone()),
+                budget: self.budget,
+            }));
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! The value restriction. Generalizing the type of a binding whose value does some work when it's
+//! evaluated, like an application that allocates a mutable reference, would let the result of that
+//! work be used at many types. Only bindings whose values are syntactic values are generalized.
+
+use vulpi_syntax::r#abstract::{Expr, ExprKind, LetDecl};
+
+/// Which bindings have their types generalized. It's set in the [crate::Env] that is given to the
+/// checker.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValueRestriction {
+    /// Only the bindings of syntactic values are generalized. The others keep their holes, so they
+    /// are filled by the first use.
+    #[default]
+    Strict,
+
+    /// Every binding is generalized.
+    Relaxed,
+}
+
+impl ValueRestriction {
+    pub fn generalizes(self, expr: &Expr) -> bool {
+        self == ValueRestriction::Relaxed || is_value(expr)
+    }
+
+    /// Top level declarations with parameters are functions, so they are always values.
+    pub fn generalizes_decl(self, decl: &LetDecl) -> bool {
+        let function = !decl.signature.binders.is_empty()
+            || decl.body.iter().any(|arm| !arm.patterns.is_empty());
+
+        function || decl.body.iter().all(|arm| self.generalizes(&arm.expr))
+    }
+}
+
+/// Checks if an expression is a syntactic value, an expression that cannot do anything when it's
+/// evaluated. Lambdas, literals, names and constructors applied to values are values.
+pub fn is_value(expr: &Expr) -> bool {
+    match &expr.data {
+        ExprKind::Lambda(_)
+        | ExprKind::Variable(_)
+        | ExprKind::Constructor(_)
+        | ExprKind::Function(_)
+        | ExprKind::Literal(_)
+        | ExprKind::Hole(_)
+        | ExprKind::Error => true,
+        ExprKind::Application(_) => constructor_application(expr),
+        ExprKind::Annotation(annotation) => is_value(&annotation.expr),
+        ExprKind::TypeApplication(application) => is_value(&application.expr),
+        ExprKind::Tuple(tuple) => tuple.exprs.iter().all(is_value),
+        ExprKind::RecordInstance(instance) | ExprKind::Labeled(instance) => {
+            instance.fields.iter().all(|(_, _, e)| is_value(e))
+        }
+        ExprKind::Projection(_)
+        | ExprKind::TupleProjection(_)
+        | ExprKind::Let(_)
+        | ExprKind::When(_)
+        | ExprKind::Do(_)
+        | ExprKind::RecordUpdate(_) => false,
+    }
+}
+
+fn constructor_application(expr: &Expr) -> bool {
+    match &expr.data {
+        ExprKind::Constructor(_) => true,
+        ExprKind::TypeApplication(application) => constructor_application(&application.expr),
+        ExprKind::Application(app) => {
+            constructor_application(&app.func) && app.args.iter().all(is_value)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,120 @@
+//! Zonking of types. After type checking a lot of holes are filled with other holes, so every
+//! consumer of a type would have to follow these chains. Zonking replaces each filled hole with
+//! the type that is at the end of its chain. Closing a type zonks it and also replaces the holes
+//! that are still empty by errors, so the copy cannot be filled by anyone.
+
+use crate::{
+    eval::Quote,
+    r#virtual::{self, Virtual},
+    real::{self, Real},
+    HoleInner, Level, Type, TypeKind,
+};
+
+impl Type<Virtual> {
+    /// Replaces the filled holes by their content. The holes in the middle of a chain are filled
+    /// with the last type of it, so the chain is not followed again.
+    pub fn zonk(&self) -> Type<Virtual> {
+        self.zonk_holes(false)
+    }
+
+    /// Zonks the type and replaces its empty holes by errors.
+    pub fn close(&self) -> Type<Virtual> {
+        self.zonk_holes(true)
+    }
+
+    fn zonk_holes(&self, close: bool) -> Type<Virtual> {
+        match self.as_ref() {
+            TypeKind::Hole(hole) => match hole.inner() {
+                HoleInner::Filled(typ) => {
+                    let typ = typ.zonk();
+                    hole.fill(typ.clone());
+                    if close {
+                        typ.close()
+                    } else {
+                        typ
+                    }
+                }
+                HoleInner::Empty(..) if close => Type::error(),
+                HoleInner::Empty(..) => self.clone(),
+            },
+            TypeKind::Arrow(pi) => Type::new(TypeKind::Arrow(r#virtual::Pi {
+                typ: pi.typ.zonk_holes(close),
+                body: pi.body.zonk_holes(close),
+            })),
+            TypeKind::Forall(forall) => Type::new(TypeKind::Forall(r#virtual::Forall {
+                name: forall.name.clone(),
+                kind: forall.kind.zonk_holes(close),
+                body: r#virtual::Closure {
+                    env: forall.body.env.clone(),
+                    body: forall
+                        .body
+                        .body
+                        .zonk_holes(forall.body.env.level.inc(), close),
+                },
+            })),
+            TypeKind::Tuple(types) => Type::new(TypeKind::Tuple(
+                types.iter().map(|typ| typ.zonk_holes(close)).collect(),
+            )),
+            TypeKind::Application(left, right) => Type::new(TypeKind::Application(
+                left.zonk_holes(close),
+                right.zonk_holes(close),
+            )),
+            TypeKind::Qualified(left, right) => Type::new(TypeKind::Qualified(
+                left.zonk_holes(close),
+                right.zonk_holes(close),
+            )),
+            _ => self.clone(),
+        }
+    }
+}
+
+impl Type<Real> {
+    /// Replaces the filled holes by their content. The depth is the number of variables that are
+    /// bound outside of the type.
+    pub fn zonk(&self, depth: Level) -> Type<Real> {
+        self.zonk_holes(depth, false)
+    }
+
+    /// Zonks the type and replaces its empty holes by errors.
+    pub fn close(&self, depth: Level) -> Type<Real> {
+        self.zonk_holes(depth, true)
+    }
+
+    fn zonk_holes(&self, depth: Level, close: bool) -> Type<Real> {
+        match self.as_ref() {
+            TypeKind::Hole(hole) => match hole.inner() {
+                HoleInner::Filled(typ) => {
+                    let typ = typ.zonk();
+                    hole.fill(typ.clone());
+                    typ.quote(depth).zonk_holes(depth, close)
+                }
+                HoleInner::Empty(..) if close => Type::error(),
+                HoleInner::Empty(..) => self.clone(),
+            },
+            TypeKind::Arrow(pi) => Type::new(TypeKind::Arrow(real::Arrow {
+                typ: pi.typ.zonk_holes(depth, close),
+                body: pi.body.zonk_holes(depth, close),
+            })),
+            TypeKind::Forall(forall) => Type::new(TypeKind::Forall(real::Forall {
+                name: forall.name.clone(),
+                kind: forall.kind.zonk_holes(depth, close),
+                body: forall.body.zonk_holes(depth.inc(), close),
+            })),
+            TypeKind::Tuple(types) => Type::new(TypeKind::Tuple(
+                types
+                    .iter()
+                    .map(|typ| typ.zonk_holes(depth, close))
+                    .collect(),
+            )),
+            TypeKind::Application(left, right) => Type::new(TypeKind::Application(
+                left.zonk_holes(depth, close),
+                right.zonk_holes(depth, close),
+            )),
+            TypeKind::Qualified(left, right) => Type::new(TypeKind::Qualified(
+                left.zonk_holes(depth, close),
+                right.zonk_holes(depth, close),
+            )),
+            _ => self.clone(),
+        }
+    }
+}
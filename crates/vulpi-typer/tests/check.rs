@@ -0,0 +1,2167 @@
+use std::time::{Duration, Instant};
+
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, registry, Severity};
+use vulpi_syntax::r#abstract::Qualified;
+use vulpi_typer::{
+    declare::{Declare, Programs},
+    value::ValueRestriction,
+    CheckOptions, Env, Summary, TraceKind, Type, TypeKind,
+};
+use vulpi_vfs::path::Path;
+
+fn resolve(source: &str) -> (Vec<vulpi_syntax::r#abstract::Program>, vulpi_report::Report) {
+    let report = hash_reporter();
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), source);
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    vulpi_resolver::resolve_modules(vec![(path, parsed)], report)
+}
+
+fn check(source: &str) -> vulpi_report::Report {
+    let (programs, report) = resolve(source);
+    vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    report
+}
+
+#[test]
+fn reports_errors_without_rendering() {
+    let report = check(
+        "type Int\n\
+         type String\n\
+         external number : Int = \"0\"\n\
+         let bad : String = number\n",
+    );
+
+    assert!(report.has_errors());
+    assert_eq!(report.len(), 1);
+
+    let diagnostic = report.iter().next().unwrap();
+
+    assert_eq!(diagnostic.severity, Severity::Error);
+    assert_eq!(
+        diagnostic.message,
+        "type mismatch: expected String but found Int"
+    );
+}
+
+#[test]
+fn good_programs_have_an_empty_report() {
+    let report = check(
+        "type Int\n\
+         external number : Int = \"0\"\n\
+         let good : Int = number\n",
+    );
+
+    assert!(!report.has_errors());
+    assert!(report.is_empty());
+}
+
+#[test]
+fn quantifies_in_order_of_first_occurrence() {
+    let (programs, report) = resolve("let const (x: b) (y: a) : b = x\n");
+
+    let mut ctx = vulpi_typer::Context::new(report);
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+
+    let def = ctx
+        .modules
+        .modules
+        .values()
+        .find_map(|module| module.variables.get(&Symbol::intern("const")))
+        .unwrap();
+
+    let names = def
+        .unbound
+        .iter()
+        .map(|(name, _)| name.get())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["b", "a"]);
+}
+
+#[test]
+fn wildcard_lets_are_checked_but_not_defined() {
+    let source = "type Int\n\
+                  type String\n\
+                  external zero : Int = \"0\"\n\
+                  let _ : String = zero\n\
+                  let _ : String = zero\n\
+                  let _ = zero\n";
+
+    let codes = check(source).iter().map(|d| d.code).collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        vec![Some(registry::TYPE_MISMATCH), Some(registry::TYPE_MISMATCH)]
+    );
+
+    let (programs, report) = resolve(source);
+
+    let mut ctx = vulpi_typer::Context::new(report);
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+
+    assert_eq!(ctx.modules.wildcards.len(), 3);
+
+    let names = ctx
+        .modules
+        .modules
+        .values()
+        .flat_map(|module| module.variables.keys().map(|name| name.get()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["zero"]);
+}
+
+#[test]
+fn public_functions_cannot_return_private_types() {
+    let source = |visibility: &str| {
+        format!(
+            "pub type Int\n\
+             external zero : Int = \"0\"\n\
+             {visibility}type Point = {{\n    \
+                 x : Int\n\
+             }}\n\
+             pub let origin : Point = Point {{ x = zero }}\n"
+        )
+    };
+
+    let private = source("");
+    let (programs, report) = resolve(&private);
+    vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    let diagnostics = report.all_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].code(),
+        Some(registry::PRIVATE_TYPE_IN_PUBLIC_INTERFACE)
+    );
+
+    let at = |span: &vulpi_location::Span| &private[span.start.0..span.end.0];
+    assert_eq!(at(&diagnostics[0].location()), "origin");
+    assert_eq!(at(&diagnostics[0].markers()[0].position), "Point");
+
+    assert!(check(&source("pub ")).is_empty());
+}
+
+#[test]
+fn mismatches_point_at_the_body_and_the_annotation() {
+    let source = "type Int\n\
+                  external zero : Int = \"0\"\n\
+                  let f : forall a. a -> a = \\_x => zero\n";
+
+    let report = check(source);
+    let diagnostics = report.all_diagnostics();
+
+    assert_eq!(diagnostics.len(), 1);
+
+    let location = diagnostics[0].location();
+    assert_eq!(&source[location.start.0..location.end.0], "zero");
+
+    let markers = diagnostics[0].markers();
+    assert_eq!(markers.len(), 1);
+
+    let annotation = &markers[0].position;
+    assert_eq!(
+        &source[annotation.start.0..annotation.end.0],
+        "forall a. a -> a"
+    );
+}
+
+#[test]
+fn arms_that_differ_from_the_first_one_are_blamed() {
+    let source = "type Int\n\
+                  type String\n\
+                  type Color =\n    \
+                      | Red\n    \
+                      | Green\n    \
+                      | Blue\n\
+                  let pick (c: Color) (n: Int) (m: Int) (s: String) = when c is\n    \
+                      Color.Red => n\n    \
+                      Color.Green => m\n    \
+                      Color.Blue => s\n";
+
+    let report = check(source);
+    let diagnostics = report.all_diagnostics();
+
+    assert_eq!(diagnostics.len(), 1);
+
+    let location = diagnostics[0].location();
+    assert_eq!(&source[location.start.0..location.end.0], "s");
+
+    let markers = diagnostics[0].markers();
+    assert_eq!(markers.len(), 1);
+
+    let first = &markers[0].position;
+    assert_eq!(&source[first.start.0..first.end.0], "n");
+    assert_eq!(
+        markers[0].subtitle.as_ref().map(|x| x.to_string()),
+        Some("the first arm has type Int".to_string())
+    );
+}
+
+#[test]
+fn guards_are_not_blamed_on_the_first_arm() {
+    let source = "type Int\n\
+                  pub type Bool =\n    \
+                      | False\n    \
+                      | True\n\
+                  type Color =\n    \
+                      | Red\n    \
+                      | Green\n\
+                  let pick (c: Color) (n: Int) (m: Int) = when c is\n    \
+                      Color.Red => n\n    \
+                      Color.Green if m => m\n";
+
+    let (programs, report) = resolve(source);
+    let options = CheckOptions {
+        prelude: Symbol::intern("Tests.Main"),
+        ..CheckOptions::default()
+    };
+    vulpi_typer::check(programs, report.clone(), options);
+
+    let diagnostics = report.all_diagnostics();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message().to_string(),
+        "type mismatch: expected Bool but found Int"
+    );
+
+    let location = diagnostics[0].location();
+    assert_eq!(&source[location.start.0..location.end.0], "m");
+    assert!(diagnostics[0].markers().is_empty());
+}
+
+#[test]
+fn foralls_are_equal_up_to_the_names_of_their_binders() {
+    let report = check(
+        "type Box a = | Box a\n\
+         let same (x: Box (forall a. a -> a)) : Box (forall b. b -> b) = x\n\
+         let nested (x: Box (forall a b. a -> b -> a)) : Box (forall c d. c -> d -> c) = x\n",
+    );
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn reordered_foralls_are_not_equal() {
+    let report = check(
+        "type Box a = | Box a\n\
+         let swap (x: Box (forall a b. a -> b -> a)) : Box (forall b a. a -> b -> a) = x\n",
+    );
+
+    let codes = report.iter().map(|d| d.code).collect::<Vec<_>>();
+    assert_eq!(codes, vec![Some(registry::TYPE_MISMATCH)]);
+}
+
+#[test]
+fn foralls_with_different_kinds_are_not_equal() {
+    let report = check(
+        "type Box a = | Box a\n\
+         type U = | U\n\
+         let k (x: Box (forall (f : * -> *). f U -> U)) : Box (forall (g : *). g -> U) = x\n",
+    );
+
+    let codes = report.iter().map(|d| d.code).collect::<Vec<_>>();
+    assert_eq!(codes, vec![Some(registry::KIND_MISMATCH)]);
+}
+
+/// The code, the message and the hint of every error, together with the source that it points to.
+fn kind_errors(source: &str) -> Vec<(Option<usize>, String, Option<String>, &str)> {
+    let report = check(source);
+
+    report
+        .all_diagnostics()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+        .map(|diagnostic| {
+            let location = diagnostic.location();
+            (
+                diagnostic.code(),
+                diagnostic.message().to_string(),
+                diagnostic.hint().map(|hint| hint.to_string()),
+                &source[location.start.0..location.end.0],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn mismatches_expect_the_type_of_the_context() {
+    let source = "pub type Int\n\
+                  pub type String\n\
+                  pub type Bool =\n    \
+                      | False\n    \
+                      | True\n\
+                  let annotation (s: String) : Int = s\n\
+                  let statement (s: String) : Int = do\n    \
+                      let x : Int = s\n    \
+                      x\n\
+                  let branch (b: Bool) (n: Int) (s: String) = when b is\n    \
+                      Bool.True => n\n    \
+                      Bool.False => s\n\
+                  let argument (f: Int -> Int) (s: String) : Int = f s\n";
+
+    let report = check(source);
+
+    let errors = report
+        .all_diagnostics()
+        .into_iter()
+        .map(|diagnostic| {
+            let location = diagnostic.location();
+            (
+                diagnostic.message().to_string(),
+                &source[location.start.0..location.end.0],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "type mismatch: expected Int but found String".to_string(),
+                "s"
+            );
+            4
+        ]
+    );
+}
+
+#[test]
+fn reports_the_extra_arguments_of_applications() {
+    let source = "pub type Int\n\
+                  pub type Maybe a =\n    \
+                      | None\n    \
+                      | Some a\n\
+                  pub let add (x: Int) (_: Int) : Int = x\n\
+                  pub let first (x: Int) : Int = add x x x x\n\
+                  pub let second (x: Int) : Maybe Int = Maybe.Some x x\n\
+                  pub let third (x: Int) : Int = x x\n";
+
+    let report = check(source);
+
+    let errors = report
+        .all_diagnostics()
+        .into_iter()
+        .map(|diagnostic| {
+            let location = diagnostic.location();
+            let notes = diagnostic.notes().iter().map(|x| x.to_string()).collect();
+            (
+                diagnostic.code(),
+                diagnostic.message().to_string(),
+                notes,
+                &source[location.start.0..location.end.0],
+            )
+        })
+        .collect::<Vec<(_, _, Vec<String>, _)>>();
+
+    assert_eq!(
+        errors,
+        vec![
+            (
+                Some(registry::TOO_MANY_ARGUMENTS),
+                "the function takes 2 arguments but 4 were given".to_string(),
+                vec!["the function has type (Int -> (Int -> Int))".to_string()],
+                "x x",
+            ),
+            (
+                Some(registry::TOO_MANY_ARGUMENTS),
+                "the function takes 1 argument but 2 were given".to_string(),
+                vec!["the function has type (Int -> (Maybe Int))".to_string()],
+                "x",
+            ),
+            (
+                Some(registry::NOT_A_FUNCTION),
+                "not a function: Int".to_string(),
+                vec![],
+                "x",
+            ),
+        ]
+    );
+}
+
+#[test]
+fn constructors_in_patterns_take_all_of_their_arguments() {
+    let source = "type Option a =\n    \
+                      | Some a\n    \
+                      | None\n\
+                  let bare (x: Option a) : Option a =\n    \
+                      when x is\n        \
+                          Option.Some => x\n        \
+                          Option.None => x\n\
+                  let extra (x: Option a) : Option a =\n    \
+                      when x is\n        \
+                          Option.Some _ _ => x\n        \
+                          Option.None _ => x\n";
+
+    let report = check(source);
+    let text = |span: &vulpi_location::Span| &source[span.start.0..span.end.0];
+
+    let errors = report
+        .all_diagnostics()
+        .into_iter()
+        .map(|diagnostic| {
+            let markers = diagnostic
+                .markers()
+                .into_iter()
+                .map(|marker| (marker.subtitle.unwrap().to_string(), text(&marker.position)))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                diagnostic.code(),
+                Some(registry::CONSTRUCTOR_ARITY_MISMATCH)
+            );
+            (
+                diagnostic.message().to_string(),
+                text(&diagnostic.location()),
+                markers,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let declared =
+        |name: &'static str| vec![(format!("the constructor {name} is declared here"), name)];
+
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "the constructor Some expects 1 argument but the pattern has 0".to_string(),
+                "Option.Some",
+                declared("Some"),
+            ),
+            (
+                "the constructor Some expects 1 argument but the pattern has 2".to_string(),
+                "Option.Some _ _",
+                declared("Some"),
+            ),
+            (
+                "the constructor None expects 0 arguments but the pattern has 1".to_string(),
+                "Option.None _",
+                declared("None"),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn reports_types_that_are_missing_arguments() {
+    let errors = kind_errors(
+        "pub type Int\n\
+         pub type Maybe a =\n    \
+             | None\n    \
+             | Some a\n\
+         pub type Apply (f : * -> *) =\n    \
+             | Apply (f Int)\n\
+         pub let first (x: Maybe) (y: Int) : Int = y\n\
+         pub let second (x: Maybe Maybe) (y: Int) : Int = y\n\
+         pub let third (x: Apply Int) (y: Int) : Int = y\n",
+    );
+
+    assert_eq!(
+        errors,
+        vec![
+            (
+                Some(registry::KIND_MISMATCH),
+                "kind mismatch: expected Type but found (Type -> Type)".to_string(),
+                Some("the type needs 1 more argument".to_string()),
+                "Maybe",
+            ),
+            (
+                Some(registry::ARGUMENT_KIND_MISMATCH),
+                "the 1st argument of 'Maybe' should have kind Type but it has kind (Type -> Type)"
+                    .to_string(),
+                Some("'Maybe' has kind (Type -> Type)".to_string()),
+                "Maybe",
+            ),
+            (
+                Some(registry::ARGUMENT_KIND_MISMATCH),
+                "the 1st argument of 'Apply' should have kind (Type -> Type) but it has kind Type"
+                    .to_string(),
+                Some("'Apply' has kind ((Type -> Type) -> Type)".to_string()),
+                "Int",
+            ),
+        ]
+    );
+}
+
+#[test]
+fn reports_types_that_are_applied_to_too_many_arguments() {
+    let errors = kind_errors(
+        "pub type Int\n\
+         pub type Maybe a =\n    \
+             | None\n    \
+             | Some a\n\
+         pub let first (x: Maybe Int Int) (y: Int) : Int = y\n\
+         pub let second (x: Int Maybe) (y: Int) : Int = y\n",
+    );
+
+    assert_eq!(
+        errors,
+        vec![
+            (
+                Some(registry::NOT_A_FUNCTION_KIND),
+                "'(Maybe Int)' has kind Type, it cannot be applied to another argument".to_string(),
+                Some("'Maybe' has kind (Type -> Type), so it takes 1 argument".to_string()),
+                "Int",
+            ),
+            (
+                Some(registry::NOT_A_FUNCTION_KIND),
+                "'Int' has kind Type, it cannot be applied to another argument".to_string(),
+                None,
+                "Maybe",
+            ),
+        ]
+    );
+}
+
+#[test]
+fn generalizes_mutually_recursive_functions() {
+    let report = check(
+        "type Int\n\
+         type String\n\
+         external choose : forall a. a -> a -> a = \"choose\"\n\
+         let even = \\n => choose n (odd n)\n\
+         let odd = \\n => choose n (even n)\n\
+         let a : Int -> Int = even\n\
+         let b : String -> String = odd\n",
+    );
+
+    assert!(report.is_empty());
+}
+
+const BINDINGS: usize = 2000;
+
+const VALUES: &str = "pub type Int\n\
+                      pub type String\n\
+                      external zero : Int = \"0\"\n\
+                      external name : String = \"name\"\n";
+
+#[test]
+fn generalizes_local_lets() {
+    let report = check(&format!(
+        "{VALUES}\
+         let pair : (Int, String) =\n    \
+             let id = \\x => x in\n    \
+             (id zero, id name)\n"
+    ));
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn generalizes_shadowed_local_lets() {
+    let report = check(&format!(
+        "{VALUES}\
+         let shadowed : (String, Int) =\n    \
+             let id = \\x => x in\n    \
+             let id = \\y => (id y, id zero) in\n    \
+             id name\n"
+    ));
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn generalizes_lets_inside_of_do_blocks() {
+    let report = check(&format!(
+        "{VALUES}\
+         let block : (Int, String) = do\n    \
+             let id = \\x => x\n    \
+             (id zero, id name)\n"
+    ));
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn keeps_variables_of_the_environment_monomorphic() {
+    let report = check(&format!(
+        "{VALUES}\
+         let constant (y: Int) : String =\n    \
+             let f = \\_x => y in\n    \
+             f name\n"
+    ));
+
+    assert_eq!(report.len(), 1);
+}
+
+#[test]
+fn unconstrained_results_are_ambiguous() {
+    let source = format!(
+        "{VALUES}\
+         let loop (n: Int) = loop n\n\
+         pub let a : String = loop zero\n\
+         pub let b : Int = loop zero\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.iter().collect::<Vec<_>>();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, Some(registry::AMBIGUOUS_TYPE));
+
+    let location = &diagnostics[0].location;
+    assert_eq!(&source[location.start.0..location.end.0], "loop");
+    assert_eq!(
+        diagnostics[0].message,
+        "the type (Int -> ?0) of 'Tests.Main.loop' is ambiguous"
+    );
+}
+
+#[test]
+fn reports_ambiguous_public_signatures() {
+    let report = check(&format!(
+        "{VALUES}\
+         pub let ambiguous (_x: f a) : Int = zero\n\
+         let private (_x: f a) : Int = zero\n"
+    ));
+
+    assert_eq!(report.len(), 1);
+
+    let diagnostic = report.iter().next().unwrap();
+    assert_eq!(
+        diagnostic.code,
+        Some(vulpi_report::registry::AMBIGUOUS_TYPE)
+    );
+}
+
+#[test]
+fn shares_types_without_holes() {
+    let mut source = VALUES.to_string();
+
+    for i in 0..300 {
+        source.push_str(&format!(
+            "let pair{i} (x: Int) (y: String) : (Int, String) = (x, y)\n\
+             let unit{i} : () = ()\n"
+        ));
+    }
+
+    let (programs, report) = resolve(&source);
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(report.is_empty());
+
+    let (hits, misses) = (ctx.interner.hits, ctx.interner.misses);
+    println!("{hits} types were shared and {misses} were allocated");
+
+    assert!(hits > misses * 100);
+}
+
+#[test]
+fn checks_thousands_of_bindings() {
+    let mut source = VALUES.to_string();
+    source.push_str("let v0 = zero\n");
+
+    for i in 1..BINDINGS {
+        source.push_str(&format!("let v{i} = v{}\n", i - 1));
+    }
+
+    source.push_str("let block : Int = do\n    let x0 = v0\n");
+
+    for i in 1..BINDINGS {
+        source.push_str(&format!("    let x{i} = x{}\n", i - 1));
+    }
+
+    source.push_str(&format!("    x{}\n", BINDINGS - 1));
+    source.push_str(&format!("let wrong : String = v{}\n", BINDINGS - 1));
+
+    let start = Instant::now();
+    let report = check(&source);
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    assert_eq!(report.len(), 1);
+    assert_eq!(
+        report.iter().next().unwrap().message,
+        "type mismatch: expected String but found Int"
+    );
+}
+
+/// The sources of the duplicated names and of the definitions that they point to.
+fn duplicates(source: &str) -> Vec<(&str, &str)> {
+    let (programs, report) = resolve(source);
+    vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    report
+        .diagnostics(FileId(0))
+        .into_iter()
+        .filter(|diagnostic| diagnostic.code() == Some(registry::DUPLICATE_DEFINITION))
+        .map(|diagnostic| {
+            let location = diagnostic.location();
+            let first = &diagnostic.markers()[0].position;
+            (
+                &source[location.start.0..location.end.0],
+                &source[first.start.0..first.end.0],
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn reports_duplicated_types() {
+    let source = "type Int\n\
+                  type Bool =\n    \
+                      | True\n\
+                  type Bool =\n    \
+                      | False\n\
+                  let t : Bool = Bool.False\n";
+
+    assert_eq!(duplicates(source), vec![("Bool", "Bool")]);
+
+    let (programs, report) = resolve(source);
+    vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+    assert_eq!(report.len(), 1);
+}
+
+#[test]
+fn reports_duplicated_constructors() {
+    let source = "type Int\n\
+                  type Color =\n    \
+                      | Red\n    \
+                      | Red Int\n";
+
+    assert_eq!(duplicates(source), vec![("Red", "Red")]);
+}
+
+#[test]
+fn reports_duplicated_fields() {
+    let source = "type Int\n\
+                  type Point = {\n    \
+                      x : Int,\n    \
+                      x : Int\n\
+                  }\n";
+
+    assert_eq!(duplicates(source), vec![("x", "x")]);
+}
+
+#[test]
+fn reports_duplicated_externals() {
+    let source = "type Int\n\
+                  external add : Int -> Int -> Int = \"add\"\n\
+                  external add : Int -> Int = \"inc\"\n";
+
+    assert_eq!(duplicates(source), vec![("add", "add")]);
+}
+
+#[test]
+fn reports_lets_that_share_the_name_of_an_external() {
+    let source = "type Int\n\
+                  external zero : Int = \"0\"\n\
+                  let zero : Int = zero\n";
+
+    assert_eq!(duplicates(source), vec![("zero", "zero")]);
+}
+
+#[test]
+fn externals_can_be_called() {
+    let report = check(
+        "type Int\n\
+         external one : Int = \"1\"\n\
+         external add : Int -> Int -> Int = \"add\"\n\
+         let two : Int = add one one\n",
+    );
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn constructors_of_different_types_can_share_names() {
+    let source = "type Shape =\n    \
+                      | Circle\n\
+                  type Sign =\n    \
+                      | Circle\n";
+
+    let (programs, report) = resolve(source);
+    vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+    assert!(report.is_empty());
+}
+
+#[test]
+fn stores_the_constructors_and_fields_of_each_type() {
+    let (programs, report) = resolve(
+        "type Int\n\
+         type Maybe a =\n    \
+             | None\n    \
+             | Some a Int\n\
+         type Point = {\n    \
+             x : Int,\n    \
+             y : Int\n\
+         }\n",
+    );
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(report.is_empty());
+
+    let qualified = |name: &str| Qualified {
+        path: Symbol::intern("Tests.Main"),
+        name: Symbol::intern(name),
+    };
+
+    let names = |names: Option<&[Qualified]>| {
+        names.map(|names| names.iter().map(|x| x.name.get()).collect::<Vec<_>>())
+    };
+
+    let maybe = qualified("Maybe");
+    let point = qualified("Point");
+
+    assert_eq!(
+        names(ctx.modules.constructors_of(&maybe)),
+        Some(vec!["None".to_string(), "Some".to_string()])
+    );
+    assert_eq!(
+        names(ctx.modules.fields_of(&point)),
+        Some(vec!["x".to_string(), "y".to_string()])
+    );
+
+    assert_eq!(ctx.modules.constructors_of(&point), None);
+    assert_eq!(ctx.modules.fields_of(&maybe), None);
+    assert_eq!(ctx.modules.constructors_of(&qualified("Int")), None);
+
+    let some = ctx.modules.constructors_of(&maybe).unwrap()[1].clone();
+    let some = ctx.modules.constructor(&some);
+    let env = Env::default().add(Some(Symbol::intern("a")), Type::new(TypeKind::Type));
+
+    assert_eq!(some.arity, 2);
+    assert_eq!(some.type_name, maybe);
+    assert_eq!(
+        some.args
+            .iter()
+            .map(|arg| arg.show(&env).to_string())
+            .collect::<Vec<_>>(),
+        vec!["a~0", "Int"]
+    );
+}
+
+const RECORDS: &str = "type Int\n\
+                       type String\n\
+                       type Person = {\n    \
+                           name : String,\n    \
+                           age : Int\n\
+                       }\n\
+                       type Pet = {\n    \
+                           name : String,\n    \
+                           legs : Int\n\
+                       }\n";
+
+#[test]
+fn records_can_share_field_names() {
+    let report = check(&format!(
+        "{RECORDS}\
+         let person (p: Person) : String = p.name\n\
+         let pet (p: Pet) : String = p.name\n\
+         let age : Person -> Int = \\p => p.age\n"
+    ));
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn projections_of_unknown_types_use_the_field_name() {
+    let report = check(&format!(
+        "{RECORDS}\
+         let legs (p: Pet) : Int = (\\x => x.legs) p\n\
+         let wrong (p: Person) : Int = (\\x => x.legs) p\n"
+    ));
+
+    let messages = report.iter().map(|x| x.message).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec!["type mismatch: expected Pet but found Person"]
+    );
+}
+
+#[test]
+fn annotated_local_bindings_can_be_polymorphic() {
+    let report = check(
+        "type Int\n\
+         type String\n\
+         let both (x: Int) (y: String) : (Int, String) =\n    \
+             let id : forall a. a -> a = \\v => v in\n    \
+             (id x, id y)\n\
+         let block (x: Int) (y: String) : (Int, String) = do\n    \
+             let id : forall a. a -> a = \\v => v\n    \
+             (id x, id y)\n",
+    );
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn local_annotations_are_checked_against_the_value() {
+    let source = "type Int\n\
+                  type String\n\
+                  let wrong (x: Int) : Int =\n    \
+                      let _y : String = x in\n    \
+                      x\n";
+
+    let report = check(source);
+    let diagnostics = report.all_diagnostics();
+    let codes = diagnostics.iter().map(|d| d.code()).collect::<Vec<_>>();
+
+    assert_eq!(codes, vec![Some(registry::TYPE_MISMATCH)]);
+
+    let location = diagnostics[0].location();
+    assert_eq!(&source[location.start.0..location.end.0], "x");
+}
+
+#[test]
+fn record_fields_can_be_punned() {
+    let report = check(&format!(
+        "{RECORDS}\
+         let person (name: String) (age: Int) : Person = Person {{ name, age }}\n\
+         let mixed (name: String) (p: Person) : Person = Person {{ name, age = p.age }}\n\
+         let rename (name: String) (p: Person) : Person = p {{ name }}\n"
+    ));
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn punned_fields_are_checked_like_the_others() {
+    let source = format!(
+        "{RECORDS}\
+         let person (name: String) (legs: Int) : Person = Person {{ name, age = legs, legs }}\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.all_diagnostics();
+    let codes = diagnostics.iter().map(|d| d.code()).collect::<Vec<_>>();
+
+    assert_eq!(codes, vec![Some(registry::NOT_FOUND_FIELD)]);
+
+    let location = diagnostics[0].location();
+    assert_eq!(&source[location.start.0..location.end.0], "legs");
+}
+
+const SHAPES: &str = "type Int\n\
+                      type Shape =\n    \
+                          | Rect { width : Int, height : Int }\n    \
+                          | Square Int\n";
+
+#[test]
+fn labeled_constructors_take_named_or_positional_fields() {
+    let report = check(&format!(
+        "{SHAPES}\
+         let named (w: Int) (height: Int) : Shape = Shape.Rect {{ height, width = w }}\n\
+         let positional (w: Int) : Shape = Shape.Rect w w\n\
+         let width (s: Shape) : Int =\n    \
+             when s is\n        \
+                 Shape.Rect {{ width }} => width\n        \
+                 Shape.Square side => side\n"
+    ));
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn labeled_constructors_need_every_field() {
+    let source = format!(
+        "{SHAPES}\
+         let rect (w: Int) : Shape = Shape.Rect {{ width = w, depth = w }}\n\
+         let square (w: Int) : Shape = Shape.Square {{ side = w }}\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.all_diagnostics();
+    let codes = diagnostics.iter().map(|d| d.code()).collect::<Vec<_>>();
+
+    assert_eq!(
+        codes,
+        vec![
+            Some(registry::NOT_FOUND_FIELD),
+            Some(registry::MISSING_FIELD),
+            Some(registry::UNLABELED_CONSTRUCTOR)
+        ]
+    );
+}
+
+#[test]
+fn accessors_can_be_chained() {
+    let report = check(&format!(
+        "{RECORDS}\
+         type Owner = {{\n    \
+             pet : Pet,\n    \
+             pair : (Person, Int)\n\
+         }}\n\
+         let pet_name (o: Owner) : String = o.pet.name\n\
+         let person_age (o: Owner) : Int = o.pair.0.age\n\
+         let nested (t: ((Int, String), Int)) : String = t.0.1\n"
+    ));
+
+    assert!(report.is_empty());
+}
+
+#[test]
+fn tuple_accessors_must_be_in_bounds() {
+    let report = check(
+        "type Int\n\
+         let third (t: (Int, Int)) : Int = t.2\n",
+    );
+
+    let diagnostics = report.iter().collect::<Vec<_>>();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].code,
+        Some(registry::TUPLE_INDEX_OUT_OF_BOUNDS)
+    );
+    assert_eq!(
+        diagnostics[0].message,
+        "the tuple has 2 elements, so there is no element 2"
+    );
+}
+
+#[test]
+fn tuples_of_different_sizes_mention_the_sizes() {
+    let report = check(
+        "type Int\n\
+         let pair (x: (Int, Int)) : (Int, Int, Int) = x\n\
+         let inner (f: Int -> (Int, Int)) : Int -> (Int, Int, Int) = f\n",
+    );
+
+    let diagnostics = report.iter().collect::<Vec<_>>();
+    let codes = diagnostics.iter().map(|d| d.code).collect::<Vec<_>>();
+    let messages = diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect::<Vec<_>>();
+
+    assert_eq!(codes, vec![Some(registry::TUPLE_ARITY_MISMATCH); 2]);
+    assert_eq!(
+        messages,
+        vec![
+            "type mismatch: expected a tuple of 3 elements but found one with 2",
+            "type mismatch: expected a tuple of 3 elements but found one with 2 in the return type \
+             of the function type",
+        ]
+    );
+}
+
+#[test]
+fn non_tuples_where_tuples_are_expected() {
+    let report = check(
+        "type Int\n\
+         let pair (x: Int) : (Int, Int) = x\n",
+    );
+
+    let messages = report.iter().map(|x| x.message).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec!["type mismatch: expected a tuple of 2 elements but found Int"]
+    );
+}
+
+#[test]
+fn reports_ambiguous_fields() {
+    let report = check(&format!(
+        "{RECORDS}\
+         let name (p: Person) : String = (\\x => x.name) p\n"
+    ));
+
+    let diagnostics = report.iter().collect::<Vec<_>>();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, Some(registry::AMBIGUOUS_FIELD));
+    assert_eq!(
+        diagnostics[0].message,
+        "the field 'name' is ambiguous, it's defined in 'Tests.Main.Person' and 'Tests.Main.Pet'"
+    );
+}
+
+#[test]
+fn infers_the_kinds_of_type_binders() {
+    let (programs, report) = resolve(
+        "type Int\n\
+         type List a =\n    \
+             | Nil\n\
+         type Wrap f a =\n    \
+             | MkWrap (f a)\n\
+         type Fix f =\n    \
+             | In (f (Fix f))\n\
+         type Proxy a =\n    \
+             | Proxy\n\
+         let wrap (x: Wrap List Int) : Wrap List Int = x\n\
+         let apply (x: forall f. f Int -> f Int) : List Int -> List Int = x\n",
+    );
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(report.is_empty());
+
+    let kind = |name: &str| {
+        let name = Qualified {
+            path: Symbol::intern("Tests.Main"),
+            name: Symbol::intern(name),
+        };
+
+        ctx.modules
+            .typ(&name)
+            .kind
+            .show(&Env::default())
+            .to_string()
+    };
+
+    assert_eq!(kind("Wrap"), "((Type -> Type) -> (Type -> Type))");
+    assert_eq!(kind("Fix"), "((Type -> Type) -> Type)");
+    assert_eq!(kind("Proxy"), "(Type -> Type)");
+}
+
+#[test]
+fn constructors_can_refine_their_return_type() {
+    let (programs, report) = resolve(
+        "type Int\n\
+         type Bool\n\
+         type Expr a =\n    \
+             | Lit Int : Expr Int\n    \
+             | IsZero (Expr Int) : Expr Bool\n    \
+             | Wrong : Bool\n",
+    );
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    let diagnostics = report.iter().collect::<Vec<_>>();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].code,
+        Some(registry::INVALID_CONSTRUCTOR_RETURN_TYPE)
+    );
+    assert_eq!(
+        diagnostics[0].message,
+        "the constructor 'Wrong' must return the type 'Tests.Main.Expr'"
+    );
+
+    let expr = Qualified {
+        path: Symbol::intern("Tests.Main"),
+        name: Symbol::intern("Expr"),
+    };
+
+    let types = ctx
+        .modules
+        .constructors_of(&expr)
+        .unwrap()
+        .to_vec()
+        .iter()
+        .map(|name| {
+            ctx.modules
+                .constructor(name)
+                .typ
+                .show(&Env::default())
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        types,
+        vec![
+            "(forall (a: Type). (Int -> (Expr Int)))",
+            "(forall (a: Type). ((Expr Int) -> (Expr Bool)))",
+            "(forall (a: Type). (Expr a~0))"
+        ]
+    );
+}
+
+/// Checks a module that is its own prelude, so literals can be used, and returns the messages.
+fn check_with_literals(source: &str) -> Vec<String> {
+    let source = format!("type Int\ntype Char\ntype Float\n{source}");
+    let (programs, report) = resolve(&source);
+
+    let mut ctx =
+        vulpi_typer::Context::new(report.clone()).with_prelude(Symbol::intern("Tests.Main"));
+
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    report.iter().map(|diagnostic| diagnostic.message).collect()
+}
+
+#[test]
+fn matches_char_literals() {
+    let messages = check_with_literals(
+        "let vowel (c: Char) : Int =\n    \
+             when c is\n        \
+                 'a' => 1\n        \
+                 '\\n' => 2\n        \
+                 _ => 0\n",
+    );
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn literal_patterns_need_a_wildcard() {
+    let messages = check_with_literals(
+        "let vowel (c: Char) : Int =\n    \
+             when c is\n        \
+                 'a' => 1\n        \
+                 'b' => 2\n",
+    );
+
+    assert_eq!(messages, vec!["non-exhaustive patterns: _ \n"]);
+}
+
+#[test]
+fn rejects_float_patterns() {
+    let messages = check_with_literals(
+        "let half (f: Float) : Int =\n    \
+             when f is\n        \
+                 0.5 => 1\n        \
+                 _ => 0\n",
+    );
+
+    assert_eq!(messages, vec!["float literals cannot be used as patterns"]);
+}
+
+#[test]
+fn local_recursive_functions() {
+    let messages = check_with_literals(
+        "external mul : Int -> Int -> Int = \"mul\"\n\
+         external sub : Int -> Int -> Int = \"sub\"\n\
+         let factorial (n: Int) : Int =\n    \
+             let rec go = \\m =>\n            \
+                 when m is\n                \
+                     0 => 1\n                \
+                     _ => mul m (go (sub m 1))\n    \
+             in go n\n",
+    );
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn local_mutually_recursive_functions() {
+    let messages = check_with_literals(
+        "type Bool = | True | False\n\
+         external sub : Int -> Int -> Int = \"sub\"\n\
+         let isEven (n: Int) : Bool =\n    \
+             let rec even = \\m =>\n            \
+                 when m is\n                \
+                     0 => Bool.True\n                \
+                     _ => odd (sub m 1)\n        \
+             and odd = \\m =>\n            \
+                 when m is\n                \
+                     0 => Bool.False\n                \
+                     _ => even (sub m 1)\n    \
+             in even n\n",
+    );
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn local_recursive_functions_are_generalized() {
+    let messages = check_with_literals(
+        "type Bool = | True | False\n\
+         let pair (n: Int) : (Int, Bool) =\n    \
+             let rec id = \\x => x in (id n, id Bool.True)\n",
+    );
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn only_recursive_lets_refer_to_themselves() {
+    let messages = check_with_literals(
+        "type Pair = | Pair Int Int\n\
+         let loop (n: Int) : Int =\n    \
+             let go = \\m => go m in go n\n\
+         let pair (n: Int) : Int =\n    \
+             let rec (Pair.Pair a b) = Pair.Pair n a in b\n",
+    );
+
+    assert_eq!(
+        messages,
+        vec![
+            "cannot find 'go'",
+            "only variables can be bound by 'let rec'"
+        ]
+    );
+}
+
+const REFERENCES: &str = "pub type Int\n\
+                          pub type String\n\
+                          pub type Ref a\n\
+                          pub type List a =\n    \
+                              | Nil\n\
+                          external ref : forall a. a -> Ref a = \"ref\"\n";
+
+/// Checks a program with the given value restriction and returns the codes of the diagnostics.
+fn check_with_restriction(source: &str, restriction: ValueRestriction) -> Vec<Option<usize>> {
+    let (programs, report) = resolve(source);
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let env = Env {
+        restriction,
+        ..Env::default()
+    };
+
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, env.clone()));
+    Declare::define(&programs, (&mut ctx, env));
+
+    report.iter().map(|diagnostic| diagnostic.code).collect()
+}
+
+#[test]
+fn applications_are_not_generalized() {
+    let source = format!(
+        "{REFERENCES}\
+         let refs : (Ref (List Int), Ref (List String)) =\n    \
+             let r = ref List.Nil in (r, r)\n\
+         let ids : (Int -> Int, String -> String) =\n    \
+             let id = \\x => x in (id, id)\n"
+    );
+
+    assert_eq!(
+        check_with_restriction(&source, ValueRestriction::Strict),
+        vec![Some(registry::TYPE_MISMATCH)]
+    );
+
+    assert_eq!(
+        check_with_restriction(&source, ValueRestriction::Relaxed),
+        vec![]
+    );
+}
+
+#[test]
+fn ambiguous_definitions_that_are_not_values() {
+    let (programs, report) = resolve(&format!(
+        "{REFERENCES}\
+         pub let empty = ref List.Nil\n"
+    ));
+
+    vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    let diagnostics = report.all_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code(), Some(registry::AMBIGUOUS_TYPE));
+    assert_eq!(
+        diagnostics[0].hint().unwrap().to_string(),
+        "it's not generalized because its value is not a function or a constructor, add a type \
+         annotation to the definition"
+    );
+}
+
+#[test]
+fn annotations_can_be_more_specific_than_the_expression() {
+    let report = check(&format!(
+        "{VALUES}\
+         let id : forall a. a -> a = \\x => x\n\
+         let int : Int -> Int = (id : Int -> Int)\n\
+         let wrong : Int = (id : Int -> Int) name\n"
+    ));
+
+    let messages = report.iter().map(|x| x.message).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec!["type mismatch: expected Int but found String"]
+    );
+}
+
+#[test]
+fn annotations_can_introduce_a_forall() {
+    let source = format!(
+        "{VALUES}\
+         let pair : (Int, String) =\n    \
+             let id = ((\\x => x) : forall a. a -> a) in\n    \
+             (id zero, id name)\n\
+         let wrong = ((\\_x => zero) : forall b. b -> b)\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.all_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+
+    let annotation = &diagnostics[0].markers()[0].position;
+    assert_eq!(
+        &source[annotation.start.0..annotation.end.0],
+        "forall b. b -> b"
+    );
+}
+
+#[test]
+fn lambdas_take_their_parameter_type_from_the_context() {
+    let source = format!(
+        "{VALUES}\
+         external apply : (Int -> Int) -> Int = \"apply\"\n\
+         external length : String -> Int = \"length\"\n\
+         let wrong : Int = apply (\\x => length x)\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.iter().collect::<Vec<_>>();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "type mismatch: expected String but found Int"
+    );
+
+    let location = &diagnostics[0].location;
+    assert_eq!(&source[location.start.0..location.end.0], "x");
+}
+
+#[test]
+fn when_checks_every_arm_against_the_expected_type() {
+    let source = format!(
+        "{VALUES}\
+         let pick (x: Int) : (Int, String) =\n    \
+             when x is\n        \
+                 _ => (zero, name)\n        \
+                 _ => (zero, zero)\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.iter().collect::<Vec<_>>();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "type mismatch: expected String but found Int"
+    );
+
+    let location = &diagnostics[0].location;
+    assert_eq!(location.start.0, source.rfind("zero").unwrap());
+}
+
+#[test]
+fn type_arguments_instantiate_one_forall() {
+    let source = format!(
+        "{VALUES}\
+         external id : forall a. a -> a = \"id\"\n\
+         let implicit : Int = id zero\n\
+         let explicit : Int = id @Int zero\n\
+         let wrong = id @Int name\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.iter().collect::<Vec<_>>();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "type mismatch: expected Int but found String"
+    );
+
+    let location = &diagnostics[0].location;
+    assert_eq!(location.start.0, source.rfind("name").unwrap());
+}
+
+#[test]
+fn reports_type_arguments_without_a_forall() {
+    let report = check(&format!(
+        "{VALUES}\
+         external id : forall a. a -> a = \"id\"\n\
+         let monomorphic = zero @Int\n\
+         let extra = id @Int @String\n"
+    ));
+
+    let codes = report.iter().map(|d| d.code).collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        vec![
+            Some(registry::TOO_MANY_TYPE_ARGUMENTS),
+            Some(registry::TOO_MANY_TYPE_ARGUMENTS)
+        ]
+    );
+}
+
+#[test]
+fn deeply_nested_definitions_exceed_the_limit() {
+    // The parser needs a bigger stack than the one of the test threads for this nesting.
+    let thread = std::thread::Builder::new().stack_size(16 << 20).spawn(|| {
+        let nested = (0..100).fold("zero".to_string(), |expr, _| format!("id ({expr})"));
+
+        let source = format!(
+            "{VALUES}\
+             external id : forall a. a -> a = \"id\"\n\
+             let deep : Int = {nested}\n\
+             let wrong : Int = name\n"
+        );
+
+        let (programs, report) = resolve(&source);
+
+        let mut ctx = vulpi_typer::Context::new(report.clone()).with_limit(50);
+
+        let programs = Programs(programs);
+        Declare::declare(&programs, (&mut ctx, Env::default()));
+        Declare::define(&programs, (&mut ctx, Env::default()));
+
+        report.iter().map(|d| d.code).collect::<Vec<_>>()
+    });
+
+    assert_eq!(
+        thread.unwrap().join().unwrap(),
+        vec![
+            Some(registry::CHECKER_LIMIT_EXCEEDED),
+            Some(registry::TYPE_MISMATCH)
+        ]
+    );
+}
+
+#[test]
+fn reports_cycles_between_type_synonyms() {
+    let source = "type A = B\ntype B = A\n";
+
+    let (programs, report) = resolve(source);
+    vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    let diagnostics = report.diagnostics(FileId(0));
+    assert_eq!(diagnostics.len(), 1);
+
+    let diagnostic = &diagnostics[0];
+    let location = diagnostic.location();
+    let other = &diagnostic.markers()[0].position;
+
+    assert_eq!(diagnostic.code(), Some(registry::CYCLIC_TYPE_SYNONYM));
+    assert_eq!(&source[location.start.0..location.end.0], "A");
+    assert_eq!(&source[other.start.0..other.end.0], "B");
+    assert_eq!(
+        report.iter().next().unwrap().message,
+        "cycle between the type synonyms 'A' -> 'B'"
+    );
+}
+
+#[test]
+fn recursive_sums_are_not_cycles() {
+    let report = check("type List a =\n    | Nil\n    | Cons a (List a)\n");
+    assert!(report.is_empty());
+}
+
+#[test]
+fn traces_the_decisions_of_the_checker() {
+    let source = "external id : forall a. a -> a = \"id\"\n\
+                  let apply = \\x => id x\n";
+
+    let (programs, report) = resolve(source);
+
+    let mut ctx = vulpi_typer::Context::new(report.clone()).with_trace();
+
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(report.is_empty());
+
+    // The use of `id` is instantiated, `x` is checked against its parameter, which solves a hole,
+    // and the type of `apply` is generalized at the end.
+    let expected = [
+        TraceKind::Instantiate,
+        TraceKind::Subsume,
+        TraceKind::Solve,
+        TraceKind::Generalize,
+    ];
+
+    let trace = ctx.take_trace();
+    let mut kinds = trace.iter().map(|event| event.kind());
+
+    assert!(
+        expected
+            .iter()
+            .all(|kind| kinds.any(|found| found == *kind)),
+        "{}",
+        vulpi_typer::show_trace(&trace)
+    );
+
+    assert_eq!(trace.last().unwrap().kind(), TraceKind::Generalize);
+
+    // Nothing is recorded unless it's enabled.
+    let (programs, report) = resolve(source);
+    let mut ctx = vulpi_typer::Context::new(report);
+
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(ctx.take_trace().is_empty());
+}
+
+fn big_tuple_mismatch() -> String {
+    let types = |i: usize| {
+        (0..50)
+            .map(|n| if n == i { "Bool" } else { "Int" })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    format!(
+        "type Int\n\
+         type Bool\n\
+         external big : ({}) = \"big\"\n\
+         let small : ({}) = big\n",
+        types(39),
+        types(50)
+    )
+}
+
+#[test]
+fn elides_big_types_in_mismatches() {
+    let report = check(&big_tuple_mismatch());
+    let diagnostics = report.all_diagnostics();
+
+    let notes = diagnostics[0]
+        .notes()
+        .iter()
+        .map(|note| note.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        diagnostics[0].message().to_string(),
+        "type mismatch: expected Int but found Bool in the 40th element of the tuple"
+    );
+    assert_eq!(
+        diagnostics[0].hint().unwrap().to_string(),
+        "while comparing the expected type (Int, Int, Int, Int, Int, Int, Int, …, Int, …) with \
+         (Int, Int, Int, Int, Int, Int, Int, …, Bool, …)"
+    );
+    assert_eq!(
+        notes,
+        vec!["type truncated, use --verbose-types for the full type"]
+    );
+
+    // The whole types are kept for the tools that want them.
+    let details = diagnostics[0].details();
+    assert_eq!(details[2].0, "full expected");
+    assert_eq!(details[2].1, format!("({})", vec!["Int"; 50].join(", ")));
+
+    let (programs, report) = resolve(&big_tuple_mismatch());
+    let mut ctx = vulpi_typer::Context::new(report.clone()).with_verbose_types();
+
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(report.all_diagnostics()[0].notes().is_empty());
+}
+
+#[test]
+fn lambdas_with_many_parameters_are_curried() {
+    let (programs, report) = resolve("let k = \\x _y => x\n");
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    let def = ctx
+        .modules
+        .modules
+        .values()
+        .find_map(|module| module.variables.get(&Symbol::intern("k")))
+        .unwrap();
+
+    assert!(report.is_empty());
+    assert_eq!(
+        def.typ.show(&Env::default()).to_string(),
+        "(forall (a: Type) (b: Type). (a~1 -> (b~0 -> a~1)))"
+    );
+}
+
+#[test]
+fn errors_in_a_later_parameter_point_at_its_pattern() {
+    let source = "type Int\n\
+                  type Pair =\n    \
+                      | Pair Int Int\n\
+                  let f : Int -> Int -> Int = \\x (Pair.Pair _a _b) => x\n";
+
+    let report = check(source);
+    let diagnostics = report.all_diagnostics();
+
+    assert_eq!(diagnostics.len(), 1);
+
+    let location = diagnostics[0].location();
+
+    assert_eq!(diagnostics[0].code(), Some(registry::TYPE_MISMATCH));
+    assert_eq!(&source[location.start.0..location.end.0], "Pair.Pair _a _b");
+}
+
+const BOOL: &str = "type Bool =\n    \
+                        | True\n    \
+                        | False\n";
+
+#[test]
+fn matches_many_values_at_once() {
+    let source = format!(
+        "{BOOL}\
+         let both (x: Bool) (y: Bool) : Bool =\n    \
+             when x, y is\n        \
+                 Bool.True, Bool.True => Bool.True\n        \
+                 _, _ => Bool.False\n\
+         let partial (x: Bool) (y: Bool) : Bool =\n    \
+             when x, y is\n        \
+                 Bool.True, _ => Bool.True\n        \
+                 _, Bool.False => Bool.False\n"
+    );
+
+    let report = check(&source);
+    let codes = report.iter().map(|d| d.code).collect::<Vec<_>>();
+
+    assert_eq!(codes, vec![Some(registry::NON_EXHAUSTIVE)]);
+}
+
+#[test]
+fn arms_must_match_every_value() {
+    let source = format!(
+        "{BOOL}\
+         let first (x: Bool) (y: Bool) : Bool =\n    \
+             when x, y is\n        \
+                 Bool.True, _y => Bool.True\n        \
+                 Bool.False => Bool.False\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.all_diagnostics();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message().to_string(),
+        "wrong arity: expected 2 patterns, found 1"
+    );
+
+    let location = diagnostics[0].location();
+    let scrutinees = &diagnostics[0].markers()[0].position;
+
+    assert_eq!(&source[location.start.0..location.end.0], "Bool.False");
+    assert_eq!(&source[scrutinees.start.0..scrutinees.end.0], "x, y");
+}
+
+#[test]
+fn errors_in_later_clauses_point_inside_of_them() {
+    let source = format!(
+        "{BOOL}\
+         type Int\n\
+         let not : Bool -> Bool\n    \
+             | Bool.True => Bool.False\n    \
+             | Bool.False => 1\n\
+         let first : Bool -> Bool -> Bool\n    \
+             | Bool.True, _x => Bool.True\n    \
+             | Bool.False => Bool.False\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.all_diagnostics();
+    let second = source.find("| Bool.False => 1").unwrap();
+    let last = source.find("| Bool.False => Bool.False").unwrap();
+
+    let mut found = diagnostics
+        .iter()
+        .map(|d| (d.location().start.0, d.location().end.0))
+        .collect::<Vec<_>>();
+    found.sort();
+
+    assert_eq!(found.len(), 2);
+    assert!(second < found[0].0 && found[0].1 <= second + "| Bool.False => 1".len());
+    assert!(last < found[1].0 && found[1].1 <= source.len());
+}
+
+#[test]
+fn missing_cases_of_clauses_cover_all_of_them() {
+    let source = format!(
+        "{BOOL}\
+         let both : Bool -> Bool -> Bool\n    \
+             | Bool.True, Bool.True => Bool.True\n    \
+             | Bool.False, _y => Bool.False\n"
+    );
+
+    let report = check(&source);
+    let diagnostics = report.all_diagnostics();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code(), Some(registry::NON_EXHAUSTIVE));
+
+    let location = diagnostics[0].location();
+    assert_eq!(
+        &source[location.start.0..location.end.0],
+        "Bool.True, Bool.True => Bool.True\n    | Bool.False, _y"
+    );
+}
+
+#[test]
+fn elaborated_declarations_keep_the_order_of_the_source() {
+    let source = format!(
+        "{BOOL}\
+         let first : Bool = second\n\
+         let second : Bool = third\n\
+         let third : Bool = Bool.True\n\
+         let fourth : Bool = first\n"
+    );
+
+    let names = || {
+        let (programs, report) = resolve(&source);
+        let checked = vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+        assert!(report.is_empty());
+
+        checked.elaborated.unwrap()[0]
+            .lets
+            .keys()
+            .map(|name| name.name.get())
+            .collect::<Vec<_>>()
+    };
+
+    let expected = vec!["first", "second", "third", "fourth"];
+
+    assert_eq!(names(), expected);
+    assert_eq!(names(), expected);
+}
+
+#[test]
+fn malformed_clauses_keep_the_others_checked() {
+    let source = format!(
+        "{BOOL}\
+         type Int\n\
+         external zero : Int = \"0\"\n\
+         let not : Bool -> Bool\n    \
+             | Bool.True => )\n    \
+             | Bool.False => zero\n\
+         let broken : Bool = )\n\
+         let use_them : Bool = not broken\n"
+    );
+
+    let report = check(&source);
+    let mut codes = report.iter().map(|d| d.code).collect::<Vec<_>>();
+    codes.sort();
+
+    assert_eq!(
+        codes,
+        vec![
+            Some(registry::UNEXPECTED_TOKEN),
+            Some(registry::UNEXPECTED_TOKEN),
+            Some(registry::TYPE_MISMATCH),
+        ]
+    );
+}
+
+#[test]
+fn inline_modules_register_under_their_path() {
+    let source = "mod Foo where\n    \
+                      mod Bar where\n        \
+                          pub type Int\n        \
+                          pub external zero : Int = \"0\"\n\
+                  let x : Foo.Bar.Int = Foo.Bar.zero\n";
+
+    let (programs, report) = resolve(source);
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    let elaborated = Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(report.is_empty());
+
+    let module = ctx
+        .modules
+        .get(&Symbol::intern("Tests.Main.Foo.Bar"))
+        .unwrap();
+    assert!(module.types.contains_key(&Symbol::intern("Int")));
+    assert!(module.variables.contains_key(&Symbol::intern("zero")));
+
+    let bar = &elaborated[0].modules[&Symbol::intern("Foo")].modules[&Symbol::intern("Bar")];
+    let names = bar.types.keys().map(|x| x.to_string()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["Tests.Main.Foo.Bar.Int"]);
+}
+
+#[test]
+fn documentation_comments_reach_the_modules() {
+    let source = "--- A number.\n\
+                  type Int\n\
+                  pub type Bool =\n    \
+                      --- The truth.\n    \
+                      | True\n    \
+                      | False\n\
+                  type Point = {\n    \
+                      --- The horizontal position.\n    \
+                      x : Int\n\
+                  }\n\
+                  -- Not documentation.\n\n\
+                  --- Negates a boolean.\n\
+                  ---\n\
+                  --- It takes the cases in order.\n\
+                  pub let not : Bool -> Bool\n    \
+                      --- Not the documentation of the function.\n    \
+                      | Bool.True => Bool.False\n    \
+                      | Bool.False => Bool.True\n\
+                  -- Not documentation.\n\
+                  let id (x: Bool) : Bool = x\n";
+
+    let (programs, report) = resolve(source);
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(report.is_empty());
+
+    let docs = |name: &str| {
+        let name = Symbol::intern(name);
+        ctx.modules.modules.values().find_map(|module| {
+            let variable = module.variables.get(&name).map(|x| x.docs.clone());
+            let typ = module.types.get(&name).map(|x| x.docs.clone());
+            let cons = module.constructors.get(&name).map(|x| x.docs.clone());
+            let field = module
+                .fields
+                .get(&name)
+                .map(|_| module.field_docs.get(&name).cloned());
+
+            variable
+                .or(typ)
+                .or(cons)
+                .or(field)
+                .map(|x| x.map(|x| x.get()))
+        })
+    };
+
+    assert_eq!(
+        docs("not").unwrap().as_deref(),
+        Some("Negates a boolean.\n\nIt takes the cases in order.")
+    );
+    assert_eq!(docs("id").unwrap(), None);
+    assert_eq!(docs("Int").unwrap().as_deref(), Some("A number."));
+    assert_eq!(docs("Bool").unwrap(), None);
+    assert_eq!(docs("True").unwrap().as_deref(), Some("The truth."));
+    assert_eq!(docs("False").unwrap(), None);
+    assert_eq!(
+        docs("x").unwrap().as_deref(),
+        Some("The horizontal position.")
+    );
+}
+
+#[test]
+fn attributes_are_checked_and_reach_the_modules() {
+    let source = "pub type Int\n\
+                  external number : Int = \"0\"\n\
+                  --- The first number.\n\
+                  @deprecated(\"use two\")\n\
+                  pub let one : Int = number\n\
+                  @inline\n\
+                  pub let two : Int = number\n\
+                  @deprecated\n\
+                  pub type Old\n";
+
+    let (programs, report) = resolve(source);
+
+    let mut ctx = vulpi_typer::Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    let diagnostics = report
+        .iter()
+        .map(|x| (x.severity, x.message.to_string()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            (
+                Severity::Warning,
+                "unknown attribute '@inline', it is ignored".to_string()
+            ),
+            (
+                Severity::Error,
+                "the attribute '@deprecated' takes 1 argument but 0 were given".to_string()
+            ),
+        ]
+    );
+
+    let module = &ctx.modules.modules[&Symbol::intern("Tests.Main")];
+    let one = &module.variables[&Symbol::intern("one")];
+
+    assert_eq!(
+        one.docs.as_ref().map(|x| x.get()).as_deref(),
+        Some("The first number.")
+    );
+    assert_eq!(one.attributes.len(), 1);
+    assert_eq!(one.attributes[0].name.get(), "deprecated");
+    assert_eq!(one.attributes[0].args.len(), 1);
+    assert_eq!(module.types[&Symbol::intern("Old")].attributes.len(), 1);
+}
+
+#[test]
+fn deprecated_items_warn_where_they_are_used() {
+    let report = check(
+        "pub type Int\n\
+         external number : Int = \"0\"\n\
+         @deprecated(\"use number\")\n\
+         pub let old : Int = number\n\
+         @deprecated(\"use Int\")\n\
+         pub type Count\n\
+         pub external count : Count = \"0\"\n\
+         pub let new : Int = old\n",
+    );
+
+    let diagnostics = report
+        .all_diagnostics()
+        .iter()
+        .map(|x| x.code())
+        .collect::<Vec<_>>();
+
+    let messages = report
+        .iter()
+        .map(|x| (x.severity, x.message.to_string()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(diagnostics, vec![Some(registry::DEPRECATED); 2]);
+    assert_eq!(
+        messages,
+        vec![
+            (
+                Severity::Warning,
+                "'Count' is deprecated: use Int".to_string()
+            ),
+            (
+                Severity::Warning,
+                "'old' is deprecated: use number".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn deprecated_functions_warn_at_each_call_site() {
+    let source = "pub type Int\n\
+                  @deprecated(\"use add\")\n\
+                  pub let plus (x: Int) : Int = x\n\
+                  pub let twice (x: Int) : Int = plus (plus x)\n";
+
+    let report = check(source);
+    let diagnostics = report.all_diagnostics();
+
+    let calls = source
+        .match_indices("plus (")
+        .chain(source.match_indices("plus x"))
+        .map(|(start, _)| start)
+        .collect::<Vec<_>>();
+
+    let definition = source.find("plus (x").unwrap();
+
+    let starts = diagnostics
+        .iter()
+        .map(|x| x.location().start.0)
+        .collect::<Vec<_>>();
+
+    assert_eq!(starts, calls[1..]);
+
+    for diagnostic in &diagnostics {
+        assert_eq!(diagnostic.code(), Some(registry::DEPRECATED));
+        assert_eq!(diagnostic.markers()[0].position.start.0, definition);
+    }
+
+    let messages = report.iter().map(|x| x.message).collect::<Vec<_>>();
+    assert_eq!(messages, vec!["'plus' is deprecated: use add"; 2]);
+}
+
+#[test]
+fn deprecated_constructors_warn_in_patterns_but_not_inside_deprecated_items() {
+    let report = check(
+        "pub type Int\n\
+         pub type Shape =\n    \
+             | Square Int\n    \
+             @deprecated(\"use Square\")\n    \
+             | Rect Int Int\n\
+         pub let side (s: Shape) : Int =\n    \
+             when s is\n        \
+                 Shape.Square x => x\n        \
+                 Shape.Rect x _ => x\n\
+         @deprecated(\"use side\")\n\
+         pub let width (s: Shape) : Int =\n    \
+             when s is\n        \
+                 Shape.Rect x _ => x\n        \
+                 Shape.Square x => side (Shape.Rect x x)\n",
+    );
+
+    let messages = report
+        .iter()
+        .map(|x| (x.severity, x.message))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        messages,
+        vec![(
+            Severity::Warning,
+            "'Rect' is deprecated: use Square".to_string()
+        )]
+    );
+}
+
+#[test]
+fn checks_a_whole_program_with_the_given_options() {
+    let source = "pub type Int\n\
+                  pub let id (x: Int) : Int = x\n\
+                  let unused (x: Int) : Int = id x\n";
+
+    let (programs, report) = resolve(source);
+
+    let options = CheckOptions {
+        warn_dead_code: true,
+        ..Default::default()
+    };
+
+    let checked = vulpi_typer::check(programs, report.clone(), options);
+
+    let main = Symbol::intern("Tests.Main");
+    let id = Qualified {
+        path: main.clone(),
+        name: Symbol::intern("id"),
+    };
+
+    let typ = checked.types[&id].show(&Env::default()).to_string();
+    assert_eq!(typ, "(Int -> Int)");
+
+    let kind = checked.modules[&main].types[&Symbol::intern("Int")].show(&Env::default());
+    assert_eq!(kind.to_string(), "Type");
+    assert_eq!(checked.elaborated.map(|programs| programs.len()), Some(1));
+    assert_eq!(
+        checked.summary,
+        Summary {
+            errors: 0,
+            warnings: 1
+        }
+    );
+
+    let (programs, report) = resolve("pub type Int\npub let wrong : Int = \"text\"\n");
+    let checked = vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    assert!(checked.elaborated.is_none());
+    assert_eq!(checked.summary.errors, 1);
+
+    let (programs, report) = resolve("pub type Int\npub let hole (_x: Int) : Int = ?todo\n");
+    let checked = vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    assert!(checked.elaborated.is_none());
+    assert_eq!(checked.summary, Summary::default());
+}
+
+#[test]
+fn summaries_only_count_the_diagnostics_of_their_check() {
+    let report = check("pub type Int\npub type String\npub let wrong : Int = \"text\"\n");
+    assert_eq!(report.error_count(), 1);
+
+    let (programs, _) = resolve("pub type Int\npub let right (x: Int) : Int = x\n");
+    let checked = vulpi_typer::check(programs, report.clone(), CheckOptions::default());
+
+    assert_eq!(checked.summary, Summary::default());
+    assert!(checked.elaborated.is_some());
+}
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+use vulpi_report::registry::{format_code, REGISTRY};
+
+#[test]
+fn every_kind_of_error_has_its_own_code() {
+    let tables = [
+        vulpi_lexer::error::LEXER_ERROR_CODES,
+        vulpi_parser::error::PARSER_ERROR_CODES,
+        vulpi_resolver::RESOLVER_ERROR_CODES,
+        vulpi_typer::TYPE_ERROR_CODES,
+    ];
+
+    let mut owners = HashMap::new();
+
+    for (variant, code) in tables.into_iter().flatten() {
+        if let Some(other) = owners.insert(code, variant) {
+            panic!(
+                "{} is the code of {other} and {variant}",
+                format_code(*code)
+            );
+        }
+
+        assert!(
+            REGISTRY.iter().any(|(registered, _)| registered == code),
+            "{} of {variant} is not in the registry",
+            format_code(*code)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, registry, Report};
+use vulpi_typer::{
+    declare::{Declare, Programs},
+    Context, Env,
+};
+use vulpi_vfs::path::Path;
+
+const PRELUDE: &str = "pub type Int\n\
+                       pub type String\n\
+                       pub type Bool =\n    \
+                           | False\n    \
+                           | True\n\
+                       pub external concat : String -> String -> String = \"concat\"\n\
+                       pub external eqInt : Int -> Int -> Bool = \"eq\"\n\
+                       pub external showInt : Int -> String = \"id\"\n";
+
+/// Checks a module that is also its own prelude, and gives the types of the named functions.
+fn check(source: &str, names: &[&str]) -> (Vec<String>, Report) {
+    let report = hash_reporter();
+    let source = format!("{PRELUDE}{source}");
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), &source);
+    let (programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    let mut ctx = Context::new(report.clone()).with_prelude(Symbol::intern("Tests.Main"));
+    let programs = Programs(programs);
+
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    let module = &ctx.modules.modules[&Symbol::intern("Tests.Main")];
+
+    let types = names
+        .iter()
+        .map(|name| {
+            let def = &module.variables[&Symbol::intern(name)];
+            def.typ.show(&Env::default()).to_string()
+        })
+        .collect();
+
+    (types, report)
+}
+
+fn messages(report: &Report) -> Vec<String> {
+    report.iter().map(|x| x.message.to_string()).collect()
+}
+
+#[test]
+fn derives_recursive_functions_for_recursive_types() {
+    let (types, report) = check(
+        "type Expr =\n    \
+             | Num Int\n    \
+             | Add Expr Expr\n    \
+             | Neg { value : Expr }\n    \
+             derive (Eq, Show)\n\
+         let same (x: Expr) : Bool = eqExpr x (Expr.Neg { value = x })\n\
+         let text : String = showExpr (Expr.Add (Expr.Num 1) (Expr.Num 2))\n",
+        &["eqExpr", "showExpr"],
+    );
+
+    assert_eq!(messages(&report), Vec::<String>::new());
+    assert_eq!(types, vec!["(Expr -> (Expr -> Bool))", "(Expr -> String)",]);
+}
+
+#[test]
+fn type_parameters_take_the_function_for_them() {
+    let (types, report) = check(
+        "type List a =\n    \
+             | Nil\n    \
+             | Cons a (List a)\n    \
+             derive (Eq, Show)\n\
+         type Point = { x : Int, y : List Int } derive (Eq, Show)\n\
+         let text : String = showList showInt (List.Cons 1 List.Nil)\n",
+        &["eqList", "showPoint"],
+    );
+
+    assert_eq!(messages(&report), Vec::<String>::new());
+    assert_eq!(
+        types,
+        vec![
+            "(forall (a: Type). ((a~0 -> (a~0 -> Bool)) -> ((List a~0) -> ((List a~0) -> Bool))))",
+            "(Point -> String)",
+        ]
+    );
+}
+
+#[test]
+fn reports_the_fields_that_cannot_be_derived() {
+    let source = "type Float\n\
+                  type Money =\n    \
+                      | Cash Float\n    \
+                      | Card (Int -> Int)\n    \
+                      derive (Eq)\n\
+                  let same (x: Money) : Bool = eqMoney x x\n";
+
+    let (_, report) = check(source, &["eqMoney"]);
+    let diagnostics = report.all_diagnostics();
+
+    let codes = diagnostics.iter().map(|x| x.code()).collect::<Vec<_>>();
+    assert_eq!(codes, vec![Some(registry::CANNOT_DERIVE); 2]);
+
+    assert_eq!(
+        messages(&report),
+        vec![
+            "cannot derive Eq for 'Money', this field needs 'eqFloat', which is not defined",
+            "cannot derive Eq for 'Money', the type of this field is not supported",
+        ]
+    );
+}
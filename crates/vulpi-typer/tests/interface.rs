@@ -0,0 +1,176 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, registry, Report};
+use vulpi_syntax::r#abstract::Program;
+use vulpi_typer::{
+    declare::{Declare, Programs},
+    interface_hash, read_interface, write_interface, Context, Env, InterfaceError,
+};
+use vulpi_vfs::path::Path;
+
+const LIB: &str = "pub type Int\n\
+                   pub type String\n\
+                   pub type Option a = | pub Some a | pub None\n\
+                   pub type Pair a = { first : a, second : a }\n\
+                   pub external zero : Int = \"0\"\n\
+                   pub external name : String = \"name\"\n\
+                   pub let id (x: a) : a = x\n";
+
+fn resolve(main: &str, report: Report) -> (Program, Program) {
+    let parsed = [("Lib", LIB), ("Main", main)]
+        .iter()
+        .enumerate()
+        .map(|(i, (name, source))| {
+            let path = Path {
+                segments: vec![Symbol::intern("Tests"), Symbol::intern(name)],
+            };
+
+            (path, vulpi_parser::parse(report.clone(), FileId(i), source))
+        })
+        .collect();
+
+    let (mut programs, _) = vulpi_resolver::resolve_modules(parsed, report);
+    let main = programs.pop().unwrap();
+    (programs.pop().unwrap(), main)
+}
+
+fn check(ctx: &mut Context, program: Program) {
+    let programs = Programs(vec![program]);
+    Declare::declare(&programs, (&mut *ctx, Env::default()));
+    Declare::define(&programs, (ctx, Env::default()));
+}
+
+/// Checks the library alone, writes its interface and checks the main module against it in a new
+/// context.
+fn check_separately(main: &str) -> Vec<String> {
+    let report = hash_reporter();
+    let (lib, main) = resolve(main, report.clone());
+
+    let mut ctx = Context::new(report.clone());
+    check(&mut ctx, lib);
+    assert!(!report.has_errors());
+
+    let text = write_interface(&ctx, &Symbol::intern("Tests.Lib"), LIB).unwrap();
+
+    let mut ctx = Context::new(report.clone());
+    let module = read_interface(&mut ctx, &text, LIB).unwrap();
+    assert_eq!(module.get(), "Tests.Lib");
+
+    check(&mut ctx, main);
+
+    report.iter().map(|diagnostic| diagnostic.message).collect()
+}
+
+#[test]
+fn checks_modules_against_loaded_interfaces() {
+    let messages = check_separately(
+        "use Tests.Lib\n\
+         let number : Int = id zero\n\
+         let option : Option String = Option.Some (id name)\n\
+         let pair : Pair Int = Pair { first = zero, second = zero }\n\
+         let first (p: Pair Int) : Int = p.first\n",
+    );
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn reports_errors_against_loaded_interfaces() {
+    let report = hash_reporter();
+    let (lib, main) = resolve(
+        "use Tests.Lib\nlet number : Int = id name\n",
+        report.clone(),
+    );
+
+    let mut ctx = Context::new(report.clone());
+    check(&mut ctx, lib);
+
+    let text = write_interface(&ctx, &Symbol::intern("Tests.Lib"), LIB).unwrap();
+
+    let mut ctx = Context::new(report.clone());
+    read_interface(&mut ctx, &text, LIB).unwrap();
+    check(&mut ctx, main);
+
+    let codes = report.iter().map(|d| d.code).collect::<Vec<_>>();
+    assert_eq!(codes, vec![Some(registry::TYPE_MISMATCH)]);
+}
+
+#[test]
+fn interfaces_are_written_in_the_same_way() {
+    let report = hash_reporter();
+
+    let write = || {
+        let (lib, _) = resolve("", report.clone());
+        let mut ctx = Context::new(report.clone());
+        check(&mut ctx, lib);
+        write_interface(&ctx, &Symbol::intern("Tests.Lib"), LIB).unwrap()
+    };
+
+    assert_eq!(write(), write());
+}
+
+#[test]
+fn rejects_stale_and_foreign_interfaces() {
+    let report = hash_reporter();
+    let (lib, _) = resolve("", report.clone());
+
+    let mut ctx = Context::new(report.clone());
+    check(&mut ctx, lib);
+
+    let text = write_interface(&ctx, &Symbol::intern("Tests.Lib"), LIB).unwrap();
+
+    let mut ctx = Context::new(report);
+
+    let changed = format!("{LIB}pub type Bool\n");
+    assert_eq!(
+        read_interface(&mut ctx, &text, &changed),
+        Err(InterfaceError::Stale)
+    );
+
+    let future = text.replacen("\"2\"", "\"3\"", 1);
+    assert_eq!(
+        read_interface(&mut ctx, &future, LIB),
+        Err(InterfaceError::Version("3".to_string()))
+    );
+
+    assert_eq!(
+        read_interface(&mut ctx, "(\"type\"", LIB),
+        Err(InterfaceError::Malformed)
+    );
+}
+
+#[test]
+fn interface_hashes_only_change_with_what_other_modules_see() {
+    let hash = |main: &str| {
+        let report = hash_reporter();
+        let (lib, main) = resolve(main, report.clone());
+
+        let mut ctx = Context::new(report.clone());
+        check(&mut ctx, lib);
+        check(&mut ctx, main);
+        assert!(!report.has_errors());
+
+        interface_hash(&ctx, &Symbol::intern("Tests.Main"))
+    };
+
+    let original = hash(
+        "use Tests.Lib\n\
+         pub type Flag = | On | Off\n\
+         pub let number : Int = zero\n",
+    );
+
+    let moved = hash(
+        "use Tests.Lib\n\n\n\
+         pub type Flag = | On | Off\n\
+         pub let number : Int = id (id zero)\n",
+    );
+
+    let changed = hash(
+        "use Tests.Lib\n\
+         pub type Flag = | On | Off\n\
+         pub let number : String = name\n",
+    );
+
+    assert_eq!(original, moved);
+    assert_ne!(original, changed);
+}
@@ -0,0 +1,236 @@
+use vulpi_intern::Symbol;
+use vulpi_location::{FileId, Span, Spanned};
+use vulpi_report::{hash_reporter, registry, Report};
+use vulpi_syntax::{
+    elaborated::{
+        Case, DecisionTree, ExprKind, PatApplication, PatOr, Pattern, PatternArm, PatternKind,
+    },
+    r#abstract::Qualified,
+};
+use vulpi_typer::{
+    declare::{Declare, Programs},
+    Context, Env,
+};
+use vulpi_vfs::path::Path;
+
+const PRELUDE: &str = "pub type Int\n\
+                       pub type Bool =\n    \
+                           | True\n    \
+                           | False\n\
+                       pub type Option a =\n    \
+                           | None\n    \
+                           | Some a\n\
+                       pub type Pair a b =\n    \
+                           | Pair a b\n\
+                       external zero : Int = \"0\"\n";
+
+/// Checks a module that is also its own prelude, and gives the tree of the `when` in the body of
+/// `f`.
+fn compile(source: &str) -> (DecisionTree, Report) {
+    let report = hash_reporter();
+    let source = format!("{PRELUDE}{source}");
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), &source);
+    let (programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    let mut ctx = Context::new(report.clone()).with_prelude(Symbol::intern("Tests.Main"));
+    let programs = Programs(programs);
+
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    let elaborated = Declare::define(&programs, (&mut ctx, Env::default()));
+
+    let f = &elaborated[0].lets[&qualified("f")];
+
+    match &*f.body[0].expr.data {
+        ExprKind::When(when) => (when.tree.clone(), report),
+        _ => panic!("the body of `f` is not a `when`"),
+    }
+}
+
+fn qualified(name: &str) -> Qualified {
+    Qualified {
+        path: Symbol::intern("Tests.Main"),
+        name: Symbol::intern(name),
+    }
+}
+
+fn render(tree: &DecisionTree) -> String {
+    match tree {
+        DecisionTree::Switch(occurrence, cases, default) => {
+            let path = occurrence.path.iter().map(|i| format!(".{i}"));
+            let occurrence = format!("{}{}", occurrence.scrutinee, path.collect::<String>());
+
+            let cases = cases
+                .iter()
+                .map(|(case, tree)| match case {
+                    Case::Constructor(name) => format!("{} => {}", name.name.get(), render(tree)),
+                    Case::Literal(_) => format!("_ => {}", render(tree)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            match default {
+                Some(default) => {
+                    format!("switch {occurrence} {{ {cases} }} else {}", render(default))
+                }
+                None => format!("switch {occurrence} {{ {cases} }}"),
+            }
+        }
+        DecisionTree::Bind(name, occurrence, tree) => {
+            let path = occurrence.path.iter().map(|i| format!(".{i}"));
+            let occurrence = format!("{}{}", occurrence.scrutinee, path.collect::<String>());
+            format!("{} = {occurrence}; {}", name.get(), render(tree))
+        }
+        DecisionTree::Guard(arm, tree) => format!("#{arm} if else {}", render(tree)),
+        DecisionTree::Leaf(arm) => format!("#{arm}"),
+        DecisionTree::Fail => "fail".to_string(),
+    }
+}
+
+/// How many times each arm is reached in the tree.
+fn leaves(tree: &DecisionTree, found: &mut Vec<usize>) {
+    match tree {
+        DecisionTree::Switch(_, cases, default) => {
+            for (_, tree) in cases {
+                leaves(tree, found);
+            }
+
+            if let Some(default) = default {
+                leaves(default, found);
+            }
+        }
+        DecisionTree::Bind(_, _, tree) => leaves(tree, found),
+        DecisionTree::Guard(arm, tree) => {
+            found.push(*arm);
+            leaves(tree, found);
+        }
+        DecisionTree::Leaf(arm) => found.push(*arm),
+        DecisionTree::Fail => (),
+    }
+}
+
+#[test]
+fn nested_options_of_pairs_test_each_part_once() {
+    let (tree, report) = compile(
+        "let f (x: Option (Pair (Option Int) (Option Int))) : Int =\n    \
+             when x is\n        \
+                 Option.Some (Pair.Pair (Option.Some _a) (Option.Some _b)) => zero\n        \
+                 Option.Some (Pair.Pair Option.None _) => zero\n        \
+                 Option.Some (Pair.Pair _ Option.None) => zero\n        \
+                 Option.None => zero\n",
+    );
+
+    assert!(report.is_empty());
+
+    assert_eq!(
+        render(&tree),
+        "switch 0 { \
+             Some => switch 0.0.0 { \
+                 Some => switch 0.0.1 { Some => _a = 0.0.0.0; _b = 0.0.1.0; #0, None => #2 }, \
+                 None => #1 \
+             }, \
+             None => #3 \
+         }"
+    );
+
+    let mut found = Vec::new();
+    leaves(&tree, &mut found);
+    found.sort();
+
+    assert_eq!(found, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn guards_fall_through_to_the_next_arms() {
+    let (tree, report) = compile(
+        "let f (x: Option Bool) : Int =\n    \
+             when x is\n        \
+                 Option.Some b if b => zero\n        \
+                 Option.Some _ => zero\n        \
+                 Option.None => zero\n",
+    );
+
+    assert!(report.is_empty());
+    assert_eq!(
+        render(&tree),
+        "switch 0 { Some => b = 0.0; #0 if else #1, None => #2 }"
+    );
+}
+
+#[test]
+fn failures_are_the_missing_cases() {
+    let (tree, report) = compile(
+        "let f (x: Option Bool) : Int =\n    \
+             when x is\n        \
+                 Option.Some b if b => zero\n        \
+                 Option.None => zero\n",
+    );
+
+    let codes = report.iter().map(|x| x.code).collect::<Vec<_>>();
+
+    assert_eq!(codes, vec![Some(registry::NON_EXHAUSTIVE)]);
+    assert_eq!(
+        render(&tree),
+        "switch 0 { Some => b = 0.0; #0 if else fail, None => #1 }"
+    );
+
+    let (tree, _) = compile(
+        "let f (x: Int) (y: Bool) : Int =\n    \
+             when x, y is\n        \
+                 _, Bool.True => zero\n",
+    );
+
+    assert_eq!(render(&tree), "switch 1 { True => #0 } else fail");
+}
+
+#[test]
+fn or_patterns_share_the_arm() {
+    let report = hash_reporter();
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), PRELUDE);
+    let (programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    let mut ctx = Context::new(report.clone());
+    let programs = Programs(programs);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    let some = ctx.modules.constructors_of(&qualified("Option")).unwrap()[1].clone();
+
+    let some = |arg: Pattern| {
+        Box::new(PatternKind::Application(PatApplication {
+            func: some.clone(),
+            args: vec![arg],
+        }))
+    };
+
+    let x = || Box::new(PatternKind::Variable(Symbol::intern("x")));
+    let wildcard = || Box::new(PatternKind::Wildcard);
+
+    // (Some x, _) | (_, Some x)
+    let or = Box::new(PatternKind::Or(PatOr {
+        left: Box::new(PatternKind::Tuple(vec![some(x()), wildcard()])),
+        right: Box::new(PatternKind::Tuple(vec![wildcard(), some(x())])),
+    }));
+
+    let arm = |pattern: Pattern| PatternArm::<()> {
+        patterns: vec![pattern],
+        expr: Spanned::new(Box::new(ExprKind::Error), Span::default()),
+        guard: None,
+    };
+
+    let tree = ctx.compile_match(&[arm(or), arm(wildcard())]);
+
+    assert_eq!(
+        render(&tree),
+        "switch 0.0 { Some => x = 0.0.0; #0 } else switch 0.1 { Some => x = 0.1.0; #0 } else #1"
+    );
+}
@@ -0,0 +1,722 @@
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, registry, sink::Collector, Report};
+use vulpi_resolver::ResolveOptions;
+use vulpi_syntax::r#abstract::{Program, Qualified};
+use vulpi_typer::{
+    check_project, check_project_with_options, check_project_with_prelude, DefinitionSite,
+    EntryPoint, EntryResult, ItemKind, ProjectOptions, ProjectOutput,
+};
+use vulpi_vfs::path::Path;
+
+fn resolve(files: &[(&str, &str)], report: Report) -> Vec<(FileId, Program)> {
+    resolve_with_options(files, report, ResolveOptions::default())
+}
+
+fn resolve_with_options(
+    files: &[(&str, &str)],
+    report: Report,
+    options: ResolveOptions,
+) -> Vec<(FileId, Program)> {
+    let parsed = files
+        .iter()
+        .enumerate()
+        .map(|(i, (name, source))| {
+            let path = Path {
+                segments: vec![Symbol::intern("Tests"), Symbol::intern(name)],
+            };
+
+            (path, vulpi_parser::parse(report.clone(), FileId(i), source))
+        })
+        .collect();
+
+    let (programs, _) = vulpi_resolver::resolve_modules_with_options(parsed, report, options);
+
+    programs
+        .into_iter()
+        .enumerate()
+        .map(|(i, program)| (FileId(i), program))
+        .collect()
+}
+
+fn check(files: &[(&str, &str)]) -> (ProjectOutput, Vec<String>) {
+    let report = hash_reporter();
+    let output = check_project(resolve(files, report.clone()), report.clone());
+    (output, messages(&report))
+}
+
+fn messages(report: &Report) -> Vec<String> {
+    report.iter().map(|diagnostic| diagnostic.message).collect()
+}
+
+#[test]
+fn checks_files_after_the_files_that_they_use() {
+    let (output, messages) = check(&[
+        (
+            "Main",
+            "use Tests.Lib\n\
+             let number : Int = id zero\n\
+             let text : String = id name\n",
+        ),
+        (
+            "Lib",
+            "pub type Int\n\
+             pub type String\n\
+             pub external zero : Int = \"0\"\n\
+             pub external name : String = \"name\"\n\
+             pub let id = \\x => x\n",
+        ),
+    ]);
+
+    assert!(messages.is_empty(), "{messages:?}");
+    assert_eq!(output.programs.len(), 2);
+    assert_eq!(output.programs[0].0, FileId(0));
+    assert!(output.programs[0]
+        .1
+        .lets
+        .contains_key(&vulpi_syntax::r#abstract::Qualified {
+            path: Symbol::intern("Tests.Main"),
+            name: Symbol::intern("number"),
+        }));
+}
+
+#[test]
+fn reports_cycles_between_modules() {
+    let (output, _) = check(&[
+        ("A", "use Tests.B\npub type Int\n"),
+        ("B", "use Tests.A\npub type String\n"),
+    ]);
+
+    let cycles = output
+        .report
+        .iter()
+        .filter(|diagnostic| diagnostic.code == Some(registry::CYCLE_BETWEEN_MODULES))
+        .map(|diagnostic| diagnostic.message)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        cycles,
+        vec!["cycle between the modules 'Tests.A -> Tests.B'"]
+    );
+}
+
+#[test]
+fn unknown_modules_are_reported_at_the_use() {
+    let source = "use Tests.Missing\ntype Int\n";
+    let (output, messages) = check(&[("Main", source)]);
+
+    assert_eq!(messages, vec!["the path 'Tests.Missing' cannot be found"]);
+
+    let location = output.report.iter().next().unwrap().location;
+    assert_eq!(&source[location.start.0..location.end.0], "Tests.Missing");
+}
+
+#[test]
+fn references_to_modules_that_are_not_checked() {
+    let source = "use Tests.Lib\n\
+                  let a (_x: Int) : () = ()\n\
+                  let b : () = id ()\n";
+
+    // The resolver knows both of the files, but only one of them is given to the type checker.
+    let report = hash_reporter();
+    let mut files = resolve(
+        &[
+            ("Main", source),
+            ("Lib", "pub type Int\npub let id = \\x => x\n"),
+        ],
+        report.clone(),
+    );
+    files.truncate(1);
+
+    check_project(files, report.clone());
+
+    assert_eq!(
+        messages(&report),
+        vec![
+            "cannot find the module 'Tests.Lib'",
+            "cannot find the module 'Tests.Lib'"
+        ]
+    );
+
+    let spans = report
+        .iter()
+        .map(|diagnostic| &source[diagnostic.location.start.0..diagnostic.location.end.0])
+        .collect::<Vec<_>>();
+
+    assert_eq!(spans, vec!["Int", "id"]);
+}
+
+const OPTION: &str = "pub type Int\n\
+                      pub type Option a =\n    \
+                          | pub Some a\n    \
+                          | pub None\n";
+
+#[test]
+fn aliased_types_in_signatures() {
+    let (_, messages) = check(&[
+        (
+            "Main",
+            "use Tests.Lib as L\nlet wrap (x: L.Int) : L.Option L.Int = L.Option.Some x\n",
+        ),
+        ("Lib", OPTION),
+    ]);
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn aliased_constructors_in_patterns() {
+    let (_, messages) = check(&[
+        (
+            "Main",
+            "use Tests.Lib as L\n\
+             let unwrap (x: L.Option L.Int) (default: L.Int) : L.Int =\n    \
+                 when x is\n        \
+                     L.Option.Some y => y\n        \
+                     L.Option.None => default\n",
+        ),
+        ("Lib", OPTION),
+    ]);
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn constructors_in_patterns_resolve_to_their_declaration() {
+    let main = "use Tests.Lib\n\
+                let unwrap (x: Option Int) (default: Int) : Int =\n    \
+                    when x is\n        \
+                        Option.Some y => y\n        \
+                        Option.None => default\n";
+
+    let (output, messages) = check(&[("Main", main), ("Lib", OPTION)]);
+    assert!(messages.is_empty(), "{messages:?}");
+
+    let resolutions = &output.resolutions;
+
+    let at = |needle: &str| main.find(needle).unwrap();
+    let text = |site: &DefinitionSite, source: &str| {
+        source[site.span.start.0..site.span.end.0].to_string()
+    };
+
+    let site = resolutions
+        .definition_at(FileId(0), at("Some"))
+        .unwrap()
+        .clone();
+
+    assert_eq!(site.kind, ItemKind::Constructor);
+    assert_eq!(site.file, FileId(1));
+    assert_eq!(text(&site, OPTION), "Some");
+    assert_eq!(resolutions.references_of(&site).len(), 1);
+
+    // The `y` after the arrow refers to the `y` bound by the pattern.
+    let site = resolutions
+        .definition_at(FileId(0), at("=> y") + 3)
+        .unwrap();
+
+    assert_eq!(site.kind, ItemKind::Variable);
+    assert_eq!(site.span.start.0, at("y =>"));
+}
+
+#[test]
+fn completions_inside_of_a_lambda() {
+    let main = "use Tests.Lib as L\n\
+                let number : L.Int = (\\x => x) L.zero\n";
+
+    let lib = format!("{OPTION}pub external zero : Int = \"0\"\nlet secret : Int = zero\n");
+
+    let (output, messages) = check(&[("Main", main), ("Lib", &lib)]);
+    assert!(messages.is_empty(), "{messages:?}");
+
+    let completions = output
+        .completions
+        .completions_at(FileId(0), main.find("=> x").unwrap() + 3);
+
+    let names = completions
+        .iter()
+        .map(|completion| (completion.name.as_str(), completion.kind))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        names,
+        vec![
+            ("L.Int", ItemKind::Type),
+            ("L.Option", ItemKind::Type),
+            ("L.Option.None", ItemKind::Constructor),
+            ("L.Option.Some", ItemKind::Constructor),
+            ("L.zero", ItemKind::External),
+            ("number", ItemKind::Let),
+            ("x", ItemKind::Variable),
+        ]
+    );
+
+    // The type of the parameter is only known after the argument is checked.
+    assert_eq!(completions[6].typ, "Int");
+}
+
+const DEEP: &str = "pub type Thing =\n    \
+                        | pub Thing\n\
+                    pub let make (x: Thing) : Thing = x\n";
+
+#[test]
+fn reexports_through_public_uses() {
+    let (_, messages) = check(&[
+        (
+            "Main",
+            "use Tests.Facade\n\
+             let a (x: Thing) : Thing = make x\n\
+             let b : Tests.Facade.Thing = Tests.Facade.make Thing.Thing\n\
+             let c (x: Thing) : Thing =\n    \
+                 when x is\n        \
+                     Thing.Thing => x\n",
+        ),
+        ("Facade", "pub use Tests.Internal\n"),
+        ("Internal", "pub use Tests.Deep\n"),
+        ("Deep", DEEP),
+    ]);
+
+    assert!(messages.is_empty(), "{messages:?}");
+}
+
+#[test]
+fn private_uses_are_not_reexported() {
+    let (_, messages) = check(&[
+        (
+            "Main",
+            "use Tests.Facade\nlet a (x: Thing) : Thing = x\nlet c : () = Thing.Thing\n",
+        ),
+        (
+            "Facade",
+            "use Tests.Deep\npub let b (x: Thing) : Thing = x\n",
+        ),
+        ("Deep", DEEP),
+    ]);
+
+    assert_eq!(
+        messages,
+        vec![
+            "nothing from the module 'Tests.Facade' is used",
+            "cannot find 'Thing'",
+            "cannot find 'Thing'",
+            "the path 'Thing' cannot be found"
+        ]
+    );
+}
+
+#[test]
+fn cycles_of_reexports_terminate() {
+    let (output, _) = check(&[
+        ("Main", "use Tests.A\nlet a : Thing = thing\n"),
+        ("A", "pub use Tests.B\n"),
+        ("B", "pub use Tests.A\n"),
+    ]);
+
+    let codes = output
+        .report
+        .iter()
+        .map(|diagnostic| diagnostic.code)
+        .collect::<Vec<_>>();
+
+    assert!(codes.contains(&Some(registry::CYCLE_BETWEEN_MODULES)));
+}
+
+fn tests_path(name: &str) -> Path {
+    Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern(name)],
+    }
+}
+
+fn core_prelude() -> ResolveOptions {
+    ResolveOptions {
+        prelude: Some(tests_path("Core")),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn builtins_come_from_a_custom_prelude() {
+    let report = hash_reporter();
+
+    let files = resolve_with_options(
+        &[
+            (
+                "Core",
+                "pub type Bool =\n\
+                 \x20 | pub True\n\
+                 \x20 | pub False\n\
+                 pub type Unit\n",
+            ),
+            (
+                "Main",
+                "let choose (x: Bool) : Bool =\n\
+                 \x20 when x is\n\
+                 \x20   y if y => Bool.False\n\
+                 \x20   _ => Bool.True\n\
+                 let count : Unit = 1\n",
+            ),
+        ],
+        report.clone(),
+        core_prelude(),
+    );
+
+    let output = check_project_with_prelude(files, report, Symbol::intern("Tests.Core"));
+
+    let diagnostics = output.report.iter().collect::<Vec<_>>();
+
+    assert_eq!(diagnostics.len(), 1, "{:?}", messages(&output.report));
+    assert_eq!(diagnostics[0].code, Some(registry::MISSING_BUILTIN));
+    assert_eq!(
+        diagnostics[0].message,
+        "the prelude 'Tests.Core' does not define the builtin type 'Int'"
+    );
+}
+
+#[test]
+fn modules_can_opt_out_of_the_prelude() {
+    let report = hash_reporter();
+
+    let files = resolve_with_options(
+        &[
+            (
+                "Core",
+                "pub type Bool =\n\
+                 \x20 | pub True\n\
+                 \x20 | pub False\n",
+            ),
+            ("Main", "let choose (x: Bool) : Bool = x\n"),
+        ],
+        report.clone(),
+        ResolveOptions {
+            without_prelude: vec![tests_path("Main")],
+            ..core_prelude()
+        },
+    );
+
+    let output = check_project_with_prelude(files, report, Symbol::intern("Tests.Core"));
+
+    let messages = messages(&output.report);
+
+    assert!(!messages.is_empty());
+    assert!(
+        messages
+            .iter()
+            .all(|message| message.contains("cannot find 'Bool'")),
+        "{messages:?}"
+    );
+}
+
+#[test]
+fn derives_in_files_that_are_defined_with_others() {
+    let report = hash_reporter();
+
+    let files = resolve(
+        &[
+            (
+                "Main",
+                "use Tests.Lib\n\
+                 let same (x: Box) : Bool = eqBox x x\n",
+            ),
+            (
+                "Lib",
+                "pub type Int\n\
+                 pub type Bool =\n\
+                 \x20 | pub False\n\
+                 \x20 | pub True\n\
+                 pub external eqInt : Int -> Int -> Bool = \"eq\"\n\
+                 pub type Box = | pub Box Int derive (Eq)\n",
+            ),
+        ],
+        report.clone(),
+    );
+
+    let output = check_project_with_prelude(files, report, Symbol::intern("Tests.Lib"));
+
+    assert!(output.report.is_empty(), "{:?}", messages(&output.report));
+
+    let lib = &output.programs[1].1;
+    assert!(lib.lets.keys().any(|name| name.name.get() == "eqBox"));
+}
+
+#[test]
+fn unused_variables_are_tracked_per_file() {
+    let (output, messages) = check(&[
+        ("Lib", "pub type Int\npub external zero : Int = \"0\"\n"),
+        ("Used", "use Tests.Lib\nlet f (x: Int) : Int = x\n"),
+        ("Wasted", "use Tests.Lib\nlet f (x: Int) : Int = zero\n"),
+    ]);
+
+    assert_eq!(messages, vec!["the variable 'x' is never used"]);
+    assert_eq!(output.report.iter().next().unwrap().file, FileId(2));
+}
+
+#[test]
+fn checks_independent_projects_on_different_threads() {
+    let threads = ["First", "Second"].map(|name| {
+        std::thread::spawn(move || {
+            let source = "type Int\n\
+                          type String\n\
+                          external zero : Int = \"0\"\n\
+                          let number : Int = zero\n\
+                          let text : String = zero\n";
+
+            let (output, messages) = check(&[(name, source)]);
+
+            let names = output.programs[0]
+                .1
+                .lets
+                .keys()
+                .map(|name| name.name.clone())
+                .collect::<Vec<_>>();
+
+            (names, messages)
+        })
+    });
+
+    let [first, second] = threads.map(|thread| thread.join().unwrap());
+
+    assert_eq!(
+        first.1,
+        vec!["type mismatch: expected String but found Int"]
+    );
+    assert_eq!(first.1, second.1);
+
+    for (names, _) in [first, second] {
+        assert!(names.contains(&Symbol::intern("number")));
+        assert!(names.contains(&Symbol::intern("text")));
+    }
+}
+
+/// Checks the files with the given number of threads, and describes the diagnostics in the order
+/// that they were reported, with the module instead of the file.
+fn reported(files: &[(&'static str, &str)], threads: usize) -> Vec<String> {
+    let collector = Collector::new();
+    let report = hash_reporter().with_sink(collector.clone());
+
+    let options = ProjectOptions {
+        threads,
+        ..Default::default()
+    };
+
+    check_project_with_options(resolve(files, report.clone()), report, options);
+
+    collector
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| {
+            let location = diagnostic.location();
+
+            let markers = diagnostic
+                .markers()
+                .iter()
+                .map(|marker| {
+                    let subtitle = marker.subtitle.as_ref().map(|x| x.to_string());
+                    let position = &marker.position;
+                    format!("{}..{} {subtitle:?}", position.start.0, position.end.0)
+                })
+                .collect::<Vec<_>>();
+
+            format!(
+                "{} {}..{} {} {markers:?}",
+                files[location.file.0].0,
+                location.start.0,
+                location.end.0,
+                diagnostic.message(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn diagnostics_do_not_depend_on_the_order_of_the_files() {
+    let files = [
+        ("D", "use Tests.Lib\nlet d : Int = ?d\n"),
+        ("B", "use Tests.Lib\nlet b : Int = ?b\n"),
+        ("Lib", "pub type Int\nlet lib : Int = ?lib\n"),
+        ("A", "use Tests.Lib\nlet a : Int = ?a\n"),
+        ("C", "use Tests.Lib\nlet c : Int = ?c\n"),
+    ];
+
+    let mut reversed = files;
+    reversed.reverse();
+
+    let diagnostics = reported(&files, 1);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            "Lib 29..33 found hole ?lib of type Int []",
+            "A 28..30 found hole ?a of type Int []",
+            "B 28..30 found hole ?b of type Int []",
+            "C 28..30 found hole ?c of type Int []",
+            "D 28..30 found hole ?d of type Int []",
+        ]
+    );
+
+    assert_eq!(diagnostics, reported(&reversed, 1));
+}
+
+#[test]
+fn ambiguous_definitions_are_not_fixed_by_their_first_user() {
+    let files = [
+        (
+            "Lib",
+            "pub type Int\n\
+             pub type String\n\
+             pub external mk : forall a. () -> a = \"mk\"\n\
+             pub let r = mk ()\n",
+        ),
+        ("A", "use Tests.Lib\nlet a : Int = r\n"),
+        ("B", "use Tests.Lib\nlet b : String = r\n"),
+    ];
+
+    let sequential = reported(&files, 1);
+
+    assert_eq!(
+        sequential,
+        vec!["Lib 80..81 the type ?0 of 'Tests.Lib.r' is ambiguous []"]
+    );
+
+    assert_eq!(reported(&files, 4), sequential);
+}
+
+#[test]
+fn independent_modules_are_defined_in_parallel_with_the_same_diagnostics() {
+    let files = [
+        ("A", "type Int\nlet a = \\f => (f : Int) f\n"),
+        (
+            "B",
+            "type Int\n\
+             type String\n\
+             external zero : Int = \"0\"\n\
+             let b : String = zero\n",
+        ),
+        (
+            "C",
+            "type Int\nlet c (x: Int) = ?c x\nlet other = \\y => y y\n",
+        ),
+        (
+            "D",
+            "type Int\n\
+             type Bool =\n    \
+                 | True\n\
+             let d (b: Bool) : Int = when b is\n    \
+                 Bool.True => b\n",
+        ),
+        (
+            "Lib",
+            "pub type Int\n\
+             pub type String\n\
+             pub external mk : forall a. () -> a = \"mk\"\n\
+             pub let r = mk ()\n",
+        ),
+        ("E", "use Tests.Lib\nlet e : Int = r\n"),
+        ("F", "use Tests.Lib\nlet f : String = r\n"),
+    ];
+
+    let sequential = reported(&files, 1);
+
+    assert_eq!(
+        sequential,
+        vec![
+            "A 33..34 not a function: Int []",
+            "B 64..68 type mismatch: expected String but found Int \
+             [\"55..61 Some(\\\"expected because of this annotation\\\")\"]",
+            "C 13..14 the type (Int -> ?1) of 'Tests.C.c' is ambiguous []",
+            "C 51..52 infinite type []",
+            "C 26..28 found hole ?c of type (Int -> ?1) []",
+            "D 83..84 type mismatch: expected Int but found Bool []",
+            "Lib 80..81 the type ?3 of 'Tests.Lib.r' is ambiguous []",
+        ]
+    );
+
+    for _ in 0..8 {
+        assert_eq!(reported(&files, 4), sequential);
+    }
+}
+
+fn check_entry(source: &str, entry: EntryPoint) -> Vec<String> {
+    let report = hash_reporter();
+
+    let options = ProjectOptions {
+        entry: Some(entry),
+        ..Default::default()
+    };
+
+    let files = resolve(&[("Main", source)], report.clone());
+    check_project_with_options(files, report.clone(), options);
+
+    messages(&report)
+}
+
+fn main_entry() -> EntryPoint {
+    EntryPoint::new(Symbol::intern("Tests.Main"))
+}
+
+#[test]
+fn accepts_entry_points_that_take_unit() {
+    let constant = check_entry("pub let main : () = ()\n", main_entry());
+    let function = check_entry("pub let main (x: ()) : () = x\n", main_entry());
+    let annotated = check_entry("pub let main : () -> () = \\x => x\n", main_entry());
+
+    assert!(constant.is_empty(), "{constant:?}");
+    assert!(function.is_empty(), "{function:?}");
+    assert!(annotated.is_empty(), "{annotated:?}");
+}
+
+#[test]
+fn reports_missing_entry_points_at_the_start_of_the_module() {
+    let report = hash_reporter();
+
+    let options = ProjectOptions {
+        entry: Some(main_entry()),
+        ..Default::default()
+    };
+
+    let files = resolve(&[("Main", "pub let start : () = ()\n")], report.clone());
+    check_project_with_options(files, report.clone(), options);
+
+    let diagnostics = report.diagnostics(FileId(0));
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code(), Some(registry::MISSING_ENTRY_POINT));
+    assert_eq!(diagnostics[0].location().start.0, 0);
+}
+
+#[test]
+fn reports_entry_points_with_wrong_arity() {
+    let messages = check_entry("pub let main (x: ()) (_y: ()) : () = x\n", main_entry());
+
+    assert_eq!(
+        messages,
+        vec!["'main' has type (() -> (() -> ())), expected () or () -> ()"]
+    );
+}
+
+#[test]
+fn reports_private_entry_points() {
+    let messages = check_entry("let main : () = ()\n", main_entry());
+    assert_eq!(messages, vec!["'main' must be public"]);
+}
+
+#[test]
+fn entry_points_follow_the_configured_result() {
+    let source = "pub type Int\n\
+                  pub external zero : Int = \"0\"\n\
+                  pub let main : Int = zero\n";
+
+    let int = Qualified {
+        path: Symbol::intern("Tests.Main"),
+        name: Symbol::intern("Int"),
+    };
+
+    let default = check_entry(source, main_entry());
+    let named = check_entry(source, main_entry().with_result(EntryResult::Named(int)));
+    let any = check_entry(source, main_entry().with_result(EntryResult::Any));
+
+    assert_eq!(
+        default,
+        vec!["'main' has type Int, expected () or () -> ()"]
+    );
+    assert!(named.is_empty(), "{named:?}");
+    assert!(any.is_empty(), "{any:?}");
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "serde")]
+
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+use vulpi_report::{hash_reporter, Report};
+use vulpi_syntax::r#abstract::Program;
+use vulpi_typer::{
+    declare::{Declare, Programs},
+    serialize_module_types, Context, Env, ModuleTypes,
+};
+use vulpi_vfs::path::Path;
+
+const SOURCE: &str = "pub type Int\n\
+                      pub type Option a = | pub Some a | None\n\
+                      pub type Pair a = { pub first : a, second : a }\n\
+                      pub external zero : Int = \"0\"\n\
+                      let hidden : Int = zero\n\
+                      pub let id (x: a) : a = x\n";
+
+fn resolve(report: Report) -> Program {
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vec![(path, vulpi_parser::parse(report.clone(), FileId(0), SOURCE))];
+
+    let (mut programs, _) = vulpi_resolver::resolve_modules(parsed, report);
+    programs.pop().unwrap()
+}
+
+fn check(report: Report) -> ModuleTypes {
+    let program = resolve(report.clone());
+    let mut ctx = Context::new(report.clone());
+
+    let programs = Programs(vec![program]);
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(!report.has_errors());
+
+    serialize_module_types(&ctx, &programs.0[0])
+}
+
+#[test]
+fn program_matches_golden() {
+    let program = resolve(hash_reporter());
+    let json = serde_json::to_string_pretty(&program).unwrap();
+
+    assert_eq!(json, include_str!("golden/program.json").trim_end());
+}
+
+#[test]
+fn module_types_match_golden() {
+    let types = check(hash_reporter());
+    let json = serde_json::to_string_pretty(&types).unwrap();
+
+    assert_eq!(json, include_str!("golden/types.json").trim_end());
+}
+
+#[test]
+fn module_types_round_trip() {
+    let types = check(hash_reporter());
+    let json = serde_json::to_string(&types).unwrap();
+
+    assert_eq!(serde_json::from_str::<ModuleTypes>(&json).unwrap(), types);
+}
@@ -0,0 +1,54 @@
+use vulpi_intern::Symbol;
+use vulpi_report::hash_reporter;
+use vulpi_syntax::r#abstract::Qualified;
+use vulpi_typer::{r#virtual::Virtual, Context, Env, Hole, Type, TypeKind};
+
+const CHAIN: usize = 5000;
+
+fn hole(typ: &Type<Virtual>) -> Hole {
+    let TypeKind::Hole(hole) = typ.as_ref() else {
+        panic!("not a hole")
+    };
+
+    hole.clone()
+}
+
+#[test]
+fn long_chains_of_holes() {
+    let mut ctx = Context::new(hash_reporter());
+    let env = Env::default();
+
+    let holes = (0..CHAIN)
+        .map(|_| ctx.hole(&env, Type::new(TypeKind::Type)))
+        .collect::<Vec<Type<Virtual>>>();
+
+    let int = Type::new(TypeKind::Variable(Qualified {
+        path: Symbol::intern("Tests"),
+        name: Symbol::intern("Int"),
+    }));
+
+    for pair in holes.windows(2) {
+        assert!(ctx.overlaps(env.clone(), pair[0].clone(), pair[1].clone()));
+    }
+
+    // Joining by rank keeps the class shallow, so no unification walks the rest of the chain.
+    let root = hole(&holes[0]).index();
+    let limit = CHAIN.ilog2() as usize;
+
+    for typ in &holes {
+        assert!(hole(typ) == hole(&holes[0]));
+        assert!(hole(typ).depth() <= limit);
+        assert_eq!(typ.show(&env).to_string(), format!("?{root}"));
+    }
+
+    assert!(ctx.overlaps(env.clone(), holes[0].clone(), int.clone()));
+
+    // Every unification compresses the path of the holes that it finds.
+    for typ in &holes {
+        assert!(ctx.overlaps(env.clone(), typ.clone(), int.clone()));
+        assert!(hole(typ).depth() <= 1);
+        assert_eq!(typ.show(&env).to_string(), "Int");
+    }
+
+    assert!(!ctx.reporter.has_errors());
+}
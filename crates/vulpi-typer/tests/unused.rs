@@ -0,0 +1,213 @@
+use vulpi_intern::Symbol;
+use vulpi_location::{FileId, Span};
+use vulpi_report::{hash_reporter, registry, Report};
+use vulpi_typer::{
+    declare::{Declare, Programs},
+    Context, Env,
+};
+use vulpi_vfs::path::Path;
+
+const PRELUDE: &str = "pub type Int\n\
+                       pub type Bool =\n    \
+                           | True\n    \
+                           | False\n\
+                       external zero : Int = \"0\"\n";
+
+/// Checks a module that is also its own prelude. The prelude is added before the source and the
+/// warnings are enabled by `configure`.
+fn check(source: &str, configure: fn(&mut Context)) -> (String, Report) {
+    let report = hash_reporter();
+    let source = format!("{PRELUDE}{source}");
+
+    let path = Path {
+        segments: vec![Symbol::intern("Tests"), Symbol::intern("Main")],
+    };
+
+    let parsed = vulpi_parser::parse(report.clone(), FileId(0), &source);
+    let (programs, _) = vulpi_resolver::resolve_modules(vec![(path, parsed)], report.clone());
+
+    let mut ctx = Context::new(report.clone()).with_prelude(Symbol::intern("Tests.Main"));
+    configure(&mut ctx);
+
+    let programs = Programs(programs);
+
+    Declare::declare(&programs, (&mut ctx, Env::default()));
+    Declare::define(&programs, (&mut ctx, Env::default()));
+
+    assert!(!report.has_errors(), "{:?}", messages(&report));
+
+    (source, report)
+}
+
+/// The line of the source given to [check] where the span starts.
+fn line(source: &str, span: &Span) -> usize {
+    source[PRELUDE.len()..span.start.0].lines().count()
+}
+
+/// Returns the name of every unused variable together with the line where it's bound.
+fn unused(source: &str) -> Vec<(usize, String)> {
+    let (source, report) = check(source, |_| ());
+
+    report
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.code, Some(registry::UNUSED_VARIABLE));
+            let name = &source[diagnostic.location.start.0..diagnostic.location.end.0];
+            (line(&source, &diagnostic.location), name.to_string())
+        })
+        .collect()
+}
+
+fn messages(report: &Report) -> Vec<String> {
+    report.iter().map(|diagnostic| diagnostic.message).collect()
+}
+
+#[test]
+fn reports_unused_bindings() {
+    let found = unused(
+        "let a (x: Int) (y: Int) : Int = y\n\
+         let b : Int -> Int = \\z => zero\n\
+         let c : Int = let w = zero in zero\n\
+         let d : Int = do\n    \
+             let v = zero\n    \
+             zero\n",
+    );
+
+    let names = found.into_iter().map(|(_, name)| name).collect::<Vec<_>>();
+    assert_eq!(names, vec!["x", "z", "w", "v"]);
+}
+
+#[test]
+fn variables_used_only_in_guards_are_used() {
+    let found = unused(
+        "let choose (b: Bool) : Int =\n    \
+             when b is\n        \
+                 c if c => zero\n        \
+                 _ => zero\n",
+    );
+
+    assert!(found.is_empty(), "{found:?}");
+}
+
+#[test]
+fn shadowed_variables_are_tracked_apart() {
+    let found = unused(
+        "let shadowed : Int =\n    \
+             let x = zero in\n    \
+             let x = zero in\n    \
+             x\n\
+         let unused : Int =\n    \
+             let x = zero in\n    \
+             let x = x in\n    \
+             zero\n",
+    );
+
+    assert_eq!(found, vec![(2, "x".to_string()), (7, "x".to_string())]);
+}
+
+#[test]
+fn underscores_silence_unused_variables() {
+    let found = unused("let a (_x: Int) : Int = zero\n");
+    assert!(found.is_empty(), "{found:?}");
+}
+
+const SHADOWING: &str = "let f (x: Int) : Int =\n    \
+                             let g = \\x => x in\n    \
+                             when g zero is\n        \
+                                 x => x\n";
+
+#[test]
+fn shadowing_warnings_point_at_both_binders() {
+    let (source, report) = check(SHADOWING, |ctx| ctx.warn_shadowing = true);
+
+    let shadowed = report
+        .diagnostics(FileId(0))
+        .into_iter()
+        .filter(|diagnostic| diagnostic.code() == Some(registry::SHADOWED_VARIABLE))
+        .map(|diagnostic| {
+            let binder = line(&source, &diagnostic.location());
+            let shadowed = line(&source, &diagnostic.markers()[0].position);
+            (binder, shadowed)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(shadowed, vec![(2, 1), (4, 1)]);
+}
+
+#[test]
+fn shadowing_warnings_are_opt_in() {
+    let (_, report) = check(SHADOWING, |_| ());
+
+    assert!(report
+        .iter()
+        .all(|diagnostic| diagnostic.code != Some(registry::SHADOWED_VARIABLE)));
+}
+
+/// Returns the names of the definitions of the source that are reported as dead code.
+fn dead(source: &str) -> Vec<String> {
+    let (source, report) = check(source, |ctx| ctx.warn_dead_code = true);
+
+    report
+        .iter()
+        .filter(|diagnostic| diagnostic.code == Some(registry::UNUSED_DEFINITION))
+        .filter(|diagnostic| diagnostic.location.start.0 >= PRELUDE.len())
+        .map(|diagnostic| {
+            source[diagnostic.location.start.0..diagnostic.location.end.0].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn helpers_used_only_by_dead_helpers_are_dead() {
+    let found = dead(
+        "type Hidden = | Hidden\n\
+         type Shown = | Shown\n\
+         let helper (x: Int) : Int = x\n\
+         let other : Int = helper zero\n\
+         let used : Shown = Shown.Shown\n\
+         pub let api : Int = do\n    \
+             let _s = used\n    \
+             zero\n\
+         let _ignored : Hidden = Hidden.Hidden\n",
+    );
+
+    assert_eq!(found, vec!["Hidden", "helper", "other"]);
+}
+
+#[test]
+fn kept_items_and_what_they_use_are_alive() {
+    let found = dead(
+        "type Hidden = | Hidden\n\
+         let helper (x: Int) : Int = x\n\
+         @keep\n\
+         let debug : Int = helper zero\n\
+         @keep\n\
+         type Kept = | Kept Hidden\n\
+         let other : Int = zero\n",
+    );
+
+    assert_eq!(found, vec!["other"]);
+}
+
+#[test]
+fn private_types_used_by_public_bodies_are_used() {
+    let found = dead(
+        "type Secret = | Secret\n\
+         type Inner = | Inner\n\
+         type Outer = | Outer Inner\n\
+         let hide (_o: Outer) : Secret = Secret.Secret\n\
+         pub let count : Int = when hide (Outer.Outer Inner.Inner) is\n    \
+             Secret.Secret => zero\n",
+    );
+
+    assert!(found.is_empty(), "{found:?}");
+}
+
+#[test]
+fn dead_code_warnings_are_opt_in() {
+    let (_, report) = check("let helper : Int = zero\n", |_| ());
+
+    assert!(report
+        .iter()
+        .all(|diagnostic| diagnostic.code != Some(registry::UNUSED_DEFINITION)));
+}
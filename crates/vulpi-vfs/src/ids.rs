@@ -0,0 +1,64 @@
+//! The ids of the files that were loaded. The id of a file comes from a hash of its path instead of
+//! the order that the files were loaded in, so a project gets the same ids in every run and the
+//! data that is stored with the ids, like the spans of the interfaces, stays valid.
+
+use std::{collections::HashMap, hash::Hash, path::Path};
+
+use vulpi_location::{stable_hash, FileId};
+
+/// A two way map between the paths of the loaded files and their ids.
+pub struct FileIds<P> {
+    ids: HashMap<P, FileId>,
+    paths: HashMap<FileId, P>,
+}
+
+impl<P> Default for FileIds<P> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            paths: HashMap::new(),
+        }
+    }
+}
+
+/// A hash of a path that does not change between runs of the compiler.
+fn path_hash(path: &Path) -> u32 {
+    stable_hash(path.to_string_lossy().as_bytes()) as u32
+}
+
+impl<P: AsRef<Path> + Hash + Eq + Clone> FileIds<P> {
+    pub fn id_of(&self, path: &P) -> Option<FileId> {
+        self.ids.get(path).copied()
+    }
+
+    pub fn path_of(&self, id: FileId) -> Option<&P> {
+        self.paths.get(&id)
+    }
+
+    /// Gives an id to a path, or returns the one that it already has. Two paths with the same hash
+    /// are the only case where the id depends on the order, and the second one gets the next id
+    /// that is free.
+    pub fn insert(&mut self, path: P) -> FileId {
+        if let Some(id) = self.id_of(&path) {
+            return id;
+        }
+
+        let mut id = FileId(path_hash(path.as_ref()) as usize);
+
+        while self.paths.contains_key(&id) {
+            id = FileId(id.0.wrapping_add(1));
+        }
+
+        self.ids.insert(path.clone(), id);
+        self.paths.insert(id, path);
+
+        id
+    }
+
+    /// Forgets the path of an id, and returns it.
+    pub fn remove(&mut self, id: FileId) -> Option<P> {
+        let path = self.paths.remove(&id)?;
+        self.ids.remove(&path);
+        Some(path)
+    }
+}
@@ -1,12 +1,16 @@
 //! Virtual file system for the compiler. It's used to store the source code of the files that are
 //! being compiled.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, rc::Rc};
 
 use filetime::FileTime;
+pub use ids::FileIds;
+pub use memory::MemoryFileSystem;
 pub use path::Path;
-use vulpi_location::FileId;
+use vulpi_location::{FileId, LineIndex};
 
+pub mod ids;
+pub mod memory;
 pub mod path;
 
 #[derive(Debug)]
@@ -25,9 +29,17 @@ pub trait FileSystem {
     fn unload(&mut self, id: FileId) -> Result<(), Error>;
     fn path(&self, id: FileId) -> Result<&Self::Path, Error>;
 
+    /// Checks if there is a file in the path, even if it was not loaded.
+    fn exists(&self, path: Self::Path) -> bool;
+
     fn store(&mut self, id: FileId, content: String) -> Result<(), Error>;
     fn read(&self, id: FileId) -> Result<String, Error>;
 
+    /// The lines of a file. Implementations should cache it until the content of the file changes.
+    fn line_index(&self, id: FileId) -> Result<Rc<LineIndex>, Error> {
+        Ok(Rc::new(LineIndex::new(&self.read(id)?)))
+    }
+
     fn create(&mut self, path: Self::Path) -> Result<FileId, Error>;
     fn write(&mut self, id: FileId) -> Result<(), Error>;
     fn delete(&mut self, id: FileId) -> Result<(), Error>;
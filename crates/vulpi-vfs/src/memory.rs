@@ -0,0 +1,161 @@
+//! A file system that keeps every file in memory. It's used by tests and by places where there is
+//! no disk, like a playground that runs in the browser.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use filetime::FileTime;
+use vulpi_intern::Symbol;
+use vulpi_location::FileId;
+
+use crate::{path::Path, Error, FileIds, FileSystem};
+
+/// The content of a file that is "on disk" and the last time that it was written.
+struct Stored {
+    content: String,
+    modified: FileTime,
+}
+
+/// A [FileSystem] that stores the files in a map from paths to their contents. Loading a file
+/// copies it from the map, and writing it copies it back.
+pub struct MemoryFileSystem {
+    root: Option<Symbol>,
+    build_root: PathBuf,
+    disk: HashMap<PathBuf, Stored>,
+    ids: FileIds<PathBuf>,
+    files: HashMap<FileId, String>,
+}
+
+impl Default for MemoryFileSystem {
+    fn default() -> Self {
+        Self {
+            root: None,
+            build_root: PathBuf::from("build"),
+            disk: HashMap::new(),
+            ids: FileIds::default(),
+            files: HashMap::new(),
+        }
+    }
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the project, so the module paths that start with it are looked up without
+    /// the first segment, like in a real project.
+    pub fn with_root(mut self, root: Symbol) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Adds a file or replaces its content.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, content: impl Into<String>) {
+        let stored = Stored {
+            content: content.into(),
+            modified: FileTime::now(),
+        };
+
+        self.disk.insert(path.into(), stored);
+    }
+}
+
+impl<P: Into<PathBuf>, S: Into<String>> FromIterator<(P, S)> for MemoryFileSystem {
+    fn from_iter<T: IntoIterator<Item = (P, S)>>(iter: T) -> Self {
+        let mut fs = Self::new();
+
+        for (path, content) in iter {
+            fs.insert(path, content);
+        }
+
+        fs
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    type Path = PathBuf;
+
+    fn load(&mut self, path: PathBuf) -> Result<FileId, Error> {
+        if let Some(id) = self.ids.id_of(&path) {
+            return Ok(id);
+        }
+
+        let stored = self.disk.get(&path).ok_or(Error::NotFound(path.clone()))?;
+        let content = stored.content.clone();
+
+        let id = self.ids.insert(path);
+        self.files.insert(id, content);
+
+        Ok(id)
+    }
+
+    fn unload(&mut self, id: FileId) -> Result<(), Error> {
+        self.files.remove(&id).ok_or(Error::NotFoundId)?;
+        self.ids.remove(id);
+        Ok(())
+    }
+
+    fn path(&self, id: FileId) -> Result<&PathBuf, Error> {
+        self.ids.path_of(id).ok_or(Error::NotFoundId)
+    }
+
+    fn exists(&self, path: PathBuf) -> bool {
+        self.disk.contains_key(&path)
+    }
+
+    fn store(&mut self, id: FileId, content: String) -> Result<(), Error> {
+        let file = self.files.get_mut(&id).ok_or(Error::NotFoundId)?;
+        *file = content;
+        Ok(())
+    }
+
+    fn read(&self, id: FileId) -> Result<String, Error> {
+        let file = self.files.get(&id).ok_or(Error::NotFoundId)?;
+        Ok(file.clone())
+    }
+
+    fn create(&mut self, path: PathBuf) -> Result<FileId, Error> {
+        if self.disk.contains_key(&path) || self.ids.id_of(&path).is_some() {
+            return Err(Error::AlreadyExists);
+        }
+
+        let id = self.ids.insert(path);
+        self.files.insert(id, String::new());
+
+        Ok(id)
+    }
+
+    fn write(&mut self, id: FileId) -> Result<(), Error> {
+        let path = self.path(id)?.clone();
+        let content = self.read(id)?;
+        self.insert(path, content);
+        Ok(())
+    }
+
+    fn delete(&mut self, id: FileId) -> Result<(), Error> {
+        let path = self.path(id)?.clone();
+
+        self.disk
+            .remove(&path)
+            .map(|_| ())
+            .ok_or(Error::NotFound(path))
+    }
+
+    fn modification_time(&self, path: PathBuf) -> Result<FileTime, Error> {
+        let stored = self.disk.get(&path).ok_or(Error::NotFound(path.clone()))?;
+        Ok(stored.modified)
+    }
+
+    fn from_cached_path(&self, path: Path) -> PathBuf {
+        path.to_pathbuf(self.build_root.clone())
+    }
+
+    fn from_src_path(&self, path: Path) -> PathBuf {
+        match &self.root {
+            Some(root) if path.segments.first() == Some(root) => {
+                path.shift().to_pathbuf(PathBuf::new())
+            }
+            _ => path.to_pathbuf(PathBuf::new()),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use vulpi_intern::Symbol;
+use vulpi_vfs::{path::Path, FileSystem, MemoryFileSystem};
+
+#[test]
+fn loads_the_files_that_were_inserted() {
+    let mut fs = MemoryFileSystem::from_iter([("Main.vp", "let main = 1\n"), ("Lib.vp", "")]);
+
+    assert!(fs.exists(PathBuf::from("Main.vp")));
+    assert!(!fs.exists(PathBuf::from("Other.vp")));
+
+    let id = fs.load(PathBuf::from("Main.vp")).unwrap();
+
+    assert_eq!(fs.load(PathBuf::from("Main.vp")).unwrap(), id);
+    assert_eq!(fs.read(id).unwrap(), "let main = 1\n");
+    assert_eq!(fs.path(id).unwrap(), &PathBuf::from("Main.vp"));
+    assert!(fs.load(PathBuf::from("Other.vp")).is_err());
+}
+
+#[test]
+fn writes_the_stored_content_back() {
+    let mut fs = MemoryFileSystem::new();
+
+    let id = fs.create(PathBuf::from("Out.js")).unwrap();
+    fs.store(id, "main()".to_string()).unwrap();
+
+    assert!(!fs.exists(PathBuf::from("Out.js")));
+
+    fs.write(id).unwrap();
+
+    assert!(fs.exists(PathBuf::from("Out.js")));
+    assert!(fs.create(PathBuf::from("Out.js")).is_err());
+
+    fs.delete(id).unwrap();
+    assert!(!fs.exists(PathBuf::from("Out.js")));
+}
+
+#[test]
+fn maps_module_paths_to_files() {
+    let fs = MemoryFileSystem::new().with_root(Symbol::intern("Project"));
+
+    let path = Path {
+        segments: vec![
+            Symbol::intern("Project"),
+            Symbol::intern("Data"),
+            Symbol::intern("List"),
+        ],
+    };
+
+    assert_eq!(
+        fs.from_src_path(path.clone()),
+        PathBuf::from("Data/List.vp")
+    );
+    assert_eq!(
+        fs.from_cached_path(path),
+        PathBuf::from("build/Project/Data/List.vp")
+    );
+}
+
+#[test]
+fn ids_do_not_depend_on_the_order_of_loading() {
+    let files = [("Main.vp", "let main = 1\n"), ("Lib.vp", "")];
+
+    let load = |order: [&str; 2]| {
+        let mut fs = MemoryFileSystem::from_iter(files);
+
+        let ids = order.map(|path| fs.load(PathBuf::from(path)).unwrap());
+
+        for (id, path) in ids.iter().zip(order) {
+            assert_eq!(fs.path(*id).unwrap(), &PathBuf::from(path));
+        }
+
+        ids
+    };
+
+    let [main, lib] = load(["Main.vp", "Lib.vp"]);
+    let [lib_again, main_again] = load(["Lib.vp", "Main.vp"]);
+
+    assert_ne!(main, lib);
+    assert_eq!(main, main_again);
+    assert_eq!(lib, lib_again);
+}
+
+#[test]
+fn unloaded_files_get_the_same_id_again() {
+    let mut fs = MemoryFileSystem::from_iter([("Main.vp", "let main = 1\n")]);
+
+    let id = fs.load(PathBuf::from("Main.vp")).unwrap();
+    fs.unload(id).unwrap();
+
+    assert!(fs.path(id).is_err());
+    assert_eq!(fs.load(PathBuf::from("Main.vp")).unwrap(), id);
+}